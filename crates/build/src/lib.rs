@@ -0,0 +1,211 @@
+//! Generates Rust tests from `.tree` specs in build scripts.
+//!
+//! [`generate_rust_tests`] scaffolds the tests of every tree matching a set
+//! of glob patterns into a directory, usually `OUT_DIR`, and tells Cargo to
+//! rerun the build script when one of the trees changes. The generated test
+//! modules are then included with `include!`, so they always match the spec
+//! and never need to be committed.
+//!
+//! ```no_run
+//! // build.rs
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! bulloak_build::generate_rust_tests(
+//!     &out_dir,
+//!     &["specs/**/*.tree"],
+//!     &bulloak_build::Config::default(),
+//! )
+//! .unwrap();
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub use bulloak_rust::Config;
+use thiserror::Error;
+
+/// A specialized `Result` type for `bulloak-build`.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error returned while generating tests.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A glob pattern is invalid.
+    #[error("invalid pattern `{pattern}`: {source}")]
+    Pattern {
+        /// The pattern.
+        pattern: String,
+        /// Why the pattern is invalid.
+        #[source]
+        source: glob::PatternError,
+    },
+    /// A file could not be read or written.
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying I/O error.
+        #[source]
+        source: io::Error,
+    },
+    /// The tests of a tree could not be generated.
+    #[error("failed to generate tests for {}: {source}", path.display())]
+    Generate {
+        /// The path of the tree.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        source: bulloak_rust::Error,
+    },
+    /// Two trees would generate the same file, since generated files are
+    /// named after their tree.
+    #[error(
+        "{} and {} would both generate {}",
+        first.display(),
+        second.display(),
+        file.display()
+    )]
+    Conflict {
+        /// The tree matched first.
+        first: PathBuf,
+        /// The tree matched second.
+        second: PathBuf,
+        /// The generated file both trees map to.
+        file: PathBuf,
+    },
+}
+
+/// Scaffolds the Rust tests of every tree matching `globs` into `out_dir`.
+///
+/// Each tree generates a file named like the one `bulloak scaffold -b rust`
+/// writes, e.g. `specs/hash_pair.tree` generates
+/// `<out_dir>/hash_pair_test.rs`. Files whose contents didn't change are not
+/// rewritten, so that they don't trigger a rebuild. Patterns are relative to
+/// the current directory, which is the package root in build scripts.
+///
+/// For every tree, `cargo:rerun-if-changed` is printed so that Cargo reruns
+/// the build script when the tree changes. Trees added later only get picked
+/// up once the build script runs again.
+///
+/// Returns the paths of the generated files, in the order of `globs`.
+///
+/// # Errors
+///
+/// Returns an error if a pattern is invalid, if a tree can't be read or
+/// scaffolded, if two trees would generate the same file or if a file can't
+/// be written.
+pub fn generate_rust_tests(
+    out_dir: impl AsRef<Path>,
+    globs: &[&str],
+    config: &Config,
+) -> Result<Vec<PathBuf>> {
+    let out_dir = out_dir.as_ref();
+    let mut trees = Vec::new();
+    for pattern in globs {
+        let paths = glob::glob(pattern).map_err(|source| Error::Pattern {
+            pattern: (*pattern).to_owned(),
+            source,
+        })?;
+        trees.extend(paths.filter_map(std::result::Result::ok));
+    }
+
+    let mut generated: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut files = Vec::with_capacity(trees.len());
+    for tree in trees {
+        let test_file = bulloak_rust::scaffold::test_file_path(&tree);
+        let file = out_dir.join(test_file.file_name().unwrap_or_default());
+        if let Some(first) = generated.get(&file) {
+            if *first == tree {
+                continue;
+            }
+            return Err(Error::Conflict {
+                first: first.clone(),
+                second: tree,
+                file,
+            });
+        }
+
+        println!("cargo:rerun-if-changed={}", tree.display());
+        let text = fs::read_to_string(&tree)
+            .map_err(|source| Error::Io { path: tree.clone(), source })?;
+        let emitted =
+            bulloak_rust::scaffold_tree(&text, &config.for_tree(&tree))
+                .map_err(|source| Error::Generate {
+                    path: tree.clone(),
+                    source,
+                })?;
+        if fs::read_to_string(&file).ok().as_deref() != Some(emitted.as_str()) {
+            fs::write(&file, emitted)
+                .map_err(|source| Error::Io { path: file.clone(), source })?;
+        }
+
+        generated.insert(file.clone(), tree);
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{generate_rust_tests, Config, Error};
+
+    #[test]
+    fn generates_a_test_file_per_tree() {
+        let specs = tempfile::tempdir().unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+        fs::write(specs.path().join("foo.tree"), "foo\n└── It should work.")
+            .unwrap();
+        fs::write(
+            specs.path().join("bar.tree"),
+            "bar\n└── When baz\n    └── It should qux.",
+        )
+        .unwrap();
+
+        let pattern = specs.path().join("*.tree");
+        let files = generate_rust_tests(
+            out_dir.path(),
+            &[pattern.to_str().unwrap()],
+            &Config::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            [
+                out_dir.path().join("bar_test.rs"),
+                out_dir.path().join("foo_test.rs")
+            ]
+        );
+        let foo =
+            fs::read_to_string(out_dir.path().join("foo_test.rs")).unwrap();
+        assert!(foo.contains("fn test_should_work()"));
+    }
+
+    #[test]
+    fn rejects_trees_generating_the_same_file() {
+        let specs = tempfile::tempdir().unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+        for dir in ["a", "b"] {
+            fs::create_dir(specs.path().join(dir)).unwrap();
+            fs::write(
+                specs.path().join(dir).join("foo.tree"),
+                "foo\n└── It should work.",
+            )
+            .unwrap();
+        }
+
+        let pattern = specs.path().join("*/foo.tree");
+        let err = generate_rust_tests(
+            out_dir.path(),
+            &[pattern.to_str().unwrap()],
+            &Config::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Conflict { .. }));
+    }
+}
@@ -1,18 +1,66 @@
 #![allow(missing_docs)]
-use bulloak_foundry::scaffold;
+use std::fs;
+
+use bulloak_foundry::{
+    check::{
+        context::Context,
+        rules::{Checker, StructuralMatcher},
+    },
+    config::Config,
+    scaffold,
+};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
+fn load_tree() -> String {
+    fs::read_to_string("benches/bench_data/cancel.tree").unwrap()
+}
+
 fn emit_big_tree(c: &mut Criterion) {
-    let tree =
-        std::fs::read_to_string("benches/bench_data/cancel.tree").unwrap();
+    let tree = load_tree();
 
-    let cfg = Default::default();
+    let cfg = Config::default();
+    let rust_cfg = bulloak_rust::Config::default();
     let mut group = c.benchmark_group("sample-size-10");
     group.bench_function("emit-big-tree", |b| {
-        b.iter(|| scaffold::scaffold(black_box(&tree), &cfg))
+        b.iter(|| scaffold::scaffold(black_box(&tree), &cfg));
+    });
+    group.bench_function("emit-big-tree-rust", |b| {
+        b.iter(|| bulloak_rust::scaffold_tree(black_box(&tree), &rust_cfg));
+    });
+    group.finish();
+}
+
+fn check_big_tree(c: &mut Criterion) {
+    let tree = load_tree();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("cancel.tree");
+    fs::write(&tree_path, &tree).unwrap();
+
+    // Check against the files `scaffold` generates, so every test is found.
+    let cfg = Config::default();
+    let sol = scaffold::scaffold(&tree, &cfg).unwrap();
+    let sol = forge_fmt::fmt(&sol).unwrap_or(sol);
+    fs::write(tree_path.with_extension("t.sol"), sol).unwrap();
+    let rust_cfg = bulloak_rust::Config::default();
+    let rust = bulloak_rust::scaffold_tree(&tree, &rust_cfg).unwrap();
+    let rust_path = bulloak_rust::scaffold::test_file_path(&tree_path);
+    fs::write(rust_path, rust).unwrap();
+
+    let mut group = c.benchmark_group("sample-size-10");
+    group.bench_function("check-big-tree", |b| {
+        b.iter(|| {
+            let ctx = Context::new(black_box(tree_path.clone()), &cfg).unwrap();
+            StructuralMatcher::check(&ctx)
+        });
+    });
+    group.bench_function("check-big-tree-rust", |b| {
+        b.iter(|| {
+            bulloak_rust::check::check(black_box(&tree_path), &rust_cfg)
+                .unwrap()
+        });
     });
     group.finish();
 }
 
-criterion_group!(benches, emit_big_tree);
+criterion_group!(benches, emit_big_tree, check_big_tree);
 criterion_main!(benches);
@@ -0,0 +1,197 @@
+//! A programmatic interface to `bulloak`'s commands.
+//!
+//! Commands print their output just like the `bulloak` binary does, but they
+//! never exit the process. Instead, they return a [`RunReport`] and leave the
+//! exit-code policy to the caller.
+//!
+//! ```no_run
+//! use bulloak::api::{self, Check, Cli, Commands};
+//!
+//! let check = Check { files: vec!["spec.tree".into()], ..Check::default() };
+//! let cli = Cli { command: Commands::Check(check), ..Cli::default() };
+//! let report = api::execute(&cli);
+//! assert!(report.is_success());
+//! ```
+
+use std::path::PathBuf;
+
+pub use bulloak_syntax::Capabilities;
+
+pub use crate::{
+    capabilities::{capabilities, ShowCapabilities},
+    check::{Check, ErrorOn, OutputFormat, SortBy},
+    cli::{Backend, Cli, ColorChoice, Commands},
+    discovery::{discover_specs, Discovery, IGNORE_FILE},
+    docs::{Docs, DocsFormat},
+    explain::Explain,
+    impact::Impact,
+    report::{
+        parse_check_report, CheckReport, EditReport, ErrorReport, FixPlan,
+        ReportError, ViolationReport, SCHEMA, SCHEMA_VERSION,
+    },
+    run::Run,
+    scaffold::Scaffold,
+};
+
+/// The exit code of a `bulloak check` that found no violations but stopped
+/// before checking every spec file.
+pub const INCOMPLETE_EXIT_CODE: i32 = 3;
+
+/// The outcome of a `bulloak` command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunReport {
+    /// The spec files the command processed.
+    pub files: Vec<PathBuf>,
+    /// The spec files that could not be processed, with the reason why.
+    pub errors: Vec<(PathBuf, String)>,
+    /// The spec files `bulloak check` left unchecked because it stopped
+    /// early, e.g. once `--max-duration` ran out.
+    pub unchecked: Vec<PathBuf>,
+    /// The number of violations `bulloak check` found and did not fix.
+    pub violations: usize,
+    /// How many of `violations` `bulloak check --fix` can fix.
+    pub fixable: usize,
+    /// How many of `violations` are warnings, which don't fail the command
+    /// unless `--warnings-as-errors` is passed.
+    pub warnings: usize,
+    /// The number of violations `bulloak check --fix` fixed.
+    pub fixed: usize,
+    /// The number of actions, the leaves of the specs, `bulloak check` found.
+    pub leaves: usize,
+    /// How many of `leaves` have a test.
+    pub covered_leaves: usize,
+    /// The number of actions whose tests failed or did not run under
+    /// `bulloak run`.
+    pub failed_actions: usize,
+    /// Which violations make the command fail.
+    pub error_on: ErrorOn,
+}
+
+impl RunReport {
+    /// Whether the command succeeded.
+    ///
+    /// Violations only count as failures under the [`ErrorOn`] policy of the
+    /// report. Warnings are left out, except under [`ErrorOn::FixableOnly`],
+    /// which is only about what `--fix` can fix.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        let violations = match self.error_on {
+            ErrorOn::Violations => {
+                self.violations.saturating_sub(self.warnings)
+            }
+            ErrorOn::FixableOnly => self.fixable,
+            ErrorOn::Never => 0,
+        };
+        self.errors.is_empty() && violations == 0 && self.failed_actions == 0
+    }
+
+    /// The exit code the `bulloak` binary uses for this report.
+    ///
+    /// This is `1` if the command failed, or else [`INCOMPLETE_EXIT_CODE`]
+    /// if some spec files were left unchecked, so that CI can tell a partial
+    /// run from a full one.
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        if !self.is_success() {
+            1
+        } else if !self.unchecked.is_empty() {
+            INCOMPLETE_EXIT_CODE
+        } else {
+            0
+        }
+    }
+}
+
+/// Runs the command described by `cli`.
+#[must_use]
+pub fn execute(cli: &Cli) -> RunReport {
+    crate::cli::execute(cli)
+}
+
+/// Parses the process arguments like the `bulloak` binary does and runs the
+/// resulting command.
+///
+/// Exits the process if the arguments are invalid or if `--help` or
+/// `--version` were passed.
+///
+/// # Errors
+///
+/// Returns an error if the configuration can't be assembled.
+pub fn run_cli() -> anyhow::Result<RunReport> {
+    crate::cli::run()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{
+        execute, Check, Cli, Commands, ErrorOn, RunReport, INCOMPLETE_EXIT_CODE,
+    };
+
+    #[test]
+    fn report_exit_codes() {
+        assert_eq!(RunReport::default().exit_code(), 0);
+        let report = RunReport { violations: 2, ..RunReport::default() };
+        assert_eq!(report.exit_code(), 1);
+        let report = RunReport {
+            errors: vec![(PathBuf::from("a.tree"), "boom".to_owned())],
+            ..RunReport::default()
+        };
+        assert!(!report.is_success());
+    }
+
+    #[test]
+    fn report_exit_codes_of_partial_runs() {
+        let report = RunReport {
+            unchecked: vec![PathBuf::from("b.tree")],
+            ..RunReport::default()
+        };
+        assert!(report.is_success());
+        assert_eq!(report.exit_code(), INCOMPLETE_EXIT_CODE);
+        let report = RunReport { violations: 1, ..report };
+        assert_eq!(report.exit_code(), 1);
+    }
+
+    #[test]
+    fn report_exit_codes_ignore_warnings() {
+        let report =
+            RunReport { violations: 2, warnings: 2, ..RunReport::default() };
+        assert_eq!(report.exit_code(), 0);
+        let report = RunReport { warnings: 1, ..report };
+        assert_eq!(report.exit_code(), 1);
+    }
+
+    #[test]
+    fn report_exit_codes_under_error_policies() {
+        let report = RunReport {
+            violations: 3,
+            fixable: 1,
+            error_on: ErrorOn::FixableOnly,
+            ..RunReport::default()
+        };
+        assert_eq!(report.exit_code(), 1);
+        let report = RunReport { fixable: 0, ..report };
+        assert_eq!(report.exit_code(), 0);
+        let report =
+            RunReport { fixable: 3, error_on: ErrorOn::Never, ..report };
+        assert_eq!(report.exit_code(), 0);
+        let report = RunReport {
+            errors: vec![(PathBuf::from("a.tree"), "boom".to_owned())],
+            ..report
+        };
+        assert_eq!(report.exit_code(), 1);
+    }
+
+    #[test]
+    fn check_reports_violations_without_exiting() {
+        let tree = PathBuf::from("tests/check/missing_contract.tree");
+        let check = Check { files: vec![tree.clone()], ..Check::default() };
+        let cli = Cli { command: Commands::Check(check), ..Cli::default() };
+
+        let report = execute(&cli);
+        assert_eq!(report.files, vec![tree]);
+        assert_eq!(report.violations, 1);
+        assert_eq!(report.exit_code(), 1);
+    }
+}
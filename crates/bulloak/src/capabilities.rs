@@ -0,0 +1,92 @@
+//! Defines the `bulloak capabilities` command.
+//!
+//! This command describes what a backend supports, e.g. whether `--fix` can
+//! fix its violations and which tags it understands, so that tooling can
+//! adapt to each backend.
+
+use bulloak_syntax::Capabilities;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::RunReport,
+    cli::{Backend, Cli},
+};
+
+/// Describe what a backend supports.
+#[derive(Debug, Parser, Clone, Default, Serialize, Deserialize)]
+pub struct ShowCapabilities {
+    /// The target backend/language to describe.
+    #[arg(short = 'b', long = "backend", visible_alias = "lang", value_enum, default_value_t = Backend::Solidity)]
+    pub backend: Backend,
+    /// Print the capabilities as a JSON object on stdout.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+impl ShowCapabilities {
+    /// Entrypoint for `bulloak capabilities`.
+    pub(crate) fn run(&self, _cfg: &Cli) -> RunReport {
+        let capabilities = capabilities(self.backend);
+        if self.json {
+            println!("{}", to_json(self.backend, &capabilities));
+        } else {
+            let yes_no = |supported: bool| if supported { "yes" } else { "no" };
+            println!("fix: {}", yes_no(capabilities.supports_fix));
+            println!(
+                "order check: {}",
+                yes_no(capabilities.supports_order_check)
+            );
+            println!(
+                "attributes: {}",
+                capabilities.supported_attributes.join(", ")
+            );
+            println!("file pattern: {}", capabilities.file_pattern);
+        }
+        RunReport::default()
+    }
+}
+
+/// Returns what `backend` supports.
+#[must_use]
+pub fn capabilities(backend: Backend) -> Capabilities {
+    match backend {
+        Backend::Solidity => bulloak_foundry::capabilities(),
+        Backend::Rust => bulloak_rust::capabilities(),
+    }
+}
+
+/// Returns the capabilities of `backend` as a JSON object.
+fn to_json(backend: Backend, capabilities: &Capabilities) -> serde_json::Value {
+    serde_json::json!({
+        "backend": backend,
+        "supports_fix": capabilities.supports_fix,
+        "supports_order_check": capabilities.supports_order_check,
+        "supported_attributes": capabilities.supported_attributes,
+        "file_pattern": capabilities.file_pattern,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capabilities, to_json};
+    use crate::cli::Backend;
+
+    #[test]
+    fn describes_each_backend() {
+        let solidity = capabilities(Backend::Solidity);
+        assert_eq!(solidity.file_pattern, "{stem}.t.sol");
+        assert!(solidity.supported_attributes.contains(&"virtual"));
+        assert!(solidity.supported_attributes.contains(&"combine"));
+
+        let rust = capabilities(Backend::Rust);
+        assert_eq!(rust.file_pattern, "{stem}_test.rs");
+        assert!(rust.supported_attributes.contains(&"only"));
+        assert!(rust.supported_attributes.contains(&"combine"));
+        assert!(!rust.supported_attributes.contains(&"virtual"));
+
+        let json = to_json(Backend::Rust, &rust);
+        assert_eq!(json["backend"], "rust");
+        assert_eq!(json["supports_fix"], true);
+    }
+}
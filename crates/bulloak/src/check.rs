@@ -3,25 +3,161 @@
 //! This command performs checks on the relationship between a bulloak tree and
 //! a Solidity file.
 
-use std::{fs, path::PathBuf};
+use std::{
+    cmp::Reverse,
+    fmt::Write,
+    fs,
+    num::{NonZeroU64, NonZeroUsize},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use bulloak_foundry::{
     check::{
+        conflicts::find_conflicts,
         context::{fix_order, Context},
+        coverage::coverage,
+        fix::{fix_all, FixedContext},
+        location::Location,
         rules::{self, Checker},
     },
-    sol::find_contract,
     violation::{Violation, ViolationKind},
 };
-use bulloak_syntax::utils::pluralize;
-use clap::Parser;
-use owo_colors::OwoColorize;
+use bulloak_rust::{constants::DEFAULT_HEADER, Severity};
+use bulloak_syntax::{
+    markdown::read_spec,
+    utils::{match_line_endings, pluralize},
+};
+use clap::{Parser, ValueEnum};
+use owo_colors::{OwoColorize, Stream, Style};
 use serde::{Deserialize, Serialize};
 
-use crate::{cli::{Backend, Cli}, glob::expand_glob};
+use crate::{
+    api::RunReport,
+    cli::{header, pairs, Backend, Cli, Naming},
+    diff,
+    discovery::{expand_patterns, warn_ignored_comments},
+    jobs,
+    plugin::{Facts, Finding, Rules},
+    progress::Progress,
+    report::{
+        CheckReport, EditReport, ErrorReport, FixPlan, ViolationReport,
+        SCHEMA_VERSION,
+    },
+    transaction::Transaction,
+    workspace::{self, Manifest},
+};
+
+/// Which violations make `bulloak check` fail.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorOn {
+    /// Fail on any violation.
+    #[default]
+    Violations,
+    /// Fail only on violations that `--fix` can fix.
+    FixableOnly,
+    /// Never fail on violations, e.g. to only report them.
+    Never,
+}
+
+/// How `bulloak check` prints its results.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Human-readable diagnostics.
+    #[default]
+    Text,
+    /// A single JSON document following the schema in
+    /// `crates/bulloak/schema`.
+    Json,
+    /// One JSON object per violation, in the shape of the violations of a
+    /// `json` report, printed as soon as its spec is checked.
+    JsonLines,
+}
+
+/// How `bulloak check` orders the violations it reports.
+///
+/// Whatever the key, ties are broken by file and then by line, so the output
+/// doesn't depend on the number of jobs.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortBy {
+    /// By file, then by line.
+    #[default]
+    File,
+    /// Errors first, then warnings.
+    Severity,
+    /// By rule ID, e.g. all `BK105` violations together.
+    Kind,
+}
+
+impl SortBy {
+    /// Sorts Solidity `violations` by this key.
+    fn sort_solidity(self, violations: &mut [Violation]) {
+        violations.sort_by_cached_key(|v| {
+            let (file, line) = match &v.location {
+                Location::Code(file, line) => (PathBuf::from(file), *line),
+                Location::File(file) => (PathBuf::from(file), 0),
+            };
+            (self.rank(Severity::Error, v.kind.rule()), file, line)
+        });
+    }
+
+    /// Sorts Rust `violations` by this key.
+    fn sort_rust(self, violations: &mut [bulloak_rust::Violation]) {
+        violations.sort_by_cached_key(|v| {
+            let rank = self.rank(v.kind.severity(), v.kind.rule());
+            (rank, v.file_path.clone(), v.line.unwrap_or(0))
+        });
+    }
+
+    /// What violations are sorted by before their file and line.
+    fn rank(
+        self,
+        severity: Severity,
+        rule: &'static str,
+    ) -> (Reverse<Severity>, &'static str) {
+        match self {
+            SortBy::File => (Reverse(Severity::Error), ""),
+            SortBy::Severity => (Reverse(severity), ""),
+            SortBy::Kind => (Reverse(Severity::Error), rule),
+        }
+    }
+}
 
 /// Check that the tests match the spec.
-#[doc(hidden)]
 #[derive(Debug, Parser, Clone, Serialize, Deserialize)]
 pub struct Check {
     /// The set of tree files to use as spec.
@@ -35,15 +171,215 @@ pub struct Check {
     /// to standard output instead of writing to files.
     #[arg(long, requires = "fix-violations", default_value_t = false)]
     pub stdout: bool,
+    /// When `--fix` is passed, use `--diff` to print a unified diff of the
+    /// changes instead of writing them to files.
+    #[arg(
+        long,
+        requires = "fix-violations",
+        conflicts_with = "stdout",
+        default_value_t = false
+    )]
+    pub diff: bool,
+    /// When `--fix` is passed, use `--plan-only` to print the edits it would
+    /// make to each file, as byte ranges and their replacements, instead of
+    /// writing them. Pass `--format json` for a machine-readable plan.
+    #[arg(
+        long,
+        requires = "fix-violations",
+        conflicts_with_all = ["stdout", "diff"],
+        default_value_t = false
+    )]
+    pub plan_only: bool,
+    /// Stop checking at the first violation. Same as `--max-violations 1`.
+    #[arg(long, conflicts_with_all = ["fix", "max_violations"])]
+    pub fail_fast: bool,
+    /// Stop checking once `N` violations were found.
+    #[arg(long, value_name = "N", conflicts_with = "fix")]
+    pub max_violations: Option<NonZeroUsize>,
+    /// Stop checking new files once `SECS` seconds have passed, and list
+    /// the files left unchecked.
+    ///
+    /// Files being checked by then are checked to the end. If no violation
+    /// was found, `bulloak` exits with code 3 rather than 0.
+    #[arg(long, value_name = "SECS", conflicts_with = "fix")]
+    pub max_duration: Option<NonZeroU64>,
+    /// Fail when a pattern in `files` is invalid or matches no files,
+    /// instead of warning.
+    #[arg(long, default_value_t = false)]
+    pub strict_globs: bool,
+    /// Check every package under `DIR`, the current directory by default,
+    /// instead of `files`.
+    ///
+    /// Trees are grouped by the nearest directory with a `foundry.toml` or a
+    /// `Cargo.toml`, and each group is checked with the matching backend.
+    /// Trees outside of any package use `--backend`.
+    #[arg(
+        long,
+        value_name = "DIR",
+        num_args = 0..=1,
+        default_missing_value = ".",
+        conflicts_with = "files"
+    )]
+    pub workspace: Option<PathBuf>,
+    /// Which violations make `bulloak check` fail.
+    ///
+    /// Files that can't be processed always make it fail.
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = ErrorOn::Violations)]
+    pub error_on: ErrorOn,
+    /// Make warnings, like unused Rust helpers, fail the command too.
+    #[arg(long, default_value_t = false)]
+    pub warnings_as_errors: bool,
+    /// How to print the results.
+    ///
+    /// `json` prints a single document to stdout, which
+    /// `bulloak::api::parse_check_report` reads back. With `--fix`, it needs
+    /// `--plan-only` and prints the plan instead. `json-lines` prints one
+    /// object per violation as soon as it's found, and can't be combined with
+    /// `--fix`.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        conflicts_with = "workspace"
+    )]
+    pub format: OutputFormat,
+    /// How to order the violations.
+    #[arg(long, value_enum, value_name = "KEY", default_value_t = SortBy::File)]
+    pub sort_by: SortBy,
+    /// Print the violations of each file under a header naming it, one line
+    /// each, instead of repeating the path for every violation.
+    #[arg(long, default_value_t = false)]
+    pub group_by_file: bool,
+    /// Whether tests that aren't implemented yet must call `vm.skip(true)`,
+    /// as `scaffold -S` generates them.
+    #[arg(short = 'S', long = "vm-skip", default_value_t = false)]
+    pub with_vm_skip: bool,
     /// Whether to emit modifiers.
     #[arg(short = 'm', long, default_value_t = false)]
     pub skip_modifiers: bool,
     /// Whether to capitalize and punctuate branch descriptions.
     #[arg(long = "format-descriptions", default_value_t = false)]
     pub format_descriptions: bool,
+    /// The name of the contract to check when the Solidity file defines
+    /// several.
+    ///
+    /// Defaults to the contract named after the tree's root, or the first
+    /// contract in the file.
+    #[arg(long, value_name = "NAME")]
+    pub contract: Option<String>,
+    /// Whether to add commented-out assertions for actions like `it should
+    /// return X` or `it should emit Y`.
+    #[arg(long, default_value_t = false)]
+    pub assertion_stubs: bool,
+    /// Whether to add an `insta::assert_snapshot!` stub to Rust tests of
+    /// actions tagged `[snapshot]`, importing the macro in the test module.
+    #[arg(long, default_value_t = false)]
+    pub snapshot_stubs: bool,
+    /// Whether to add `vm.expectEmit();` and a commented-out event to Solidity
+    /// tests of actions like `it should emit Y`, and check that they call
+    /// `vm.expectEmit`.
+    #[arg(long, default_value_t = false)]
+    pub expect_emit: bool,
+    /// A comment to add to the body of every generated test, e.g.
+    /// `TODO(bulloak): implement — see {tree}`.
+    ///
+    /// `{tree}`, `{title}` and `{test}` are replaced with the path of the tree
+    /// file, the title of the branch the test covers and the name of the test.
+    #[arg(long = "todo", value_name = "TEMPLATE")]
+    pub todo_marker: Option<String>,
+    /// Whether to include the spec as a comment block at the top of Rust test
+    /// files, and report banners that don't match the spec.
+    #[arg(long, default_value_t = false)]
+    pub tree_banner: bool,
+    /// A comment to put at the top of generated files, e.g.
+    /// `Generated by bulloak v{version} from {tree}`.
+    ///
+    /// `{version}` and `{tree}` are replaced with the version of `bulloak` and
+    /// the path of the tree file. Rust files get `Generated by bulloak` by
+    /// default. With `--fix`, code above the header is kept as hand-written.
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "no_header")]
+    pub header: Option<String>,
+    /// Whether to leave out the header comment of generated files.
+    #[arg(long, default_value_t = false)]
+    pub no_header: bool,
+    /// A Solidity import to add to generated files, e.g.
+    /// `Test,StdCheats=forge-std/Test.sol` or `src/Vault.sol`. Can be passed
+    /// multiple times.
+    ///
+    /// Symbols before the `=` are imported by name. Configuring `Test`
+    /// replaces the default `forge-std` import.
+    #[arg(long = "import", value_name = "IMPORT")]
+    pub imports: Vec<String>,
+    /// A remapping applied to the paths of Solidity imports, e.g.
+    /// `forge-std/=lib/forge-std/src/`. Can be passed multiple times.
+    #[arg(
+        long = "remapping",
+        value_name = "PREFIX=PATH",
+        value_parser = crate::cli::parse_remapping
+    )]
+    pub remappings: Vec<String>,
+    /// Code to add at the start of the tests of branches with a tag, e.g.
+    /// `fork=vm.createSelectFork("mainnet");`. Can be passed multiple times.
+    ///
+    /// A test gets the snippets of the tags of its condition, of the
+    /// conditions above it and of its actions.
+    #[arg(
+        long = "snippet",
+        value_name = "TAG=CODE",
+        value_parser = crate::cli::parse_snippet
+    )]
+    pub snippets: Vec<String>,
+    /// Whether to add a comment summarizing the branch path above each test,
+    /// e.g. `// Given paused › When caller is owner`.
+    #[arg(long, default_value_t = false)]
+    pub branch_summary: bool,
+    /// Whether to document Rust tests and helpers with `///` comments holding
+    /// their branch path and actions, instead of commenting test bodies.
+    #[arg(long, default_value_t = false)]
+    pub doc_comments: bool,
+    /// The Cargo feature to gate generated Rust tests behind, e.g. `e2e`.
+    ///
+    /// The test module gets `#[cfg(all(test, feature = "e2e"))]`.
+    #[arg(long, value_name = "NAME")]
+    pub feature: Option<String>,
+    /// Whether to gate the whole generated Rust file with
+    /// `#![cfg(feature = "...")]` instead, for integration tests under
+    /// `tests/`.
+    #[arg(long, default_value_t = false, requires = "feature")]
+    pub gate_file: bool,
+    /// Whether to also reject code in Rust test files that the spec doesn't
+    /// derive: non-test functions no condition scaffolds, stray modules and
+    /// tests outside of the test module.
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+    /// The directory to load custom check rules from, as `.wasm` modules.
+    ///
+    /// No custom rule runs if the directory doesn't exist.
+    #[arg(long, value_name = "DIR", default_value = ".bulloak/rules")]
+    pub rules_dir: PathBuf,
     /// The target backend/language for checking.
     #[arg(short = 'b', long = "backend", value_enum, default_value_t = Backend::Solidity)]
     pub backend: Backend,
+    /// The options that decide the names of the tests.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub naming: Naming,
+    /// Fail on `[only]` tags, e.g. in CI, instead of narrowing the trees
+    /// down to the actions they focus.
+    #[arg(long, default_value_t = false)]
+    pub forbid_only: bool,
+    /// Expect the `testFork_` tests of branches tagged `[fork]`, which are
+    /// ignored by default since they need an RPC endpoint to run.
+    #[arg(long, default_value_t = false)]
+    pub include_fork: bool,
+    /// The number of worker threads to check files with. Defaults to the
+    /// number of available CPUs.
+    #[arg(short = 'j', long, value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+    /// Whether to report how long the slowest files took, to help find
+    /// pathological specs.
+    #[arg(long, default_value_t = false)]
+    pub timings: bool,
 }
 
 impl Default for Check {
@@ -56,196 +392,1179 @@ impl Check {
     /// Entrypoint for `bulloak check`.
     ///
     /// Note that we don't deal with `solang_parser` errors at all.
-    pub(crate) fn run(&self, cfg: &Cli) {
-        if self.backend == Backend::Rust {
-            return self.run_rust_check();
+    pub(crate) fn run(&self, cfg: &Cli) -> RunReport {
+        if let Some(root) = &self.workspace {
+            return self.check_workspace(root, cfg);
+        }
+        if self.fix && !self.plan_only && self.format == OutputFormat::Json {
+            let e = "`--format json` with `--fix` needs `--plan-only`";
+            eprintln!(
+                "{}: {e}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red())
+            );
+            return RunReport {
+                errors: vec![(PathBuf::from("--format"), e.to_owned())],
+                ..RunReport::default()
+            };
+        }
+        if self.fix && self.format == OutputFormat::JsonLines {
+            let e = "`--format json-lines` can't be combined with `--fix`";
+            eprintln!(
+                "{}: {e}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red())
+            );
+            return RunReport {
+                errors: vec![(PathBuf::from("--format"), e.to_owned())],
+                ..RunReport::default()
+            };
         }
 
-        // Solidity check
-        let mut specs = Vec::new();
-        for pattern in &self.files {
-            match expand_glob(pattern.clone()) {
-                Ok(iter) => specs.extend(iter),
-                Err(e) => eprintln!(
-                    "{}: could not expand {}: {}",
-                    "warn".yellow(),
-                    pattern.display(),
-                    e
-                ),
+        let (specs, errors) = expand_patterns(&self.files, self.strict_globs);
+        if !errors.is_empty() {
+            return RunReport { errors, ..RunReport::default() };
+        }
+        for spec in &specs {
+            if let Ok(text) = fs::read_to_string(spec) {
+                warn_ignored_comments(spec, &text);
             }
         }
 
-        let mut violations = Vec::new();
-        let ctxs: Vec<Context> = specs
-            .iter()
-            .filter_map(|tree_path| {
-                Context::new(tree_path.clone(), &cfg.into())
-                    .map_err(|violation| violations.push(violation))
-                    .ok()
-            })
-            .collect();
+        let rules = match self.rules() {
+            Ok(rules) => rules,
+            Err(report) => return *report,
+        };
 
-        if !self.fix {
-            for ctx in ctxs {
-                violations.append(&mut rules::StructuralMatcher::check(&ctx));
-            }
+        if self.backend == Backend::Rust {
+            return self.run_rust_check(specs, &rules);
+        }
 
-            return exit(&violations);
+        // Solidity check
+        if !self.fix {
+            return self.check_solidity(specs, cfg, &rules);
         }
 
+        self.fix_solidity(specs, cfg)
+    }
+
+    /// Fixes the Solidity tests of `specs`.
+    fn fix_solidity(&self, specs: Vec<PathBuf>, cfg: &Cli) -> RunReport {
+        let ctxs = self.contexts(&specs, cfg);
         let mut fixed_count = 0;
-        for mut ctx in ctxs {
+        let mut conflicts = Vec::new();
+        let mut tx = Transaction::default();
+        for ctx in ctxs {
+            // Keep the line endings of the original file when writing fixes.
+            let src = ctx.src.clone();
             let violations = rules::StructuralMatcher::check(&ctx);
-            let fixable_count =
-                violations.iter().filter(|v| v.is_fixable()).count();
 
-            // Process violations that don't affect function order first.
-            let violations = violations.iter().filter(|v| {
-                !matches!(v.kind, ViolationKind::FunctionOrderMismatch(_, _, _))
-            });
-            for violation in violations {
-                ctx = match violation.kind.fix(ctx.clone()) {
-                    Ok(ctx) => ctx,
-                    Err(e) => {
-                        eprintln!(
-                            "unable to fix \"{}\" due to:\n{}",
-                            violation.kind, e
-                        );
-                        continue;
-                    }
-                };
+            // Leave files alone if fixing them would clobber hand-written
+            // code.
+            let found = find_conflicts(&ctx, &violations);
+            if !found.is_empty() {
+                conflicts.extend(found);
+                continue;
             }
-
-            // Second pass fixing order violations.
-            let violations = rules::StructuralMatcher::check(&ctx);
-            let violations: Vec<Violation> = violations
-                .into_iter()
-                .filter(|v| {
-                    matches!(
-                        v.kind,
-                        ViolationKind::FunctionOrderMismatch(_, _, _)
-                    )
-                })
-                .collect();
-            if !violations.is_empty() {
-                if let Some(contract_sol) = find_contract(&ctx.pt) {
-                    if let Some(contract_hir) = ctx.hir.clone().find_contract()
-                    {
-                        ctx = fix_order(
-                            &violations,
-                            &contract_sol,
-                            contract_hir,
-                            ctx,
-                        );
-                    }
-                }
+            let FixedContext { ctx, fixed, failed } = fix_all(ctx);
+            for (violation, e) in failed {
+                eprintln!(
+                    "unable to fix \"{}\" due to:\n{}",
+                    violation.kind, e
+                );
             }
 
             let sol = ctx.sol.clone();
             let formatted =
                 ctx.fmt().expect("should format the emitted solidity code");
-            self.write(&formatted, sol);
+            self.write(&mut tx, &match_line_endings(&formatted, &src), sol);
 
-            fixed_count += fixable_count;
+            fixed_count += fixed;
         }
 
-        let issue_literal = pluralize(fixed_count, "issue", "issues");
-        println!(
-            "\n{}: {} {} fixed.",
-            "success".bold().green(),
-            fixed_count,
-            issue_literal
+        if !conflicts.is_empty() {
+            self.sort_by.sort_solidity(&mut conflicts);
+            report(&conflicts, self.group_by_file);
+        }
+        let mut errors = Vec::new();
+        if let Some(error) = self.apply(tx) {
+            errors.push(error);
+            fixed_count = 0;
+        }
+        if !self.plan_only {
+            let issue_literal = pluralize(fixed_count, "issue", "issues");
+            println!(
+                "\n{}: {} {} fixed.",
+                "success".if_supports_color(Stream::Stdout, |t| t
+                    .style(Style::new().bold().green())),
+                fixed_count,
+                issue_literal
+            );
+        }
+
+        RunReport {
+            files: specs,
+            errors,
+            violations: conflicts.len(),
+            fixed: fixed_count,
+            error_on: self.error_on,
+            ..RunReport::default()
+        }
+    }
+
+    /// Loads the custom rules of the rules directory, or else the report of
+    /// why they couldn't be loaded.
+    fn rules(&self) -> Result<Rules, Box<RunReport>> {
+        Rules::load(&self.rules_dir).map_err(|e| {
+            eprintln!(
+                "{}: {e}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red())
+            );
+            Box::new(RunReport {
+                errors: vec![(self.rules_dir.clone(), e.to_string())],
+                ..RunReport::default()
+            })
+        })
+    }
+
+    /// Loads the contexts of the Solidity tests of `specs`.
+    ///
+    /// Specs that can't be checked can't be fixed either, so they are left
+    /// out.
+    fn contexts(&self, specs: &[PathBuf], cfg: &Cli) -> Vec<Context> {
+        let progress = Progress::new(specs.len(), self.timings);
+        let ctxs = jobs::map(specs, self.jobs, |tree_path| {
+            progress.track(tree_path, || {
+                Context::new(tree_path.clone(), &cfg.into()).ok()
+            })
+        });
+        progress.finish();
+
+        ctxs.into_iter().flatten().collect()
+    }
+
+    /// Checks the Solidity tests of the spec at `tree_path`, printing the
+    /// violations found right away with `--format json-lines`.
+    fn check_spec(
+        &self,
+        tree_path: &Path,
+        cfg: &bulloak_foundry::config::Config,
+        rust_cfg: &bulloak_rust::Config,
+        rules: &Rules,
+        lines: Option<&JsonLines>,
+    ) -> Vec<Violation> {
+        let ctx = match Context::new(tree_path.to_path_buf(), cfg) {
+            Ok(ctx) => ctx,
+            Err(violation) => {
+                if let Some(lines) = lines {
+                    lines.print([ViolationReport::from_solidity(&violation)]);
+                }
+                return vec![violation];
+            }
+        };
+        let mut violations = rules::StructuralMatcher::check(&ctx);
+        violations.append(&mut rules::SkipPolicy::check(&ctx));
+        violations.append(&mut rules::EmitPolicy::check(&ctx));
+        violations.append(&mut rules::DescriptionPolicy::check(&ctx));
+        violations.append(&mut rules::AttributePolicy::check(&ctx));
+        let (file, findings) =
+            self.check_custom_rules(rules, tree_path, cfg, rust_cfg);
+        violations.extend(
+            findings
+                .into_iter()
+                .map(|finding| solidity_violation(&file, finding)),
         );
+        if let Some(lines) = lines {
+            lines.print(violations.iter().map(|v| {
+                let suggestion = solidity_suggestion(&ctx, v)
+                    .filter(|_| v.is_fixable())
+                    .unwrap_or_default();
+                ViolationReport::from_solidity(v).with_suggestion(suggestion)
+            }));
+        }
+
+        violations
+    }
+
+    /// Checks the Solidity tests of `specs` without fixing them.
+    fn check_solidity(
+        &self,
+        mut specs: Vec<PathBuf>,
+        cfg: &Cli,
+        rules: &Rules,
+    ) -> RunReport {
+        let cfg = cfg.into();
+        let rust_cfg = self.rust_config();
+        let limit = self.violation_limit();
+        let lines = (self.format == OutputFormat::JsonLines)
+            .then(|| JsonLines::new(limit));
+        let progress = Progress::new(specs.len(), self.timings);
+        let checked = jobs::map_until(
+            &specs,
+            self.jobs,
+            limit.unwrap_or(usize::MAX),
+            self.deadline(),
+            |(violations, _): &(Vec<_>, _)| violations.len(),
+            |tree_path| {
+                progress.track(tree_path, || {
+                    let violations = self.check_spec(
+                        tree_path,
+                        &cfg,
+                        &rust_cfg,
+                        rules,
+                        lines.as_ref(),
+                    );
+                    let coverage = coverage(tree_path, &violations, &cfg);
+                    (violations, coverage)
+                })
+            },
+        );
+        progress.finish();
+        let skipped = specs.split_off(checked.len());
+        let (mut covered_leaves, mut leaves) = (0, 0);
+        let mut violations = Vec::new();
+        for (checked, (covered, total)) in checked {
+            violations.extend(checked);
+            covered_leaves += covered;
+            leaves += total;
+        }
+        let stopped = limit.is_some_and(|limit| violations.len() >= limit);
+        violations.truncate(limit.unwrap_or(usize::MAX));
+        self.sort_by.sort_solidity(&mut violations);
+        dedup(&mut violations);
+
+        if self.format == OutputFormat::Json {
+            let suggestions = solidity_suggestions(&specs, &violations, &cfg);
+            print_json(
+                &specs,
+                &skipped,
+                violations
+                    .iter()
+                    .zip(suggestions)
+                    .map(|(violation, suggestion)| {
+                        ViolationReport::from_solidity(violation)
+                            .with_suggestion(suggestion)
+                    })
+                    .collect(),
+                &[],
+                (covered_leaves, leaves),
+            );
+        } else {
+            // JSON lines were printed along the way.
+            if lines.is_none() {
+                report(&violations, self.group_by_file);
+            }
+            if stopped {
+                report_stopped(violations.len(), skipped.len());
+            } else {
+                self.report_timed_out(&skipped);
+            }
+            if lines.is_none() {
+                report_coverage(covered_leaves, leaves);
+            }
+        }
+
+        RunReport {
+            files: specs,
+            unchecked: skipped,
+            violations: violations.len(),
+            fixable: violations.iter().filter(|v| v.is_fixable()).count(),
+            leaves,
+            covered_leaves,
+            error_on: self.error_on,
+            ..RunReport::default()
+        }
     }
 
     /// Handles writing the output of the `check` command.
     ///
     /// If the `--stdout` flag was passed, then the output is printed to
-    /// stdout, else it is written to the corresponding file.
-    fn write(&self, output: &str, sol: PathBuf) {
+    /// stdout. If `--diff` was passed, the changes to the corresponding file
+    /// are printed as a unified diff. Otherwise, the output is staged in `tx`
+    /// to be written to the file.
+    fn write(&self, tx: &mut Transaction, output: &str, sol: PathBuf) {
         if self.stdout {
-            println!("{} {}", "-->".blue(), sol.to_string_lossy());
+            println!(
+                "{} {}",
+                "-->".if_supports_color(Stream::Stdout, |t| t.blue()),
+                sol.to_string_lossy()
+            );
             println!("{}", output.trim());
-            println!("{}", "<--".blue());
-        } else if let Err(e) = fs::write(sol, output) {
-            eprintln!("{}: {e}", "warn".yellow());
+            println!(
+                "{}",
+                "<--".if_supports_color(Stream::Stdout, |t| t.blue())
+            );
+        } else if self.diff {
+            let current = fs::read_to_string(&sol).unwrap_or_default();
+            print!("{}", diff::unified(&sol, &current, output));
+        } else {
+            tx.stage(sol, output.to_owned());
         }
     }
 
-    /// Run check for Rust tests.
-    fn run_rust_check(&self) {
-        let mut specs = Vec::new();
-        for pattern in &self.files {
-            match expand_glob(pattern.clone()) {
-                Ok(iter) => specs.extend(iter),
-                Err(e) => eprintln!(
-                    "{}: could not expand {}: {}",
-                    "warn".yellow(),
-                    pattern.display(),
-                    e
-                ),
+    /// Writes the files staged in `tx`, returning the error to report if that
+    /// failed.
+    ///
+    /// With `--plan-only`, nothing is written; the edits that writing the
+    /// files would make are printed instead.
+    fn apply(&self, tx: Transaction) -> Option<(PathBuf, String)> {
+        if !self.plan_only {
+            return commit(tx);
+        }
+
+        let edits: Vec<EditReport> = tx
+            .staged()
+            .iter()
+            .flat_map(|(file, contents)| edits(file, contents))
+            .collect();
+        if self.format == OutputFormat::Json {
+            let plan = FixPlan { schema_version: SCHEMA_VERSION, edits };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&plan)
+                    .expect("should serialize the fix plan")
+            );
+        } else {
+            for edit in &edits {
+                println!(
+                    "{}:{}..{}: {:?}",
+                    edit.file.display(),
+                    edit.start,
+                    edit.end,
+                    edit.replacement
+                );
             }
         }
 
-        let rust_cfg = bulloak_rust::Config {
-            files: self.files.iter().map(|p| p.display().to_string()).collect(),
-            skip_helpers: self.skip_modifiers,
-            format_descriptions: self.format_descriptions,
+        None
+    }
+
+    /// Checks every package under `root` with the backend its manifest calls
+    /// for, then prints a summary line per package.
+    fn check_workspace(&self, root: &Path, cfg: &Cli) -> RunReport {
+        let packages = match workspace::discover(root) {
+            Ok(packages) => packages,
+            Err(e) => {
+                let error = format!("could not walk {}: {e}", root.display());
+                eprintln!(
+                    "{}: {error}",
+                    "error".if_supports_color(Stream::Stderr, |t| t.red())
+                );
+                return RunReport {
+                    errors: vec![(root.to_path_buf(), error)],
+                    ..RunReport::default()
+                };
+            }
         };
 
+        let mut total =
+            RunReport { error_on: self.error_on, ..RunReport::default() };
+        let mut lines = Vec::new();
+        // Packages share the time budget, so later packages get what's left.
+        let deadline = self.deadline();
+        for package in packages {
+            let name = package.root.strip_prefix(root).unwrap_or(&package.root);
+            let name = if name.as_os_str().is_empty() {
+                ".".to_owned()
+            } else {
+                name.display().to_string()
+            };
+            let trees = package.trees.len();
+            let backend = match package.manifest {
+                Some(Manifest::Foundry) => Backend::Solidity,
+                Some(Manifest::Cargo) => Backend::Rust,
+                Some(Manifest::Nargo) => {
+                    lines.push(format!(
+                        "{name} (noir): {trees} {} skipped, there is no Noir \
+                         backend",
+                        pluralize(trees, "tree", "trees"),
+                    ));
+                    continue;
+                }
+                None => self.backend,
+            };
+            let language = match backend {
+                Backend::Solidity => "solidity",
+                Backend::Rust => "rust",
+            };
+            let left = deadline.map(|deadline| {
+                deadline.saturating_duration_since(Instant::now()).as_secs()
+            });
+            if left == Some(0) {
+                lines.push(format!(
+                    "{name} ({language}): {trees} {} not checked, out of time",
+                    pluralize(trees, "tree", "trees"),
+                ));
+                total.unchecked.extend(package.trees);
+                continue;
+            }
+
+            println!(
+                "{} {name} ({language})",
+                "Checking".if_supports_color(Stream::Stdout, |t| t.bold())
+            );
+            let check = Check {
+                files: package.trees,
+                backend,
+                workspace: None,
+                max_duration: left.and_then(NonZeroU64::new),
+                ..self.clone()
+            };
+            let report = check.run(cfg);
+
+            lines.push(format!(
+                "{name} ({language}): {trees} {}, {}",
+                pluralize(trees, "tree", "trees"),
+                describe_package(&report),
+            ));
+
+            total.files.extend(report.files);
+            total.unchecked.extend(report.unchecked);
+            total.errors.extend(report.errors);
+            total.violations += report.violations;
+            total.fixable += report.fixable;
+            total.warnings += report.warnings;
+            total.fixed += report.fixed;
+            total.leaves += report.leaves;
+            total.covered_leaves += report.covered_leaves;
+        }
+
+        println!(
+            "\n{}",
+            "Packages:".if_supports_color(Stream::Stdout, |t| t.bold())
+        );
+        for line in lines {
+            println!("  {line}");
+        }
+
+        total
+    }
+
+    /// Checks the Rust tests of `specs`, stopping once `limit` violations
+    /// are found.
+    ///
+    /// The results cover the first specs, in order.
+    fn check_rust_specs(
+        &self,
+        specs: &[PathBuf],
+        rust_cfg: &bulloak_rust::Config,
+        limit: Option<usize>,
+        rules: &Rules,
+    ) -> Vec<bulloak_rust::Result<Vec<bulloak_rust::Violation>>> {
+        let foundry_cfg = bulloak_foundry::config::Config::default();
+        let lines = (self.format == OutputFormat::JsonLines)
+            .then(|| JsonLines::new(limit));
+        let progress = Progress::new(specs.len(), self.timings);
+        let checked = jobs::map_until(
+            specs,
+            self.jobs,
+            limit.unwrap_or(usize::MAX),
+            self.deadline(),
+            |checked: &bulloak_rust::Result<Vec<_>>| {
+                checked.as_ref().map_or(0, Vec::len)
+            },
+            |tree_path| {
+                progress.track(tree_path, || {
+                    let mut violations =
+                        bulloak_rust::check::check(tree_path, rust_cfg)?;
+                    let (file, findings) = self.check_custom_rules(
+                        rules,
+                        tree_path,
+                        &foundry_cfg,
+                        rust_cfg,
+                    );
+                    violations.extend(
+                        findings
+                            .into_iter()
+                            .map(|finding| rust_violation(&file, finding)),
+                    );
+                    if let Some(lines) = &lines {
+                        lines.print(self.rust_reports(
+                            std::slice::from_ref(tree_path),
+                            &violations,
+                            rust_cfg,
+                        ));
+                    }
+                    Ok(violations)
+                })
+            },
+        );
+        progress.finish();
+
+        checked
+    }
+
+    /// Run check for Rust tests.
+    fn run_rust_check(
+        &self,
+        mut specs: Vec<PathBuf>,
+        rules: &Rules,
+    ) -> RunReport {
+        let rust_cfg = self.rust_config();
+
         let mut all_violations = Vec::new();
-        for tree_path in specs {
-            match bulloak_rust::check::check(&tree_path, &rust_cfg) {
-                Ok(violations) => {
-                    for violation in &violations {
-                        eprintln!("{}", violation);
+        let mut errors = Vec::new();
+        let mut fixed_count = 0;
+        let mut tx = Transaction::default();
+        let limit = self.violation_limit();
+        let checked = self.check_rust_specs(&specs, &rust_cfg, limit, rules);
+        let skipped = specs.split_off(checked.len());
+        let (mut covered_leaves, mut leaves) = (0, 0);
+        for (tree_path, checked) in specs.iter().zip(checked) {
+            match checked {
+                Ok(mut violations) => {
+                    let (covered, total) = bulloak_rust::check::coverage(
+                        tree_path,
+                        &violations,
+                        &rust_cfg,
+                    );
+                    covered_leaves += covered;
+                    leaves += total;
+                    if let Some(limit) = limit {
+                        violations.truncate(limit - all_violations.len());
+                    }
+                    if self.fix
+                        && violations.iter().any(|v| v.kind.is_fixable())
+                    {
+                        let fixable: Vec<_>;
+                        (fixable, violations) = violations
+                            .into_iter()
+                            .partition(|v| v.kind.is_fixable());
+                        match self
+                            .fix_rust(&mut tx, tree_path, &fixable, &rust_cfg)
+                        {
+                            Ok(()) => fixed_count += fixable.len(),
+                            Err(e) => errors.push(fix_failed(tree_path, &e)),
+                        }
                     }
                     all_violations.extend(violations);
                 }
-                Err(e) => {
-                    eprintln!(
-                        "{}: Failed to check {}: {}",
-                        "error".red(),
-                        tree_path.display(),
-                        e
-                    );
-                }
+                Err(e) => errors.push(self.check_failed(tree_path, &e)),
             }
         }
 
-        if all_violations.is_empty() {
-            println!(
-                "{}",
-                "All checks completed successfully! No issues found.".green()
+        if let Some(error) = self.apply(tx) {
+            errors.push(error);
+            fixed_count = 0;
+        }
+        let stopped = limit
+            .is_some_and(|limit| all_violations.len() >= limit)
+            .then_some(skipped.len());
+        self.sort_by.sort_rust(&mut all_violations);
+        dedup(&mut all_violations);
+        if self.plan_only {
+            // The plan is all there is to print.
+        } else if self.format == OutputFormat::Json {
+            let reports = self.rust_reports(&specs, &all_violations, &rust_cfg);
+            print_json(
+                &specs,
+                &skipped,
+                reports,
+                &errors,
+                (covered_leaves, leaves),
             );
+        } else if self.format == OutputFormat::JsonLines {
+            // The violations were printed along the way.
+            match stopped {
+                Some(skipped) => report_stopped(all_violations.len(), skipped),
+                None => self.report_timed_out(&skipped),
+            }
         } else {
-            let check_literal = pluralize(all_violations.len(), "check", "checks");
+            report_rust(
+                &all_violations,
+                fixed_count,
+                stopped,
+                self.warnings_as_errors,
+                self.group_by_file,
+            );
+            if stopped.is_none() {
+                self.report_timed_out(&skipped);
+            }
+            if !self.fix {
+                report_coverage(covered_leaves, leaves);
+            }
+        }
+
+        RunReport {
+            files: specs,
+            unchecked: skipped,
+            errors,
+            violations: all_violations.len(),
+            fixable: all_violations
+                .iter()
+                .filter(|v| v.kind.is_fixable())
+                .count(),
+            warnings: self.count_warnings(&all_violations),
+            fixed: fixed_count,
+            leaves,
+            covered_leaves,
+            error_on: self.error_on,
+            ..RunReport::default()
+        }
+    }
+
+    /// Reports that checking the tree at `tree_path` failed, unless the
+    /// report is a JSON document, returning the error to add to the run
+    /// report.
+    fn check_failed(
+        &self,
+        tree_path: &Path,
+        e: &bulloak_rust::Error,
+    ) -> (PathBuf, String) {
+        if self.format != OutputFormat::Json {
             eprintln!(
-                "\n{}: {} {} failed",
-                "warn".bold().yellow(),
-                all_violations.len(),
-                check_literal
+                "{}: Failed to check {}: {}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red()),
+                tree_path.display(),
+                e
+            );
+        }
+
+        (tree_path.to_path_buf(), e.to_string())
+    }
+
+    /// How many of `violations` are warnings that don't fail the command.
+    fn count_warnings(&self, violations: &[bulloak_rust::Violation]) -> usize {
+        if self.warnings_as_errors {
+            return 0;
+        }
+
+        violations
+            .iter()
+            .filter(|v| v.kind.severity() == Severity::Warning)
+            .count()
+    }
+
+    /// Runs the custom `rules` on the spec at `tree_path`, returning its test
+    /// file alongside what the rules found.
+    ///
+    /// Specs that don't parse are left to the built-in rules to report.
+    fn check_custom_rules(
+        &self,
+        rules: &Rules,
+        tree_path: &Path,
+        foundry_cfg: &bulloak_foundry::config::Config,
+        rust_cfg: &bulloak_rust::Config,
+    ) -> (PathBuf, Vec<Finding>) {
+        if rules.is_empty() {
+            return (PathBuf::new(), Vec::new());
+        }
+
+        match Facts::gather(tree_path, self.backend, foundry_cfg, rust_cfg) {
+            Ok(facts) => {
+                let findings = rules.check(&facts);
+                (facts.test_file, findings)
+            }
+            Err(_) => (PathBuf::new(), Vec::new()),
+        }
+    }
+
+    /// When to stop checking new files, if `--max-duration` was passed.
+    fn deadline(&self) -> Option<Instant> {
+        self.max_duration
+            .map(|secs| Instant::now() + Duration::from_secs(secs.get()))
+    }
+
+    /// Tells that checking ran out of time, listing the `skipped` files, if
+    /// it did.
+    fn report_timed_out(&self, skipped: &[PathBuf]) {
+        let Some(secs) = self.max_duration.filter(|_| !skipped.is_empty())
+        else {
+            return;
+        };
+        eprintln!(
+            "{}: stopped after {secs}s; {} {} not checked:",
+            "note".if_supports_color(Stream::Stderr, |t| t.blue()),
+            skipped.len(),
+            pluralize(skipped.len(), "file was", "files were"),
+        );
+        for file in skipped {
+            eprintln!("  {}", file.display());
+        }
+    }
+
+    /// The number of violations after which checking stops, if any.
+    fn violation_limit(&self) -> Option<usize> {
+        if self.fail_fast {
+            Some(1)
+        } else {
+            self.max_violations.map(NonZeroUsize::get)
+        }
+    }
+
+    /// Maps the flags of `bulloak check` to the Rust backend's config.
+    fn rust_config(&self) -> bulloak_rust::Config {
+        bulloak_rust::Config {
+            files: self.files.clone(),
+            skip_helpers: self.skip_modifiers,
+            format_descriptions: self.format_descriptions,
+            assertion_stubs: self.assertion_stubs,
+            snapshot_stubs: self.snapshot_stubs,
+            todo_marker: self.todo_marker.clone(),
+            doc_comments: self.doc_comments,
+            branch_summary: self.branch_summary,
+            tree_banner: self.tree_banner,
+            header: header(
+                self.header.as_ref(),
+                self.no_header,
+                Some(DEFAULT_HEADER),
+            ),
+            forbid_only: self.forbid_only,
+            metadata: Vec::new(),
+            feature: self.feature.clone(),
+            gate_file: self.gate_file,
+            strict: self.strict,
+            snippets: pairs(&self.snippets),
+            ..self.naming.rust_config()
+        }
+    }
+
+    /// The JSON reports of the Rust `violations`, along with the edits that
+    /// fix each of them.
+    fn rust_reports(
+        &self,
+        specs: &[PathBuf],
+        violations: &[bulloak_rust::Violation],
+        cfg: &bulloak_rust::Config,
+    ) -> Vec<ViolationReport> {
+        violations
+            .iter()
+            .map(|violation| {
+                let suggestion = self.rust_suggestion(specs, violation, cfg);
+                ViolationReport::from_rust(violation)
+                    .with_suggestion(suggestion)
+            })
+            .collect()
+    }
+
+    /// The edits that fix the Rust `violation` on its own, if it is fixable
+    /// and belongs to one of `specs`.
+    fn rust_suggestion(
+        &self,
+        specs: &[PathBuf],
+        violation: &bulloak_rust::Violation,
+        cfg: &bulloak_rust::Config,
+    ) -> Vec<EditReport> {
+        if !violation.kind.is_fixable() {
+            return Vec::new();
+        }
+        let tree_path = specs.iter().find(|tree| {
+            bulloak_rust::scaffold::test_file_path(tree) == violation.file_path
+        });
+        let Some(tree_path) = tree_path else { return Vec::new() };
+
+        let mut tx = Transaction::default();
+        let fixed = self.fix_rust(
+            &mut tx,
+            tree_path,
+            std::slice::from_ref(violation),
+            cfg,
+        );
+        if fixed.is_err() {
+            return Vec::new();
+        }
+        tx.staged()
+            .iter()
+            .flat_map(|(file, contents)| edits(file, contents))
+            .collect()
+    }
+
+    /// Fixes the `violations` found for the tree at `tree_path`.
+    ///
+    /// A missing test file is scaffolded, which leaves nothing else to fix.
+    /// Otherwise, the anchored test functions of the existing file are
+    /// renamed, empty unused helpers are removed, the tests are put in spec
+    /// order, their description comments are formatted and the spec banner
+    /// is brought up to date.
+    fn fix_rust(
+        &self,
+        tx: &mut Transaction,
+        tree_path: &Path,
+        violations: &[bulloak_rust::Violation],
+        cfg: &bulloak_rust::Config,
+    ) -> anyhow::Result<()> {
+        use bulloak_rust::ViolationKind;
+
+        let cfg = cfg.for_tree(tree_path);
+        let text = read_spec(tree_path)?;
+        let file = bulloak_rust::scaffold::test_file_path(tree_path);
+        let has =
+            |kind: ViolationKind| violations.iter().any(|v| v.kind == kind);
+        if has(ViolationKind::RustFileMissing) {
+            let emitted = bulloak_rust::scaffold_tree(&text, &cfg)?;
+            self.write(tx, &match_line_endings(&emitted, &text), file);
+            return Ok(());
+        }
+
+        let mut source = fs::read_to_string(&file)?;
+        for violation in violations {
+            if let ViolationKind::TestFunctionNameMismatch { expected, found } =
+                &violation.kind
+            {
+                source = bulloak_rust::check::rename_test(
+                    &source, found, expected, &cfg,
+                )?;
+            }
+        }
+        let unused: Vec<_> = violations
+            .iter()
+            .filter_map(|v| match &v.kind {
+                ViolationKind::HelperFunctionUnused { name, .. } => {
+                    Some(name.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        if !unused.is_empty() {
+            source =
+                bulloak_rust::check::remove_helpers(&source, &unused, &cfg)?;
+        }
+        if has(ViolationKind::TestOrderIncorrect) {
+            let ast = bulloak_syntax::cache::parse_one_with(
+                &text,
+                &cfg.parse_options(),
+            )?;
+            source = bulloak_rust::check::fix_order(&ast, &source, &cfg)?;
+        }
+        let misdescribed = violations.iter().any(|v| {
+            matches!(v.kind, ViolationKind::DescriptionIncorrect { .. })
+        });
+        if misdescribed {
+            let ast = bulloak_syntax::cache::parse_one_with(
+                &text,
+                &cfg.parse_options(),
+            )?;
+            source =
+                bulloak_rust::check::fix_descriptions(&ast, &source, &cfg)?;
+        }
+        if has(ViolationKind::TreeBannerOutdated) {
+            let fixed = bulloak_rust::scaffold::banner::replace(
+                &source,
+                &text,
+                cfg.header().as_deref(),
+            );
+            source = match_line_endings(&fixed, &source);
+        }
+        self.write(tx, &source, file);
+
+        Ok(())
+    }
+}
+
+/// Returns the edits that turn the file at `file` into `contents`.
+///
+/// A file that doesn't exist is created with a single edit.
+fn edits(file: &Path, contents: &str) -> Vec<EditReport> {
+    let current = fs::read_to_string(file).unwrap_or_default();
+    diff::edits(&current, contents)
+        .into_iter()
+        .map(|(range, replacement)| EditReport {
+            file: file.to_path_buf(),
+            start: range.start,
+            end: range.end,
+            replacement,
+        })
+        .collect()
+}
+
+/// The edits that fix each of the Solidity `violations` of `specs` on its
+/// own, in the same order.
+///
+/// Violations that can't be fixed, or whose fix would clobber hand-written
+/// code, get no edits.
+fn solidity_suggestions(
+    specs: &[PathBuf],
+    violations: &[Violation],
+    cfg: &bulloak_foundry::config::Config,
+) -> Vec<Vec<EditReport>> {
+    let ctxs: Vec<Context> = if violations.iter().any(Violation::is_fixable) {
+        specs
+            .iter()
+            .filter_map(|tree| Context::new(tree.clone(), cfg).ok())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    violations
+        .iter()
+        .map(|violation| {
+            // Violations are located in the test file, or in the spec if
+            // the test file lacks what the spec calls for.
+            let file = PathBuf::from(violation.location.file());
+            ctxs.iter()
+                .find(|ctx| ctx.sol == file || ctx.tree == file)
+                .filter(|_| violation.is_fixable())
+                .and_then(|ctx| solidity_suggestion(ctx, violation))
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The edits that fix the Solidity `violation` found in `ctx` on its own.
+fn solidity_suggestion(
+    ctx: &Context,
+    violation: &Violation,
+) -> Option<Vec<EditReport>> {
+    let violation = std::slice::from_ref(violation);
+    if !find_conflicts(ctx, violation).is_empty() {
+        return None;
+    }
+
+    let fixed = match violation[0].kind {
+        ViolationKind::FunctionOrderMismatch(..) => {
+            let contract_sol = ctx.find_contract()?;
+            let hir = ctx.hir.clone();
+            let contract_hir = hir.find_contract()?;
+            fix_order(violation, &contract_sol, contract_hir, ctx.clone())
+        }
+        ref kind => kind.fix(ctx.clone()).ok()?,
+    };
+    let formatted = fixed.fmt().ok()?;
+    Some(edits(&ctx.sol, &match_line_endings(&formatted, &ctx.src)))
+}
+
+/// Turns what a custom rule found in the Solidity test `file` into a
+/// violation.
+fn solidity_violation(file: &Path, finding: Finding) -> Violation {
+    let file = file.to_string_lossy().into_owned();
+    let location = match finding.line {
+        Some(line) => Location::Code(file, line),
+        None => Location::File(file),
+    };
+
+    Violation::new(
+        ViolationKind::CustomRuleViolated(finding.rule, finding.message),
+        location,
+    )
+}
+
+/// Turns what a custom rule found in the Rust test `file` into a violation.
+fn rust_violation(file: &Path, finding: Finding) -> bulloak_rust::Violation {
+    let kind = bulloak_rust::ViolationKind::CustomRuleViolated {
+        rule: finding.rule,
+        message: finding.message,
+    };
+    match finding.line {
+        Some(line) => {
+            bulloak_rust::Violation::with_line(kind, file.to_path_buf(), line)
+        }
+        None => bulloak_rust::Violation::new(kind, file.to_path_buf()),
+    }
+}
+
+/// Reports that fixing the tree at `tree_path` failed, returning the error to
+/// add to the run report.
+fn fix_failed(tree_path: &Path, e: &anyhow::Error) -> (PathBuf, String) {
+    eprintln!(
+        "{}: Failed to fix {}: {}",
+        "error".if_supports_color(Stream::Stderr, |t| t.red()),
+        tree_path.display(),
+        e
+    );
+
+    (tree_path.to_path_buf(), e.to_string())
+}
+
+/// Writes the files staged in `tx`, returning the error to report if that
+/// failed.
+fn commit(tx: Transaction) -> Option<(PathBuf, String)> {
+    let error = tx.commit().err()?;
+    eprintln!(
+        "{}: {error}",
+        "error".if_supports_color(Stream::Stderr, |t| t.red())
+    );
+
+    Some((error.path, error.error.to_string()))
+}
+
+/// Prints the outcome of checking Rust tests.
+///
+/// `stopped` holds the number of files left unchecked if checking stopped
+/// early.
+///
+/// Violations are labeled with their severity, and warnings are counted apart
+/// from failed checks unless `warnings_as_errors` is set. They are grouped
+/// under a header per file if `grouped` is set.
+fn report_rust(
+    violations: &[bulloak_rust::Violation],
+    fixed_count: usize,
+    stopped: Option<usize>,
+    warnings_as_errors: bool,
+    grouped: bool,
+) {
+    let severity = |violation: &bulloak_rust::Violation| {
+        if warnings_as_errors {
+            Severity::Error
+        } else {
+            violation.kind.severity()
+        }
+    };
+    let label = |violation: &bulloak_rust::Violation| match severity(violation)
+    {
+        Severity::Error => {
+            "error".if_supports_color(Stream::Stderr, |t| t.red()).to_string()
+        }
+        Severity::Warning => "warning"
+            .if_supports_color(Stream::Stderr, |t| t.yellow())
+            .to_string(),
+    };
+    if grouped {
+        report_grouped(violations.iter().map(|violation| {
+            let file = violation.file_path.display().to_string();
+            let message = format!("{}: {}", label(violation), violation.kind);
+            (file, violation.line, message)
+        }));
+    } else {
+        for violation in violations {
+            eprintln!("{}: {violation}", label(violation));
+        }
+    }
+    if fixed_count > 0 {
+        let issue_literal = pluralize(fixed_count, "issue", "issues");
+        println!(
+            "\n{}: {} {} fixed.",
+            "success".if_supports_color(Stream::Stdout, |t| t
+                .style(Style::new().bold().green())),
+            fixed_count,
+            issue_literal
+        );
+    } else if violations.is_empty() {
+        println!(
+            "{}",
+            "All checks completed successfully! No issues found."
+                .if_supports_color(Stream::Stdout, |t| t.green())
+        );
+    } else {
+        let warnings = violations
+            .iter()
+            .filter(|v| severity(v) == Severity::Warning)
+            .count();
+        let failed = violations.len() - warnings;
+        let warn = "warn".if_supports_color(Stream::Stderr, |t| {
+            t.style(Style::new().bold().yellow())
+        });
+        eprintln!();
+        if failed > 0 {
+            let check_literal = pluralize(failed, "check", "checks");
+            eprintln!("{warn}: {failed} {check_literal} failed");
+        }
+        if warnings > 0 {
+            let warning_literal = pluralize(warnings, "warning", "warnings");
+            eprintln!("{warn}: {warnings} {warning_literal}");
+        }
+        report_summary(violations.iter().map(|v| v.kind.category()));
+        if let Some(skipped) = stopped {
+            report_stopped(violations.len(), skipped);
+        }
+    }
+}
+
+/// Prints the results of `bulloak check` as a single JSON document to
+/// stdout.
+///
+/// `unchecked` holds the files left unchecked if checking stopped early. The
+/// last argument holds the number of covered leaves and of leaves.
+fn print_json(
+    files: &[PathBuf],
+    unchecked: &[PathBuf],
+    violations: Vec<ViolationReport>,
+    errors: &[(PathBuf, String)],
+    (covered_leaves, leaves): (usize, usize),
+) {
+    let report = CheckReport {
+        schema_version: SCHEMA_VERSION,
+        files: files.to_vec(),
+        unchecked: unchecked.to_vec(),
+        violations,
+        errors: errors
+            .iter()
+            .map(|(file, message)| ErrorReport {
+                file: file.clone(),
+                message: message.clone(),
+            })
+            .collect(),
+        leaves,
+        covered_leaves,
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report)
+            .expect("should serialize the check report")
+    );
+}
+
+/// Prints violations as JSON lines to stdout as soon as they're found, for
+/// `--format json-lines`.
+///
+/// Specs are checked in parallel, so the lines of a spec are printed together
+/// and the limit of violations is shared between them.
+struct JsonLines {
+    /// How many more violations may be printed.
+    remaining: Mutex<usize>,
+}
+
+impl JsonLines {
+    fn new(limit: Option<usize>) -> Self {
+        Self { remaining: Mutex::new(limit.unwrap_or(usize::MAX)) }
+    }
+
+    /// Prints a line for each of `violations`, while the limit allows it.
+    fn print(&self, violations: impl IntoIterator<Item = ViolationReport>) {
+        let mut remaining =
+            self.remaining.lock().unwrap_or_else(PoisonError::into_inner);
+        for violation in violations.into_iter().take(*remaining) {
+            println!(
+                "{}",
+                serde_json::to_string(&violation)
+                    .expect("should serialize the violation")
             );
-            std::process::exit(1);
+            *remaining -= 1;
         }
     }
 }
 
-fn exit(violations: &[Violation]) {
+/// Tells that `bulloak check` stopped after finding `count` violations,
+/// leaving `skipped` files unchecked.
+fn report_stopped(count: usize, skipped: usize) {
+    eprintln!(
+        "{}: stopped after {count} {}; {skipped} {} not checked",
+        "note".if_supports_color(Stream::Stderr, |t| t.blue()),
+        pluralize(count, "violation", "violations"),
+        pluralize(skipped, "file was", "files were"),
+    );
+}
+
+/// Prints the violations found by `bulloak check`.
+fn report(violations: &[Violation], grouped: bool) {
     if violations.is_empty() {
         println!(
             "{}",
-            "All checks completed successfully! No issues found.".green()
+            "All checks completed successfully! No issues found."
+                .if_supports_color(Stream::Stdout, |t| t.green())
         );
     } else {
-        for violation in violations {
-            eprintln!("{violation}");
+        if grouped {
+            report_grouped(violations.iter().map(|violation| {
+                let line = match violation.location {
+                    Location::Code(_, line) => Some(line),
+                    Location::File(_) => None,
+                };
+                let warn =
+                    "warn".if_supports_color(Stream::Stderr, |t| t.yellow());
+                let message = format!("{warn}: {}", violation.kind);
+                (violation.location.file(), line, message)
+            }));
+            eprintln!();
+        } else {
+            for violation in violations {
+                eprintln!("{violation}");
+            }
         }
 
         let check_literal = pluralize(violations.len(), "check", "checks");
         eprint!(
             "{}: {} {} failed",
-            "warn".bold().yellow(),
+            "warn".if_supports_color(Stream::Stderr, |t| t
+                .style(Style::new().bold().yellow())),
             violations.len(),
             check_literal
         );
@@ -259,7 +1578,215 @@ fn exit(violations: &[Violation]) {
         } else {
             eprintln!();
         }
+        report_summary(violations.iter().map(|v| v.kind.category()));
+    }
+}
+
+/// Prints violations under a header per file, given the file, the line if
+/// known, and the message of each, keeping the order files first appear in.
+fn report_grouped(
+    violations: impl IntoIterator<Item = (String, Option<usize>, String)>,
+) {
+    let mut files: Vec<(String, Vec<String>)> = Vec::new();
+    for (file, line, message) in violations {
+        let entry = match line {
+            Some(line) => format!("  {line:>4}: {message}"),
+            None => format!("        {message}"),
+        };
+        match files.iter_mut().find(|(f, _)| *f == file) {
+            Some((_, entries)) => entries.push(entry),
+            None => files.push((file, vec![entry])),
+        }
+    }
+
+    for (file, entries) in files {
+        eprintln!("{}", file.if_supports_color(Stream::Stderr, |t| t.bold()));
+        for entry in entries {
+            eprintln!("{entry}");
+        }
+    }
+}
+
+/// Drops the violations identical to one before them, e.g. when several
+/// specs point at the same test file.
+fn dedup<T: PartialEq>(violations: &mut Vec<T>) {
+    let mut kept: Vec<T> = Vec::with_capacity(violations.len());
+    for violation in violations.drain(..) {
+        if !kept.contains(&violation) {
+            kept.push(violation);
+        }
+    }
+    *violations = kept;
+}
+
+/// Prints how many of the `leaves` of the checked specs have a test, unless
+/// there are none.
+fn report_coverage(covered: usize, leaves: usize) {
+    if leaves > 0 {
+        println!(
+            "{}: {}",
+            "coverage".if_supports_color(Stream::Stdout, |t| t.bold()),
+            describe_coverage(covered, leaves)
+        );
+    }
+}
+
+/// Describes the results of checking a package, e.g.
+/// `2 violations, 1 error, 87% of 230 spec leaves have tests`.
+fn describe_package(report: &RunReport) -> String {
+    let mut line = format!(
+        "{} {}",
+        report.violations,
+        pluralize(report.violations, "violation", "violations"),
+    );
+    if !report.errors.is_empty() {
+        let errors = report.errors.len();
+        let noun = pluralize(errors, "error", "errors");
+        let _ = write!(line, ", {errors} {noun}");
+    }
+    if report.leaves > 0 {
+        let coverage = describe_coverage(report.covered_leaves, report.leaves);
+        let _ = write!(line, ", {coverage}");
+    }
+    line
+}
+
+/// Describes how many of `leaves` have a test, e.g.
+/// `87% of 230 spec leaves have tests`.
+///
+/// The percentage is rounded down, so it only reaches 100% once every leaf
+/// has a test.
+fn describe_coverage(covered: usize, leaves: usize) -> String {
+    let percent = (covered * 100).checked_div(leaves).unwrap_or(100);
+    let (noun, verb) =
+        if leaves == 1 { ("leaf", "has") } else { ("leaves", "have") };
+    format!("{percent}% of {leaves} spec {noun} {verb} tests")
+}
+
+/// Prints how many violations of each category `bulloak check` found.
+fn report_summary(
+    categories: impl IntoIterator<Item = (&'static str, &'static str)>,
+) {
+    eprintln!(
+        "{}: {}",
+        "summary".if_supports_color(Stream::Stderr, |t| t.bold()),
+        summarize(categories)
+    );
+}
+
+/// Counts the violations in each category and lists the categories from the
+/// most to the least frequent, e.g.
+/// `12 missing tests, 3 attribute mismatches, 1 order issue`.
+fn summarize(
+    categories: impl IntoIterator<Item = (&'static str, &'static str)>,
+) -> String {
+    let mut counts: Vec<((&str, &str), usize)> = Vec::new();
+    for category in categories {
+        match counts.iter_mut().find(|(c, _)| *c == category) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((category, 1)),
+        }
+    }
+    // The sort is stable, so ties keep their order of appearance.
+    counts.sort_by_key(|(_, count)| Reverse(*count));
 
-        std::process::exit(1);
+    counts
+        .into_iter()
+        .map(|((singular, plural), count)| {
+            format!("{count} {}", pluralize(count, singular, plural))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use bulloak_rust::{Violation, ViolationKind};
+
+    use super::{dedup, describe_coverage, summarize, SortBy};
+
+    #[test]
+    fn dedups_identical_violations() {
+        let mut violations = vec![3, 1, 3, 2, 1];
+        dedup(&mut violations);
+        assert_eq!(violations, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn describes_coverage() {
+        assert_eq!(
+            describe_coverage(200, 230),
+            "86% of 230 spec leaves have tests"
+        );
+        assert_eq!(
+            describe_coverage(229, 230),
+            "99% of 230 spec leaves have tests"
+        );
+        assert_eq!(describe_coverage(1, 1), "100% of 1 spec leaf has tests");
+    }
+
+    #[test]
+    fn summarizes_categories_by_frequency() {
+        let test = ("missing test", "missing tests");
+        let order = ("order issue", "order issues");
+        let attribute = ("attribute mismatch", "attribute mismatches");
+        let categories = [order, test, attribute, test, attribute, test];
+
+        assert_eq!(
+            summarize(categories),
+            "3 missing tests, 2 attribute mismatches, 1 order issue"
+        );
+        assert_eq!(summarize([]), "");
+    }
+
+    #[test]
+    fn sorts_rust_violations() {
+        let unused = ViolationKind::HelperFunctionUnused {
+            name: "bar".to_owned(),
+            empty: true,
+        };
+        let mut violations = vec![
+            Violation::with_line(unused, PathBuf::from("b_test.rs"), 3),
+            Violation::with_line(
+                ViolationKind::TestOrderIncorrect,
+                PathBuf::from("b_test.rs"),
+                1,
+            ),
+            Violation::new(
+                ViolationKind::RustFileMissing,
+                PathBuf::from("c_test.rs"),
+            ),
+            Violation::with_line(
+                ViolationKind::TestOrderIncorrect,
+                PathBuf::from("a_test.rs"),
+                7,
+            ),
+        ];
+        let order = |violations: &[Violation]| {
+            violations
+                .iter()
+                .map(|v| {
+                    format!("{}:{}", v.file_path.display(), v.line.unwrap_or(0))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        SortBy::File.sort_rust(&mut violations);
+        assert_eq!(
+            order(&violations),
+            ["a_test.rs:7", "b_test.rs:1", "b_test.rs:3", "c_test.rs:0"]
+        );
+        SortBy::Severity.sort_rust(&mut violations);
+        assert_eq!(
+            order(&violations),
+            ["a_test.rs:7", "b_test.rs:1", "c_test.rs:0", "b_test.rs:3"]
+        );
+        SortBy::Kind.sort_rust(&mut violations);
+        assert_eq!(
+            order(&violations),
+            ["c_test.rs:0", "a_test.rs:7", "b_test.rs:1", "b_test.rs:3"]
+        );
     }
 }
@@ -1,10 +1,42 @@
 //! `bulloak`'s CLI config.
-use clap::{Parser, Subcommand, ValueEnum};
-use figment::{providers::Serialized, Figment};
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use bulloak_foundry::config::SolidityImport;
+use bulloak_syntax::{tokenizer::DEFAULT_TAB_WIDTH, Keywords};
+use clap::{
+    parser::ValueSource, ArgMatches, Args, CommandFactory, FromArgMatches,
+    Parser, Subcommand, ValueEnum,
+};
+use figment::{
+    providers::{Env, Format, Serialized, Toml},
+    value::{Dict, Map, Value},
+    Figment, Metadata, Profile, Provider,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::api::RunReport;
+
+/// The prefix of the environment variables that configure `bulloak`.
+const ENV_PREFIX: &str = "BULLOAK_";
+/// The file in the current directory that configures `bulloak`.
+const CONFIG_FILE: &str = "bulloak.toml";
+
 /// The target backend/language for code generation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Backend {
     /// Solidity (Foundry) backend.
@@ -14,6 +46,48 @@ pub enum Backend {
     Rust,
 }
 
+/// When to use colors in the output.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Use colors when writing to a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+}
+
+impl ColorChoice {
+    /// Makes every colored output of `bulloak` follow this choice.
+    fn apply(self) {
+        match self {
+            Self::Always => owo_colors::set_override(true),
+            Self::Never => owo_colors::set_override(false),
+            // See https://no-color.org.
+            Self::Auto
+                if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) =>
+            {
+                owo_colors::set_override(false);
+            }
+            // Colors are enabled per stream, depending on whether it's a
+            // terminal.
+            Self::Auto => owo_colors::unset_override(),
+        }
+    }
+}
+
 /// `bulloak`'s configuration.
 #[derive(Parser, Debug, Clone, Default, Serialize, Deserialize)]
 #[command(author, version, about, long_about = None)] // Read from `Cargo.toml`
@@ -21,6 +95,18 @@ pub struct Cli {
     /// `bulloak`'s commands.
     #[clap(subcommand)]
     pub command: Commands,
+    /// When to use colors in the output.
+    ///
+    /// `auto` disables colors when the output is not a terminal or when the
+    /// `NO_COLOR` environment variable is set.
+    #[arg(long, value_enum, value_name = "WHEN", global = true, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+    /// Disable colors in the output. Same as `--color never`.
+    #[arg(long, global = true, conflicts_with = "color")]
+    pub no_color: bool,
+    /// Read options from this file instead of looking for a `bulloak.toml`.
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
 }
 
 /// `bulloak`'s commands.
@@ -32,6 +118,21 @@ pub enum Commands {
     /// `bulloak check`.
     #[command(name = "check")]
     Check(crate::check::Check),
+    /// `bulloak run`.
+    #[command(name = "run")]
+    Run(crate::run::Run),
+    /// `bulloak docs`.
+    #[command(name = "docs")]
+    Docs(crate::docs::Docs),
+    /// `bulloak impact`.
+    #[command(name = "impact")]
+    Impact(crate::impact::Impact),
+    /// `bulloak explain`.
+    #[command(name = "explain")]
+    Explain(crate::explain::Explain),
+    /// `bulloak capabilities`.
+    #[command(name = "capabilities")]
+    Capabilities(crate::capabilities::ShowCapabilities),
 }
 
 impl Default for Commands {
@@ -40,6 +141,100 @@ impl Default for Commands {
     }
 }
 
+/// The options that decide what generated tests are named and how they're
+/// found, shared by every command that pairs specs with tests.
+#[derive(Debug, Clone, Args, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Naming {
+    /// Additional attributes that mark a Rust function as a test, e.g.
+    /// `my_harness::test`. Can be passed multiple times.
+    ///
+    /// `#[test]`, `#[tokio::test]`, `#[async_std::test]`, `#[rstest]`,
+    /// `#[test_case]` and `#[googletest::test]` are always recognized.
+    #[arg(long = "test-attribute", value_name = "PATH")]
+    pub test_attributes: Vec<String>,
+    /// Prefix test names with their zero-padded index in the spec, e.g.
+    /// `test_001_...`, so that alphabetical sorting keeps spec order.
+    #[arg(long, default_value_t = false)]
+    pub ordering_prefix: bool,
+    /// Keep the "when"/"given" prefix of conditions in Rust helper and test
+    /// names, e.g. `when_amount_is_zero` instead of `amount_is_zero`.
+    #[arg(long, default_value_t = false)]
+    pub keep_condition_prefix: bool,
+    /// Give each action of a condition with several actions its own Rust
+    /// test, e.g. `test_when_paused_should_revert`, instead of a single
+    /// test describing all of them.
+    #[arg(long, default_value_t = false)]
+    pub split_actions: bool,
+    /// Keep an existing Rust test name that doesn't follow `bulloak`'s
+    /// conventions, e.g. `vault.tree:test_when_paused=paused_reverts`. Can be
+    /// passed multiple times.
+    ///
+    /// The tree matches the trailing components of the path of a spec, and
+    /// the test `bulloak` would name `test_when_paused` is expected under the
+    /// existing name instead.
+    #[arg(
+        long = "rename",
+        value_name = "TREE:GENERATED=EXISTING",
+        value_parser = parse_rename
+    )]
+    pub renames: Vec<String>,
+    /// Add a prefix to the names of Rust helpers, e.g. `setup_` for
+    /// `setup_amount_is_zero`, so they don't collide with functions imported
+    /// into the test scope.
+    #[arg(long, value_name = "PREFIX", default_value = "")]
+    pub helper_prefix: String,
+    /// Add a suffix to the names of Rust helpers, e.g. `_state` for
+    /// `amount_is_zero_state`.
+    #[arg(long, value_name = "SUFFIX", default_value = "")]
+    pub helper_suffix: String,
+    /// Nest the Rust tests of each top-level condition in a module named
+    /// after it, e.g. `mod when_paused`, instead of disambiguating clashing
+    /// test names with the names of their ancestors.
+    #[arg(long, default_value_t = false)]
+    pub group_modules: bool,
+    /// Extra words that start a condition, e.g. `cuando`. Can be passed
+    /// multiple times.
+    ///
+    /// `when` and `given` are always recognized.
+    #[arg(long = "condition-keyword", value_name = "WORD")]
+    pub condition_keywords: Vec<String>,
+    /// Extra words that start an action, e.g. `debería`. Can be passed
+    /// multiple times.
+    ///
+    /// `it` is always recognized.
+    #[arg(long = "action-keyword", value_name = "WORD")]
+    pub action_keywords: Vec<String>,
+    /// The number of columns a tab stands for when indenting `.tree` files.
+    #[arg(long, value_name = "WIDTH", default_value_t = DEFAULT_TAB_WIDTH)]
+    pub tab_width: usize,
+}
+
+impl Naming {
+    /// The extra keyword set of `--condition-keyword` and `--action-keyword`.
+    pub(crate) fn keywords(&self) -> Keywords {
+        keywords(&self.condition_keywords, &self.action_keywords)
+    }
+
+    /// Maps the naming options to the Rust backend's config, which commands
+    /// extend with their own options.
+    pub(crate) fn rust_config(&self) -> bulloak_rust::Config {
+        bulloak_rust::Config {
+            keep_condition_prefix: self.keep_condition_prefix,
+            split_actions: self.split_actions,
+            helper_prefix: self.helper_prefix.clone(),
+            helper_suffix: self.helper_suffix.clone(),
+            group_modules: self.group_modules,
+            ordering_prefix: self.ordering_prefix,
+            test_attributes: self.test_attributes.clone(),
+            tab_width: self.tab_width,
+            keywords: self.keywords(),
+            renames: renames(&self.renames),
+            ..Default::default()
+        }
+    }
+}
+
 impl From<&Cli> for bulloak_foundry::config::Config {
     fn from(cli: &Cli) -> Self {
         match &cli.command {
@@ -49,27 +244,390 @@ impl From<&Cli> for bulloak_foundry::config::Config {
                 emit_vm_skip: cmd.with_vm_skip,
                 skip_modifiers: cmd.skip_modifiers,
                 format_descriptions: cmd.format_descriptions,
+                emit_assertion_stubs: cmd.assertion_stubs,
+                emit_expect_emit: cmd.expect_emit,
+                emit_branch_summary: cmd.branch_summary,
+                todo_marker: cmd.todo_marker.clone(),
+                header: header(cmd.header.as_ref(), cmd.no_header, None),
+                ordering_prefix: cmd.naming.ordering_prefix,
+                tab_width: cmd.naming.tab_width,
+                keywords: cmd.naming.keywords(),
+                forbid_only: cmd.forbid_only,
+                imports: imports(&cmd.imports),
+                remappings: pairs(&cmd.remappings),
+                snippets: pairs(&cmd.snippets),
                 ..Self::default()
             },
             Commands::Check(cmd) => Self {
                 files: cmd.files.clone(),
+                emit_vm_skip: cmd.with_vm_skip,
                 skip_modifiers: cmd.skip_modifiers,
                 format_descriptions: cmd.format_descriptions,
+                emit_assertion_stubs: cmd.assertion_stubs,
+                emit_expect_emit: cmd.expect_emit,
+                emit_branch_summary: cmd.branch_summary,
+                todo_marker: cmd.todo_marker.clone(),
+                header: header(cmd.header.as_ref(), cmd.no_header, None),
+                contract: cmd.contract.clone(),
+                ordering_prefix: cmd.naming.ordering_prefix,
+                tab_width: cmd.naming.tab_width,
+                keywords: cmd.naming.keywords(),
+                forbid_only: cmd.forbid_only,
+                imports: imports(&cmd.imports),
+                remappings: pairs(&cmd.remappings),
+                snippets: pairs(&cmd.snippets),
+                include_fork: cmd.include_fork,
+                ..Self::default()
+            },
+            Commands::Run(cmd) => Self {
+                files: cmd.files.clone(),
+                ordering_prefix: cmd.naming.ordering_prefix,
+                tab_width: cmd.naming.tab_width,
+                keywords: cmd.naming.keywords(),
+                ..Self::default()
+            },
+            Commands::Docs(cmd) => Self {
+                files: cmd.files.clone(),
+                skip_modifiers: cmd.skip_modifiers,
+                ordering_prefix: cmd.naming.ordering_prefix,
+                tab_width: cmd.naming.tab_width,
+                keywords: cmd.naming.keywords(),
                 ..Self::default()
             },
+            Commands::Impact(cmd) => Self {
+                files: vec![cmd.old.clone(), cmd.new.clone()],
+                ordering_prefix: cmd.naming.ordering_prefix,
+                tab_width: cmd.naming.tab_width,
+                keywords: cmd.naming.keywords(),
+                ..Self::default()
+            },
+            Commands::Explain(_) | Commands::Capabilities(_) => Self::default(),
         }
     }
 }
 
+/// Builds the extra keyword set from the `--condition-keyword` and
+/// `--action-keyword` arguments.
+pub(crate) fn keywords(conditions: &[String], actions: &[String]) -> Keywords {
+    Keywords { conditions: conditions.to_vec(), actions: actions.to_vec() }
+}
+
+/// Parses the Solidity imports passed with `--import`.
+pub(crate) fn imports(imports: &[String]) -> Vec<SolidityImport> {
+    imports.iter().map(|import| SolidityImport::from(import.as_str())).collect()
+}
+
+/// Splits `key=value` arguments, e.g. the remappings passed with
+/// `--remapping`, into their key and value.
+pub(crate) fn pairs(args: &[String]) -> Vec<(String, String)> {
+    args.iter()
+        .filter_map(|arg| arg.split_once('='))
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+}
+
+/// Validates a `--remapping` argument, which must look like `prefix=path`.
+pub(crate) fn parse_remapping(remapping: &str) -> Result<String, String> {
+    match remapping.split_once('=') {
+        Some((prefix, _)) if !prefix.is_empty() => Ok(remapping.to_owned()),
+        _ => Err(format!("expected `prefix=path`, found `{remapping}`")),
+    }
+}
+
+/// Splits `tree:generated=existing` arguments, the renames passed with
+/// `--rename`, into their parts.
+pub(crate) fn renames(args: &[String]) -> Vec<(String, String, String)> {
+    args.iter()
+        .filter_map(|arg| split_rename(arg))
+        .map(|(tree, generated, existing)| {
+            (tree.to_owned(), generated.to_owned(), existing.to_owned())
+        })
+        .collect()
+}
+
+/// Validates a `--rename` argument, which must look like
+/// `tree:generated=existing`.
+pub(crate) fn parse_rename(rename: &str) -> Result<String, String> {
+    match split_rename(rename) {
+        Some((tree, generated, existing))
+            if !tree.is_empty()
+                && !generated.is_empty()
+                && !existing.is_empty() =>
+        {
+            Ok(rename.to_owned())
+        }
+        _ => {
+            Err(format!("expected `tree:generated=existing`, found `{rename}`"))
+        }
+    }
+}
+
+/// Splits a rename into its tree, generated and existing names.
+///
+/// Test names can't contain `:`, so the tree ends at the last one, which
+/// keeps Windows paths like `C:\repo\vault.tree` whole.
+fn split_rename(rename: &str) -> Option<(&str, &str, &str)> {
+    let (tree, rest) = rename.rsplit_once(':')?;
+    let (generated, existing) = rest.split_once('=')?;
+    Some((tree, generated, existing))
+}
+
+/// Validates a `--snippet` argument, which must look like `tag=code`.
+pub(crate) fn parse_snippet(snippet: &str) -> Result<String, String> {
+    match snippet.split_once('=') {
+        Some((tag, _)) if !tag.is_empty() => Ok(snippet.to_owned()),
+        _ => Err(format!("expected `tag=code`, found `{snippet}`")),
+    }
+}
+
+/// Picks the header of generated files from the `--header` and `--no-header`
+/// arguments, falling back to `default`.
+pub(crate) fn header(
+    template: Option<&String>,
+    no_header: bool,
+    default: Option<&str>,
+) -> Option<String> {
+    if no_header {
+        return None;
+    }
+
+    template.cloned().or_else(|| default.map(str::to_owned))
+}
+
 /// Main entrypoint of `bulloak`'s execution.
-pub(crate) fn run() -> anyhow::Result<()> {
-    let config: Cli =
-        Figment::new().merge(Serialized::defaults(Cli::parse())).extract()?;
+pub(crate) fn run() -> anyhow::Result<RunReport> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let keys = option_keys(&cli, &matches);
+    let mut figment = Figment::new().merge(Serialized::defaults(&cli));
+    for path in config_files(cli.config.as_deref())? {
+        figment = figment.merge(ConfigFile { path, keys: keys.clone() });
+    }
+    let config: Cli = figment.merge(env_provider(keys)).extract()?;
 
-    match &config.command {
-        Commands::Scaffold(command) => command.run(&config),
-        Commands::Check(command) => command.run(&config),
+    Ok(execute(&config))
+}
+
+/// Returns the config files to read options from, from lowest to highest
+/// precedence.
+///
+/// That is `file` if given, and otherwise the `bulloak.toml` at the root of
+/// the workspace, i.e. the nearest directory holding a `.git`, followed by
+/// the one in the current directory. Either may not exist.
+fn config_files(file: Option<&Path>) -> anyhow::Result<Vec<PathBuf>> {
+    if let Some(file) = file {
+        anyhow::ensure!(
+            file.is_file(),
+            "could not find the config file {}",
+            file.display()
+        );
+        return Ok(vec![file.to_path_buf()]);
+    }
+
+    let cwd = env::current_dir()?;
+    let root = cwd.ancestors().find(|dir| dir.join(".git").exists());
+    Ok(root
+        .filter(|root| *root != cwd)
+        .map(|root| root.join(CONFIG_FILE))
+        .into_iter()
+        .chain([PathBuf::from(CONFIG_FILE)])
+        .collect())
+}
+
+/// Maps the names under which options of the command in `cli` can be set
+/// outside of the command line to their key paths in [`Cli`].
+///
+/// Options are named after their long flags, e.g. `skip_modifiers` or
+/// `vm_skip`. `lang` is an alias of `backend` and `trees` of the files to
+/// work on. Options passed on the command line are left out, since they take
+/// precedence.
+fn option_keys(cli: &Cli, matches: &ArgMatches) -> HashMap<String, String> {
+    let (variant, name) = match cli.command {
+        Commands::Scaffold(_) => ("Scaffold", "scaffold"),
+        Commands::Check(_) => ("Check", "check"),
+        Commands::Run(_) => ("Run", "run"),
+        Commands::Docs(_) => ("Docs", "docs"),
+        Commands::Impact(_) => ("Impact", "impact"),
+        Commands::Explain(_) => ("Explain", "explain"),
+        Commands::Capabilities(_) => ("Capabilities", "capabilities"),
+    };
+
+    let command = Cli::command();
+    let mut keys = HashMap::new();
+    let mut add = |arg: &clap::Arg, path: String, matches: &ArgMatches| {
+        let id = arg.get_id().as_str();
+        if matches.value_source(id) == Some(ValueSource::CommandLine) {
+            return;
+        }
+        if let Some(long) = arg.get_long() {
+            keys.insert(long.replace('-', "_"), path.clone());
+        }
+        keys.insert(id.to_owned(), path);
+    };
+    for arg in command.get_arguments() {
+        add(arg, arg.get_id().to_string(), matches);
+    }
+    let subcommand = command.find_subcommand(name);
+    let sub_matches = matches.subcommand_matches(name);
+    if let (Some(subcommand), Some(sub_matches)) = (subcommand, sub_matches) {
+        for arg in subcommand.get_arguments() {
+            let path = format!("command.{variant}.{}", arg.get_id());
+            add(arg, path, sub_matches);
+        }
+    }
+    for (alias, key) in [("lang", "backend"), ("trees", "files")] {
+        if let Some(path) = keys.get(key).cloned() {
+            keys.entry(alias.to_owned()).or_insert(path);
+        }
+    }
+
+    keys
+}
+
+/// Reads options from `BULLOAK_*` environment variables, e.g.
+/// `BULLOAK_SKIP_MODIFIERS`.
+///
+/// `keys` maps option names to their key paths, see [`option_keys`].
+fn env_provider(keys: HashMap<String, String>) -> Env {
+    // Keys are case sensitive since they include the name of the command, so
+    // they must not be lowercased once mapped.
+    Env::prefixed(ENV_PREFIX)
+        .filter_map(move |key| {
+            keys.get(&key.as_str().to_ascii_lowercase())
+                .map(|path| path.clone().into())
+        })
+        .lowercase(false)
+}
+
+/// Options set in a `bulloak.toml` file.
+///
+/// Top-level keys are option names, see [`option_keys`], e.g.
+/// `trees = ["spec/**/*.tree"]` or `skip_modifiers = true`. Other keys are
+/// ignored. Relative `trees` patterns are relative to the directory of the
+/// file.
+struct ConfigFile {
+    /// The path of the file, which doesn't need to exist.
+    path: PathBuf,
+    /// The key paths of the options that can be set.
+    keys: HashMap<String, String>,
+}
+
+impl Provider for ConfigFile {
+    fn metadata(&self) -> Metadata {
+        Metadata::from(CONFIG_FILE, self.path.as_path())
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        if !self.path.is_file() {
+            return Ok(Map::new());
+        }
+
+        let options: Dict = Figment::from(Toml::file(&self.path)).extract()?;
+        let dir = self.path.parent().map(relative_to_cwd).unwrap_or_default();
+        let mut figment = Figment::new();
+        for (key, value) in options {
+            if let Some(path) = self.keys.get(&key) {
+                let value = match path.rsplit('.').next() {
+                    Some("files") => resolve_patterns(value, &dir),
+                    Some("renames") => {
+                        validate_renames(&value).map_err(|e| {
+                            figment::Error::from(format!("`{key}`: {e}"))
+                        })?;
+                        value
+                    }
+                    _ => value,
+                };
+                figment = figment.merge(Serialized::default(path, value));
+            }
+        }
+
+        figment.data()
+    }
+}
+
+/// Resolves the relative spec patterns in `value` against `dir`, the
+/// directory of the config file that set them.
+fn resolve_patterns(value: Value, dir: &Path) -> Value {
+    match value {
+        Value::String(tag, pattern) => {
+            let pattern = dir.join(pattern).to_string_lossy().into_owned();
+            Value::String(tag, pattern)
+        }
+        Value::Array(tag, patterns) => Value::Array(
+            tag,
+            patterns
+                .into_iter()
+                .map(|pattern| resolve_patterns(pattern, dir))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Checks that the renames in `value` look like `tree:generated=existing`,
+/// since values from the config file don't go through `parse_rename`.
+fn validate_renames(value: &Value) -> Result<(), String> {
+    match value {
+        Value::String(_, rename) => parse_rename(rename).map(|_| ()),
+        Value::Array(_, renames) => {
+            renames.iter().try_for_each(validate_renames)
+        }
+        _ => Err("expected `tree:generated=existing`".to_owned()),
+    }
+}
+
+/// Returns `dir` relative to the current directory if it's one of its
+/// ancestors, e.g. `..` for the parent directory, and `dir` as is otherwise.
+fn relative_to_cwd(dir: &Path) -> PathBuf {
+    let Ok(cwd) = env::current_dir() else {
+        return dir.to_path_buf();
     };
+    match cwd.strip_prefix(dir) {
+        Ok(rest) if dir.is_absolute() => {
+            rest.components().map(|_| Path::new("..")).collect()
+        }
+        _ => dir.to_path_buf(),
+    }
+}
 
-    Ok(())
+/// Runs the command in `config`.
+pub(crate) fn execute(config: &Cli) -> RunReport {
+    if config.no_color {
+        ColorChoice::Never.apply();
+    } else {
+        config.color.apply();
+    }
+
+    match &config.command {
+        Commands::Scaffold(command) => command.run(config),
+        Commands::Check(command) => command.run(config),
+        Commands::Run(command) => command.run(config),
+        Commands::Docs(command) => command.run(config),
+        Commands::Impact(command) => command.run(config),
+        Commands::Explain(command) => command.run(config),
+        Commands::Capabilities(command) => command.run(config),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{parse_rename, renames};
+
+    #[test]
+    fn splits_renames_of_windows_paths() {
+        let rename = r"C:\repo\vault.tree:test_x=y".to_owned();
+        assert!(parse_rename(&rename).is_ok());
+        assert_eq!(
+            renames(&[rename]),
+            vec![(
+                r"C:\repo\vault.tree".to_owned(),
+                "test_x".to_owned(),
+                "y".to_owned()
+            )]
+        );
+        assert!(parse_rename("test_x=y").is_err());
+        assert!(parse_rename("vault.tree:test_x=").is_err());
+    }
 }
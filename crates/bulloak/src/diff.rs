@@ -0,0 +1,198 @@
+//! Unified diffs of the changes `bulloak check --fix` makes.
+
+use std::{fmt::Write, ops::Range, path::Path};
+
+use owo_colors::{OwoColorize, Stream};
+
+/// The number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// Renders the changes from `old` to `new` in `path` as a unified diff.
+///
+/// Returns an empty string if there are no changes.
+pub(crate) fn unified(path: &Path, old: &str, new: &str) -> String {
+    // `diff::lines` would see an empty line after a trailing newline.
+    let lines = diff::lines(
+        old.strip_suffix('\n').unwrap_or(old),
+        new.strip_suffix('\n').unwrap_or(new),
+    );
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, diff::Result::Both(..)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let path = path.display();
+    let mut out = String::new();
+    let _ = writeln!(out, "--- {path}\n+++ {path}");
+    for (start, end) in hunks(&changed, lines.len()) {
+        // Line numbers are 1-based, and the lines before the hunk tell where
+        // it starts in each file.
+        let before = &lines[..start];
+        let old_start = 1 + before.iter().filter(|l| in_old(l)).count();
+        let new_start = 1 + before.iter().filter(|l| in_new(l)).count();
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| in_old(l)).count();
+        let new_len = hunk.iter().filter(|l| in_new(l)).count();
+        let header = format!(
+            "@@ -{} +{} @@",
+            range(old_start, old_len),
+            range(new_start, new_len)
+        );
+        let _ = writeln!(
+            out,
+            "{}",
+            header.if_supports_color(Stream::Stdout, |t| t.cyan())
+        );
+        for line in hunk {
+            let _ = match line {
+                diff::Result::Both(text, _) => writeln!(out, " {text}"),
+                diff::Result::Left(text) => writeln!(
+                    out,
+                    "{}",
+                    format!("-{text}")
+                        .if_supports_color(Stream::Stdout, |t| t.red())
+                ),
+                diff::Result::Right(text) => writeln!(
+                    out,
+                    "{}",
+                    format!("+{text}")
+                        .if_supports_color(Stream::Stdout, |t| t.green())
+                ),
+            };
+        }
+    }
+
+    out
+}
+
+/// Returns the edits that turn `old` into `new`, as the byte ranges of `old`
+/// to replace along with their replacements, in order.
+///
+/// Edits span whole lines. Their ranges refer to `old`, so applying them from
+/// last to first keeps the ranges of the others valid.
+pub(crate) fn edits(old: &str, new: &str) -> Vec<(Range<usize>, String)> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let mut edits = Vec::new();
+    let mut pending: Option<(Range<usize>, String)> = None;
+    let mut offset = 0;
+    for line in diff::slice(&old_lines, &new_lines) {
+        match line {
+            diff::Result::Both(text, _) => {
+                edits.extend(pending.take());
+                offset += text.len();
+            }
+            diff::Result::Left(text) => {
+                let edit = pending
+                    .get_or_insert_with(|| (offset..offset, String::new()));
+                offset += text.len();
+                edit.0.end = offset;
+            }
+            diff::Result::Right(text) => pending
+                .get_or_insert_with(|| (offset..offset, String::new()))
+                .1
+                .push_str(text),
+        }
+    }
+    edits.extend(pending);
+
+    edits
+}
+
+/// Groups the indices of `changed` lines into hunks, as ranges of line
+/// indices that include the surrounding context.
+fn hunks(changed: &[usize], len: usize) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(len);
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
+
+/// Formats a hunk range, which omits the length when it is 1 and points at
+/// the line before an empty range.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start - 1),
+        1 => start.to_string(),
+        _ => format!("{start},{len}"),
+    }
+}
+
+/// Whether `line` is part of the old file.
+fn in_old(line: &diff::Result<&str>) -> bool {
+    !matches!(line, diff::Result::Right(_))
+}
+
+/// Whether `line` is part of the new file.
+fn in_new(line: &diff::Result<&str>) -> bool {
+    !matches!(line, diff::Result::Left(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{edits, unified};
+
+    #[test]
+    fn renders_hunks_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\nk\n";
+        let diff = unified(Path::new("foo.t.sol"), old, new);
+
+        assert_eq!(
+            diff,
+            "--- foo.t.sol\n+++ foo.t.sol\n@@ -2,9 +2,10 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n i\n j\n+k\n"
+        );
+    }
+
+    #[test]
+    fn splits_distant_changes() {
+        let mut lines: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+        let old = lines.join("\n");
+        lines[1] = "one".to_owned();
+        lines[18] = "eighteen".to_owned();
+        let new = lines.join("\n");
+        let diff = unified(Path::new("foo.t.sol"), &old, &new);
+
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n 0\n-1\n+one\n"));
+        assert!(diff.contains(
+            "@@ -16,5 +16,5 @@\n 15\n 16\n 17\n-18\n+eighteen\n 19\n"
+        ));
+    }
+
+    #[test]
+    fn lists_the_edits_of_byte_ranges() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nc\nd\ne\n";
+        let edits = edits(old, new);
+        assert_eq!(
+            edits,
+            vec![(2..4, "B\n".to_owned()), (8..8, "e\n".to_owned())]
+        );
+
+        let mut applied = old.to_owned();
+        for (range, replacement) in edits.iter().rev() {
+            applied.replace_range(range.clone(), replacement);
+        }
+        assert_eq!(applied, new);
+        assert!(super::edits(old, old).is_empty());
+    }
+
+    #[test]
+    fn renders_nothing_without_changes() {
+        assert!(unified(Path::new("foo.t.sol"), "a\n", "a\n").is_empty());
+    }
+}
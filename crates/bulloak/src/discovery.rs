@@ -0,0 +1,345 @@
+//! Finds the specs that command-line patterns stand for.
+//!
+//! Every command that works on specs discovers them the same way, and other
+//! tools can too through [`discover_specs`]:
+//!
+//! - A path to an existing file stands for that file.
+//! - A path to a directory stands for the `.tree` files under it, except in
+//!   hidden directories and build outputs or vendored dependencies.
+//! - Anything else is a glob pattern, like `spec/**/*.tree`.
+//!
+//! Specs matching a pattern of the [`IGNORE_FILE`] are left out, and a spec
+//! found by several patterns is only kept the first time. Each pattern's
+//! specs are sorted by path, and patterns keep the order they were given in.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use bulloak_syntax::{ignored_comments, markdown::is_markdown};
+use glob::{glob, Pattern};
+use owo_colors::{OwoColorize, Stream};
+
+use crate::workspace::SKIPPED_DIRS;
+
+/// The file, in the directory `bulloak` runs from, that lists glob patterns
+/// of specs to leave out, one per line.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub const IGNORE_FILE: &str = ".bulloakignore";
+
+/// The specs that a list of patterns stands for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Discovery {
+    /// The specs found, without duplicates.
+    pub specs: Vec<PathBuf>,
+    /// The patterns that are invalid or match nothing, with the reason why.
+    pub problems: Vec<(PathBuf, String)>,
+}
+
+/// Finds the specs `patterns` stand for, leaving out the ones that the
+/// [`IGNORE_FILE`] of the current directory lists.
+///
+/// Patterns that match specs which are all ignored are not problems.
+#[must_use]
+pub fn discover_specs(patterns: &[PathBuf]) -> Discovery {
+    discover(patterns, &ignored(Path::new(IGNORE_FILE)))
+}
+
+/// Finds the specs `patterns` stand for, leaving out the ones matching one of
+/// `ignored`.
+fn discover(patterns: &[PathBuf], ignored: &[Pattern]) -> Discovery {
+    let mut discovery = Discovery::default();
+    for pattern in patterns {
+        let problem = match expand_glob(pattern.clone()) {
+            Ok(paths) => {
+                let mut paths: Vec<PathBuf> = paths.collect();
+                if !paths.is_empty() {
+                    paths.sort();
+                    for path in paths {
+                        let path = normalize(&path);
+                        let is_ignored =
+                            ignored.iter().any(|p| p.matches_path(&path));
+                        if !is_ignored && !discovery.specs.contains(&path) {
+                            discovery.specs.push(path);
+                        }
+                    }
+                    continue;
+                }
+                format!("no files match {}", pattern.display())
+            }
+            Err(e) => format!("could not expand {}: {e}", pattern.display()),
+        };
+        discovery.problems.push((pattern.clone(), problem));
+    }
+
+    discovery
+}
+
+/// Reads the patterns of the ignore file at `path`, if it exists.
+///
+/// Invalid patterns are skipped.
+fn ignored(path: &Path) -> Vec<Pattern> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| Pattern::new(line.trim_start_matches("./")).ok())
+        .collect()
+}
+
+/// Drops the `.` components of `path`, so that `./a.tree` and `a.tree` are
+/// the same spec.
+fn normalize(path: &Path) -> PathBuf {
+    path.components().filter(|c| *c != Component::CurDir).collect()
+}
+
+/// Expands `input` into the paths it matches.
+///
+/// Paths to existing files are returned as is, so that file names containing
+/// glob metacharacters and Windows verbatim paths (`\\?\C:\...`) work.
+/// Directories, given or matched, are expanded into the `.tree` files under
+/// them.
+pub(crate) fn expand_glob(
+    input: PathBuf,
+) -> anyhow::Result<Box<dyn Iterator<Item = PathBuf>>> {
+    if input.is_file() {
+        return Ok(Box::new(std::iter::once(input)));
+    }
+    if input.is_dir() {
+        return Ok(Box::new(trees(&input).into_iter()));
+    }
+
+    let pattern = input.to_string_lossy();
+    let paths = glob(&pattern)?.filter_map(Result::ok).flat_map(|path| {
+        if path.is_dir() {
+            trees(&path)
+        } else {
+            vec![path]
+        }
+    });
+    Ok(Box::new(paths))
+}
+
+/// Lists the `.tree` files under `dir`, sorted by path.
+///
+/// Hidden directories and the ones in [`SKIPPED_DIRS`] are not walked, and
+/// directories that can't be read are skipped.
+fn trees(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut paths: Vec<PathBuf> =
+        entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    paths.sort();
+
+    let mut trees = Vec::new();
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref())
+            {
+                trees.extend(self::trees(&path));
+            }
+        } else if path.extension().is_some_and(|ext| ext == "tree") {
+            trees.push(path);
+        }
+    }
+
+    trees
+}
+
+/// Expands every pattern in `patterns` into the specs it stands for, as
+/// [`discover_specs`] does.
+///
+/// Patterns that are invalid or match no files are reported on stderr, since
+/// a typo would otherwise go unnoticed. With `strict`, they are errors
+/// instead, returned with the reason why.
+pub(crate) fn expand_patterns(
+    patterns: &[PathBuf],
+    strict: bool,
+) -> (Vec<PathBuf>, Vec<(PathBuf, String)>) {
+    let Discovery { specs, problems } = discover_specs(patterns);
+    let mut errors = Vec::new();
+    for (pattern, problem) in problems {
+        if strict {
+            eprintln!(
+                "{}: {problem}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red())
+            );
+            errors.push((pattern, problem));
+        } else {
+            eprintln!(
+                "{}: {problem}",
+                "warn".if_supports_color(Stream::Stderr, |t| t.yellow())
+            );
+        }
+    }
+
+    (specs, errors)
+}
+
+/// Warns about the comments in `text`, the contents of the spec at `path`,
+/// that aren't part of any tree and so produce no tests.
+///
+/// Markdown docs are left alone, since their prose is never part of a tree.
+pub(crate) fn warn_ignored_comments(path: &Path, text: &str) {
+    if is_markdown(path) {
+        return;
+    }
+    for span in ignored_comments(text) {
+        eprintln!(
+            "{}: {}:{}:{}: comment outside of any tree is ignored",
+            "warn".if_supports_color(Stream::Stderr, |t| t.yellow()),
+            path.display(),
+            span.start.line,
+            span.start.column
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use glob::Pattern;
+
+    use super::{discover, expand_glob, expand_patterns, Discovery};
+
+    /// Helper to collect and sort the output.
+    fn sorted_matches(pattern: &str) -> Vec<String> {
+        let mut v: Vec<_> = expand_glob(PathBuf::from(pattern))
+            .unwrap()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn literal_path_round_trips() {
+        // This crate has a Cargo.toml in its root.
+        let out = sorted_matches("Cargo.toml");
+        assert_eq!(out, vec!["Cargo.toml".to_string()]);
+    }
+
+    #[test]
+    fn no_such_file_yields_empty() {
+        let out = sorted_matches("no-such-file-xyz.tree");
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn simple_star_glob() {
+        // Match all .rs files in src/
+        let out = sorted_matches("src/*.rs");
+        assert!(out.iter().any(|e| e.ends_with("main.rs")));
+        assert!(out.iter().any(|e| e.ends_with("check.rs")));
+    }
+
+    #[test]
+    fn recursive_double_star_glob() {
+        // `tests` directory has .tree files under tests/scaffold/.
+        let out = sorted_matches("tests/scaffold/**/*.tree");
+        assert!(out.iter().any(|e| e.ends_with("basic.tree")));
+        assert!(out.iter().any(|e| e.ends_with("complex.tree")));
+    }
+
+    #[test]
+    fn forward_slash_glob_works_everywhere() {
+        let out = sorted_matches("tests/scaffold/*.tree");
+        assert!(out.iter().any(|e| e.ends_with("basic.tree")));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn backslash_glob_works_the_same() {
+        let fwd = sorted_matches("tests/scaffold/*.tree");
+        let bwd = sorted_matches("tests\\scaffold\\*.tree");
+        assert_eq!(
+            fwd, bwd,
+            "backslash‐based glob must match forward‐slash one"
+        );
+    }
+
+    #[test]
+    fn existing_file_with_metacharacters_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a[1].tree");
+        std::fs::write(&file, "").unwrap();
+
+        let out: Vec<_> = expand_glob(file.clone()).unwrap().collect();
+        assert_eq!(out, vec![file]);
+    }
+
+    #[test]
+    fn invalid_pattern_returns_error() {
+        // Invalid glob syntax (unmatched '[') must return Err.
+        let bad = PathBuf::from("tests/scaffold/*[.tree");
+        let res = expand_glob(bad);
+        assert!(res.is_err(), "expected invalid glob to Err");
+    }
+
+    #[test]
+    fn patterns_matching_nothing_are_errors_when_strict() {
+        let patterns = [
+            PathBuf::from("tests/scaffold/basic.tree"),
+            PathBuf::from("tests/scaffold/*.treee"),
+            PathBuf::from("tests/scaffold/*[.tree"),
+        ];
+
+        let (paths, errors) = expand_patterns(&patterns, false);
+        assert_eq!(paths, [PathBuf::from("tests/scaffold/basic.tree")]);
+        assert!(errors.is_empty());
+
+        let (paths, errors) = expand_patterns(&patterns, true);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, patterns[1]);
+        assert!(errors[0].1.starts_with("no files match"));
+        assert!(errors[1].1.starts_with("could not expand"));
+    }
+
+    #[test]
+    fn discovers_directories_without_duplicates_or_ignored_specs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "spec/b.tree",
+            "spec/a.tree",
+            "spec/nested/c.tree",
+            "spec/nested/draft.tree",
+            "spec/notes.md",
+            "spec/.hidden/d.tree",
+            "spec/target/e.tree",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let ignored = [Pattern::new("**/draft.tree").unwrap()];
+
+        let patterns = [
+            root.join("spec/b.tree"),
+            root.join("spec"),
+            root.join("spec/*.md"),
+            root.join("missing/*.tree"),
+        ];
+        let discovery = discover(&patterns, &ignored);
+        assert_eq!(
+            discovery,
+            Discovery {
+                specs: vec![
+                    root.join("spec/b.tree"),
+                    root.join("spec/a.tree"),
+                    root.join("spec/nested/c.tree"),
+                    root.join("spec/notes.md"),
+                ],
+                problems: vec![(
+                    patterns[3].clone(),
+                    format!("no files match {}", patterns[3].display())
+                )],
+            }
+        );
+    }
+}
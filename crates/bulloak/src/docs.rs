@@ -0,0 +1,454 @@
+//! Defines the `bulloak docs` command.
+//!
+//! This command generates a static site documenting the specs: every tree,
+//! the tests it maps to and whether those tests match it.
+
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bulloak_foundry::check::{
+    context::Context,
+    rules::{self, Checker},
+};
+use bulloak_syntax::{markdown::read_spec, utils::pluralize};
+use clap::{Parser, ValueEnum};
+use owo_colors::{OwoColorize, Stream, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::RunReport,
+    cli::{Backend, Cli, Naming},
+    discovery::expand_patterns,
+    report::ViolationReport,
+    run,
+};
+
+/// The format of the pages `bulloak docs` writes.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DocsFormat {
+    /// Markdown pages, e.g. to browse on GitHub.
+    #[default]
+    Markdown,
+    /// Standalone HTML pages.
+    Html,
+}
+
+impl DocsFormat {
+    /// The extension of the pages in this format.
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Generate a site documenting the specs and the state of their tests.
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Docs {
+    /// The set of tree files to document.
+    pub files: Vec<PathBuf>,
+    /// The directory to write the site to.
+    #[arg(short = 'o', long, value_name = "DIR", default_value = "docs/specs")]
+    pub out_dir: PathBuf,
+    /// The format of the pages.
+    #[arg(long, value_enum, default_value_t = DocsFormat::Markdown)]
+    pub format: DocsFormat,
+    /// The target backend/language whose tests are documented.
+    #[arg(short = 'b', long = "backend", value_enum, default_value_t = Backend::Solidity)]
+    pub backend: Backend,
+    /// Fail when a pattern in `files` is invalid or matches no files,
+    /// instead of warning.
+    #[arg(long, default_value_t = false)]
+    pub strict_globs: bool,
+    /// Whether to leave missing modifiers out of the check status.
+    #[arg(short = 'm', long, default_value_t = false)]
+    pub skip_modifiers: bool,
+    /// The options that decide the names of the tests.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub naming: Naming,
+}
+
+impl Default for Docs {
+    fn default() -> Self {
+        Docs::parse_from(Vec::<String>::new())
+    }
+}
+
+impl Docs {
+    /// Entrypoint for `bulloak docs`.
+    pub(crate) fn run(&self, cfg: &Cli) -> RunReport {
+        let (specs, errors) = expand_patterns(&self.files, self.strict_globs);
+        if !errors.is_empty() {
+            return RunReport { errors, ..RunReport::default() };
+        }
+
+        let foundry_cfg: bulloak_foundry::config::Config = cfg.into();
+        let rust_cfg = bulloak_rust::Config {
+            skip_helpers: self.skip_modifiers,
+            ..self.naming.rust_config()
+        };
+
+        let mut pages = Vec::new();
+        let mut errors = Vec::new();
+        let mut names = HashSet::new();
+        for tree_path in &specs {
+            let page = self.document(tree_path, &foundry_cfg, &rust_cfg);
+            if let Some(error) = &page.error {
+                errors.push((tree_path.clone(), error.clone()));
+            }
+            let page = Page { file: page_name(tree_path, &mut names), ..page };
+            pages.push(page);
+        }
+
+        if let Err(e) = self.write(&pages) {
+            eprintln!(
+                "{}: could not write {}: {e}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red()),
+                self.out_dir.display(),
+            );
+            errors.push((self.out_dir.clone(), e.to_string()));
+        } else {
+            println!(
+                "{}: documented {} {} in {}",
+                "success".if_supports_color(Stream::Stdout, |t| t
+                    .style(Style::new().bold().green())),
+                pages.len(),
+                pluralize(pages.len(), "spec", "specs"),
+                self.out_dir.display(),
+            );
+        }
+
+        RunReport { files: specs, errors, ..RunReport::default() }
+    }
+
+    /// Gathers what the page of the tree at `tree_path` shows.
+    fn document(
+        &self,
+        tree_path: &Path,
+        foundry_cfg: &bulloak_foundry::config::Config,
+        rust_cfg: &bulloak_rust::Config,
+    ) -> Page {
+        let mut page = Page {
+            tree: tree_path.to_path_buf(),
+            title: tree_path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            ..Page::default()
+        };
+        let text = match read_spec(tree_path) {
+            Ok(text) => text,
+            Err(e) => {
+                page.error = Some(e.to_string());
+                return page;
+            }
+        };
+        match run::spec(&text, self.backend, foundry_cfg, rust_cfg) {
+            Ok((name, report)) => {
+                page.title = name;
+                page.hierarchy = report.render_tests();
+                page.tests = report
+                    .test_names()
+                    .into_iter()
+                    .map(str::to_owned)
+                    .collect();
+            }
+            Err(e) => {
+                page.hierarchy = text;
+                page.error = Some(e.to_string());
+                return page;
+            }
+        }
+
+        let violations = match self.backend {
+            Backend::Solidity => {
+                let violations =
+                    match Context::new(tree_path.to_path_buf(), foundry_cfg) {
+                        Ok(ctx) => rules::StructuralMatcher::check(&ctx),
+                        Err(violation) => vec![violation],
+                    };
+                violations.iter().map(ViolationReport::from_solidity).collect()
+            }
+            Backend::Rust => {
+                match bulloak_rust::check::check(tree_path, rust_cfg) {
+                    Ok(violations) => violations
+                        .iter()
+                        .map(ViolationReport::from_rust)
+                        .collect(),
+                    Err(e) => {
+                        page.error = Some(e.to_string());
+                        Vec::new()
+                    }
+                }
+            }
+        };
+        page.violations = violations;
+
+        page
+    }
+
+    /// Writes the index and a page per spec to the output directory.
+    fn write(&self, pages: &[Page]) -> std::io::Result<()> {
+        fs::create_dir_all(&self.out_dir)?;
+        let extension = self.format.extension();
+        let (index, render): (String, fn(&Page) -> String) = match self.format {
+            DocsFormat::Markdown => (markdown_index(pages), markdown_page),
+            DocsFormat::Html => (html_index(pages), html_page),
+        };
+        fs::write(self.out_dir.join(format!("index.{extension}")), index)?;
+        for page in pages {
+            let path = self.out_dir.join(format!("{}.{extension}", page.file));
+            fs::write(path, render(page))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything the page of a single spec shows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Page {
+    /// The name of the page, without extension.
+    file: String,
+    /// The path of the tree file.
+    tree: PathBuf,
+    /// The name of the spec's contract or root.
+    title: String,
+    /// The spec, with every action annotated with the test covering it.
+    hierarchy: String,
+    /// The names of the tests that implement the spec.
+    tests: Vec<String>,
+    /// The violations `bulloak check` finds for the spec.
+    violations: Vec<ViolationReport>,
+    /// Why the spec could not be documented or checked, if it couldn't.
+    error: Option<String>,
+}
+
+impl Page {
+    /// Describes whether the tests match the spec.
+    fn status(&self) -> String {
+        match (&self.error, self.violations.len()) {
+            (Some(_), _) => "✗ not checked".to_owned(),
+            (None, 0) => "✓ up to date".to_owned(),
+            (None, count) => format!(
+                "✗ {count} {}",
+                pluralize(count, "violation", "violations")
+            ),
+        }
+    }
+}
+
+/// Picks a page name for the tree at `tree_path` that isn't in `taken` yet.
+///
+/// Pages are named after the tree's file stem, with a numeric suffix when
+/// trees in different directories share a name.
+fn page_name(tree_path: &Path, taken: &mut HashSet<String>) -> String {
+    let stem = tree_path.file_stem().unwrap_or_default().to_string_lossy();
+    let stem = if stem == "index" { "index-spec".into() } else { stem };
+    let mut name = stem.to_string();
+    let mut suffix = 1;
+    while !taken.insert(name.clone()) {
+        suffix += 1;
+        name = format!("{stem}-{suffix}");
+    }
+
+    name
+}
+
+/// Renders the Markdown index listing every spec.
+fn markdown_index(pages: &[Page]) -> String {
+    let mut out = String::from(
+        "# Specs\n\n| Spec | Tree | Tests | Status |\n| --- | --- | --- | --- |\n",
+    );
+    for page in pages {
+        let _ = writeln!(
+            out,
+            "| [{}]({}.md) | `{}` | {} | {} |",
+            page.title.replace('|', "\\|"),
+            page.file,
+            page.tree.display(),
+            page.tests.len(),
+            page.status(),
+        );
+    }
+
+    out
+}
+
+/// Renders the Markdown page of a single spec.
+fn markdown_page(page: &Page) -> String {
+    let mut out = format!(
+        "# {}\n\nSpec: `{}`\n\nStatus: {}\n",
+        page.title,
+        page.tree.display(),
+        page.status()
+    );
+    if let Some(error) = &page.error {
+        let _ = writeln!(out, "\n> {error}");
+    }
+    let _ = write!(out, "\n## Tree\n\n```text\n{}```\n", page.hierarchy);
+    if !page.tests.is_empty() {
+        out.push_str("\n## Tests\n\n");
+        for test in &page.tests {
+            let _ = writeln!(out, "- `{test}`");
+        }
+    }
+    if !page.violations.is_empty() {
+        out.push_str("\n## Violations\n\n");
+        for violation in &page.violations {
+            let _ = writeln!(out, "- {}", violation.message);
+        }
+    }
+
+    out
+}
+
+/// Renders the HTML index listing every spec.
+fn html_index(pages: &[Page]) -> String {
+    let mut body = String::from(
+        "<h1>Specs</h1>\n<table>\n<tr><th>Spec</th><th>Tree</th>\
+         <th>Tests</th><th>Status</th></tr>\n",
+    );
+    for page in pages {
+        let _ = writeln!(
+            body,
+            "<tr><td><a href=\"{}.html\">{}</a></td><td><code>{}</code></td>\
+             <td>{}</td><td>{}</td></tr>",
+            escape(&page.file),
+            escape(&page.title),
+            escape(&page.tree.display().to_string()),
+            page.tests.len(),
+            page.status(),
+        );
+    }
+    body.push_str("</table>\n");
+
+    html_document("Specs", &body)
+}
+
+/// Renders the HTML page of a single spec.
+fn html_page(page: &Page) -> String {
+    let mut body = format!(
+        "<p><a href=\"index.html\">Specs</a></p>\n<h1>{}</h1>\n\
+         <p>Spec: <code>{}</code></p>\n<p>Status: {}</p>\n",
+        escape(&page.title),
+        escape(&page.tree.display().to_string()),
+        page.status(),
+    );
+    if let Some(error) = &page.error {
+        let _ = writeln!(body, "<blockquote>{}</blockquote>", escape(error));
+    }
+    let _ =
+        writeln!(body, "<h2>Tree</h2>\n<pre>{}</pre>", escape(&page.hierarchy));
+    if !page.tests.is_empty() {
+        body.push_str("<h2>Tests</h2>\n<ul>\n");
+        for test in &page.tests {
+            let _ = writeln!(body, "<li><code>{}</code></li>", escape(test));
+        }
+        body.push_str("</ul>\n");
+    }
+    if !page.violations.is_empty() {
+        body.push_str("<h2>Violations</h2>\n<ul>\n");
+        for violation in &page.violations {
+            let _ = writeln!(body, "<li>{}</li>", escape(&violation.message));
+        }
+        body.push_str("</ul>\n");
+    }
+
+    html_document(&page.title, &body)
+}
+
+/// Wraps `body` in a standalone HTML document.
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\
+         body {{ font-family: sans-serif; max-width: 60rem; margin: 2rem auto; }}\
+         pre {{ background: #f6f8fa; padding: 1rem; overflow-x: auto; }}\
+         td, th {{ padding: 0.25rem 0.75rem; text-align: left; }}\
+         </style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// Escapes the characters of `text` that have a meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, path::Path};
+
+    use pretty_assertions::assert_eq;
+
+    use super::{escape, markdown_page, page_name, Page};
+
+    #[test]
+    fn names_pages_uniquely() {
+        let mut taken = HashSet::new();
+        assert_eq!(page_name(Path::new("a/foo.tree"), &mut taken), "foo");
+        assert_eq!(page_name(Path::new("b/foo.tree"), &mut taken), "foo-2");
+        assert_eq!(
+            page_name(Path::new("index.tree"), &mut taken),
+            "index-spec"
+        );
+    }
+
+    #[test]
+    fn renders_markdown_pages() {
+        let page = Page {
+            file: "foo".to_owned(),
+            tree: "foo.tree".into(),
+            title: "FooTest".to_owned(),
+            hierarchy: "FooTest\n└── It works. (test_Works)\n".to_owned(),
+            tests: vec!["test_Works".to_owned()],
+            ..Page::default()
+        };
+
+        assert_eq!(
+            markdown_page(&page),
+            "# FooTest\n\nSpec: `foo.tree`\n\nStatus: ✓ up to date\n\n\
+             ## Tree\n\n```text\nFooTest\n└── It works. (test_Works)\n```\n\n\
+             ## Tests\n\n- `test_Works`\n"
+        );
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            escape("a < b && \"c\""),
+            "a &lt; b &amp;&amp; &quot;c&quot;"
+        );
+    }
+}
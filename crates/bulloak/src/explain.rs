@@ -0,0 +1,118 @@
+//! Defines the `bulloak explain` command.
+//!
+//! This command describes the rules `bulloak check` enforces, by the ID its
+//! violations are reported with, e.g. `bulloak explain BK105`.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use owo_colors::{OwoColorize, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{api::RunReport, cli::Cli};
+
+/// The ID and the explanation of every rule, sorted by ID.
+const RULES: &[(&str, &str)] = &[
+    ("BK001", include_str!("../explain/BK001.md")),
+    ("BK101", include_str!("../explain/BK101.md")),
+    ("BK102", include_str!("../explain/BK102.md")),
+    ("BK103", include_str!("../explain/BK103.md")),
+    ("BK104", include_str!("../explain/BK104.md")),
+    ("BK105", include_str!("../explain/BK105.md")),
+    ("BK106", include_str!("../explain/BK106.md")),
+    ("BK107", include_str!("../explain/BK107.md")),
+    ("BK108", include_str!("../explain/BK108.md")),
+    ("BK109", include_str!("../explain/BK109.md")),
+    ("BK110", include_str!("../explain/BK110.md")),
+    ("BK111", include_str!("../explain/BK111.md")),
+    ("BK112", include_str!("../explain/BK112.md")),
+    ("BK113", include_str!("../explain/BK113.md")),
+    ("BK201", include_str!("../explain/BK201.md")),
+    ("BK202", include_str!("../explain/BK202.md")),
+    ("BK203", include_str!("../explain/BK203.md")),
+    ("BK204", include_str!("../explain/BK204.md")),
+    ("BK205", include_str!("../explain/BK205.md")),
+    ("BK301", include_str!("../explain/BK301.md")),
+    ("BK302", include_str!("../explain/BK302.md")),
+    ("BK303", include_str!("../explain/BK303.md")),
+    ("BK401", include_str!("../explain/BK401.md")),
+    ("BK501", include_str!("../explain/BK501.md")),
+];
+
+/// Explain a rule of `bulloak check`.
+#[derive(Debug, Parser, Clone, Default, Serialize, Deserialize)]
+pub struct Explain {
+    /// The ID of the rule, e.g. `BK105`. Lists every rule if left out.
+    pub rule: Option<String>,
+}
+
+impl Explain {
+    /// Entrypoint for `bulloak explain`.
+    pub(crate) fn run(&self, _cfg: &Cli) -> RunReport {
+        let Some(id) = &self.rule else {
+            for (id, explanation) in RULES {
+                println!("{id}  {}", title(explanation));
+            }
+            return RunReport::default();
+        };
+
+        if let Some(explanation) = explanation(id) {
+            print!("{explanation}");
+            return RunReport::default();
+        }
+
+        let e = "no such rule; run `bulloak explain` to list them";
+        eprintln!(
+            "{}: {id}: {e}",
+            "error".if_supports_color(Stream::Stderr, |t| t.red()),
+        );
+        RunReport {
+            errors: vec![(PathBuf::from(id), e.to_owned())],
+            ..RunReport::default()
+        }
+    }
+}
+
+/// Returns the explanation of the rule with the given ID, in any case.
+fn explanation(id: &str) -> Option<&'static str> {
+    RULES
+        .iter()
+        .find(|(rule, _)| rule.eq_ignore_ascii_case(id))
+        .map(|(_, explanation)| *explanation)
+}
+
+/// Returns the title of an explanation, from its `# BKxxx: title` heading.
+fn title(explanation: &str) -> &str {
+    let heading = explanation.lines().next().unwrap_or_default();
+    heading.split_once(": ").map_or(heading, |(_, title)| title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{explanation, title, RULES};
+
+    #[test]
+    fn every_explanation_is_headed_by_its_rule() {
+        for window in RULES.windows(2) {
+            assert!(
+                window[0].0 < window[1].0,
+                "{} is out of order",
+                window[1].0
+            );
+        }
+        for (id, explanation) in RULES {
+            assert!(explanation.starts_with(&format!("# {id}: ")));
+            assert!(explanation.contains("\n## Fix\n"), "{id} has no fix");
+            assert!(!title(explanation).is_empty());
+        }
+    }
+
+    #[test]
+    fn looks_rules_up_in_any_case() {
+        assert_eq!(explanation("bk105"), explanation("BK105"));
+        assert!(explanation("BK105")
+            .unwrap()
+            .starts_with("# BK105: a test function is missing"));
+        assert_eq!(explanation("BK999"), None);
+    }
+}
@@ -0,0 +1,359 @@
+//! Defines the `bulloak impact` command.
+//!
+//! This command compares two versions of a spec and reports which generated
+//! test functions an edit adds, removes or renames, and which of the
+//! implemented tests it puts at risk, without touching any file.
+
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bulloak_syntax::{markdown::read_spec, utils::pluralize, Ast};
+use clap::Parser;
+use owo_colors::{OwoColorize, Stream, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::RunReport,
+    cli::{Backend, Cli, Naming},
+    run::{self, Report},
+};
+
+/// Report how an edit to a spec changes its generated tests.
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+pub struct Impact {
+    /// The spec before the edit.
+    pub old: PathBuf,
+    /// The spec after the edit.
+    pub new: PathBuf,
+    /// The target backend/language whose tests are compared.
+    #[arg(short = 'b', long = "backend", visible_alias = "lang", value_enum, default_value_t = Backend::Solidity)]
+    pub backend: Backend,
+    /// The test file whose implemented tests may be at risk.
+    ///
+    /// Defaults to the test file of the new spec, or of the old one if the
+    /// new spec has none.
+    #[arg(long, value_name = "FILE")]
+    pub test_file: Option<PathBuf>,
+    /// The options that decide the names of the tests.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub naming: Naming,
+}
+
+impl Default for Impact {
+    fn default() -> Self {
+        Impact::parse_from(["impact", "old.tree", "new.tree"])
+    }
+}
+
+impl Impact {
+    /// Entrypoint for `bulloak impact`.
+    pub(crate) fn run(&self, cfg: &Cli) -> RunReport {
+        let files = vec![self.old.clone(), self.new.clone()];
+        let changes = match self.changes(cfg) {
+            Ok(changes) => changes,
+            Err((path, e)) => {
+                eprintln!(
+                    "{}: {}: {e}",
+                    "error".if_supports_color(Stream::Stderr, |t| t.red()),
+                    path.display(),
+                );
+                return RunReport {
+                    files,
+                    errors: vec![(path, e)],
+                    ..RunReport::default()
+                };
+            }
+        };
+
+        print!("{}", changes.render());
+        RunReport {
+            files,
+            violations: changes.at_risk.len(),
+            ..RunReport::default()
+        }
+    }
+
+    /// Compares the tests generated from both specs, and looks up which of
+    /// the affected tests are implemented.
+    fn changes(&self, cfg: &Cli) -> Result<Changes, (PathBuf, String)> {
+        let foundry_cfg: bulloak_foundry::config::Config = cfg.into();
+        let rust_cfg = self.naming.rust_config();
+        let functions = |path: &Path| {
+            let text = read_spec(path).map_err(|e| e.to_string())?;
+            run::spec(&text, self.backend, &foundry_cfg, &rust_cfg)
+                .map(|(_, report)| functions(&report))
+                .map_err(|e| e.to_string())
+        };
+        let old = functions(&self.old).map_err(|e| (self.old.clone(), e))?;
+        let new = functions(&self.new).map_err(|e| (self.new.clone(), e))?;
+
+        let mut changes = Changes::new(&old, &new);
+        if let Some(path) = self.test_file() {
+            let implemented = self
+                .implemented(&path, &rust_cfg)
+                .map_err(|e| (path.clone(), e.to_string()))?;
+            changes.flag(&implemented);
+        }
+
+        Ok(changes)
+    }
+
+    /// The test file to look up implemented tests in, if there is one.
+    fn test_file(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.test_file {
+            return Some(path.clone());
+        }
+
+        [&self.new, &self.old]
+            .into_iter()
+            .map(|tree| run::test_file(tree, self.backend))
+            .find(|path| path.is_file())
+    }
+
+    /// The names of the tests in the file at `path` that have a body beyond
+    /// what `bulloak scaffold` emits.
+    fn implemented(
+        &self,
+        path: &Path,
+        rust_cfg: &bulloak_rust::Config,
+    ) -> anyhow::Result<HashSet<String>> {
+        let src = fs::read_to_string(path)?;
+        Ok(run::tests(&src, self.backend, rust_cfg)?
+            .into_iter()
+            .filter(|test| test.implemented)
+            .map(|test| test.name)
+            .collect())
+    }
+}
+
+/// A generated test function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Function {
+    /// The name of the function.
+    name: String,
+    /// What identifies the function across edits: the anchor of its branch,
+    /// or else the titles of the actions it covers.
+    identity: String,
+}
+
+/// Lists the test functions `report` maps the spec to, in spec order.
+fn functions(report: &Report) -> Vec<Function> {
+    let mut functions: Vec<(String, Option<String>, Vec<String>)> = vec![];
+    for (tree, ast) in report.asts().iter().enumerate() {
+        collect(report, tree, ast, None, &mut 0, &mut functions);
+    }
+
+    functions
+        .into_iter()
+        .map(|(name, anchor, titles)| Function {
+            name,
+            identity: anchor.map_or_else(
+                || titles.join("\n"),
+                |anchor| format!("#{anchor}"),
+            ),
+        })
+        .collect()
+}
+
+/// Adds the actions under `ast` to the test functions covering them.
+///
+/// `anchor` is the anchor of the closest anchored condition above `ast`, and
+/// `index` the number of actions of the tree before `ast`.
+fn collect(
+    report: &Report,
+    tree: usize,
+    ast: &Ast,
+    anchor: Option<&str>,
+    index: &mut usize,
+    functions: &mut Vec<(String, Option<String>, Vec<String>)>,
+) {
+    let (children, anchor) = match ast {
+        Ast::Root(root) => (&root.children, anchor),
+        Ast::Condition(condition) => {
+            (&condition.children, condition.anchor.as_deref().or(anchor))
+        }
+        Ast::Action(action) => {
+            *index += 1;
+            let Some(name) = report.test(tree, *index - 1) else {
+                return;
+            };
+            match functions.iter_mut().find(|(n, ..)| n == name) {
+                Some((_, _, titles)) => titles.push(action.title.clone()),
+                None => functions.push((
+                    name.to_owned(),
+                    anchor.map(str::to_owned),
+                    vec![action.title.clone()],
+                )),
+            }
+            return;
+        }
+        Ast::ActionDescription(_) => return,
+    };
+
+    for child in children {
+        collect(report, tree, child, anchor, index, functions);
+    }
+}
+
+/// How an edit changes the generated test functions.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Changes {
+    /// Functions only the new spec generates.
+    added: Vec<String>,
+    /// Functions only the old spec generates.
+    removed: Vec<String>,
+    /// Functions that cover the same branch under a new name, as `(old,
+    /// new)` pairs.
+    renamed: Vec<(String, String)>,
+    /// Removed or renamed functions whose tests are implemented, by their
+    /// old name.
+    at_risk: Vec<String>,
+}
+
+impl Changes {
+    /// Compares the functions generated before and after the edit.
+    fn new(old: &[Function], new: &[Function]) -> Self {
+        let has = |functions: &[Function], name: &str| {
+            functions.iter().any(|f| f.name == name)
+        };
+        let mut added: Vec<&Function> =
+            new.iter().filter(|f| !has(old, &f.name)).collect();
+        let mut changes = Changes::default();
+        for function in old.iter().filter(|f| !has(new, &f.name)) {
+            let renamed =
+                added.iter().position(|f| f.identity == function.identity);
+            match renamed {
+                Some(index) => {
+                    let to = added.remove(index);
+                    changes
+                        .renamed
+                        .push((function.name.clone(), to.name.clone()));
+                }
+                None => changes.removed.push(function.name.clone()),
+            }
+        }
+        changes.added = added.into_iter().map(|f| f.name.clone()).collect();
+
+        changes
+    }
+
+    /// Flags the removed or renamed functions whose tests are in
+    /// `implemented`.
+    fn flag(&mut self, implemented: &HashSet<String>) {
+        let affected =
+            self.removed.iter().chain(self.renamed.iter().map(|(old, _)| old));
+        self.at_risk = affected
+            .filter(|name| implemented.contains(*name))
+            .cloned()
+            .collect();
+    }
+
+    /// Whether the test of the function called `name` is at risk.
+    fn is_at_risk(&self, name: &str) -> bool {
+        self.at_risk.iter().any(|n| n == name)
+    }
+
+    /// Renders the changes, one function per line, followed by a summary.
+    fn render(&self) -> String {
+        let risk = |name: &str| {
+            if self.is_at_risk(name) {
+                " (implemented, at risk)".to_owned()
+            } else {
+                String::new()
+            }
+        };
+
+        let mut out = String::new();
+        for name in &self.added {
+            let mark = "+".if_supports_color(Stream::Stdout, |t| t.green());
+            let _ = writeln!(out, "{mark} {name}");
+        }
+        for name in &self.removed {
+            let mark = "-".if_supports_color(Stream::Stdout, |t| t.red());
+            let _ = writeln!(out, "{mark} {name}{}", risk(name));
+        }
+        for (old, new) in &self.renamed {
+            let mark = "~".if_supports_color(Stream::Stdout, |t| t.yellow());
+            let _ = writeln!(out, "{mark} {old} -> {new}{}", risk(old));
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+
+        let counts = format!(
+            "{} added, {} removed, {} renamed; {} {} at risk",
+            self.added.len(),
+            self.removed.len(),
+            self.renamed.len(),
+            self.at_risk.len(),
+            pluralize(
+                self.at_risk.len(),
+                "implemented test",
+                "implemented tests"
+            ),
+        );
+        let status = if self.at_risk.is_empty() {
+            "success"
+                .if_supports_color(Stream::Stdout, |t| {
+                    t.style(Style::new().bold().green())
+                })
+                .to_string()
+        } else {
+            "warn"
+                .if_supports_color(Stream::Stdout, |t| {
+                    t.style(Style::new().bold().yellow())
+                })
+                .to_string()
+        };
+        let _ = writeln!(out, "{status}: {counts}");
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{Changes, Function};
+
+    fn function(name: &str, identity: &str) -> Function {
+        Function { name: name.to_owned(), identity: identity.to_owned() }
+    }
+
+    #[test]
+    fn pairs_renamed_functions_by_identity() {
+        let old = [
+            function("test_A", "it should a."),
+            function("test_WhenB", "#b"),
+            function("test_WhenC", "it should c."),
+        ];
+        let new = [
+            function("test_A", "it should a."),
+            function("test_WhenBee", "#b"),
+            function("test_WhenD", "it should d."),
+        ];
+
+        let mut changes = Changes::new(&old, &new);
+        changes.flag(&HashSet::from(["test_WhenB".to_owned()]));
+        assert_eq!(
+            changes,
+            Changes {
+                added: vec!["test_WhenD".to_owned()],
+                removed: vec!["test_WhenC".to_owned()],
+                renamed: vec![(
+                    "test_WhenB".to_owned(),
+                    "test_WhenBee".to_owned()
+                )],
+                at_risk: vec!["test_WhenB".to_owned()],
+            }
+        );
+    }
+}
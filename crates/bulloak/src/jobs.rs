@@ -0,0 +1,199 @@
+//! Processes files on a pool of worker threads.
+
+use std::{
+    num::NonZeroUsize,
+    panic,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Instant,
+};
+
+/// Maps `f` over `items` on up to `jobs` worker threads.
+///
+/// The results keep the order of `items`, so whatever is printed from them
+/// doesn't depend on scheduling. When `jobs` is `None`, one worker is spawned
+/// per available CPU.
+pub(crate) fn map<T, R, F>(
+    items: &[T],
+    jobs: Option<NonZeroUsize>,
+    f: F,
+) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    map_until(items, jobs, usize::MAX, None, |_| 0, f)
+}
+
+/// Like [`map`], but stops taking new items once the results so far weigh
+/// `limit` in total, as measured by `weight`, or once `deadline` has passed.
+///
+/// Items are taken in order, so the results always cover the first items, up
+/// to the one that reached `limit`, whatever the number of workers. Items
+/// being processed when `deadline` passes are processed to the end.
+pub(crate) fn map_until<T, R, F, W>(
+    items: &[T],
+    jobs: Option<NonZeroUsize>,
+    limit: usize,
+    deadline: Option<Instant>,
+    weight: W,
+    f: F,
+) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    W: Fn(&R) -> usize + Sync,
+{
+    let jobs = jobs
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len());
+    let total = AtomicUsize::new(0);
+    let process = |item: &T| {
+        let result = f(item);
+        total.fetch_add(weight(&result), Ordering::Relaxed);
+        result
+    };
+    let is_done = || {
+        total.load(Ordering::Relaxed) >= limit
+            || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    };
+
+    if jobs <= 1 {
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            if is_done() {
+                break;
+            }
+            results.push(process(item));
+        }
+        return results;
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    while !is_done() {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else { break };
+                        done.push((i, process(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            let done =
+                worker.join().unwrap_or_else(|e| panic::resume_unwind(e));
+            for (i, result) in done {
+                results[i] = Some(result);
+            }
+        }
+    });
+
+    // Busy workers finish their item even once the limit is reached, so drop
+    // the results past the item that reached it, which keeps the results the
+    // same as with a single worker.
+    let mut results: Vec<R> =
+        results.into_iter().map_while(|result| result).collect();
+    let mut total = 0;
+    if let Some(last) = results.iter().position(|result| {
+        total += weight(result);
+        total >= limit
+    }) {
+        results.truncate(last + 1);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroUsize,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::{map, map_until};
+
+    #[test]
+    fn keeps_the_order_of_items() {
+        let items: Vec<u64> = (0..32).collect();
+        let results = map(&items, NonZeroUsize::new(4), |&i| {
+            // Finish later items first.
+            thread::sleep(Duration::from_millis(32 - i));
+            i * 2
+        });
+
+        assert_eq!(results, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn limits_the_number_of_workers() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items = [(); 16];
+        map(&items, NonZeroUsize::new(2), |()| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn handles_no_items() {
+        let items: [u8; 0] = [];
+        assert!(map(&items, None, |&i| i).is_empty());
+    }
+
+    #[test]
+    fn stops_once_the_limit_is_reached() {
+        let items: Vec<usize> = (0..64).collect();
+        for jobs in [1, 4] {
+            let results = map_until(
+                &items,
+                NonZeroUsize::new(jobs),
+                3,
+                None,
+                |&even| usize::from(even),
+                |&i| i % 2 == 0,
+            );
+
+            assert_eq!(results, [true, false, true, false, true]);
+        }
+    }
+
+    #[test]
+    fn stops_once_the_deadline_has_passed() {
+        let items: Vec<u64> = (0..64).collect();
+        for jobs in [1, 4] {
+            let deadline = Instant::now() + Duration::from_millis(20);
+            let results = map_until(
+                &items,
+                NonZeroUsize::new(jobs),
+                usize::MAX,
+                Some(deadline),
+                |_| 0,
+                |&i| {
+                    thread::sleep(Duration::from_millis(5));
+                    i
+                },
+            );
+
+            assert!(!results.is_empty() && results.len() < items.len());
+            assert_eq!(results, items[..results.len()]);
+        }
+    }
+}
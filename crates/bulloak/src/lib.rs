@@ -0,0 +1,29 @@
+//! `bulloak` as a library.
+//!
+//! The [`api`] module runs `bulloak`'s commands from other programs. The
+//! `bulloak` binary is a thin wrapper around it that turns the returned
+//! [`api::RunReport`] into an exit code.
+//!
+//! The [`macro@from_tree`] attribute expands a `.tree` file into test stubs
+//! inside a Rust module at compile time.
+
+pub mod api;
+mod capabilities;
+mod check;
+mod cli;
+mod diff;
+mod discovery;
+mod docs;
+mod explain;
+mod impact;
+mod jobs;
+mod markers;
+mod plugin;
+mod progress;
+mod report;
+mod run;
+mod scaffold;
+mod transaction;
+mod workspace;
+
+pub use bulloak_macros::from_tree;
@@ -1,14 +1,12 @@
 #![doc = include_str!("../README.md")]
 use std::process;
 
-mod check;
-mod cli;
-mod glob;
-mod scaffold;
-
 fn main() {
-    if let Err(e) = crate::cli::run() {
-        eprintln!("Error: {e:?}");
-        process::exit(1);
+    match bulloak::api::run_cli() {
+        Ok(report) => process::exit(report.exit_code()),
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            process::exit(1);
+        }
     }
 }
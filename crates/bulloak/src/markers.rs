@@ -0,0 +1,356 @@
+//! Markers around scaffolded code.
+//!
+//! With `bulloak scaffold --markers`, the scaffolded code is wrapped in a
+//! `// bulloak:begin` and a `// bulloak:end` line. Scaffolding again over a
+//! file with these markers regenerates what's between them only, so code
+//! written before or after them is kept. Between the markers, the bodies of
+//! the functions, modifiers and structs already in the file are kept too, so
+//! implemented tests survive.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    iter,
+    ops::Range,
+};
+
+/// The keywords that introduce an item whose body belongs to the user once
+/// scaffolded, in Solidity and Rust.
+const ITEMS: [&str; 4] = ["function", "modifier", "fn", "struct"];
+
+/// The line that opens the code `bulloak` owns.
+pub(crate) const BEGIN: &str = "// bulloak:begin";
+/// The line that closes the code `bulloak` owns.
+pub(crate) const END: &str = "// bulloak:end";
+
+/// Wraps `code` in the markers.
+pub(crate) fn wrap(code: &str) -> String {
+    format!("{BEGIN}\n{}\n{END}\n", code.trim_end_matches(['\r', '\n']))
+}
+
+/// Replaces the marked region of `existing` with the marked region of
+/// `generated`, keeping the rest of `existing` as is.
+///
+/// The bodies of items in the generated region are taken from the items of
+/// the same name in the existing region, if any.
+///
+/// Returns `None` if either text lacks a `BEGIN` line followed by an `END`
+/// line.
+pub(crate) fn splice(existing: &str, generated: &str) -> Option<String> {
+    let old = region(existing)?;
+    let new = region(generated)?;
+    Some(format!(
+        "{}{}{}",
+        &existing[..old.start],
+        keep_bodies(&existing[old.clone()], &generated[new]),
+        &existing[old.end..]
+    ))
+}
+
+/// Returns `generated` with the body of every item replaced by the body of
+/// the item of the same name in `existing`.
+///
+/// Items that share a name are paired in order.
+fn keep_bodies(existing: &str, generated: &str) -> String {
+    let mut bodies: HashMap<&str, VecDeque<&str>> = HashMap::new();
+    for (name, body) in items(existing) {
+        bodies.entry(name).or_default().push_back(&existing[body]);
+    }
+
+    let mut kept = String::with_capacity(generated.len());
+    let mut last = 0;
+    for (name, body) in items(generated) {
+        let Some(old) = bodies.get_mut(name).and_then(VecDeque::pop_front)
+        else {
+            continue;
+        };
+        kept.push_str(&generated[last..body.start]);
+        kept.push_str(old);
+        last = body.end;
+    }
+    kept.push_str(&generated[last..]);
+
+    kept
+}
+
+/// Returns the name and the byte range of the braced body of every item in
+/// `code`, in order.
+///
+/// Items without a body, e.g. `function f() external;`, are skipped, and so
+/// are items nested in a body.
+fn items(code: &str) -> Vec<(&str, Range<usize>)> {
+    let bytes = code.as_bytes();
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip_literal(bytes, i) {
+            i = end;
+            continue;
+        }
+        if !is_ident(bytes[i]) {
+            i += 1;
+            continue;
+        }
+
+        let word = ident_end(bytes, i);
+        if !ITEMS.contains(&&code[i..word]) {
+            i = word;
+            continue;
+        }
+        let start =
+            word + (code[word..].len() - code[word..].trim_start().len());
+        let name = &code[start..ident_end(bytes, start)];
+        i = start + name.len();
+        if name.is_empty() {
+            continue;
+        }
+        if let Some(body) = body(bytes, i) {
+            i = body.end;
+            items.push((name, body));
+        }
+    }
+
+    items
+}
+
+/// Returns the byte range of the braced body that follows `from`, or `None`
+/// if a `;` ends the item first.
+fn body(bytes: &[u8], from: usize) -> Option<Range<usize>> {
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut i = from;
+    while i < bytes.len() {
+        if let Some(end) = skip_literal(bytes, i) {
+            i = end;
+            continue;
+        }
+        match bytes[i] {
+            b';' if start.is_none() => return None,
+            b'{' => {
+                start.get_or_insert(i);
+                depth += 1;
+            }
+            b'}' if start.is_some() => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start?..i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// If a comment or a string starts at `i`, returns the index right after it.
+fn skip_literal(bytes: &[u8], i: usize) -> Option<usize> {
+    if let Some(end) = skip_raw_string(bytes, i) {
+        return Some(end);
+    }
+
+    let rest = &bytes[i..];
+    let end = if rest.starts_with(b"//") {
+        rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len())
+    } else if rest.starts_with(b"/*") {
+        rest.windows(2)
+            .skip(2)
+            .position(|w| w == b"*/")
+            .map_or(rest.len(), |at| at + 4)
+    } else if let Some(&quote @ (b'"' | b'\'')) = rest.first() {
+        // A `'` without a closing one on its line is a Rust lifetime.
+        let mut j = 1;
+        loop {
+            match rest.get(j) {
+                Some(b'\\') => j += 2,
+                Some(&b) if b == quote => break j + 1,
+                Some(b'\n') | None if quote == b'\'' => return None,
+                None => break rest.len(),
+                Some(_) => j += 1,
+            }
+        }
+    } else {
+        return None;
+    };
+
+    Some(i + end)
+}
+
+/// If a Rust raw string, e.g. `r#"a " }"#`, starts at `i`, returns the index
+/// right after it.
+///
+/// Raw strings have no escapes and end at the first `"` followed by as many
+/// `#` as they open with.
+fn skip_raw_string(bytes: &[u8], i: usize) -> Option<usize> {
+    if i > 0 && is_ident(bytes[i - 1]) {
+        return None;
+    }
+    let rest = &bytes[i..];
+    let prefix = if rest.starts_with(b"br") || rest.starts_with(b"cr") {
+        2
+    } else if rest.starts_with(b"r") {
+        1
+    } else {
+        return None;
+    };
+    let hashes = rest[prefix..].iter().take_while(|&&b| b == b'#').count();
+    let open = prefix + hashes + 1;
+    if rest.get(open - 1) != Some(&b'"') {
+        return None;
+    }
+
+    let close: Vec<u8> =
+        iter::once(b'"').chain(iter::repeat_n(b'#', hashes)).collect();
+    let end = rest[open..]
+        .windows(close.len())
+        .position(|w| w == close.as_slice())
+        .map_or(rest.len(), |at| open + at + close.len());
+    Some(i + end)
+}
+
+/// Whether `b` may be part of a Solidity or Rust identifier.
+const fn is_ident(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+/// Returns the index right after the identifier starting at `from`.
+fn ident_end(bytes: &[u8], from: usize) -> usize {
+    bytes[from..]
+        .iter()
+        .position(|&b| !is_ident(b))
+        .map_or(bytes.len(), |at| from + at)
+}
+
+/// Returns the byte range of the marked region of `text`, from the start of
+/// the `BEGIN` line to the end of the `END` line, without its line break.
+fn region(text: &str) -> Option<Range<usize>> {
+    let mut offset = 0;
+    let mut start = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        match start {
+            None if trimmed == BEGIN => start = Some(offset),
+            Some(start) if trimmed == END => {
+                let end = offset + line.trim_end_matches(['\r', '\n']).len();
+                return Some(start..end);
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{items, splice, wrap};
+
+    #[test]
+    fn regenerates_marked_regions_only() {
+        let existing = "import {Helper} from \"./Helper.sol\";\n\n// \
+                        bulloak:begin\ncontract Old {}\n// bulloak:end\n\n\
+                        contract Helper {}\n";
+        let generated = wrap("contract New {}\n");
+
+        let spliced = splice(existing, &generated).unwrap();
+        assert_eq!(
+            spliced,
+            "import {Helper} from \"./Helper.sol\";\n\n// \
+             bulloak:begin\ncontract New {}\n// bulloak:end\n\ncontract \
+             Helper {}\n"
+        );
+        assert_eq!(splice(&spliced, &generated).unwrap(), spliced);
+    }
+
+    #[test]
+    fn keeps_existing_bodies() {
+        let existing = wrap(
+            "contract T {\n    modifier whenPaused() {\n        \
+             vault.pause();\n        _;\n    }\n\n    function test_A() \
+             external {\n        assertEq(\"}\", a()); // }\n    }\n}\n",
+        );
+        let generated = wrap(
+            "contract T {\n    modifier whenPaused() {\n        _;\n    \
+             }\n\n    function test_A() external {\n        // it works\n    \
+             }\n\n    function test_B() external whenPaused {\n        // \
+             it reverts\n    }\n}\n",
+        );
+
+        assert_eq!(
+            splice(&existing, &generated).unwrap(),
+            wrap(
+                "contract T {\n    modifier whenPaused() {\n        \
+                 vault.pause();\n        _;\n    }\n\n    function test_A() \
+                 external {\n        assertEq(\"}\", a()); // }\n    \
+                 }\n\n    function test_B() external whenPaused {\n        \
+                 // it reverts\n    }\n}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn finds_rust_items() {
+        let code = "#[derive(Default)]\nstruct TestContext { n: u8 }\nfn \
+                    helper<'a>(ctx: &'a str) -> &'a str {\n    ctx\n}\nmod \
+                    tests {\n    fn test_a() {\n        let c = '{';\n    \
+                    }\n}\nstruct Unit;\n";
+        let names: Vec<_> = items(code)
+            .into_iter()
+            .map(|(name, body)| (name, &code[body]))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("TestContext", "{ n: u8 }"),
+                ("helper", "{\n    ctx\n}"),
+                ("test_a", "{\n        let c = '{';\n    }"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_rust_raw_strings() {
+        let code = r##"fn test_a() {
+    let s = r#"a " }"#;
+    let b = br"\";
+}
+fn test_b() {}
+"##;
+        let names: Vec<_> = items(code)
+            .into_iter()
+            .map(|(name, body)| (name, &code[body]))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (
+                    "test_a",
+                    "{\n    let s = r#\"a \" }\"#;\n    let b = br\"\\\";\n}"
+                ),
+                ("test_b", "{}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn needs_both_markers() {
+        let generated = wrap("contract New {}");
+        assert_eq!(splice("contract Old {}\n", &generated), None);
+        assert_eq!(
+            splice("// bulloak:begin\ncontract Old {}\n", &generated),
+            None
+        );
+        assert_eq!(splice(&generated, "contract New {}"), None);
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings_around_the_region() {
+        let existing = "// bulloak:begin\r\nold\r\n// bulloak:end\r\nmine\r\n";
+        let generated = "// bulloak:begin\r\nnew\r\n// bulloak:end\r\n";
+        assert_eq!(
+            splice(existing, generated).unwrap(),
+            "// bulloak:begin\r\nnew\r\n// bulloak:end\r\nmine\r\n"
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Loads custom check rules compiled to WebAssembly.
+//!
+//! A rule is a `.wasm` module in the rules directory, named after the module's
+//! file stem. The module exports:
+//!
+//! - `memory`, its linear memory.
+//! - `alloc(len: i32) -> i32`, which reserves `len` bytes for bulloak to write
+//!   the input to.
+//! - `check(ptr: i32, len: i32) -> i64`, which reads the input and returns
+//!   where its output is, packed as `ptr << 32 | len`.
+//!
+//! The input is a JSON object holding the [`Facts`] about a spec and its test
+//! file. The output is a JSON array of `{"message": "...", "line": 12}`
+//! objects, one per violation, where `line` is optional.
+//!
+//! Rules run sandboxed, with no imports, with a bounded amount of fuel so that
+//! a rule that loops forever fails instead of hanging `bulloak check`, and with
+//! a bounded amount of memory so that a rule can't exhaust the host's.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use bulloak_syntax::{markdown::read_spec, Ast};
+use serde::{Deserialize, Serialize};
+use wasmi::{
+    Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::{
+    cli::Backend,
+    run::{self, Test},
+};
+
+/// The number of instructions, roughly, that a rule can run per spec.
+const FUEL: u64 = 1 << 32;
+/// The number of bytes the linear memory of a rule can grow to.
+const MEMORY: usize = 64 << 20;
+
+/// The custom rules loaded from a rules directory.
+pub(crate) struct Rules {
+    /// The engine the rules were compiled with.
+    engine: Engine,
+    /// The name and the compiled module of each rule.
+    rules: Vec<(String, Module)>,
+}
+
+impl Rules {
+    /// Loads every `.wasm` module in `dir`, in alphabetical order.
+    ///
+    /// There are no rules if `dir` doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` can't be read, or if one of the modules is
+    /// not valid WebAssembly.
+    pub(crate) fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        if !dir.is_dir() {
+            return Ok(Self { engine, rules: Vec::new() });
+        }
+
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "wasm"));
+        paths.sort();
+        let mut rules = Vec::with_capacity(paths.len());
+        for path in paths {
+            let wasm = fs::read(&path)?;
+            let module = Module::new(&engine, &wasm).map_err(|e| {
+                anyhow::anyhow!("invalid rule {}: {e}", path.display())
+            })?;
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            rules.push((name, module));
+        }
+
+        Ok(Self { engine, rules })
+    }
+
+    /// Whether there is no rule to run.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Runs every rule on `facts`.
+    ///
+    /// A rule that fails, e.g. because it traps or returns malformed output,
+    /// is reported as a finding of that rule.
+    pub(crate) fn check(&self, facts: &Facts) -> Vec<Finding> {
+        let input = serde_json::to_vec(facts).expect("should serialize facts");
+        self.rules
+            .iter()
+            .flat_map(|(name, module)| {
+                let outputs = self.run(module, &input).unwrap_or_else(|e| {
+                    vec![Output {
+                        message: format!("the rule failed: {e:#}"),
+                        line: None,
+                    }]
+                });
+                outputs.into_iter().map(|output| Finding {
+                    rule: name.clone(),
+                    message: output.message,
+                    line: output.line,
+                })
+            })
+            .collect()
+    }
+
+    /// Instantiates `module` and calls its `check` function on `input`.
+    fn run(
+        &self,
+        module: &Module,
+        input: &[u8],
+    ) -> anyhow::Result<Vec<Output>> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MEMORY)
+            .instances(1)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(FUEL).map_err(wasmi::Error::from)?;
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .context("the rule exports no `memory`")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+        let check =
+            instance.get_typed_func::<(i32, i32), i64>(&store, "check")?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory
+            .write(&mut store, usize::try_from(ptr)?, input)
+            .map_err(wasmi::Error::from)?;
+        let packed = check.call(&mut store, (ptr, len))?.to_be_bytes();
+        let (ptr, len) = packed.split_at(4);
+        let ptr = u32::from_be_bytes(ptr.try_into()?);
+        let len = u32::from_be_bytes(len.try_into()?);
+        let mut output = vec![0; usize::try_from(len)?];
+        memory
+            .read(&store, usize::try_from(ptr)?, &mut output)
+            .map_err(wasmi::Error::from)?;
+
+        serde_json::from_slice(&output).context("malformed output")
+    }
+}
+
+/// What a rule reports about a violation.
+#[derive(Debug, Deserialize)]
+struct Output {
+    /// What is wrong.
+    message: String,
+    /// The line of the test file the violation was found at, if any.
+    #[serde(default)]
+    line: Option<usize>,
+}
+
+/// A violation reported by a custom rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Finding {
+    /// The name of the rule.
+    pub(crate) rule: String,
+    /// What is wrong.
+    pub(crate) message: String,
+    /// The line of the test file the violation was found at, if any.
+    pub(crate) line: Option<usize>,
+}
+
+/// What custom rules are told about a spec and its test file.
+#[derive(Debug, Serialize)]
+pub(crate) struct Facts {
+    /// The backend the test file is written for.
+    backend: Backend,
+    /// The path of the spec.
+    spec: PathBuf,
+    /// The trees that make up the spec.
+    trees: Vec<Node>,
+    /// The names of the test functions the spec calls for.
+    expected_tests: Vec<String>,
+    /// The path of the test file.
+    pub(crate) test_file: PathBuf,
+    /// The test functions of the test file, if it exists and parses.
+    tests: Vec<Test>,
+}
+
+impl Facts {
+    /// Gathers the facts about the spec at `tree_path` and its test file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spec can't be read or parsed.
+    pub(crate) fn gather(
+        tree_path: &Path,
+        backend: Backend,
+        foundry_cfg: &bulloak_foundry::config::Config,
+        rust_cfg: &bulloak_rust::Config,
+    ) -> anyhow::Result<Self> {
+        let text = read_spec(tree_path)?;
+        let (_, report) = run::spec(&text, backend, foundry_cfg, rust_cfg)?;
+        let test_file = run::test_file(tree_path, backend);
+        let tests = fs::read_to_string(&test_file)
+            .ok()
+            .and_then(|src| run::tests(&src, backend, rust_cfg).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            backend,
+            spec: tree_path.to_path_buf(),
+            trees: report.asts().iter().map(Node::from).collect(),
+            expected_tests: report
+                .test_names()
+                .into_iter()
+                .map(str::to_owned)
+                .collect(),
+            test_file,
+            tests,
+        })
+    }
+}
+
+/// A node of a tree, as custom rules see it.
+#[derive(Debug, Serialize)]
+struct Node {
+    /// One of `root`, `condition`, `action` or `description`.
+    kind: &'static str,
+    /// The title of the node, or the text of a description.
+    title: String,
+    /// The line of the spec the node starts at.
+    line: usize,
+    /// The anchor of the node, if any.
+    anchor: Option<String>,
+    /// The tags of the node.
+    tags: Vec<String>,
+    /// The children of the node.
+    children: Vec<Node>,
+}
+
+impl From<&Ast> for Node {
+    fn from(ast: &Ast) -> Self {
+        let children =
+            |children: &[Ast]| children.iter().map(Node::from).collect();
+        let line = ast.span().start.line;
+        match ast {
+            Ast::Root(root) => Node {
+                kind: "root",
+                title: root.contract_name.clone(),
+                line,
+                anchor: None,
+                tags: Vec::new(),
+                children: children(&root.children),
+            },
+            Ast::Condition(condition) => Node {
+                kind: "condition",
+                title: condition.title.clone(),
+                line,
+                anchor: condition.anchor.clone(),
+                tags: condition.tags.clone(),
+                children: children(&condition.children),
+            },
+            Ast::Action(action) => Node {
+                kind: "action",
+                title: action.title.clone(),
+                line,
+                anchor: action.anchor.clone(),
+                tags: action.tags.clone(),
+                children: children(&action.children),
+            },
+            Ast::ActionDescription(description) => Node {
+                kind: "description",
+                title: description.text.clone(),
+                line,
+                anchor: None,
+                tags: Vec::new(),
+                children: Vec::new(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Facts, Finding, Rules};
+    use crate::cli::Backend;
+
+    /// A rule that reports `output`, whatever the input.
+    fn rule(output: &str) -> Vec<u8> {
+        let escaped = output.replace('"', "\\\"");
+        wat::parse_str(format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "{escaped}")
+                (func (export "alloc") (param i32) (result i32) i32.const 1024)
+                (func (export "check") (param i32 i32) (result i64)
+                    i64.const {}))"#,
+            output.len()
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn runs_the_rules_of_a_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let rules = dir.path().join("rules");
+        fs::create_dir(&rules).unwrap();
+        let output = r#"[{"message":"use the fixtures","line":3}]"#;
+        fs::write(rules.join("fixtures.wasm"), rule(output)).unwrap();
+        let trap = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) i32.const 0)
+                (func (export "check") (param i32 i32) (result i64)
+                    unreachable))"#,
+        )
+        .unwrap();
+        fs::write(rules.join("broken.wasm"), trap).unwrap();
+        fs::write(rules.join("README.md"), "not a rule").unwrap();
+
+        let tree = dir.path().join("foo.tree");
+        fs::write(&tree, "Foo\n└── when bar\n    └── it works.").unwrap();
+        let facts = Facts::gather(
+            &tree,
+            Backend::Solidity,
+            &bulloak_foundry::config::Config::default(),
+            &bulloak_rust::Config::default(),
+        )
+        .unwrap();
+        assert_eq!(facts.expected_tests, vec!["test_WhenBar"]);
+        assert_eq!(facts.test_file, dir.path().join("foo.t.sol"));
+
+        let findings = Rules::load(&rules).unwrap().check(&facts);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].rule, "broken");
+        assert!(findings[0].message.starts_with("the rule failed: "));
+        assert_eq!(
+            findings[1],
+            Finding {
+                rule: "fixtures".to_owned(),
+                message: "use the fixtures".to_owned(),
+                line: Some(3),
+            }
+        );
+
+        assert!(Rules::load(&dir.path().join("missing")).unwrap().is_empty());
+        fs::write(rules.join("invalid.wasm"), "not wasm").unwrap();
+        assert!(Rules::load(&rules).is_err());
+    }
+
+    #[test]
+    fn caps_the_memory_of_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let rules = dir.path().join("rules");
+        fs::create_dir(&rules).unwrap();
+        // 2048 pages of 64 KiB are 128 MiB.
+        let grows = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) i32.const 0)
+                (func (export "check") (param i32 i32) (result i64)
+                    (drop (memory.grow (i32.const 2048)))
+                    i64.const 0))"#,
+        )
+        .unwrap();
+        fs::write(rules.join("grows.wasm"), grows).unwrap();
+        let starts_big = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 2048)
+                (func (export "alloc") (param i32) (result i32) i32.const 0)
+                (func (export "check") (param i32 i32) (result i64)
+                    i64.const 0))"#,
+        )
+        .unwrap();
+        fs::write(rules.join("starts_big.wasm"), starts_big).unwrap();
+
+        let tree = dir.path().join("foo.tree");
+        fs::write(&tree, "Foo\n└── it works.").unwrap();
+        let facts = Facts::gather(
+            &tree,
+            Backend::Solidity,
+            &bulloak_foundry::config::Config::default(),
+            &bulloak_rust::Config::default(),
+        )
+        .unwrap();
+
+        let findings = Rules::load(&rules).unwrap().check(&facts);
+        assert_eq!(findings.len(), 2);
+        for finding in findings {
+            assert!(
+                finding.message.starts_with("the rule failed: "),
+                "{finding:?}"
+            );
+        }
+    }
+}
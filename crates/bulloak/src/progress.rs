@@ -0,0 +1,116 @@
+//! Progress bars and timings for runs over many files.
+
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use owo_colors::{OwoColorize, Stream};
+
+/// The number of files under which no progress bar is shown, since the run
+/// is over before it would be of any use.
+const MIN_FILES: usize = 16;
+/// The number of files `--timings` reports.
+const SLOWEST: usize = 10;
+
+/// Tracks the files processed by a command, drawing a progress bar on
+/// standard error when it is a terminal.
+pub(crate) struct Progress {
+    /// The progress bar, hidden for short runs.
+    bar: ProgressBar,
+    /// How long each file took, if `--timings` was passed.
+    timings: Option<Mutex<Vec<(PathBuf, Duration)>>>,
+}
+
+impl Progress {
+    /// Creates a tracker for a run over `len` files, which records how long
+    /// each file takes if `timings` is set.
+    pub(crate) fn new(len: usize, timings: bool) -> Self {
+        let bar = if len < MIN_FILES {
+            ProgressBar::hidden()
+        } else {
+            let style = ProgressStyle::with_template(
+                "{bar:32} {pos}/{len} [{elapsed}] {wide_msg}",
+            )
+            .unwrap_or_else(|_| ProgressStyle::default_bar());
+            ProgressBar::new(len as u64).with_style(style)
+        };
+
+        Self { bar, timings: timings.then(Mutex::default) }
+    }
+
+    /// Runs `f` for the file at `path`, advancing the progress bar once it
+    /// returns.
+    pub(crate) fn track<R>(&self, path: &Path, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        self.bar.set_message(format!("{} ({})", path.display(), ms(elapsed)));
+        self.bar.inc(1);
+        if let Some(timings) = &self.timings {
+            let mut timings =
+                timings.lock().unwrap_or_else(PoisonError::into_inner);
+            timings.push((path.to_path_buf(), elapsed));
+        }
+
+        result
+    }
+
+    /// Clears the progress bar and, with `--timings`, reports the slowest
+    /// files.
+    pub(crate) fn finish(self) {
+        self.bar.finish_and_clear();
+        let Some(timings) = self.timings else { return };
+        let mut timings =
+            timings.into_inner().unwrap_or_else(PoisonError::into_inner);
+        if timings.is_empty() {
+            return;
+        }
+
+        let total: Duration = timings.iter().map(|(_, elapsed)| *elapsed).sum();
+        timings.sort_by_key(|(_, elapsed)| Reverse(*elapsed));
+        let files = if timings.len() == 1 { "file" } else { "files" };
+        eprintln!(
+            "{}: {} {files}, {} in total, slowest first:",
+            "timings".if_supports_color(Stream::Stderr, |t| t.bold()),
+            timings.len(),
+            ms(total)
+        );
+        for (path, elapsed) in timings.iter().take(SLOWEST) {
+            eprintln!("{:>10}  {}", ms(*elapsed), path.display());
+        }
+    }
+}
+
+/// Formats `duration` in milliseconds, e.g. `12.3ms`.
+fn ms(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use super::{ms, Progress};
+
+    #[test]
+    fn records_timings_per_file() {
+        let progress = Progress::new(2, true);
+        assert_eq!(progress.track(Path::new("a.tree"), || 1), 1);
+        progress.track(Path::new("b.tree"), || ());
+
+        let timings = progress.timings.as_ref().unwrap().lock().unwrap();
+        let paths: Vec<_> = timings.iter().map(|(p, _)| p.as_path()).collect();
+        assert_eq!(paths, [Path::new("a.tree"), Path::new("b.tree")]);
+    }
+
+    #[test]
+    fn formats_milliseconds() {
+        assert_eq!(ms(Duration::from_micros(12_345)), "12.3ms");
+        assert_eq!(ms(Duration::ZERO), "0.0ms");
+    }
+}
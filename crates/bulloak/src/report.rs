@@ -0,0 +1,277 @@
+//! The machine-readable output of `bulloak check --format json`.
+//!
+//! The output follows the JSON Schema in [`SCHEMA`]. Fields may be added
+//! without notice, but removing or changing one bumps [`SCHEMA_VERSION`], so
+//! consumers can tell reports they don't understand apart.
+//!
+//! With `--fix --plan-only`, the output is a [`FixPlan`] instead.
+
+use std::{borrow::Cow, fmt, path::PathBuf};
+
+use bulloak_foundry::check::location::Location;
+use serde::{Deserialize, Serialize};
+
+/// The version of the report format, stored in every report.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema describing the reports of this version.
+pub const SCHEMA: &str = include_str!("../schema/check-report.v1.json");
+
+/// The outcome of `bulloak check`, as printed with `--format json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// The version of the format this report follows.
+    pub schema_version: u32,
+    /// The spec files that were checked.
+    pub files: Vec<PathBuf>,
+    /// The spec files left unchecked because checking stopped early, e.g.
+    /// once `--max-duration` ran out.
+    #[serde(default)]
+    pub unchecked: Vec<PathBuf>,
+    /// The violations found, in the order they were reported.
+    pub violations: Vec<ViolationReport>,
+    /// The spec files that could not be checked.
+    pub errors: Vec<ErrorReport>,
+    /// The number of actions in the checked specs.
+    pub leaves: usize,
+    /// How many of `leaves` have a test.
+    pub covered_leaves: usize,
+}
+
+/// A violation found by `bulloak check`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViolationReport {
+    /// The file the violation was found in.
+    pub file: PathBuf,
+    /// The line the violation was found at, if known.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The stable ID of the rule the violation breaks, e.g. `BK105`, which
+    /// `bulloak explain` describes.
+    #[serde(default)]
+    pub rule: String,
+    /// The kind of violation, e.g. `missing test`.
+    pub category: String,
+    /// What is wrong.
+    pub message: String,
+    /// How to fix it, if there is a hint.
+    #[serde(default)]
+    pub help: Option<String>,
+    /// Whether `bulloak check --fix` fixes it.
+    pub fixable: bool,
+    /// The edits that fix it on its own, e.g. for an editor quick fix, if
+    /// it is fixable and fixing it doesn't clobber hand-written code.
+    #[serde(default)]
+    pub suggestion: Vec<EditReport>,
+}
+
+impl ViolationReport {
+    /// Describes a violation found in a Solidity test file.
+    pub(crate) fn from_solidity(
+        violation: &bulloak_foundry::violation::Violation,
+    ) -> Self {
+        let line = match violation.location {
+            Location::Code(_, line) => Some(line),
+            Location::File(_) => None,
+        };
+        Self {
+            file: violation.location.file().into(),
+            line,
+            rule: violation.kind.rule().to_owned(),
+            category: violation.kind.category().0.to_owned(),
+            message: violation.kind.to_string(),
+            help: violation.kind.help().map(Cow::into_owned),
+            fixable: violation.is_fixable(),
+            suggestion: Vec::new(),
+        }
+    }
+
+    /// Describes a violation found in a Rust test file.
+    pub(crate) fn from_rust(violation: &bulloak_rust::Violation) -> Self {
+        Self {
+            file: violation.file_path.clone(),
+            line: violation.line,
+            rule: violation.kind.rule().to_owned(),
+            category: violation.kind.category().0.to_owned(),
+            message: violation.kind.to_string(),
+            help: violation.kind.help(),
+            fixable: violation.kind.is_fixable(),
+            suggestion: Vec::new(),
+        }
+    }
+
+    /// Attaches the edits that fix the violation to its report.
+    pub(crate) fn with_suggestion(self, suggestion: Vec<EditReport>) -> Self {
+        Self { suggestion, ..self }
+    }
+}
+
+/// The edits `bulloak check --fix --plan-only` would make, as printed with
+/// `--format json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixPlan {
+    /// The version of the format this plan follows.
+    pub schema_version: u32,
+    /// The edits, grouped by file and in file order.
+    ///
+    /// Byte ranges refer to the files as they are, so the edits of a file are
+    /// applied from last to first.
+    pub edits: Vec<EditReport>,
+}
+
+/// An edit of a file: its bytes from `start` to `end` are replaced with
+/// `replacement`.
+///
+/// A file that doesn't exist yet is created with a single edit at `0..0`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditReport {
+    /// The file to edit.
+    pub file: PathBuf,
+    /// The offset of the first byte to replace.
+    pub start: usize,
+    /// The offset past the last byte to replace.
+    pub end: usize,
+    /// The text to put in place of the range.
+    pub replacement: String,
+}
+
+/// A spec file that `bulloak check` could not check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// The spec file.
+    pub file: PathBuf,
+    /// Why it could not be checked.
+    pub message: String,
+}
+
+/// An error returned when reading a report.
+#[derive(Debug)]
+pub enum ReportError {
+    /// The report is not valid JSON or misses required fields.
+    Json(serde_json::Error),
+    /// The report follows a newer version of the format.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Json(e) => write!(f, "invalid report: {e}"),
+            ReportError::UnsupportedVersion(version) => write!(
+                f,
+                "report schema version {version} is newer than the supported \
+                 version {SCHEMA_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReportError::Json(e) => Some(e),
+            ReportError::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+/// Reads a report printed by `bulloak check --format json`.
+///
+/// Fields this version doesn't know about are ignored, so reports from
+/// releases that only added fields still parse.
+///
+/// # Errors
+///
+/// Returns an error if `json` is not a report, or if it follows a newer
+/// version of the format.
+pub fn parse_check_report(json: &str) -> Result<CheckReport, ReportError> {
+    let report: CheckReport =
+        serde_json::from_str(json).map_err(ReportError::Json)?;
+    if report.schema_version > SCHEMA_VERSION {
+        return Err(ReportError::UnsupportedVersion(report.schema_version));
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+
+    use super::{
+        parse_check_report, CheckReport, EditReport, ReportError,
+        ViolationReport, SCHEMA, SCHEMA_VERSION,
+    };
+
+    fn report() -> CheckReport {
+        CheckReport {
+            schema_version: SCHEMA_VERSION,
+            files: vec!["foo.tree".into()],
+            violations: vec![ViolationReport {
+                file: "foo.t.sol".into(),
+                line: Some(3),
+                rule: "BK105".to_owned(),
+                category: "missing test".to_owned(),
+                message: "function \"test_Foo\" is missing in .sol".to_owned(),
+                help: None,
+                fixable: true,
+                suggestion: vec![EditReport {
+                    file: "foo.t.sol".into(),
+                    start: 120,
+                    end: 120,
+                    replacement: "    function test_Foo() external {}\n"
+                        .to_owned(),
+                }],
+            }],
+            leaves: 2,
+            covered_leaves: 1,
+            ..CheckReport::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_and_ignores_unknown_fields() {
+        let json = serde_json::to_string(&report()).unwrap();
+        assert_eq!(parse_check_report(&json).unwrap(), report());
+
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        value["added_later"] = Value::Bool(true);
+        assert_eq!(parse_check_report(&value.to_string()).unwrap(), report());
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let newer =
+            CheckReport { schema_version: SCHEMA_VERSION + 1, ..report() };
+        let json = serde_json::to_string(&newer).unwrap();
+        assert!(matches!(
+            parse_check_report(&json),
+            Err(ReportError::UnsupportedVersion(v)) if v == SCHEMA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn schema_matches_the_report() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+
+        let report = serde_json::to_value(report()).unwrap();
+        let violation = &report["violations"][0];
+        for (required, value) in [
+            (&schema["required"], &report),
+            (&schema["$defs"]["violation"]["required"], violation),
+        ] {
+            for field in required.as_array().unwrap() {
+                assert!(value.get(field.as_str().unwrap()).is_some());
+            }
+        }
+        let properties = schema["properties"].as_object().unwrap();
+        for field in report.as_object().unwrap().keys() {
+            assert!(properties.contains_key(field), "{field} is undocumented");
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! Runs the tests of a spec through `cargo test`.
+
+use std::{collections::HashMap, iter, path::Path, process::Command};
+
+use anyhow::Context;
+use bulloak_rust::{
+    check::rules::expected_tests,
+    scaffold::{test_file_path, wiring::module_path},
+    Config,
+};
+use bulloak_syntax::{markdown::read_spec, Ast};
+
+use super::{pair_tests, Outcome, Report, Status};
+
+/// Runs the Rust tests generated from the tree at `tree_path`.
+///
+/// Only the test functions that `bulloak scaffold -b rust` would emit for the
+/// tree are selected, through exact libtest name filters. Any extra `args` are
+/// forwarded to `cargo test`, and the ones after a `--` to libtest.
+pub(crate) fn run(
+    tree_path: &Path,
+    cfg: &Config,
+    args: &[String],
+) -> anyhow::Result<Report> {
+    let text = read_spec(tree_path)
+        .with_context(|| format!("could not read {}", tree_path.display()))?;
+    let (_, mut report, tests) = select(&text, &cfg.for_tree(tree_path))?;
+    let module = module_path(&test_file_path(tree_path));
+
+    // libtest's JSON output is unstable, so read the results from its default
+    // output, which works on every toolchain.
+    let command = command(&tests, module.as_deref(), args);
+    let output = Command::new(&command[0])
+        .args(&command[1..])
+        .output()
+        .context("could not execute `cargo`")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let outcomes = parse_output(&stdout).with_context(|| {
+        String::from_utf8_lossy(&output.stderr).trim().to_owned()
+    })?;
+    for (path, name) in &tests {
+        if let Some(outcome) = find(&outcomes, path, module.as_deref()) {
+            report.record(name, outcome.clone());
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns the `cargo test` command line that runs the Rust tests generated
+/// from the tree at `tree_path`, forwarding `args`.
+pub(crate) fn command_line(
+    tree_path: &Path,
+    cfg: &Config,
+    args: &[String],
+) -> anyhow::Result<Vec<String>> {
+    let text = read_spec(tree_path)
+        .with_context(|| format!("could not read {}", tree_path.display()))?;
+    let (_, _, tests) = select(&text, &cfg.for_tree(tree_path))?;
+    let module = module_path(&test_file_path(tree_path));
+
+    Ok(command(&tests, module.as_deref(), args))
+}
+
+/// The `cargo test` command that selects `tests`, given the path of the
+/// module of their test file, if known.
+///
+/// The arguments in `args` after a `--` go to libtest, the others to `cargo
+/// test`. Tests are selected by their full path with `--exact` when the
+/// module is known, and else by their path within the test file, which
+/// libtest matches as a substring of the full path.
+fn command(
+    tests: &[(String, String)],
+    module: Option<&str>,
+    args: &[String],
+) -> Vec<String> {
+    let (cargo_args, libtest_args) =
+        match args.iter().position(|arg| arg == "--") {
+            Some(i) => (&args[..i], &args[i + 1..]),
+            None => (args, &[][..]),
+        };
+
+    let mut command = vec!["cargo".to_owned(), "test".to_owned()];
+    command.extend_from_slice(cargo_args);
+    command.push("--".to_owned());
+    if module.is_some() {
+        command.push("--exact".to_owned());
+    }
+    command.extend_from_slice(libtest_args);
+    let mut paths: Vec<String> =
+        tests.iter().map(|(path, _)| qualify(path, module)).collect();
+    paths.sort_unstable();
+    paths.dedup();
+    command.extend(paths);
+    command
+}
+
+/// The full path of the test at `path` in a test file whose module is
+/// `module`, if known.
+fn qualify(path: &str, module: Option<&str>) -> String {
+    match module {
+        Some("") | None => path.to_owned(),
+        Some(module) => format!("{module}::{path}"),
+    }
+}
+
+/// The outcome of the test at `path` in a test file whose module is
+/// `module`, if libtest ran it.
+///
+/// Without a module, the test is looked up by the end of its full path, and
+/// only found if no other test path ends the same way.
+fn find<'a>(
+    outcomes: &'a HashMap<String, Outcome>,
+    path: &str,
+    module: Option<&str>,
+) -> Option<&'a Outcome> {
+    if module.is_some() {
+        return outcomes.get(&qualify(path, module));
+    }
+
+    let suffix = format!("::{path}");
+    let mut found = outcomes
+        .iter()
+        .filter(|(full, _)| *full == path || full.ends_with(&suffix));
+    match (found.next(), found.next()) {
+        (Some((_, outcome)), None) => Some(outcome),
+        _ => None,
+    }
+}
+
+/// Maps every action in `text` to the Rust test function covering it.
+///
+/// Returns the name of the tree's root alongside the mapping.
+pub(super) fn spec(
+    text: &str,
+    cfg: &Config,
+) -> anyhow::Result<(String, Report)> {
+    let (name, report, _) = select(text, cfg)?;
+    Ok((name, report))
+}
+
+/// The test functions of a spec, by their path within the test file, e.g.
+/// `tests::test_when_paused`, along with their name.
+type Tests = Vec<(String, String)>;
+
+/// Like [`spec`], but also lists the test functions of the spec.
+fn select(text: &str, cfg: &Config) -> anyhow::Result<(String, Report, Tests)> {
+    let ast =
+        bulloak_syntax::cache::parse_one_with(text, &cfg.parse_options())?;
+    let expected = expected_tests(&ast, cfg)?;
+    let paths = expected
+        .iter()
+        .map(|test| {
+            let path = match &test.module {
+                Some(module) => format!("tests::{module}::{}", test.name),
+                None => format!("tests::{}", test.name),
+            };
+            (path, test.name.clone())
+        })
+        .collect();
+
+    let mut expected = expected.into_iter();
+    let asts = vec![ast];
+    let tests = pair_tests(&asts, |actions| {
+        let mut names = Vec::new();
+        while names.len() < actions {
+            let Some(info) = expected.next() else { break };
+            names.extend(iter::repeat_n(info.name, info.actions.len()));
+        }
+        names
+    });
+    let name = match &asts[0] {
+        Ast::Root(root) => root.contract_name.clone(),
+        _ => String::new(),
+    };
+
+    Ok((name, Report::new(asts, tests), paths))
+}
+
+/// Extracts the outcome of every test from the output of libtest.
+///
+/// libtest prints a `test <path> ... <result>` line per test, and a
+/// `test result:` line per test binary. Tests are keyed by their full path.
+/// libtest doesn't time tests on stable toolchains, so outcomes have no
+/// duration.
+fn parse_output(stdout: &str) -> anyhow::Result<HashMap<String, Outcome>> {
+    if !stdout.lines().any(|line| line.starts_with("test result:")) {
+        anyhow::bail!("`cargo test` did not produce any results");
+    }
+
+    let mut outcomes = HashMap::new();
+    for line in stdout.lines() {
+        let Some((name, result)) = line
+            .strip_prefix("test ")
+            .and_then(|line| line.split_once(" ... "))
+        else {
+            continue;
+        };
+        let status = match result.trim_end() {
+            "ok" => Status::Passed,
+            "FAILED" => Status::Failed,
+            result if result.starts_with("ignored") => Status::Skipped,
+            _ => continue,
+        };
+        let path = name.strip_suffix(" - should panic").unwrap_or(name);
+        outcomes.insert(path.to_owned(), Outcome { status, duration: None });
+    }
+
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use bulloak_rust::Config;
+    use pretty_assertions::assert_eq;
+
+    use super::{command_line, find, parse_output, spec};
+    use crate::run::{tests::action_index, Outcome, Status};
+
+    #[test]
+    fn pairs_actions_with_scaffolded_tests() {
+        let tree = r"HashPair
+├── It should never panic.
+└── When first arg is smaller
+    ├── It should match the result.
+    └── It should be sorted.";
+        let (name, report) = spec(tree, &Config::default()).unwrap();
+
+        assert_eq!(name, "HashPair");
+
+        let index = action_index(&report.asts[0], "It should never").unwrap();
+        assert_eq!(report.tests[&(0, index)], "test_should_never_panic");
+        let index = action_index(&report.asts[0], "It should be").unwrap();
+        assert_eq!(report.tests[&(0, index)], "test_when_first_arg_is_smaller");
+        assert_eq!(report.test_names().len(), 2);
+    }
+
+    #[test]
+    fn builds_the_cargo_command_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree_path = dir.path().join("hash_pair.tree");
+        let tree = "HashPair\n├── It should never panic.\n└── When first \
+                    arg is smaller\n    └── It should match the result.";
+        std::fs::write(&tree_path, tree).unwrap();
+
+        let args = ["--lib".to_owned()];
+        let command =
+            command_line(&tree_path, &Config::default(), &args).unwrap();
+        assert_eq!(
+            command,
+            [
+                "cargo",
+                "test",
+                "--lib",
+                "--",
+                "tests::test_should_never_panic",
+                "tests::test_when_first_arg_is_smaller",
+            ]
+        );
+    }
+
+    #[test]
+    fn selects_tests_by_full_path_in_a_crate() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("src/vault")).unwrap();
+        let tree_path = dir.path().join("src/vault/deposit.tree");
+        let tree = "Deposit\n├── when a\n│   └── it works.\n└── when a and \
+                    b\n    └── it works.";
+        std::fs::write(&tree_path, tree).unwrap();
+
+        let args = ["--release", "--", "--nocapture"].map(str::to_owned);
+        let command =
+            command_line(&tree_path, &Config::default(), &args).unwrap();
+        assert_eq!(
+            command,
+            [
+                "cargo",
+                "test",
+                "--release",
+                "--",
+                "--exact",
+                "--nocapture",
+                "vault::deposit_test::tests::test_when_a",
+                "vault::deposit_test::tests::test_when_a_and_b",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_outcomes_by_path() {
+        let passed = Outcome { status: Status::Passed, duration: None };
+        let outcomes = [
+            ("vault_test::tests::test_when_a", passed.clone()),
+            ("vault_test::tests::test_when_a_and_b", passed.clone()),
+            ("swap_test::tests::test_when_b", passed.clone()),
+            ("tests::test_when_b", passed.clone()),
+        ]
+        .map(|(path, outcome)| (path.to_owned(), outcome))
+        .into_iter()
+        .collect();
+
+        let module = Some("vault_test");
+        assert!(find(&outcomes, "tests::test_when_a", module).is_some());
+        assert!(find(&outcomes, "tests::test_when_b", module).is_none());
+        assert!(find(&outcomes, "tests::test_when_a", None).is_some());
+        assert!(find(&outcomes, "tests::test_when_b", None).is_none());
+    }
+
+    #[test]
+    fn parses_libtest_output() {
+        let stdout = r"
+running 4 tests
+test hash_pair_test::tests::test_a ... ok
+test hash_pair_test::tests::test_b - should panic ... FAILED
+test hash_pair_test::tests::test_c ... ignored
+test hash_pair_test::tests::test_d ... ignored, slow
+
+failures:
+
+---- hash_pair_test::tests::test_b stdout ----
+thread 'hash_pair_test::tests::test_b' panicked at src/lib.rs:3:5:
+not yet implemented
+
+failures:
+    hash_pair_test::tests::test_b
+
+test result: FAILED. 1 passed; 1 failed; 2 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        let outcomes = parse_output(stdout).unwrap();
+
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(
+            outcomes["hash_pair_test::tests::test_a"],
+            Outcome { status: Status::Passed, duration: None }
+        );
+        assert_eq!(
+            outcomes["hash_pair_test::tests::test_b"].status,
+            Status::Failed
+        );
+        assert_eq!(
+            outcomes["hash_pair_test::tests::test_c"].status,
+            Status::Skipped
+        );
+        assert_eq!(
+            outcomes["hash_pair_test::tests::test_d"].status,
+            Status::Skipped
+        );
+    }
+
+    #[test]
+    fn errors_without_json_output() {
+        assert!(parse_output("error[E0425]: cannot find value").is_err());
+    }
+}
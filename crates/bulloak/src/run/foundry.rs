@@ -0,0 +1,278 @@
+//! Runs the tests of a spec through `forge test`.
+
+use std::{collections::HashMap, path::Path, process::Command, time::Duration};
+
+use anyhow::Context;
+use bulloak_foundry::{config::Config, hir::Hir};
+use bulloak_syntax::{markdown::read_spec, utils::sanitize};
+use serde_json::Value;
+
+use super::{pair_tests, Outcome, Report, Status};
+
+/// Runs the Foundry tests generated from the tree at `tree_path`.
+///
+/// Only the test functions that `bulloak scaffold` would emit for the tree are
+/// selected, through `--match-contract` and `--match-test`. Any extra `args`
+/// are forwarded to `forge test`.
+pub(crate) fn run(
+    tree_path: &Path,
+    cfg: &Config,
+    args: &[String],
+) -> anyhow::Result<Report> {
+    let text = read_spec(tree_path)
+        .with_context(|| format!("could not read {}", tree_path.display()))?;
+    let (contract, mut report) = spec(&text, cfg)?;
+
+    let command = command(&contract, &report, args, true);
+    let output = Command::new(&command[0])
+        .args(&command[1..])
+        .output()
+        .context("could not execute `forge`; is Foundry installed?")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let outcomes = parse_output(&stdout, &contract).with_context(|| {
+        String::from_utf8_lossy(&output.stderr).trim().to_owned()
+    })?;
+    for (name, outcome) in outcomes {
+        report.record(&name, outcome);
+    }
+
+    Ok(report)
+}
+
+/// Returns the `forge test` command line that runs the Foundry tests
+/// generated from the tree at `tree_path`, forwarding `args`.
+pub(crate) fn command_line(
+    tree_path: &Path,
+    cfg: &Config,
+    args: &[String],
+) -> anyhow::Result<Vec<String>> {
+    let text = read_spec(tree_path)
+        .with_context(|| format!("could not read {}", tree_path.display()))?;
+    let (contract, report) = spec(&text, cfg)?;
+
+    Ok(command(&contract, &report, args, false))
+}
+
+/// The `forge test` command that selects the test functions of `report` in
+/// `contract`, with results printed as JSON if `json` is set.
+fn command(
+    contract: &str,
+    report: &Report,
+    args: &[String],
+    json: bool,
+) -> Vec<String> {
+    let mut command = vec!["forge".to_owned(), "test".to_owned()];
+    if json {
+        command.push("--json".to_owned());
+    }
+    command.extend([
+        "--match-contract".to_owned(),
+        format!("^{contract}$"),
+        "--match-test".to_owned(),
+        format!("^({})$", report.test_names().join("|")),
+    ]);
+    command.extend_from_slice(args);
+    command
+}
+
+/// Maps every action in `text` to the Solidity test function covering it.
+///
+/// Returns the name of the emitted contract alongside the mapping.
+pub(super) fn spec(
+    text: &str,
+    cfg: &Config,
+) -> anyhow::Result<(String, Report)> {
+    let asts = bulloak_syntax::parse_with(text, &cfg.parse_options())?;
+    let hir = bulloak_foundry::hir::translate(text, cfg)?;
+    let contract =
+        hir.find_contract().context("the tree does not define a contract")?;
+
+    // The translator emits one function per top-level action and one per
+    // condition with actions, so walking the trees in the same order lets us
+    // pair each action with its function without re-deriving names.
+    let mut functions =
+        contract.children.iter().filter_map(|child| match child {
+            Hir::Function(f) if f.is_function() => Some(f.identifier.clone()),
+            _ => None,
+        });
+    let tests = pair_tests(&asts, |actions| {
+        functions.next().map(|name| vec![name; actions]).unwrap_or_default()
+    });
+
+    // The contract is named the way the emitter names it, e.g. `Foobar` for a
+    // single `Foo::bar` root.
+    Ok((sanitize(&contract.identifier), Report::new(asts, tests)))
+}
+
+/// Extracts the outcome of every test in `contract` from the output of
+/// `forge test --json`.
+fn parse_output(
+    stdout: &str,
+    contract: &str,
+) -> anyhow::Result<HashMap<String, Outcome>> {
+    let json = stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+        .find(Value::is_object)
+        .context("`forge test` did not produce any results")?;
+
+    // Suites are keyed by `<path>:<contract>`.
+    let suffix = format!(":{contract}");
+    let suites = json.as_object().into_iter().flatten();
+    let suites = suites.filter(|(suite, _)| suite.ends_with(&suffix));
+
+    let mut outcomes = HashMap::new();
+    for (_, suite) in suites {
+        let results = suite["test_results"].as_object().into_iter().flatten();
+        for (signature, result) in results {
+            let name = signature.split('(').next().unwrap_or(signature);
+            let status = match result["status"].as_str() {
+                Some("Success") => Status::Passed,
+                Some("Skipped") => Status::Skipped,
+                _ => Status::Failed,
+            };
+            let duration = parse_duration(&result["duration"]);
+            outcomes.insert(name.to_owned(), Outcome { status, duration });
+        }
+    }
+
+    Ok(outcomes)
+}
+
+/// Parses a serialized `std::time::Duration`.
+fn parse_duration(value: &Value) -> Option<Duration> {
+    let secs = value["secs"].as_u64()?;
+    let nanos = value["nanos"].as_u64()?;
+    Some(Duration::new(secs, u32::try_from(nanos).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bulloak_foundry::config::Config;
+    use pretty_assertions::assert_eq;
+
+    use super::{command_line, parse_output, spec};
+    use crate::run::{tests::action_index, Outcome, Status};
+
+    #[test]
+    fn pairs_actions_with_emitted_functions() {
+        let tree = r"HashPairTest
+├── It should never revert.
+└── When first arg is smaller than second arg
+    ├── When first arg is zero
+    │   └── It should do something.
+    └── It should match the result.";
+        let (contract, report) = spec(tree, &Config::default()).unwrap();
+
+        assert_eq!(contract, "HashPairTest");
+        assert_eq!(
+            report.tests[&(
+                0,
+                action_index(&report.asts[0], "It should never").unwrap()
+            )],
+            "test_ShouldNeverRevert"
+        );
+        assert_eq!(
+            report.tests[&(
+                0,
+                action_index(&report.asts[0], "It should match").unwrap()
+            )],
+            "test_WhenFirstArgIsSmallerThanSecondArg"
+        );
+        assert_eq!(
+            report.tests
+                [&(0, action_index(&report.asts[0], "It should do").unwrap())],
+            "test_WhenFirstArgIsZero"
+        );
+    }
+
+    #[test]
+    fn builds_the_forge_command_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree_path = dir.path().join("HashPair.tree");
+        let tree = "HashPairTest\n├── It should never revert.\n└── When \
+                    first arg is smaller\n    └── It should match the result.";
+        std::fs::write(&tree_path, tree).unwrap();
+
+        let args = ["-vvv".to_owned()];
+        let command =
+            command_line(&tree_path, &Config::default(), &args).unwrap();
+        assert_eq!(
+            command,
+            [
+                "forge",
+                "test",
+                "--match-contract",
+                "^HashPairTest$",
+                "--match-test",
+                "^(test_ShouldNeverRevert|test_WhenFirstArgIsSmaller)$",
+                "-vvv",
+            ]
+        );
+    }
+
+    #[test]
+    fn pairs_actions_across_roots() {
+        let tree = r"Foo::bar
+└── When stuff
+    └── It should revert.
+
+Foo::baz
+└── When stuff
+    └── It should revert.";
+        let (contract, report) = spec(tree, &Config::default()).unwrap();
+
+        assert_eq!(contract, "Foo");
+        assert_eq!(
+            report.test_names(),
+            vec!["test_Bar_RevertWhen_Stuff", "test_Baz_RevertWhen_Stuff"]
+        );
+        let index = action_index(&report.asts[1], "It should").unwrap();
+        assert_eq!(report.tests[&(1, index)], "test_Baz_RevertWhen_Stuff");
+    }
+
+    #[test]
+    fn names_single_function_roots_like_the_emitter() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree_path = dir.path().join("Foo.tree");
+        let tree = "Foo::bar\n└── When stuff\n    └── It should revert.";
+        std::fs::write(&tree_path, tree).unwrap();
+
+        let (contract, _) = spec(tree, &Config::default()).unwrap();
+        assert_eq!(contract, "Foobar");
+        let emitted =
+            bulloak_foundry::scaffold::scaffold(tree, &Config::default())
+                .unwrap();
+        assert!(emitted.contains(&format!("contract {contract} {{")));
+
+        let command =
+            command_line(&tree_path, &Config::default(), &[]).unwrap();
+        assert_eq!(command[2..4], ["--match-contract", "^Foobar$"]);
+    }
+
+    #[test]
+    fn parses_forge_json_output() {
+        let stdout = r#"Compiling 1 files with 0.8.23
+{"test/Foo.t.sol:Foo":{"duration":"1ms","test_results":{"test_A()":{"status":"Success","reason":null,"duration":{"secs":0,"nanos":2000000}},"test_B()":{"status":"Failure","reason":"boom"},"test_C()":{"status":"Skipped","reason":null}},"warnings":[]},"test/Bar.t.sol:Bar":{"test_results":{"test_D()":{"status":"Success"}}}}"#;
+        let outcomes = parse_output(stdout, "Foo").unwrap();
+
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(
+            outcomes["test_A"],
+            Outcome {
+                status: Status::Passed,
+                duration: Some(Duration::from_millis(2))
+            }
+        );
+        assert_eq!(outcomes["test_B"].status, Status::Failed);
+        assert_eq!(outcomes["test_C"].status, Status::Skipped);
+    }
+
+    #[test]
+    fn errors_without_json_output() {
+        assert!(parse_output("Error: compilation failed", "Foo").is_err());
+    }
+}
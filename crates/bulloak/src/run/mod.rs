@@ -0,0 +1,700 @@
+//! Defines the `bulloak run` command.
+//!
+//! This command executes the tests generated from a bulloak tree through the
+//! backend's test runner and maps the results back onto the spec.
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bulloak_syntax::{utils::pluralize, Ast};
+use clap::Parser;
+use owo_colors::{OwoColorize, Stream, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::RunReport,
+    cli::{Backend, Cli, Naming},
+    discovery::expand_patterns,
+};
+
+mod cargo;
+mod foundry;
+
+/// Run the tests that implement the spec.
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Run {
+    /// The set of tree files to use as spec.
+    ///
+    /// Test names are derived from the specs the same way `bulloak scaffold`
+    /// derives them.
+    pub files: Vec<PathBuf>,
+    /// The target backend/language whose tests should run.
+    #[arg(short = 'b', long = "backend", value_enum, default_value_t = Backend::Solidity)]
+    pub backend: Backend,
+    /// Fail when a pattern in `files` is invalid or matches no files,
+    /// instead of warning.
+    #[arg(long, default_value_t = false)]
+    pub strict_globs: bool,
+    /// Extra arguments forwarded verbatim to the test runner.
+    ///
+    /// For example: `bulloak run foo.tree -- -vvv`. For Rust, the arguments
+    /// after a second `--` go to libtest instead of `cargo test`, e.g.
+    /// `bulloak run -b rust foo.tree -- --release -- --nocapture`.
+    #[arg(last = true)]
+    pub runner_args: Vec<String>,
+    /// Print the command line that runs the tests of each spec instead of
+    /// running them, e.g. to run them by hand or from another tool.
+    #[arg(long, default_value_t = false)]
+    pub print: bool,
+    /// The options that decide the names of the tests.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub naming: Naming,
+}
+
+impl Default for Run {
+    fn default() -> Self {
+        Run::parse_from(Vec::<String>::new())
+    }
+}
+
+impl Run {
+    /// Entrypoint for `bulloak run`.
+    pub(crate) fn run(&self, cfg: &Cli) -> RunReport {
+        let (specs, errors) = expand_patterns(&self.files, self.strict_globs);
+        if !errors.is_empty() {
+            return RunReport { errors, ..RunReport::default() };
+        }
+        // Running nothing would pass, so a run without specs is an error even
+        // without `--strict-globs`.
+        if specs.is_empty() {
+            let error = "no specs to run".to_owned();
+            eprintln!(
+                "{}: {error}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red())
+            );
+            return RunReport {
+                errors: vec![(PathBuf::new(), error)],
+                ..RunReport::default()
+            };
+        }
+
+        let foundry_cfg: bulloak_foundry::config::Config = cfg.into();
+        let rust_cfg = self.naming.rust_config();
+        if self.print {
+            return self.print(specs, &foundry_cfg, &rust_cfg);
+        }
+
+        let mut summary = Summary::default();
+        let mut errors = Vec::new();
+        for tree_path in &specs {
+            let report = match self.backend {
+                Backend::Solidity => {
+                    foundry::run(tree_path, &foundry_cfg, &self.runner_args)
+                }
+                Backend::Rust => {
+                    cargo::run(tree_path, &rust_cfg, &self.runner_args)
+                }
+            };
+
+            match report {
+                Ok(report) => {
+                    println!("{}", report.render().trim_end());
+                    summary.merge(&report.summary());
+                }
+                Err(e) => {
+                    eprintln!(
+                        "{}: failed to run {}: {e}",
+                        "error".if_supports_color(Stream::Stderr, |t| t.red()),
+                        tree_path.display(),
+                    );
+                    errors.push((tree_path.clone(), e.to_string()));
+                }
+            }
+        }
+
+        println!("\n{summary}");
+        RunReport {
+            files: specs,
+            errors,
+            failed_actions: summary.failed + summary.missing,
+            ..RunReport::default()
+        }
+    }
+
+    /// Prints the command line that runs the tests of each of `specs`.
+    fn print(
+        &self,
+        specs: Vec<PathBuf>,
+        foundry_cfg: &bulloak_foundry::config::Config,
+        rust_cfg: &bulloak_rust::Config,
+    ) -> RunReport {
+        let mut errors = Vec::new();
+        for tree_path in &specs {
+            let command = match self.backend {
+                Backend::Solidity => foundry::command_line(
+                    tree_path,
+                    foundry_cfg,
+                    &self.runner_args,
+                ),
+                Backend::Rust => {
+                    cargo::command_line(tree_path, rust_cfg, &self.runner_args)
+                }
+            };
+
+            match command {
+                Ok(command) => println!("{}", shell_words(&command)),
+                Err(e) => {
+                    eprintln!(
+                        "{}: failed to read {}: {e}",
+                        "error".if_supports_color(Stream::Stderr, |t| t.red()),
+                        tree_path.display(),
+                    );
+                    errors.push((tree_path.clone(), e.to_string()));
+                }
+            }
+        }
+
+        RunReport { files: specs, errors, ..RunReport::default() }
+    }
+}
+
+/// Joins `words` into a command line a POSIX shell splits back into them,
+/// quoting the words that need it.
+fn shell_words(words: &[String]) -> String {
+    let quote = |word: &String| {
+        let plain = !word.is_empty()
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+        if plain {
+            word.clone()
+        } else {
+            format!("'{}'", word.replace('\'', r"'\''"))
+        }
+    };
+
+    words.iter().map(quote).collect::<Vec<_>>().join(" ")
+}
+
+/// Maps every action in the spec `text` to the test function covering it,
+/// the same way `bulloak scaffold` names tests for `backend`.
+///
+/// Returns the name of the spec's contract, or of its root for the Rust
+/// backend, alongside the mapping.
+pub(crate) fn spec(
+    text: &str,
+    backend: Backend,
+    foundry_cfg: &bulloak_foundry::config::Config,
+    rust_cfg: &bulloak_rust::Config,
+) -> anyhow::Result<(String, Report)> {
+    match backend {
+        Backend::Solidity => foundry::spec(text, foundry_cfg),
+        Backend::Rust => cargo::spec(text, rust_cfg),
+    }
+}
+
+/// Pairs every action in `asts` with the test function covering it.
+///
+/// Actions are keyed by the index of their tree and their index among the
+/// tree's actions, in spec order. Spans can't tell actions apart, since the
+/// conditions `[combine]` expands to share the spans of the spec.
+///
+/// Tests are handed out in the order the backends emit them: a test per
+/// top-level action, and for every condition with actions, the tests covering
+/// them before the tests of the conditions under it. `next(n)` returns the
+/// names of the tests covering the next `n` actions of a condition.
+pub(crate) fn pair_tests(
+    asts: &[Ast],
+    mut next: impl FnMut(usize) -> Vec<String>,
+) -> HashMap<(usize, usize), String> {
+    let mut tests = HashMap::new();
+    for (tree, ast) in asts.iter().enumerate() {
+        let Ast::Root(root) = ast else { continue };
+        let mut index = 0;
+        pair_children(
+            tree,
+            &root.children,
+            true,
+            &mut next,
+            &mut index,
+            &mut tests,
+        );
+    }
+    tests
+}
+
+fn pair_children(
+    tree: usize,
+    children: &[Ast],
+    top_level: bool,
+    next: &mut impl FnMut(usize) -> Vec<String>,
+    index: &mut usize,
+    tests: &mut HashMap<(usize, usize), String>,
+) {
+    let actions = children.iter().filter(|c| c.is_action()).count();
+    let mut names = if top_level || actions == 0 {
+        Vec::new().into_iter()
+    } else {
+        next(actions).into_iter()
+    };
+    for child in children {
+        match child {
+            Ast::Action(_) => {
+                let name = if top_level {
+                    next(1).into_iter().next()
+                } else {
+                    names.next()
+                };
+                if let Some(name) = name {
+                    tests.insert((tree, *index), name);
+                }
+                *index += 1;
+            }
+            Ast::Condition(condition) => {
+                pair_children(
+                    tree,
+                    &condition.children,
+                    false,
+                    next,
+                    index,
+                    tests,
+                );
+            }
+            Ast::Root(_) | Ast::ActionDescription(_) => {}
+        }
+    }
+}
+
+/// The path of the test file `bulloak scaffold` writes for the spec at `tree`
+/// with `backend`.
+pub(crate) fn test_file(tree: &Path, backend: Backend) -> PathBuf {
+    match backend {
+        Backend::Solidity => tree.with_extension("t.sol"),
+        Backend::Rust => bulloak_rust::scaffold::test_file_path(tree),
+    }
+}
+
+/// A test function found in a test file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Test {
+    /// The name of the function.
+    pub(crate) name: String,
+    /// Whether the function has a body beyond what `bulloak scaffold` emits.
+    pub(crate) implemented: bool,
+}
+
+/// Lists the test functions of the test file `src`, written for `backend`.
+///
+/// Rust functions count as tests if they carry one of the test attributes
+/// of `rust_cfg`.
+pub(crate) fn tests(
+    src: &str,
+    backend: Backend,
+    rust_cfg: &bulloak_rust::Config,
+) -> anyhow::Result<Vec<Test>> {
+    let tests = match backend {
+        Backend::Solidity => bulloak_foundry::sol::test_fns(src)?
+            .into_iter()
+            .map(|(name, implemented)| Test { name, implemented })
+            .collect(),
+        Backend::Rust => {
+            use bulloak_rust::rust::ParsedRustFile;
+
+            ParsedRustFile::parse(src)?
+                .with_test_attributes(&rust_cfg.test_attributes)
+                .find_test_functions()
+                .into_iter()
+                .map(|f| Test {
+                    name: f.sig.ident.to_string(),
+                    implemented: !ParsedRustFile::is_empty_test(f),
+                })
+                .collect()
+        }
+    };
+
+    Ok(tests)
+}
+
+/// The outcome of a single test function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    /// The test ran and passed.
+    Passed,
+    /// The test ran and failed.
+    Failed,
+    /// The test was skipped by the runner.
+    Skipped,
+}
+
+/// The result of running a single test function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Outcome {
+    /// Whether the test passed, failed or was skipped.
+    pub(crate) status: Status,
+    /// How long the test took, if the runner reports it.
+    pub(crate) duration: Option<Duration>,
+}
+
+/// The results of running the tests of a single spec.
+///
+/// Every action in the spec is covered by exactly one test function. The
+/// mapping between the two is kept here so that results can be rendered
+/// against the tree they came from.
+#[derive(Debug)]
+pub(crate) struct Report {
+    /// The trees that make up the spec.
+    asts: Vec<Ast>,
+    /// The test function covering each action, keyed by the index of the
+    /// tree the action belongs to and the action's index in the tree.
+    tests: HashMap<(usize, usize), String>,
+    /// The outcome of each test function, keyed by the function's name.
+    outcomes: HashMap<String, Outcome>,
+}
+
+impl Report {
+    /// Creates a new report with no outcomes.
+    pub(crate) fn new(
+        asts: Vec<Ast>,
+        tests: HashMap<(usize, usize), String>,
+    ) -> Self {
+        Self { asts, tests, outcomes: HashMap::new() }
+    }
+
+    /// The sorted, deduplicated names of the test functions that implement
+    /// the spec.
+    pub(crate) fn test_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> =
+            self.tests.values().map(String::as_str).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// The trees that make up the spec.
+    pub(crate) fn asts(&self) -> &[Ast] {
+        &self.asts
+    }
+
+    /// The name of the test function covering the `index`-th action of the
+    /// `tree`-th tree of the spec.
+    pub(crate) fn test(&self, tree: usize, index: usize) -> Option<&str> {
+        self.tests.get(&(tree, index)).map(String::as_str)
+    }
+
+    /// Records the outcome of the test function called `name`.
+    pub(crate) fn record(&mut self, name: &str, outcome: Outcome) {
+        self.outcomes.insert(name.to_owned(), outcome);
+    }
+
+    /// Counts the outcomes of every action in the spec.
+    pub(crate) fn summary(&self) -> Summary {
+        let mut summary = Summary::default();
+        for (i, ast) in self.asts.iter().enumerate() {
+            self.count(i, ast, &mut 0, &mut summary);
+        }
+        summary
+    }
+
+    fn count(
+        &self,
+        tree: usize,
+        ast: &Ast,
+        index: &mut usize,
+        summary: &mut Summary,
+    ) {
+        match ast {
+            Ast::Root(root) => root
+                .children
+                .iter()
+                .for_each(|c| self.count(tree, c, index, summary)),
+            Ast::Condition(condition) => condition
+                .children
+                .iter()
+                .for_each(|c| self.count(tree, c, index, summary)),
+            Ast::Action(_) => {
+                let outcome = self.outcome(tree, *index);
+                *index += 1;
+                match outcome {
+                    Some(Outcome { status: Status::Passed, .. }) => {
+                        summary.passed += 1;
+                    }
+                    Some(Outcome { status: Status::Failed, .. }) => {
+                        summary.failed += 1;
+                    }
+                    Some(Outcome { status: Status::Skipped, .. }) => {
+                        summary.skipped += 1;
+                    }
+                    None => summary.missing += 1,
+                }
+            }
+            Ast::ActionDescription(_) => {}
+        }
+    }
+
+    fn outcome(&self, tree: usize, index: usize) -> Option<&Outcome> {
+        self.tests.get(&(tree, index)).and_then(|name| self.outcomes.get(name))
+    }
+
+    /// Renders the spec with every action annotated with its outcome.
+    pub(crate) fn render(&self) -> String {
+        self.render_with(true)
+    }
+
+    /// Renders the spec with every action annotated with the test covering
+    /// it, leaving outcomes out.
+    pub(crate) fn render_tests(&self) -> String {
+        self.render_with(false)
+    }
+
+    fn render_with(&self, outcomes: bool) -> String {
+        let mut out = String::new();
+        for (i, ast) in self.asts.iter().enumerate() {
+            if let Ast::Root(root) = ast {
+                let _ = writeln!(out, "{}", root.contract_name);
+                self.render_children(
+                    i,
+                    &root.children,
+                    "",
+                    outcomes,
+                    &mut 0,
+                    &mut out,
+                );
+            }
+        }
+        out
+    }
+
+    fn render_children(
+        &self,
+        tree: usize,
+        children: &[Ast],
+        indent: &str,
+        outcomes: bool,
+        index: &mut usize,
+        out: &mut String,
+    ) {
+        let children: Vec<&Ast> = children
+            .iter()
+            .filter(|c| !matches!(c, Ast::ActionDescription(_)))
+            .collect();
+        for (j, child) in children.iter().enumerate() {
+            let is_last = j == children.len() - 1;
+            let (branch, next_indent) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            match child {
+                Ast::Condition(condition) => {
+                    let _ =
+                        writeln!(out, "{indent}{branch}{}", condition.title);
+                    self.render_children(
+                        tree,
+                        &condition.children,
+                        &format!("{indent}{next_indent}"),
+                        outcomes,
+                        index,
+                        out,
+                    );
+                }
+                Ast::Action(action) => {
+                    let name = self.tests.get(&(tree, *index));
+                    let outcome = self.outcome(tree, *index);
+                    *index += 1;
+                    let mark = match outcome.map(|o| o.status) {
+                        Some(Status::Passed) => "✓",
+                        Some(Status::Failed) => "✗",
+                        Some(Status::Skipped) => "○",
+                        None => "?",
+                    };
+                    let _ = write!(out, "{indent}{branch}");
+                    if outcomes {
+                        let _ = write!(out, "{mark} ");
+                    }
+                    out.push_str(&action.title);
+                    match (name, outcome.and_then(|o| o.duration)) {
+                        (Some(name), Some(duration)) => {
+                            let _ = write!(out, " ({name}, {duration:.2?})");
+                        }
+                        (Some(name), None) => {
+                            let _ = write!(out, " ({name})");
+                        }
+                        _ => {}
+                    }
+                    out.push('\n');
+                }
+                Ast::Root(_) | Ast::ActionDescription(_) => {}
+            }
+        }
+    }
+}
+
+/// Aggregated outcome counts over a set of spec actions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Summary {
+    /// Actions whose test passed.
+    pub(crate) passed: usize,
+    /// Actions whose test failed.
+    pub(crate) failed: usize,
+    /// Actions whose test was skipped.
+    pub(crate) skipped: usize,
+    /// Actions whose test was not found in the runner's output.
+    pub(crate) missing: usize,
+}
+
+impl Summary {
+    fn merge(&mut self, other: &Summary) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+        self.missing += other.missing;
+    }
+
+    /// Whether every action in the spec is backed by a test that did not
+    /// fail.
+    pub(crate) fn is_success(&self) -> bool {
+        self.failed == 0 && self.missing == 0
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.passed + self.failed + self.skipped + self.missing;
+        let action_literal = pluralize(total, "action", "actions");
+        let counts = format!(
+            "{} passed, {} failed, {} skipped, {} not run",
+            self.passed, self.failed, self.skipped, self.missing
+        );
+        if self.is_success() {
+            write!(
+                f,
+                "{}: {total} {action_literal}; {counts}",
+                "success".if_supports_color(Stream::Stdout, |t| t
+                    .style(Style::new().bold().green()))
+            )
+        } else {
+            write!(
+                f,
+                "{}: {total} {action_literal}; {counts}",
+                "warn".if_supports_color(Stream::Stdout, |t| t
+                    .style(Style::new().bold().yellow()))
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use bulloak_syntax::{parse, Ast};
+    use pretty_assertions::assert_eq;
+
+    use super::{shell_words, Outcome, Report, Status, Summary};
+
+    /// Returns the index of the first action in `ast` whose title starts
+    /// with `title`, among the actions of `ast` in spec order.
+    pub(super) fn action_index(ast: &Ast, title: &str) -> Option<usize> {
+        fn find(ast: &Ast, title: &str, index: &mut usize) -> Option<usize> {
+            let children = match ast {
+                Ast::Root(root) => &root.children,
+                Ast::Condition(condition) => &condition.children,
+                Ast::Action(action) => {
+                    *index += 1;
+                    return action.title.starts_with(title).then(|| *index - 1);
+                }
+                Ast::ActionDescription(_) => return None,
+            };
+            children.iter().find_map(|child| find(child, title, index))
+        }
+
+        find(ast, title, &mut 0)
+    }
+
+    #[test]
+    fn quotes_shell_words() {
+        let words = ["forge", "test", "--match-test", "^(a|b)$", "it's", ""]
+            .map(str::to_owned);
+        assert_eq!(
+            shell_words(&words),
+            r"forge test --match-test '^(a|b)$' 'it'\''s' ''"
+        );
+    }
+
+    fn report() -> Report {
+        let tree = r"HashPairTest
+├── It should never revert.
+└── When first arg is smaller than second arg
+    ├── It should match the result.
+    │   └── Because it is sorted.
+    └── When the args are equal
+        └── It should not overflow.";
+        let asts = parse(tree).unwrap();
+        let tests = [
+            ("It should never", "test_ShouldNeverRevert"),
+            ("It should match", "test_WhenFirstArgIsSmallerThanSecondArg"),
+            ("It should not", "test_WhenTheArgsAreEqual"),
+        ]
+        .into_iter()
+        .map(|(action, name)| {
+            ((0, action_index(&asts[0], action).unwrap()), name.to_owned())
+        })
+        .collect::<HashMap<_, _>>();
+        Report::new(asts, tests)
+    }
+
+    #[test]
+    fn renders_outcomes_against_the_spec() {
+        let mut report = report();
+        report.record(
+            "test_ShouldNeverRevert",
+            Outcome {
+                status: Status::Passed,
+                duration: Some(Duration::from_micros(1500)),
+            },
+        );
+        report.record(
+            "test_WhenFirstArgIsSmallerThanSecondArg",
+            Outcome { status: Status::Failed, duration: None },
+        );
+
+        let expected = r"HashPairTest
+├── ✓ It should never revert. (test_ShouldNeverRevert, 1.50ms)
+└── When first arg is smaller than second arg
+    ├── ✗ It should match the result. (test_WhenFirstArgIsSmallerThanSecondArg)
+    └── When the args are equal
+        └── ? It should not overflow. (test_WhenTheArgsAreEqual)
+";
+        assert_eq!(report.render(), expected);
+        assert_eq!(
+            report.summary(),
+            Summary { passed: 1, failed: 1, skipped: 0, missing: 1 }
+        );
+    }
+
+    #[test]
+    fn skipped_tests_do_not_fail_the_run() {
+        let mut report = report();
+        for name in report
+            .test_names()
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+        {
+            report.record(
+                &name,
+                Outcome { status: Status::Skipped, duration: None },
+            );
+        }
+
+        let summary = report.summary();
+        assert_eq!(summary.skipped, 3);
+        assert!(summary.is_success());
+    }
+}
@@ -3,20 +3,33 @@
 //! This command scaffolds a Solidity file from a spec `.tree` file.
 
 use std::{
-    fs,
+    fs, io,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
 };
 
 use bulloak_foundry::{constants::DEFAULT_SOL_VERSION, scaffold::scaffold};
+use bulloak_rust::{constants::DEFAULT_HEADER, scaffold::wiring::Wiring};
+use bulloak_syntax::{
+    markdown::{self, read_spec},
+    utils::match_line_endings,
+    ParseOptions,
+};
 use clap::Parser;
 use forge_fmt::fmt;
-use owo_colors::OwoColorize;
+use owo_colors::{OwoColorize, Stream};
 use serde::{Deserialize, Serialize};
 
-use crate::{cli::{Backend, Cli}, glob::expand_glob};
+use crate::{
+    api::RunReport,
+    cli::{header, pairs, Backend, Cli, Naming},
+    discovery::{expand_patterns, warn_ignored_comments},
+    jobs, markers,
+    progress::Progress,
+    transaction::Transaction,
+};
 
 /// Generate test files based on your spec.
-#[doc(hidden)]
 #[derive(Parser, Debug, Clone, Serialize, Deserialize)]
 pub struct Scaffold {
     /// The set of tree files to generate from.
@@ -41,6 +54,26 @@ pub struct Scaffold {
         default_value_t = false
     )]
     pub force_write: bool,
+    /// Wrap the scaffolded code in `// bulloak:begin` and `// bulloak:end`
+    /// lines.
+    ///
+    /// With `--write-files`, an existing file with these markers has the
+    /// code between them regenerated, and code outside them is kept. The
+    /// bodies of functions, modifiers and structs already between them are
+    /// kept too.
+    #[arg(long, default_value_t = false)]
+    pub markers: bool,
+    /// Read the tree from stdin, as if it were the file at this path.
+    ///
+    /// The path names the output file and fills in the `{tree}`
+    /// placeholders, e.g. so that editors can preview the output of unsaved
+    /// buffers. The file itself doesn't need to exist.
+    #[arg(long, value_name = "PATH", conflicts_with = "files")]
+    pub stdin_filepath: Option<PathBuf>,
+    /// Fail when a pattern in `files` is invalid or matches no files,
+    /// instead of warning.
+    #[arg(long, default_value_t = false)]
+    pub strict_globs: bool,
     /// Sets a Solidity version for the test contracts.
     #[arg(short = 's', long, default_value = DEFAULT_SOL_VERSION)]
     pub solidity_version: String,
@@ -53,9 +86,121 @@ pub struct Scaffold {
     /// Whether to capitalize and punctuate branch descriptions.
     #[arg(short = 'F', long = "format-descriptions", default_value_t = false)]
     pub format_descriptions: bool,
+    /// Whether to add commented-out assertions for actions like `it should
+    /// return X` or `it should emit Y`.
+    #[arg(long, default_value_t = false)]
+    pub assertion_stubs: bool,
+    /// Whether to add an `insta::assert_snapshot!` stub to Rust tests of
+    /// actions tagged `[snapshot]`, importing the macro in the test module.
+    #[arg(long, default_value_t = false)]
+    pub snapshot_stubs: bool,
+    /// Whether to add `vm.expectEmit();` and a commented-out event to Solidity
+    /// tests of actions like `it should emit Y`, and check that they call
+    /// `vm.expectEmit`.
+    #[arg(long, default_value_t = false)]
+    pub expect_emit: bool,
+    /// A comment to add to the body of every generated test, e.g.
+    /// `TODO(bulloak): implement — see {tree}`.
+    ///
+    /// `{tree}`, `{title}` and `{test}` are replaced with the path of the tree
+    /// file, the title of the branch the test covers and the name of the test.
+    #[arg(long = "todo", value_name = "TEMPLATE")]
+    pub todo_marker: Option<String>,
+    /// Whether to include the spec as a comment block at the top of Rust test
+    /// files.
+    #[arg(long, default_value_t = false)]
+    pub tree_banner: bool,
+    /// A comment to put at the top of generated files, e.g.
+    /// `Generated by bulloak v{version} from {tree}`.
+    ///
+    /// `{version}` and `{tree}` are replaced with the version of `bulloak` and
+    /// the path of the tree file. Rust files get `Generated by bulloak` by
+    /// default.
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "no_header")]
+    pub header: Option<String>,
+    /// Whether to leave out the header comment of generated files.
+    #[arg(long, default_value_t = false)]
+    pub no_header: bool,
+    /// A Solidity import to add to generated files, e.g.
+    /// `Test,StdCheats=forge-std/Test.sol` or `src/Vault.sol`. Can be passed
+    /// multiple times.
+    ///
+    /// Symbols before the `=` are imported by name. Configuring `Test`
+    /// replaces the default `forge-std` import.
+    #[arg(long = "import", value_name = "IMPORT")]
+    pub imports: Vec<String>,
+    /// A remapping applied to the paths of Solidity imports, e.g.
+    /// `forge-std/=lib/forge-std/src/`. Can be passed multiple times.
+    #[arg(
+        long = "remapping",
+        value_name = "PREFIX=PATH",
+        value_parser = crate::cli::parse_remapping
+    )]
+    pub remappings: Vec<String>,
+    /// Code to add at the start of the tests of branches with a tag, e.g.
+    /// `fork=vm.createSelectFork("mainnet");`. Can be passed multiple times.
+    ///
+    /// A test gets the snippets of the tags of its condition, of the
+    /// conditions above it and of its actions.
+    #[arg(
+        long = "snippet",
+        value_name = "TAG=CODE",
+        value_parser = crate::cli::parse_snippet
+    )]
+    pub snippets: Vec<String>,
+    /// Whether to add a comment summarizing the branch path above each test,
+    /// e.g. `// Given paused › When caller is owner`.
+    #[arg(long, default_value_t = false)]
+    pub branch_summary: bool,
+    /// Whether to document Rust tests and helpers with `///` comments holding
+    /// their branch path and actions, instead of commenting test bodies.
+    #[arg(long, default_value_t = false)]
+    pub doc_comments: bool,
+    /// The Cargo feature to gate generated Rust tests behind, e.g. `e2e`.
+    ///
+    /// The test module gets `#[cfg(all(test, feature = "e2e"))]`.
+    #[arg(long, value_name = "NAME")]
+    pub feature: Option<String>,
+    /// Whether to gate the whole generated Rust file with
+    /// `#![cfg(feature = "...")]` instead, for integration tests under
+    /// `tests/`.
+    #[arg(long, default_value_t = false, requires = "feature")]
+    pub gate_file: bool,
+    /// Whether to declare the Rust test files written with `--write-files`
+    /// as modules, so that Cargo compiles them: in their parent module under
+    /// `src/`, or in `tests/bulloak_generated/main.rs` under subdirectories
+    /// of `tests/`.
+    #[arg(long, requires = "file-handling", default_value_t = false)]
+    pub wire_modules: bool,
     /// The target backend/language for code generation.
     #[arg(short = 'b', long = "backend", value_enum, default_value_t = Backend::Solidity)]
     pub backend: Backend,
+    /// The options that decide the names of the tests.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub naming: Naming,
+    /// Fail on `[only]` tags, e.g. in CI, instead of narrowing the trees
+    /// down to the actions they focus.
+    #[arg(long, default_value_t = false)]
+    pub forbid_only: bool,
+    /// The number of worker threads to scaffold files with. Defaults to the
+    /// number of available CPUs.
+    #[arg(short = 'j', long, value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+    /// Whether to report how long the slowest files took, to help find
+    /// pathological specs.
+    #[arg(long, default_value_t = false)]
+    pub timings: bool,
+}
+
+/// The code scaffolded from a tree file.
+struct Scaffolded {
+    /// The contents of the tree file.
+    text: String,
+    /// The scaffolded code.
+    code: String,
+    /// The file the code is written to with `--write-files`.
+    output_file: PathBuf,
 }
 
 impl Default for Scaffold {
@@ -67,110 +212,214 @@ impl Default for Scaffold {
 impl Scaffold {
     /// Runs the scaffold command, processing all specified files.
     ///
-    /// This method iterates through all input files, processes them, and either
-    /// writes the output to files or prints to stdout based on the config.
+    /// This method scaffolds all input files on up to `--jobs` worker threads,
+    /// then either writes the output to files or prints to stdout based on the
+    /// config, in the order the files were given.
     ///
     /// If any errors occur during processing, they are collected and reported.
-    pub(crate) fn run(&self, cfg: &Cli) {
-        let mut files = Vec::with_capacity(self.files.len());
-        for pattern in &self.files {
-            match expand_glob(pattern.clone()) {
-                Ok(iter) => files.extend(iter),
+    pub(crate) fn run(&self, cfg: &Cli) -> RunReport {
+        let (files, errors) = match &self.stdin_filepath {
+            Some(path) => (vec![path.clone()], Vec::new()),
+            None => expand_patterns(&self.files, self.strict_globs),
+        };
+        if !errors.is_empty() {
+            return RunReport { errors, ..RunReport::default() };
+        }
+        let stdin = match &self.stdin_filepath {
+            Some(path) => match io::read_to_string(io::stdin()) {
+                Ok(text) => Some(text),
                 Err(e) => {
                     eprintln!(
-                        "{}: could not expand {}: {}",
-                        "warn".yellow(),
-                        pattern.display(),
-                        e
+                        "{}: failed to read stdin: {e}",
+                        "error".if_supports_color(Stream::Stderr, |t| t.red())
                     );
+                    return RunReport {
+                        files,
+                        errors: vec![(path.clone(), e.to_string())],
+                        ..RunReport::default()
+                    };
                 }
+            },
+            None => None,
+        };
+
+        let progress = Progress::new(files.len(), self.timings);
+        let scaffolded = jobs::map(&files, self.jobs, |file| {
+            progress
+                .track(file, || self.process_file(file, stdin.as_deref(), cfg))
+        });
+        progress.finish();
+        let mut tx = Transaction::default();
+        let mut wiring = Wiring::default();
+        let mut errors = Vec::new();
+        for (file, scaffolded) in files.iter().zip(scaffolded) {
+            let wired = scaffolded.and_then(|scaffolded| {
+                self.output(&mut tx, &scaffolded);
+                if self.wire_modules && self.backend == Backend::Rust {
+                    wiring.add(&scaffolded.output_file)?;
+                }
+                Ok(())
+            });
+            if let Err(e) = wired {
+                errors.push((file.as_path(), e));
             }
         }
-
-        let errors = files
-            .iter()
-            .filter_map(|file| {
-                self.process_file(file, cfg)
-                    .map_err(|e| (file.as_path(), e))
-                    .err()
-            })
-            .collect::<Vec<_>>();
-
         if !errors.is_empty() {
             Scaffold::report_errors(&errors);
-            std::process::exit(1);
         }
+
+        let mut errors: Vec<_> = errors
+            .into_iter()
+            .map(|(file, e)| (file.to_path_buf(), e.to_string()))
+            .collect();
+        for (path, contents) in wiring.into_edits() {
+            // The aggregator of `tests/` goes in a directory of its own.
+            let dir = path.parent().filter(|dir| !dir.exists());
+            if let Some(Err(e)) = dir.map(fs::create_dir_all) {
+                eprintln!(
+                    "{}: failed to create {}: {e}",
+                    "error".if_supports_color(Stream::Stderr, |t| t.red()),
+                    path.display()
+                );
+                errors.push((path, e.to_string()));
+                continue;
+            }
+            tx.stage(path, contents);
+        }
+        if let Err(e) = tx.commit() {
+            eprintln!(
+                "{}: {e}",
+                "error".if_supports_color(Stream::Stderr, |t| t.red())
+            );
+            errors.push((e.path, e.error.to_string()));
+        }
+        RunReport { files, errors, ..RunReport::default() }
     }
 
     /// Processes a single input file.
     ///
-    /// This method reads the input file, scaffolds the code, formats
-    /// it, and either writes it to a file or prints it to stdout.
-    fn process_file(&self, file: &Path, cfg: &Cli) -> anyhow::Result<()> {
-        let text = fs::read_to_string(file)?;
+    /// This method reads the input file, or takes `stdin` as its contents if
+    /// given, scaffolds the code and formats it.
+    fn process_file(
+        &self,
+        file: &Path,
+        stdin: Option<&str>,
+        cfg: &Cli,
+    ) -> anyhow::Result<Scaffolded> {
+        let text = match stdin {
+            Some(text) => text.to_owned(),
+            None => read_spec(file)?,
+        };
+        warn_ignored_comments(file, &text);
 
-        match self.backend {
+        let scaffolded = match self.backend {
             Backend::Rust => {
-                let ast = bulloak_syntax::parse_one(&text)?;
                 let rust_cfg = bulloak_rust::Config {
-                    files: self.files.iter().map(|p| p.display().to_string()).collect(),
+                    files: self.files.clone(),
                     skip_helpers: self.skip_modifiers,
                     format_descriptions: self.format_descriptions,
-                };
-                let emitted = bulloak_rust::scaffold(&ast, &rust_cfg)?;
-
-                if self.write_files {
-                    let file_stem = file
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .ok_or_else(|| anyhow::anyhow!("Invalid file name: {}", file.display()))?;
-                    let output_file = file.with_file_name(format!("{}_test.rs", file_stem));
-                    self.write_file(&emitted, &output_file);
-                } else {
-                    println!("{emitted}");
+                    assertion_stubs: self.assertion_stubs,
+                    snapshot_stubs: self.snapshot_stubs,
+                    todo_marker: self.todo_marker.clone(),
+                    doc_comments: self.doc_comments,
+                    branch_summary: self.branch_summary,
+                    tree_banner: self.tree_banner,
+                    header: header(
+                        self.header.as_ref(),
+                        self.no_header,
+                        Some(DEFAULT_HEADER),
+                    ),
+                    forbid_only: self.forbid_only,
+                    feature: self.feature.clone(),
+                    gate_file: self.gate_file,
+                    snippets: pairs(&self.snippets),
+                    ..self.naming.rust_config()
                 }
+                .for_tree(file);
+                let code = bulloak_rust::scaffold_tree(&text, &rust_cfg)
+                    .map_err(|e| {
+                        explain(
+                            file,
+                            stdin,
+                            &rust_cfg.parse_options(),
+                            e.into(),
+                        )
+                    })?;
+                let output_file = bulloak_rust::scaffold::test_file_path(file);
+                Scaffolded { text, code, output_file }
             }
             Backend::Solidity => {
-                let emitted = scaffold(&text, &cfg.into())?;
+                let foundry_cfg: bulloak_foundry::config::Config = cfg.into();
+                let foundry_cfg = foundry_cfg.for_tree(file);
+                let emitted = scaffold(&text, &foundry_cfg).map_err(|e| {
+                    explain(file, stdin, &foundry_cfg.parse_options(), e)
+                })?;
                 let formatted = fmt(&emitted).unwrap_or_else(|err| {
-                    eprintln!("{}: {}", "WARN".yellow(), err);
+                    eprintln!(
+                        "{}: {}",
+                        "WARN"
+                            .if_supports_color(Stream::Stderr, |t| t.yellow()),
+                        err
+                    );
                     emitted
                 });
-
-                if self.write_files {
-                    let file = file.with_extension("t.sol");
-                    self.write_file(&formatted, &file);
-                } else {
-                    println!("{formatted}");
-                }
+                let output_file = file.with_extension("t.sol");
+                Scaffolded { text, code: formatted, output_file }
             }
-        }
+        };
 
-        Ok(())
+        Ok(if self.markers {
+            Scaffolded { code: markers::wrap(&scaffolded.code), ..scaffolded }
+        } else {
+            scaffolded
+        })
     }
 
-    /// Writes the provided `text` to `file`.
+    /// Either stages the `scaffolded` code in `tx` to be written to its file
+    /// or prints it to stdout.
+    fn output(&self, tx: &mut Transaction, scaffolded: &Scaffolded) {
+        if self.write_files {
+            self.write_file(
+                tx,
+                &match_line_endings(&scaffolded.code, &scaffolded.text),
+                &scaffolded.output_file,
+            );
+        } else {
+            println!("{}", scaffolded.code);
+        }
+    }
+
+    /// Stages the provided `text` in `tx` to be written to `file`.
     ///
-    /// If the file doesn't exist it will create it. If it exists,
-    /// and `--force-write` was not passed, it will skip writing to the file.
-    fn write_file(&self, text: &str, file: &PathBuf) {
+    /// If the file doesn't exist it will create it. If it exists with
+    /// markers and `--markers` was passed, only the code between them is
+    /// replaced. Otherwise, if `--force-write` was not passed, it will skip
+    /// writing to the file.
+    fn write_file(&self, tx: &mut Transaction, text: &str, file: &Path) {
+        let existing = self.markers.then(|| fs::read_to_string(file).ok());
+        if let Some(spliced) = existing
+            .flatten()
+            .and_then(|existing| markers::splice(&existing, text))
+        {
+            tx.stage(file.to_path_buf(), spliced);
+            return;
+        }
+
         // Don't overwrite files unless `--force-write` was passed.
         if file.exists() && !self.force_write {
             eprintln!(
                 "{}: Skipped emitting {:?}",
-                "warn".yellow(),
-                file.as_path().blue()
+                "warn".if_supports_color(Stream::Stderr, |t| t.yellow()),
+                file.if_supports_color(Stream::Stderr, |t| t.blue())
             );
             eprintln!(
                 "    {} The corresponding `.t.sol` file already exists",
-                "=".blue()
+                "=".if_supports_color(Stream::Stderr, |t| t.blue())
             );
             return;
         }
 
-        if let Err(err) = fs::write(file, text) {
-            eprintln!("{}: {err}", "error".red());
-        };
+        tx.stage(file.to_path_buf(), text.to_owned());
     }
 
     /// Reports errors that occurred during file processing.
@@ -185,9 +434,23 @@ impl Scaffold {
 
         eprintln!(
             "\n{}: Could not scaffold {} files. Check the output above or run {}, which might prove helpful.",
-            "warn".yellow(),
-            errors.len().yellow(),
-            "bulloak check".blue()
+            "warn".if_supports_color(Stream::Stderr, |t| t.yellow()),
+            errors.len().if_supports_color(Stream::Stderr, |t| t.yellow()),
+            "bulloak check".if_supports_color(Stream::Stderr, |t| t.blue())
         );
     }
 }
+
+/// Explains `err`, raised while scaffolding `file`, in terms of the Markdown
+/// document the spec comes from, unless the spec was read from `stdin`.
+fn explain(
+    file: &Path,
+    stdin: Option<&str>,
+    options: &ParseOptions,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    if stdin.is_some() {
+        return err;
+    }
+    markdown::explain(file, options, err)
+}
@@ -0,0 +1,186 @@
+//! All-or-nothing file writes.
+//!
+//! Commands that edit several files, like `bulloak check --fix` and
+//! `bulloak scaffold --write-files`, stage their output in a [`Transaction`]
+//! and write it in one go at the end of the run. Every file is written to a
+//! temporary file next to it and renamed into place, so no file is ever left
+//! half-written, and if any write fails the files already written are rolled
+//! back to their previous contents.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A set of file writes that either all happen or none do.
+#[derive(Debug, Default)]
+pub(crate) struct Transaction {
+    /// The files to write, along with their new contents.
+    staged: Vec<(PathBuf, String)>,
+}
+
+/// A write that failed, after which the transaction was rolled back.
+#[derive(Debug)]
+pub(crate) struct WriteError {
+    /// The file that could not be written.
+    pub(crate) path: PathBuf,
+    /// Why it could not be written.
+    pub(crate) error: io::Error,
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to write {}: {}; no files were changed",
+            self.path.display(),
+            self.error
+        )
+    }
+}
+
+impl Transaction {
+    /// Stages `contents` to be written to `path` when the transaction is
+    /// committed.
+    pub(crate) fn stage(&mut self, path: PathBuf, contents: String) {
+        self.staged.push((path, contents));
+    }
+
+    /// The files to write, along with their new contents, in staging order.
+    pub(crate) fn staged(&self) -> &[(PathBuf, String)] {
+        &self.staged
+    }
+
+    /// Writes every staged file.
+    ///
+    /// All the contents are first written to temporary files, which are then
+    /// renamed over their targets. If anything fails, the temporary files are
+    /// removed and the targets already replaced are restored, or removed if
+    /// they didn't exist before.
+    pub(crate) fn commit(self) -> Result<(), WriteError> {
+        let mut temps = Vec::with_capacity(self.staged.len());
+        for (path, contents) in &self.staged {
+            let temp = temp_path(path);
+            if let Err(error) = fs::write(&temp, contents) {
+                remove_all(temps.iter().map(|(_, temp)| temp));
+                return Err(WriteError { path: path.clone(), error });
+            }
+            temps.push((path, temp));
+        }
+
+        let mut replaced: Vec<(&PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for (index, (path, temp)) in temps.iter().enumerate() {
+            let replace = backup(path).and_then(|previous| {
+                fs::rename(temp, path)?;
+                Ok(previous)
+            });
+            match replace {
+                Ok(previous) => replaced.push((path, previous)),
+                Err(error) => {
+                    rollback(&replaced);
+                    remove_all(temps[index..].iter().map(|(_, temp)| temp));
+                    return Err(WriteError { path: (*path).clone(), error });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The temporary file that `path` is written to before being renamed.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.bulloak.tmp"))
+}
+
+/// Reads the current contents of `path`, if it exists.
+fn backup(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Puts back the previous contents of the `replaced` files.
+///
+/// This is best effort, since there is nothing left to do if restoring a
+/// file fails too.
+fn rollback(replaced: &[(&PathBuf, Option<Vec<u8>>)]) {
+    for (path, previous) in replaced.iter().rev() {
+        let _ = match previous {
+            Some(contents) => fs::write(path, contents),
+            None => fs::remove_file(path),
+        };
+    }
+}
+
+/// Removes the temporary files in `temps`, ignoring any errors.
+fn remove_all<'a>(temps: impl Iterator<Item = &'a PathBuf>) {
+    for temp in temps {
+        let _ = fs::remove_file(temp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::Transaction;
+
+    #[test]
+    fn commits_every_file() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.t.sol"), dir.path().join("b.t.sol"));
+        fs::write(&a, "old").unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(a.clone(), "new a".to_owned());
+        tx.stage(b.clone(), "new b".to_owned());
+        tx.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "new b");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn rolls_back_on_failure() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.t.sol"), dir.path().join("b.t.sol"));
+        fs::write(&a, "old").unwrap();
+        // A directory can't be read as the previous contents of a file.
+        let c = dir.path().join("c.t.sol");
+        fs::create_dir(&c).unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(a.clone(), "new a".to_owned());
+        tx.stage(b.clone(), "new b".to_owned());
+        tx.stage(c.clone(), "new c".to_owned());
+        let err = tx.commit().unwrap_err();
+
+        assert_eq!(err.path, c);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old");
+        assert!(!b.exists());
+        assert!(c.is_dir());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn writes_nothing_if_a_file_cannot_be_staged() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.t.sol");
+        fs::write(&a, "old").unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(a.clone(), "new a".to_owned());
+        tx.stage(dir.path().join("missing").join("b.t.sol"), "b".to_owned());
+        assert!(tx.commit().is_err());
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,175 @@
+//! Finds the packages of a repository and the trees that belong to them.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Directories that never hold specs of their own, like build outputs and
+/// vendored dependencies.
+pub(crate) const SKIPPED_DIRS: &[&str] =
+    &["target", "node_modules", "lib", "out"];
+
+/// The kind of project a package is, told by its manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Manifest {
+    /// A Foundry project, with a `foundry.toml`.
+    Foundry,
+    /// A Noir project, with a `Nargo.toml`.
+    Nargo,
+    /// A Rust crate, with a `Cargo.toml`.
+    Cargo,
+}
+
+impl Manifest {
+    /// The manifest file names, in the order they are looked up when a
+    /// directory has several.
+    const ALL: [(Manifest, &'static str); 3] = [
+        (Manifest::Foundry, "foundry.toml"),
+        (Manifest::Nargo, "Nargo.toml"),
+        (Manifest::Cargo, "Cargo.toml"),
+    ];
+
+    /// Finds the manifest in `dir`, if any.
+    fn find(dir: &Path) -> Option<Manifest> {
+        Manifest::ALL
+            .into_iter()
+            .find(|(_, file)| dir.join(file).is_file())
+            .map(|(manifest, _)| manifest)
+    }
+}
+
+/// A directory with a manifest and the trees below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Package {
+    /// The directory holding the manifest, or the walked directory for trees
+    /// outside of any package.
+    pub(crate) root: PathBuf,
+    /// The manifest of the package, if there is one.
+    pub(crate) manifest: Option<Manifest>,
+    /// The trees that belong to the package, sorted by path.
+    pub(crate) trees: Vec<PathBuf>,
+}
+
+/// Walks `root` and groups every `.tree` file under the package with the
+/// nearest manifest.
+///
+/// Hidden directories and the ones in [`SKIPPED_DIRS`] are not walked.
+/// Packages are sorted by root, and packages without trees are left out.
+pub(crate) fn discover(root: &Path) -> io::Result<Vec<Package>> {
+    let mut packages = BTreeMap::new();
+    walk(root, (root, Manifest::find(root)), &mut packages)?;
+
+    Ok(packages
+        .into_iter()
+        .map(|(root, (manifest, mut trees))| {
+            trees.sort();
+            Package { root, manifest, trees }
+        })
+        .collect())
+}
+
+/// Collects the trees in `dir` into `packages`, where `package` is the
+/// package `dir` belongs to.
+fn walk(
+    dir: &Path,
+    package: (&Path, Option<Manifest>),
+    packages: &mut BTreeMap<PathBuf, (Option<Manifest>, Vec<PathBuf>)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+                continue;
+            }
+            let nested = Manifest::find(&path)
+                .map_or(package, |manifest| (path.as_path(), Some(manifest)));
+            walk(&path, nested, packages)?;
+        } else if path.extension().is_some_and(|ext| ext == "tree") {
+            let (root, manifest) = package;
+            packages
+                .entry(root.to_path_buf())
+                .or_insert_with(|| (manifest, Vec::new()))
+                .1
+                .push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{discover, Manifest, Package};
+
+    #[test]
+    fn groups_trees_by_nearest_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "Cargo.toml",
+            "a.tree",
+            "contracts/foundry.toml",
+            "contracts/test/b.tree",
+            "contracts/test/c.tree",
+            "contracts/lib/forge-std/d.tree",
+            "crates/core/Cargo.toml",
+            "crates/core/src/e.tree",
+            "crates/empty/Cargo.toml",
+            "circuits/Nargo.toml",
+            "circuits/f.tree",
+            "target/g.tree",
+            ".git/h.tree",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let packages = discover(root).unwrap();
+        assert_eq!(
+            packages,
+            [
+                Package {
+                    root: root.to_path_buf(),
+                    manifest: Some(Manifest::Cargo),
+                    trees: vec![root.join("a.tree")],
+                },
+                Package {
+                    root: root.join("circuits"),
+                    manifest: Some(Manifest::Nargo),
+                    trees: vec![root.join("circuits/f.tree")],
+                },
+                Package {
+                    root: root.join("contracts"),
+                    manifest: Some(Manifest::Foundry),
+                    trees: vec![
+                        root.join("contracts/test/b.tree"),
+                        root.join("contracts/test/c.tree"),
+                    ],
+                },
+                Package {
+                    root: root.join("crates/core"),
+                    manifest: Some(Manifest::Cargo),
+                    trees: vec![root.join("crates/core/src/e.tree")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_trees_outside_of_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.tree"), "").unwrap();
+
+        let packages = discover(dir.path()).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].manifest, None);
+    }
+}
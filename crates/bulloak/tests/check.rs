@@ -1,5 +1,5 @@
 #![allow(missing_docs)]
-use std::env;
+use std::{env, fs, process::Command};
 
 use common::{cmd, get_binary_path};
 use owo_colors::OwoColorize;
@@ -18,17 +18,129 @@ fn checks_invalid_structural_match() {
     let stderr = String::from_utf8(output.stderr).unwrap();
     let actual = stderr.lines().filter(|line| line.starts_with("warn:"));
 
-    let expected = r#"warn: function "givenTheStreamIsCold" is missing in .sol
+    let expected = r#"warn: incorrect position for function `test_RevertGiven_TheStreamsStatusIsCANCELED`
+warn: incorrect position for function `test_WhenTheSenderReverts`
+warn: function "givenTheStreamIsCold" is missing in .sol
 warn: function "whenTheSenderDoesNotRevert" is missing in .sol
-warn: incorrect position for function "test_RevertGiven_TheStreamsStatusIsCANCELED"
-warn: incorrect position for function "test_RevertGiven_TheStreamsStatusIsSETTLED"
-warn: incorrect position for function "test_WhenTheSenderReverts"
-warn: incorrect position for function "test_WhenThereIsReentrancy""#
+warn: 4 checks failed (run `bulloak check --fix <.tree files>` to apply 4 fixes)"#
         .lines();
 
-    for (expected, actual) in expected.zip(actual) {
-        assert_eq!(expected, actual);
+    assert_eq!(expected.collect::<Vec<_>>(), actual.collect::<Vec<_>>());
+}
+
+#[test]
+fn checks_summarize_violations_by_kind() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(
+        stderr.lines().last(),
+        Some("summary: 2 order issues, 2 missing modifiers")
+    );
+}
+
+#[test]
+fn checks_exit_code_follows_error_policy() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let check_path = cwd.join("tests").join("check");
+    let missing_sol = check_path.join("no_matching_sol.tree");
+    let fixable = check_path.join("invalid_sol_structure.tree");
+
+    let output = cmd(&binary_path, "check", &missing_sol, &[]);
+    assert!(!output.status.success());
+    let args = ["--error-on", "fixable-only"];
+    let output = cmd(&binary_path, "check", &missing_sol, &args);
+    assert!(output.status.success());
+    let output = cmd(&binary_path, "check", &fixable, &args);
+    assert!(!output.status.success());
+
+    let output = cmd(&binary_path, "check", &fixable, &["--error-on", "never"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warn: 4 checks failed"));
+}
+
+#[test]
+fn checks_stop_after_max_violations() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let check_path = cwd.join("tests").join("check");
+    let trees = ["invalid_sol_structure.tree", "empty_contract.tree"];
+    let check = |args: &[&str]| {
+        let output = Command::new(&binary_path)
+            .arg("check")
+            .args(trees.iter().map(|tree| check_path.join(tree)))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let stderr = check(&["--fail-fast", "--jobs", "1"]);
+    let actual: Vec<_> = stderr
+        .lines()
+        .filter(|line| line.starts_with("warn:") || line.starts_with("note:"))
+        .collect();
+    assert_eq!(
+        actual,
+        vec![
+            r#"warn: function "givenTheStreamIsCold" is missing in .sol"#,
+            "warn: 1 check failed (run `bulloak check --fix <.tree files>` to apply 1 fix)",
+            "note: stopped after 1 violation; 1 file was not checked",
+        ]
+    );
+
+    let stderr = check(&["--max-violations", "3", "--jobs", "1"]);
+    assert!(stderr.contains("warn: 3 checks failed"));
+    assert!(stderr
+        .contains("note: stopped after 3 violations; 1 file was not checked"));
+
+    let stderr = check(&["--fix", "--fail-fast"]);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn checks_print_the_same_output_whatever_the_jobs() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let check_path = cwd.join("tests").join("check");
+    let check = |args: &[&str]| {
+        Command::new(&binary_path)
+            .arg("check")
+            .arg(check_path.join("*.tree"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    for args in [&[][..], &["--max-violations", "5"], &["--sort-by", "kind"]] {
+        let sequential = check(&[args, &["--jobs", "1"]].concat());
+        let parallel = check(&[args, &["--jobs", "8"]].concat());
+        assert_eq!(sequential.stdout, parallel.stdout);
+        assert_eq!(sequential.stderr, parallel.stderr);
     }
+
+    let tree_path = check_path.join("invalid_sol_structure.tree");
+    let output = cmd(&binary_path, "check", &tree_path, &["--sort-by", "kind"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let actual: Vec<_> =
+        stderr.lines().filter(|line| line.starts_with("warn:")).collect();
+    assert_eq!(
+        actual[..4],
+        [
+            r#"warn: function "givenTheStreamIsCold" is missing in .sol"#,
+            r#"warn: function "whenTheSenderDoesNotRevert" is missing in .sol"#,
+            "warn: incorrect position for function `test_RevertGiven_TheStreamsStatusIsCANCELED`",
+            "warn: incorrect position for function `test_WhenTheSenderReverts`",
+        ]
+    );
 }
 
 #[test]
@@ -64,6 +176,240 @@ fn checks_modifiers_skipped() {
     );
 }
 
+#[test]
+fn checks_trees_from_config_file() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let fixtures = cwd.join("tests").join("check");
+    let project = tempfile::tempdir().unwrap();
+    let spec = project.path().join("spec");
+    fs::create_dir(&spec).unwrap();
+    for file in ["skip_modifiers.tree", "skip_modifiers.t.sol"] {
+        fs::copy(fixtures.join(file), spec.join(file)).unwrap();
+    }
+    fs::write(
+        project.path().join("bulloak.toml"),
+        "trees = [\"spec/**/*.tree\"]\nskip_modifiers = true\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&binary_path)
+        .arg("check")
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
+    );
+
+    // Trees passed on the command line replace the ones in the config file.
+    let output = Command::new(&binary_path)
+        .arg("check")
+        .arg(fixtures.join("no_matching_sol.tree"))
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("the tree is missing its matching Solidity file"));
+    assert!(!stderr.contains("skip_modifiers.tree"));
+}
+
+#[test]
+fn checks_warn_about_ignored_comments() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("spurious_comments.tree");
+
+    let output = Command::new(&binary_path)
+        .arg("check")
+        .arg(&tree_path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(stderr.contains(
+        "spurious_comments.tree:12:1: comment outside of any tree is ignored"
+    ));
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
+    );
+}
+
+#[test]
+fn checks_trees_from_explicit_config_file() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let fixtures = cwd.join("tests").join("check");
+    let project = tempfile::tempdir().unwrap();
+    let spec = project.path().join("spec");
+    fs::create_dir(&spec).unwrap();
+    for file in ["skip_modifiers.tree", "skip_modifiers.t.sol"] {
+        fs::copy(fixtures.join(file), spec.join(file)).unwrap();
+    }
+    fs::write(
+        project.path().join("bulloak.toml"),
+        "trees = [\"none/*.tree\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        project.path().join("ci.toml"),
+        "trees = [\"spec/**/*.tree\"]\nskip_modifiers = true\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&binary_path)
+        .args(["check", "--config", "ci.toml"])
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
+    );
+
+    let output = Command::new(&binary_path)
+        .args(["check", "--config", "missing.toml"])
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("could not find the config file missing.toml"));
+}
+
+#[test]
+fn checks_resolve_config_trees_against_the_config_file() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let fixtures = cwd.join("tests").join("check");
+    let workspace = tempfile::tempdir().unwrap();
+    fs::create_dir(workspace.path().join(".git")).unwrap();
+    let spec = workspace.path().join("spec");
+    fs::create_dir(&spec).unwrap();
+    for file in ["skip_modifiers.tree", "skip_modifiers.t.sol"] {
+        fs::copy(fixtures.join(file), spec.join(file)).unwrap();
+    }
+    fs::write(
+        workspace.path().join("bulloak.toml"),
+        "trees = [\"spec/**/*.tree\"]\nskip_modifiers = true\n",
+    )
+    .unwrap();
+    let ci = workspace.path().join("ci");
+    fs::create_dir(&ci).unwrap();
+    fs::write(
+        ci.join("ci.toml"),
+        "trees = [\"../spec/**/*.tree\"]\nskip_modifiers = true\n",
+    )
+    .unwrap();
+    let sub = workspace.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+
+    for args in [vec!["check"], vec!["check", "--config", "../ci/ci.toml"]] {
+        let output = Command::new(&binary_path)
+            .args(&args)
+            .current_dir(&sub)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert_eq!("", stderr, "{args:?}");
+        assert!(stdout.contains("coverage: 100% of"), "{args:?}: {stdout}");
+    }
+}
+
+#[test]
+fn checks_layer_workspace_config_files() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let fixtures = cwd.join("tests").join("check");
+    let workspace = tempfile::tempdir().unwrap();
+    fs::create_dir(workspace.path().join(".git")).unwrap();
+    let spec = workspace.path().join("pkg").join("spec");
+    fs::create_dir_all(&spec).unwrap();
+    for file in ["skip_modifiers.tree", "skip_modifiers.t.sol"] {
+        fs::copy(fixtures.join(file), spec.join(file)).unwrap();
+    }
+    fs::write(
+        workspace.path().join("bulloak.toml"),
+        "trees = [\"none/*.tree\"]\nskip_modifiers = true\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.path().join("pkg").join("bulloak.toml"),
+        "trees = [\"spec/**/*.tree\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&binary_path)
+        .arg("check")
+        .current_dir(workspace.path().join("pkg"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
+    );
+}
+
+#[test]
+fn checks_every_package_in_a_workspace() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests");
+    let repo = tempfile::tempdir().unwrap();
+    let copy = |from: &str, to: &str| {
+        let to = repo.path().join(to);
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+        fs::copy(tests_path.join(from), to).unwrap();
+    };
+    copy("check/extra_codegen_sol.tree", "contracts/test/foo.tree");
+    copy("check/extra_codegen_sol.t.sol", "contracts/test/foo.t.sol");
+    copy("check/no_matching_sol.tree", "contracts/test/bar.tree");
+    copy("scaffold_rust/basic.tree", "crates/core/tests/basic.tree");
+    copy("scaffold_rust/basic_test.rs", "crates/core/tests/basic_test.rs");
+    copy("scaffold_rust/basic.tree", "circuits/src/basic.tree");
+    for manifest in [
+        "contracts/foundry.toml",
+        "crates/core/Cargo.toml",
+        "circuits/Nargo.toml",
+    ] {
+        fs::write(repo.path().join(manifest), "").unwrap();
+    }
+
+    let output = Command::new(&binary_path)
+        .args(["check", "--workspace"])
+        .arg(repo.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("the tree is missing its matching Solidity file"));
+    assert!(stdout.contains("circuits (noir): 1 tree skipped"));
+    assert!(stdout.contains("contracts (solidity): 2 trees, 1 violation"));
+    assert!(stdout.contains(
+        "crates/core (rust): 1 tree, 0 violations, 100% of 3 spec leaves have \
+         tests"
+    ));
+}
+
 #[test]
 fn checks_modifiers_skipped_issue_81() {
     let cwd = env::current_dir().unwrap();
@@ -78,6 +424,18 @@ fn checks_modifiers_skipped_issue_81() {
     ));
 }
 
+#[test]
+fn checks_report_how_many_leaves_have_tests() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("issue_81.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-m"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("coverage: 75% of 4 spec leaves have tests"));
+}
+
 #[test]
 fn checks_missing_sol_file() {
     let cwd = env::current_dir().unwrap();
@@ -121,7 +479,6 @@ fn checks_missing_contract() {
     assert!(stderr.contains(r#"contract "MissingContract" is missing in .sol"#));
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn checks_missing_contract_identifier() {
     let cwd = env::current_dir().unwrap();
@@ -131,7 +488,7 @@ fn checks_missing_contract_identifier() {
         .join("check")
         .join("missing_contract_identifier.tree");
 
-    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let output = cmd(&binary_path, "check", &tree_path, &["--color", "always"]);
     let stderr = String::from_utf8(output.stderr).unwrap();
 
     let formatted_message = format!(
@@ -149,6 +506,30 @@ fn checks_missing_contract_identifier() {
     );
 }
 
+#[test]
+fn checks_without_colors_unless_asked() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("missing_contract.tree");
+
+    // Output is piped, so there is no terminal to color.
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(!String::from_utf8(output.stderr).unwrap().contains('\x1b'));
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--no-color"]);
+    assert!(!String::from_utf8(output.stderr).unwrap().contains('\x1b'));
+
+    let output = Command::new(&binary_path)
+        .args(["check", "--color", "always"])
+        .arg(&tree_path)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!("{}", "warn".yellow())));
+}
+
 #[test]
 fn checks_contract_name_mismatch() {
     let cwd = env::current_dir().unwrap();
@@ -164,7 +545,6 @@ fn checks_contract_name_mismatch() {
     ));
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn checks_contract_name_mismatch_multiple_roots() {
     let cwd = env::current_dir().unwrap();
@@ -174,7 +554,7 @@ fn checks_contract_name_mismatch_multiple_roots() {
         .join("check")
         .join("contract_names_mismatch_multiple_roots.tree");
 
-    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let output = cmd(&binary_path, "check", &tree_path, &["--color", "always"]);
     let stderr = String::from_utf8(output.stderr).unwrap();
 
     let formatted_message = format!(
@@ -246,6 +626,144 @@ contract MissingContract {
     assert!(actual.contains("1 issue fixed."));
 }
 
+#[test]
+fn checks_contract_named_after_tree_among_many() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("multiple_contracts.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
+    );
+}
+
+#[test]
+fn checks_contract_selected_by_name() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("multiple_contracts.tree");
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--contract", "HashPairForkTest"],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains(
+        r#"function "test_WhenFirstArgIsSmallerThanSecondArg" is missing in .sol"#
+    ));
+    assert!(!stderr.contains("is missing in .sol -- found"));
+}
+
+#[test]
+fn fixes_selected_contract_missing_among_many() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("multiple_contracts.tree");
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--contract", "HashPairUnitTest", "--fix", "--stdout"],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains("contract HashPairForkTest {"));
+    assert!(actual.contains("contract HashPairTest {"));
+    assert!(actual.contains("contract HashPairUnitTest {"));
+    assert!(actual.contains("1 issue fixed."));
+}
+
+#[test]
+fn fix_leaves_conflicting_files_untouched() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("renamed_modifier.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(!stdout.contains("contract Vault"));
+    assert!(stdout.contains("0 issues fixed."));
+    assert!(stderr.contains(
+        r#"warn: manual resolution required: modifier "whenTheVaultIsOpenAndFunded" is not in the tree"#
+    ));
+    assert!(stderr.contains("summary: 1 fix conflict"));
+}
+
+#[test]
+fn fix_prints_a_diff_of_the_changes() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("unsorted.tree");
+    let sol_path = tree_path.with_extension("t.sol");
+    let before = fs::read_to_string(&sol_path).unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--diff"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let path = sol_path.display();
+    assert!(actual
+        .starts_with(&format!("--- {path}\n+++ {path}\n@@ -6,24 +6,31 @@\n")));
+    assert!(actual.contains("-    function an_extra_function() external {\n"));
+    assert!(actual
+        .contains("+    modifier whenFirstArgIsSmallerThanSecondArg() {\n"));
+    assert!(actual.contains("1 issue fixed."));
+    assert_eq!(fs::read_to_string(&sol_path).unwrap(), before);
+}
+
+#[test]
+fn fix_plans_edits_without_applying_them() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let fixtures = cwd.join("tests").join("check");
+    let dir = tempfile::tempdir().unwrap();
+    for file in ["unsorted.tree", "unsorted.t.sol"] {
+        fs::copy(fixtures.join(file), dir.path().join(file)).unwrap();
+    }
+    let tree_path = dir.path().join("unsorted.tree");
+    let sol_path = dir.path().join("unsorted.t.sol");
+    let before = fs::read_to_string(&sol_path).unwrap();
+
+    let args = ["--fix", "--plan-only", "--format", "json"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let plan: bulloak::api::FixPlan = serde_json::from_str(&stdout).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(plan.schema_version, bulloak::api::SCHEMA_VERSION);
+    assert!(!plan.edits.is_empty());
+    assert!(plan.edits.iter().all(|edit| edit.file == sol_path));
+    assert_eq!(fs::read_to_string(&sol_path).unwrap(), before);
+
+    let mut planned = before;
+    for edit in plan.edits.iter().rev() {
+        planned.replace_range(edit.start..edit.end, &edit.replacement);
+    }
+    cmd(&binary_path, "check", &tree_path, &["--fix"]);
+    assert_eq!(fs::read_to_string(&sol_path).unwrap(), planned);
+
+    let output =
+        cmd(&binary_path, "check", &tree_path, &["--fix", "--format", "json"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("`--format json` with `--fix` needs `--plan-only`"));
+}
+
 #[test]
 fn fixes_extra_codegen_tree() {
     let cwd = env::current_dir().unwrap();
@@ -499,3 +1017,497 @@ fn check_invalid_glob_warns_but_reports_success() {
         stdout
     );
 }
+
+#[test]
+fn checks_warn_about_globs_matching_nothing() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let typo = cwd.join("tests").join("check").join("*.treee");
+
+    let output = cmd(&binary_path, "check", &typo, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert!(stderr.contains(&format!("no files match {}", typo.display())));
+
+    let output = cmd(&binary_path, "check", &typo, &["--strict-globs"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains(&format!("no files match {}", typo.display())));
+    assert!(!stdout.contains("All checks completed successfully"));
+}
+
+#[test]
+fn checks_within_a_time_budget() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("extra_codegen_sol.tree");
+
+    let args = ["--max-duration", "600", "--format", "json"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let report = bulloak::api::parse_check_report(&stdout).unwrap();
+
+    // Checking everything in time exits as usual.
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(report.files, vec![tree_path.clone()]);
+    assert!(report.unchecked.is_empty());
+
+    let output =
+        cmd(&binary_path, "check", &tree_path, &["--max-duration", "0"]);
+    assert!(!output.status.success());
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--max-duration", "1", "--fix"],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn checks_print_a_versioned_json_report() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let report = bulloak::api::parse_check_report(&stdout).unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stderr.is_empty());
+    assert_eq!(report.schema_version, bulloak::api::SCHEMA_VERSION);
+    assert_eq!(report.files, vec![tree_path]);
+    assert_eq!(report.violations.len(), 4);
+    assert!(report.violations.iter().all(|v| v.fixable));
+    assert_eq!(
+        report.violations[2].message,
+        r#"function "givenTheStreamIsCold" is missing in .sol"#
+    );
+    assert_eq!(report.violations[2].rule, "BK106");
+}
+
+#[test]
+fn checks_suggest_fixes_for_consecutive_missing_tests() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("Vault.tree");
+    fs::write(
+        &tree_path,
+        "Vault\n├── it should be empty.\n└── when paused\n    └── it should \
+         revert.\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("Vault.t.sol"),
+        "// SPDX-License-Identifier: UNLICENSED\npragma solidity \
+         0.8.0;\n\ncontract Vault {}\n",
+    )
+    .unwrap();
+
+    for format in ["json", "json-lines"] {
+        let args = ["--format", format];
+        let output = cmd(&binary_path, "check", &tree_path, &args);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+
+        assert!(!stderr.contains("panicked"), "{stderr}");
+        assert!(stdout.contains("function test_ShouldBeEmpty()"));
+        assert!(stdout.contains("function test_RevertWhen_Paused()"));
+    }
+}
+
+#[test]
+fn checks_stream_json_lines() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
+
+    let args = ["--format", "json-lines"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let violations: Vec<bulloak::api::ViolationReport> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert!(!output.status.success());
+    assert_eq!(violations.len(), 4);
+    assert!(violations.iter().all(|v| v.fixable));
+    assert!(violations.iter().any(|v| v.message
+        == r#"function "givenTheStreamIsCold" is missing in .sol"#));
+
+    let args = ["--format", "json-lines", "--max-violations", "1"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+
+    let args = ["--format", "json-lines", "--fix"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn checks_group_violations_by_file() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--group-by-file"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    let headers: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.ends_with("invalid_sol_structure.t.sol"))
+        .collect();
+    assert_eq!(headers.len(), 1);
+    let entries = stderr.lines().filter(|line| line.starts_with("  ")).count();
+    assert_eq!(entries, 4);
+    assert!(stderr.contains(
+        r#": warn: function "givenTheStreamIsCold" is missing in .sol"#
+    ));
+    assert!(stderr.contains("warn: 4 checks failed"));
+}
+
+#[test]
+fn checks_suggest_a_fix_per_violation() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let fixtures = cwd.join("tests").join("check");
+    let dir = tempfile::tempdir().unwrap();
+    for file in ["invalid_sol_structure.tree", "invalid_sol_structure.t.sol"] {
+        fs::copy(fixtures.join(file), dir.path().join(file)).unwrap();
+    }
+    let tree_path = dir.path().join("invalid_sol_structure.tree");
+    let sol_path = dir.path().join("invalid_sol_structure.t.sol");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let report = bulloak::api::parse_check_report(&stdout).unwrap();
+    assert_eq!(report.violations.len(), 4);
+    assert!(report.violations.iter().all(|v| !v.suggestion.is_empty()));
+
+    let mut fixed = fs::read_to_string(&sol_path).unwrap();
+    for edit in report.violations[2].suggestion.iter().rev() {
+        assert_eq!(edit.file, sol_path);
+        fixed.replace_range(edit.start..edit.end, &edit.replacement);
+    }
+    fs::write(&sol_path, fixed).unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let report = bulloak::api::parse_check_report(&stdout).unwrap();
+    assert_eq!(report.violations.len(), 3);
+    assert!(report
+        .violations
+        .iter()
+        .all(|v| !v.message.contains("givenTheStreamIsCold")));
+}
+
+#[test]
+fn checks_forbid_only_tags() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("focus.tree");
+    fs::write(
+        &tree_path,
+        "Focus\n├── when a\n│   └── it should x.\n└── when b\n    └── it should y. [only]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("focus.t.sol"),
+        "contract Focus {\n    function test_WhenB() external {\n        // it should y.\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--forbid-only"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("found an `[only]` tag"));
+}
+
+#[test]
+fn checks_ignore_fork_tests_unless_included() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("fork.tree");
+    fs::write(
+        &tree_path,
+        "Fork\n├── it should work.\n└── when bridged [fork]\n    └── it should revert.\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("fork.t.sol"),
+        "contract Fork {\n    function test_ShouldWork() external {\n        // it should work.\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--include-fork"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("testFork_RevertWhen_Bridged"));
+}
+
+#[test]
+fn checks_trees_embedded_in_markdown() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let doc_path = dir.path().join("vault.md");
+    let doc = "# Vault\n\nDeposits are paused in emergencies.\n\n```tree\nVault\n└── when paused\n    └── it should revert.\n```\n\n```solidity\ncontract Unrelated {}\n```\n";
+    fs::write(&doc_path, doc).unwrap();
+
+    let output = cmd(&binary_path, "scaffold", &doc_path, &["-w"]);
+    assert!(output.status.success());
+    let sol = fs::read_to_string(dir.path().join("vault.t.sol")).unwrap();
+    assert!(sol.contains("function test_RevertWhen_Paused() external"));
+    assert!(!sol.contains("Unrelated"));
+    let output = cmd(&binary_path, "check", &doc_path, &[]);
+    assert!(output.status.success());
+
+    // The document is the source of truth, so editing it makes checks fail.
+    let doc = doc.replace(
+        "└── when paused\n    └── it should revert.",
+        "├── when paused\n│   └── it should revert.\n└── it should accept \
+         deposits.",
+    );
+    fs::write(&doc_path, doc).unwrap();
+    let output = cmd(&binary_path, "check", &doc_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains(r#"function "test_ShouldAcceptDeposits" is missing"#)
+    );
+}
+
+#[test]
+fn points_at_the_markdown_block_that_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let doc_path = dir.path().join("vault.md");
+    let doc = "# Vault\n\n```tree\nVault::deposit\n└── it should work.\n```\n\n```tree\nVault::withdraw\n├── when paused\n│   └── it should revert.\n├── it should work.\n```\n";
+    fs::write(&doc_path, doc).unwrap();
+
+    let output = cmd(&binary_path, "scaffold", &doc_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("--- (line 12, column 1) ---"), "{stderr}");
+    assert!(stderr.contains("in the `tree` block at line 8"), "{stderr}");
+    let output = cmd(&binary_path, "check", &doc_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("vault.md:8"), "{stderr}");
+
+    // Blocks that are fine on their own but can't make up one spec are
+    // listed.
+    let doc = "```tree\nMd\n└── it should work.\n```\n\n```tree\nMd2\n└── \
+               it should also work.\n```\n";
+    fs::write(&doc_path, doc).unwrap();
+    let output = cmd(&binary_path, "scaffold", &doc_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("separator missing at tree root #1"), "{stderr}");
+    assert!(
+        stderr.contains("the `tree` blocks at lines 1, 6 are read as a single"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn checks_visibility_tags() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("vault.tree");
+    let sol_path = dir.path().join("vault.t.sol");
+    fs::write(
+        &tree_path,
+        "Vault\n└── when paused [virtual]\n    ├── when the caller is the owner\n    │   └── it should unpause.\n    └── it should revert. [public]\n",
+    )
+    .unwrap();
+    fs::write(&sol_path, "contract Vault {}\n").unwrap();
+
+    // Fixing scaffolds the functions with the attributes of their tags.
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix"]);
+    assert!(output.status.success());
+    let fixed = fs::read_to_string(&sol_path).unwrap();
+    assert!(fixed.contains("modifier whenPaused() virtual {"));
+    assert!(fixed.contains("function test_RevertWhen_Paused() public"));
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+
+    fs::write(&sol_path, fixed.replace(") public", ") external")).unwrap();
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr
+        .contains(r#"function "test_RevertWhen_Paused" should be `public`"#));
+}
+
+#[test]
+fn checks_format_descriptions() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("token.tree");
+    let sol_path = dir.path().join("token.t.sol");
+    fs::write(
+        &tree_path,
+        "Token\n└── when transferring\n    ├── it should move the tokens\n    └── it should emit an event\n",
+    )
+    .unwrap();
+    fs::write(
+        &sol_path,
+        "contract Token {\n    function test_WhenTransferring() external {\n        // it should emit an event\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+
+    let args = ["--format-descriptions"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains(
+        r#"test "test_WhenTransferring" is missing the comment "// It should move the tokens.""#
+    ));
+    assert!(stderr.contains(
+        r#"comment "// it should emit an event" of test "test_WhenTransferring" should read "// It should emit an event.""#
+    ));
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--fix", "--format-descriptions"],
+    );
+    assert!(output.status.success());
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&sol_path).unwrap().contains(
+        "        // It should move the tokens.\n        // It should emit an event.\n"
+    ));
+}
+
+#[test]
+fn checks_descriptions_starting_with_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("token.tree");
+    let sol_path = dir.path().join("token.t.sol");
+    let tree = "Token\n└── when transferring\n    └── it should move the tokens\n        └── `transfer()` returns true\n";
+    fs::write(&tree_path, tree).unwrap();
+
+    let args = ["--format-descriptions"];
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-w", "-F"]);
+    assert!(output.status.success());
+    let sol = fs::read_to_string(&sol_path).unwrap();
+    assert!(sol.contains("// It should move the tokens.\n"));
+    assert!(sol.contains("//     `transfer()` returns true.\n"));
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(output.status.success());
+
+    // A spec can keep its comments verbatim.
+    let sol =
+        sol.replace("It should move the tokens.", "it should move the tokens");
+    fs::write(&sol_path, sol).unwrap();
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(!output.status.success());
+    fs::write(&tree_path, format!("// format-descriptions: off\n{tree}"))
+        .unwrap();
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(output.status.success());
+}
+
+#[test]
+fn checks_the_vm_skip_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("skip.tree");
+    fs::write(
+        &tree_path,
+        "Skip\n├── it should work.\n└── when paused [skip]\n    └── it should revert.\n",
+    )
+    .unwrap();
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-w"]);
+    assert!(output.status.success());
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-S"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr
+        .contains(r#"test "test_ShouldWork" should call `vm.skip(true)`"#));
+}
+
+#[test]
+fn checks_report_timings() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("extra_codegen_tree.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--timings"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("timings: 1 file,"));
+    assert!(stderr.contains("extra_codegen_tree.tree"));
+}
+
+#[test]
+fn checks_custom_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("rules.tree");
+    fs::write(&tree_path, "Rules\n└── it should work.\n").unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-w"]);
+    assert!(output.status.success());
+
+    // A rule that reports the same violation whatever the spec.
+    let message = r#"[{"message":"tests should use the fixtures","line":2}]"#;
+    let rule = wat::parse_str(format!(
+        r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "{}")
+            (func (export "alloc") (param i32) (result i32) i32.const 1024)
+            (func (export "check") (param i32 i32) (result i64)
+                i64.const {}))"#,
+        message.replace('"', "\\\""),
+        message.len()
+    ))
+    .unwrap();
+    let rules = dir.path().join("rules");
+    fs::create_dir(&rules).unwrap();
+    fs::write(rules.join("fixtures.wasm"), rule).unwrap();
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--rules-dir", rules.to_str().unwrap()],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains(r#"tests should use the fixtures (rule "fixtures")"#)
+    );
+    assert!(stderr.contains("rules.t.sol:2"));
+    assert!(stderr.contains("1 custom rule violation"));
+
+    // The rules directory defaults to `.bulloak/rules/`, which doesn't exist
+    // here.
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Vault {
+  modifier whenTheVaultIsOpenAndFunded() {
+    vault.open();
+    vault.fund(1 ether);
+    _;
+  }
+
+  function test_RevertWhen_TheAmountIsZero() external whenTheVaultIsOpenAndFunded {
+    // it should revert.
+  }
+
+  function test_WhenTheAmountIsPositive() external whenTheVaultIsOpenAndFunded {
+    // it should deposit.
+  }
+}
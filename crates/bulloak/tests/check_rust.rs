@@ -1,11 +1,10 @@
 #![allow(missing_docs)]
-use std::{env, fs};
+use std::{env, fs, process::Command};
 
 use common::{cmd, get_binary_path};
 
 mod common;
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn check_rust_passes_when_correct() {
     let cwd = env::current_dir().unwrap();
@@ -22,7 +21,6 @@ fn check_rust_passes_when_correct() {
     assert!(stdout.contains("All checks completed successfully"));
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn check_rust_fails_when_missing_file() {
     let cwd = env::current_dir().unwrap();
@@ -48,7 +46,6 @@ fn check_rust_fails_when_missing_file() {
     fs::remove_file(temp_tree).ok();
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn check_rust_fails_when_missing_test_function() {
     let cwd = env::current_dir().unwrap();
@@ -91,8 +88,430 @@ mod tests {
     assert!(!output.status.success());
     let stderr = String::from_utf8(output.stderr).unwrap();
     assert!(stderr.contains("Test function") && stderr.contains("is missing"));
+    assert!(stderr.contains("summary: 1 missing test\n"));
 
     // Clean up
     fs::remove_file(temp_tree).ok();
     fs::remove_file(temp_test).ok();
 }
+
+#[test]
+fn check_rust_accepts_alternative_test_attributes() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+
+    let temp_tree = tests_path.join("temp_attributes.tree");
+    fs::write(
+        &temp_tree,
+        "test_func\n├── It should work.\n└── It should also work.",
+    )
+    .unwrap();
+
+    let temp_test = tests_path.join("temp_attributes_test.rs");
+    fs::write(
+        &temp_test,
+        r"
+#[cfg(test)]
+mod tests {
+    #[tokio::test]
+    async fn test_should_work() {}
+
+    #[my_harness::test]
+    fn test_should_also_work() {}
+}
+",
+    )
+    .unwrap();
+
+    // `#[my_harness::test]` is unknown unless configured.
+    let output = cmd(&binary_path, "check", &temp_tree, &["--backend", "rust"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("test_should_also_work"));
+    assert!(!stderr.contains("test_should_work"));
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &temp_tree,
+        &["--backend", "rust", "--test-attribute", "my_harness::test"],
+    );
+    assert!(output.status.success());
+
+    // Clean up
+    fs::remove_file(temp_tree).ok();
+    fs::remove_file(temp_test).ok();
+}
+
+#[test]
+fn check_rust_fix_scaffolds_missing_file() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("nested.tree");
+    fs::copy(tests_path.join("nested.tree"), &tree_path).unwrap();
+
+    let output =
+        cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--fix"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1 issue fixed"));
+
+    let actual = fs::read_to_string(dir.path().join("nested_test.rs")).unwrap();
+    let expected =
+        fs::read_to_string(tests_path.join("nested_test.rs")).unwrap();
+    assert_eq!(expected.trim(), actual.trim());
+
+    // The scaffolded file passes the check.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn check_rust_fix_prints_missing_file_with_stdout() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("basic.tree");
+    fs::copy(tests_path.join("basic.tree"), &tree_path).unwrap();
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["-b", "rust", "--fix", "--stdout"],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let expected =
+        fs::read_to_string(tests_path.join("basic_test.rs")).unwrap();
+    assert!(stdout.contains(expected.trim()));
+    assert!(!dir.path().join("basic_test.rs").exists());
+}
+
+#[test]
+fn check_rust_fix_reorders_test_functions() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("nested.tree");
+    fs::copy(tests_path.join("nested.tree"), &tree_path).unwrap();
+    let expected =
+        fs::read_to_string(tests_path.join("nested_test.rs")).unwrap();
+
+    // Move the first test to the end of the test module.
+    let first = "    #[test]\n    #[should_panic]\n    fn test_when_amount_is_zero() {\n        let _ctx = amount_is_zero(TestContext::default());\n        // it should revert\n    }\n";
+    let end = expected.rfind('}').unwrap();
+    let mut misordered = expected.clone();
+    misordered.insert_str(end, first);
+    let misordered = misordered.replacen(first, "", 1);
+    let test_path = dir.path().join("nested_test.rs");
+    fs::write(&test_path, &misordered).unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Test function order does not match spec order"));
+
+    let output =
+        cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--fix"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1 issue fixed"));
+    assert_eq!(fs::read_to_string(&test_path).unwrap(), expected);
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn check_rust_verifies_and_fixes_tree_banner() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("basic.tree");
+    fs::copy(tests_path.join("basic.tree"), &tree_path).unwrap();
+    let args = ["-b", "rust", "--tree-banner"];
+    let output = cmd(
+        &binary_path,
+        "scaffold",
+        &tree_path,
+        &[&args[..], &["-w"]].concat(),
+    );
+    assert!(output.status.success());
+    let test_path = dir.path().join("basic_test.rs");
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+    assert!(scaffolded.starts_with("// Generated by bulloak\n\n// Spec:\n"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(output.status.success());
+
+    let tree = fs::read_to_string(&tree_path).unwrap();
+    let tree = format!("{}\n    └── It should also work.", tree.trim_end());
+    fs::write(&tree_path, tree).unwrap();
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Spec banner is missing or does not match the spec")
+    );
+    // Banners are only verified when asked to.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(output.status.success());
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &[&args[..], &["--fix"]].concat(),
+    );
+    assert!(output.status.success());
+    let fixed = fs::read_to_string(&test_path).unwrap();
+    assert!(fixed.contains("// Spec:\n"));
+    assert!(fixed.contains("It should also work."));
+}
+
+#[test]
+fn check_rust_follows_anchors_across_renames() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("transfer.tree");
+    fs::write(
+        &tree_path,
+        "transfer\n├── When the amount is zero #zero\n│   └── It should revert.\n└── It should emit.\n",
+    )
+    .unwrap();
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "-w"]);
+    assert!(output.status.success());
+    let test_path = dir.path().join("transfer_test.rs");
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+    assert!(scaffolded.contains("    // #zero\n    #[test]\n    #[should_panic]\n    fn test_when_the_amount_is_zero()"));
+
+    fs::write(
+        &tree_path,
+        "transfer\n├── When the value is zero #zero\n│   └── It should revert.\n└── It should emit.\n",
+    )
+    .unwrap();
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["-b", "rust", "--skip-modifiers"],
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        "Test function 'test_when_the_amount_is_zero' should be named 'test_when_the_value_is_zero' to match its anchor"
+    ));
+    assert!(!stderr.contains("is missing"));
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["-b", "rust", "--skip-modifiers", "--fix"],
+    );
+    assert!(output.status.success());
+    let fixed = fs::read_to_string(&test_path).unwrap();
+    assert_eq!(
+        fixed,
+        scaffolded.replace(
+            "fn test_when_the_amount_is_zero()",
+            "fn test_when_the_value_is_zero()"
+        )
+    );
+}
+
+#[test]
+fn check_rust_reports_and_removes_unused_helpers() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("transfer.tree");
+    let test_path = dir.path().join("transfer_test.rs");
+    fs::write(
+        &tree_path,
+        "transfer\n└── When the amount is big\n    └── It should emit.\n",
+    )
+    .unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "-w"]);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+
+    // Helpers whose condition was pruned from the spec.
+    let helpers = "fn the_amount_is_zero(mut ctx: TestContext) -> TestContext {\n    ctx\n}\nfn the_amount_is_set(mut ctx: TestContext) -> TestContext {\n    ctx.amount = 1;\n    ctx\n}\n";
+    let source = scaffolded.replacen("#[cfg(test)]", &format!("{helpers}#[cfg(test)]"), 1);
+    fs::write(&test_path, &source).unwrap();
+
+    // Unused helpers are warnings, which only fail when promoted.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warning: "));
+    assert!(stderr.contains("Helper function 'the_amount_is_zero' is not used by any test"));
+    assert!(stderr.contains("Helper function 'the_amount_is_set' is not used by any test"));
+    assert!(stderr.contains("warn: 2 warnings"));
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--warnings-as-errors"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warn: 2 checks failed"));
+
+    // Only the helper that was never filled in is removed.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--fix", "--warnings-as-errors"]);
+    assert!(!output.status.success());
+    let fixed = fs::read_to_string(&test_path).unwrap();
+    assert!(!fixed.contains("fn the_amount_is_zero"));
+    assert!(fixed.contains("fn the_amount_is_set"));
+}
+
+#[test]
+fn check_rust_strict_rejects_code_the_spec_does_not_derive() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("transfer.tree");
+    let test_path = dir.path().join("transfer_test.rs");
+    fs::write(
+        &tree_path,
+        "transfer\n└── When the amount is big\n    └── It should emit.\n",
+    )
+    .unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "-w"]);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+
+    let extra = "fn setup() -> u8 {\n    1\n}\nmod fixtures {}\n";
+    let source = scaffolded.replacen("#[cfg(test)]", &format!("{extra}#[cfg(test)]"), 1);
+    fs::write(&test_path, &source).unwrap();
+
+    // Only strict mode cares about code the spec doesn't derive.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(output.status.success());
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--strict"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Function 'setup' is not derived from the spec"));
+    assert!(stderr.contains("Module 'fixtures' is not the test module"));
+}
+
+#[test]
+fn check_rust_checks_grouped_modules() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("vault.tree");
+    let test_path = dir.path().join("vault_test.rs");
+    fs::write(
+        &tree_path,
+        "vault\n├── It should have a name.\n└── When paused\n    └── It should revert.\n",
+    )
+    .unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "--group-modules", "-w"]);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+    assert!(scaffolded.contains("mod when_paused {"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--group-modules", "--strict"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // A flat test file doesn't have the modules grouped checks expect.
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "-w", "--force-write"]);
+    assert!(output.status.success());
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--group-modules"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Test function 'test_when_paused' is not in the module 'when_paused' of its condition"));
+}
+
+#[test]
+fn check_rust_checks_helper_affixes() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("vault.tree");
+    let test_path = dir.path().join("vault_test.rs");
+    fs::write(&tree_path, "vault\n└── When paused\n    └── It should revert.\n").unwrap();
+    let affixes = ["--helper-prefix", "setup_", "--helper-suffix", "_state"];
+    let args = [&["-b", "rust", "-w"][..], &affixes].concat();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+    assert!(scaffolded.contains("fn setup_paused_state(mut ctx: TestContext)"));
+    assert!(scaffolded.contains("fn test_when_paused()"));
+
+    let args = [&["-b", "rust", "--strict"][..], &affixes].concat();
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Without the affixes, checks expect the bare helper name.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Helper function 'paused' is missing"));
+}
+
+#[test]
+fn check_rust_keeps_renamed_legacy_tests() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("vault.tree");
+    let test_path = dir.path().join("vault_test.rs");
+    fs::write(&tree_path, "vault\n└── When paused\n    └── It should revert.\n").unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "-w"]);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+    fs::write(&test_path, scaffolded.replace("fn test_when_paused()", "fn paused_reverts()")).unwrap();
+
+    let rename = ["-b", "rust", "--strict", "--rename", "vault.tree:test_when_paused=paused_reverts"];
+    let output = cmd(&binary_path, "check", &tree_path, &rename);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Renames of other trees don't apply.
+    let other = ["-b", "rust", "--rename", "other.tree:test_when_paused=paused_reverts"];
+    let output = cmd(&binary_path, "check", &tree_path, &other);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("test_when_paused"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--rename", "test_when_paused"]);
+    assert!(!output.status.success());
+
+    // Renames in the config file are validated too.
+    fs::write(dir.path().join("bulloak.toml"), "rename = [\"test_when_paused\"]\n").unwrap();
+    let output = Command::new(&binary_path).args(["check", "-b", "rust"]).arg(&tree_path).current_dir(dir.path()).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("expected `tree:generated=existing`, found `test_when_paused`"), "{stderr}");
+}
+
+#[test]
+fn check_rust_checks_format_descriptions() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("vault.tree");
+    let test_path = dir.path().join("vault_test.rs");
+    fs::write(&tree_path, "vault\n└── When paused\n    ├── It should revert\n    └── It should log\n").unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-b", "rust", "--format-descriptions", "-w"]);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&test_path).unwrap();
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--format-descriptions"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let edited = scaffolded.replace("// It should revert.", "// it should revert").replace("        // It should log.\n", "");
+    fs::write(&test_path, &edited).unwrap();
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--format-descriptions"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Comment '// it should revert' of test function 'test_when_paused' should read '// It should revert.'"));
+    assert!(stderr.contains("Test function 'test_when_paused' is missing the comment '// It should log.'"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--format-descriptions", "--fix"]);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&test_path).unwrap(), scaffolded);
+}
@@ -1,16 +1,12 @@
 use std::{
-    env,
     path::PathBuf,
     process::{Command, Output},
 };
 
 pub(crate) fn get_binary_path() -> PathBuf {
-    let root = env::current_exe()
-        .unwrap()
-        .parent()
-        .expect("should be in the executable's directory")
-        .to_path_buf();
-    root.join("../bulloak")
+    // Cargo sets this for integration tests, including the `.exe` suffix on
+    // Windows.
+    PathBuf::from(env!("CARGO_BIN_EXE_bulloak"))
 }
 
 /// Runs a command with the specified args.
@@ -0,0 +1,48 @@
+#![allow(missing_docs)]
+use std::{env, fs};
+
+use common::{cmd, get_binary_path};
+
+mod common;
+
+#[test]
+fn docs_document_every_spec() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
+    let out = tempfile::tempdir().unwrap();
+    let out_dir = out.path().to_str().unwrap();
+
+    let output = cmd(&binary_path, "docs", &tree_path, &["-o", out_dir]);
+    assert!(output.status.success());
+
+    let index = fs::read_to_string(out.path().join("index.md")).unwrap();
+    let row = format!(
+        "| [CancelTest](invalid_sol_structure.md) | `{}` | 11 | ✗ 4 violations |",
+        tree_path.display()
+    );
+    assert!(index.contains(&row));
+
+    let page = out.path().join("invalid_sol_structure.md");
+    let page = fs::read_to_string(page).unwrap();
+    assert!(page.contains(
+        "│   └── it should revert (test_RevertWhen_DelegateCalled)\n"
+    ));
+    assert!(page.contains("- `test_WhenThereIsReentrancy`\n"));
+    assert!(page.contains(
+        "- incorrect position for function \
+                           `test_WhenTheSenderReverts`\n"
+    ));
+
+    let output = cmd(
+        &binary_path,
+        "docs",
+        &tree_path,
+        &["-o", out_dir, "--format", "html"],
+    );
+    assert!(output.status.success());
+    let page = out.path().join("invalid_sol_structure.html");
+    let page = fs::read_to_string(page).unwrap();
+    assert!(page.contains("<h1>CancelTest</h1>"));
+}
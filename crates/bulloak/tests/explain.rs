@@ -0,0 +1,39 @@
+#![allow(missing_docs)]
+use std::process::Command;
+
+use common::get_binary_path;
+
+mod common;
+
+#[test]
+fn explain_describes_a_rule() {
+    let binary_path = get_binary_path();
+    let explain = |args: &[&str]| {
+        Command::new(&binary_path)
+            .arg("explain")
+            .args(args)
+            .output()
+            .expect("should execute the command")
+    };
+
+    let output = explain(&["bk105"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.starts_with("# BK105: a test function is missing\n"));
+    assert!(stdout.contains("```solidity\n"));
+    assert!(stdout.contains("```rust\n"));
+    assert!(
+        stdout.contains("`bulloak check --fix` adds the missing test function")
+    );
+
+    let output = explain(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("BK105  a test function is missing\n"));
+    assert!(stdout.contains("BK501  a fix needs manual resolution\n"));
+
+    let output = explain(&["BK999"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("BK999: no such rule"));
+}
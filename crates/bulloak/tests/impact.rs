@@ -0,0 +1,123 @@
+#![allow(missing_docs)]
+use std::{fs, process::Command};
+
+use common::get_binary_path;
+
+mod common;
+
+const OLD: &str = r"Vault
+├── it should be callable.
+├── when the amount is zero #zero
+│   └── it should revert.
+└── when the caller is not the owner
+    └── it should revert.
+";
+
+const NEW: &str = r"Vault
+├── it should be callable.
+├── when the amount is null #zero
+│   └── it should revert.
+└── when the vault is paused
+    └── it should do nothing.
+";
+
+#[test]
+fn impact_reports_changed_tests() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old.tree");
+    let new = dir.path().join("new.tree");
+    fs::write(&old, OLD).unwrap();
+    fs::write(&new, NEW).unwrap();
+
+    let impact = |args: &[&str]| {
+        Command::new(&binary_path)
+            .arg("impact")
+            .arg(&old)
+            .arg(&new)
+            .args(args)
+            .output()
+            .expect("should execute the command")
+    };
+
+    let output = impact(&["--lang", "rust"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("+ test_when_the_vault_is_paused\n"));
+    assert!(stdout.contains("- test_when_the_caller_is_not_the_owner\n"));
+    assert!(stdout.contains(
+        "~ test_when_the_amount_is_zero -> test_when_the_amount_is_null\n"
+    ));
+    assert!(stdout.contains(
+        "1 added, 1 removed, 1 renamed; 0 implemented tests at risk"
+    ));
+
+    // Only implemented tests are at risk.
+    let sol = r"contract Vault {
+    function test_ShouldBeCallable() external {
+        // it should be callable.
+    }
+
+    // #zero
+    function test_RevertWhen_TheAmountIsZero() external {
+        vm.expectRevert();
+        vault.deposit(0);
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwner() external {
+        vm.skip(true);
+    }
+}";
+    fs::write(dir.path().join("new.t.sol"), sol).unwrap();
+    let output = impact(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert!(stdout.contains(
+        "~ test_RevertWhen_TheAmountIsZero -> \
+         test_RevertWhen_TheAmountIsNull (implemented, at risk)\n"
+    ));
+    assert!(stdout.contains("- test_RevertWhen_TheCallerIsNotTheOwner\n"));
+    assert!(stdout.contains("1 implemented test at risk"));
+}
+
+#[test]
+fn impact_finds_tests_with_custom_attributes() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old.tree");
+    let new = dir.path().join("new.tree");
+    fs::write(&old, OLD).unwrap();
+    fs::write(&new, NEW).unwrap();
+    let rs = r"#[cfg(test)]
+mod tests {
+    #[my_harness::test]
+    fn test_when_the_amount_is_zero() {
+        assert!(deposit(0).is_err());
+    }
+}
+";
+    fs::write(dir.path().join("new_test.rs"), rs).unwrap();
+
+    let impact = |args: &[&str]| {
+        Command::new(&binary_path)
+            .args(["impact", "--lang", "rust"])
+            .arg(&old)
+            .arg(&new)
+            .args(args)
+            .output()
+            .expect("should execute the command")
+    };
+
+    let output = impact(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("0 implemented tests at risk"));
+
+    let output = impact(&["--test-attribute", "my_harness::test"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert!(stdout.contains(
+        "~ test_when_the_amount_is_zero -> test_when_the_amount_is_null \
+         (implemented, at risk)\n"
+    ));
+}
@@ -0,0 +1,81 @@
+#![allow(missing_docs)]
+use std::{env, fs};
+
+use common::{cmd, get_binary_path};
+
+mod common;
+
+#[test]
+fn run_prints_every_combination() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("combinations.tree");
+
+    let output = cmd(&binary_path, "run", &tree_path, &["--print"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for name in [
+        "test_RevertWhen_TheCallerIsTheOwnerAndTheContractIsPaused",
+        "test_WhenTheCallerIsTheOwnerAndTheContractIsNotPaused",
+        "test_RevertWhen_TheCallerIsNotTheOwnerAndTheContractIsPaused",
+        "test_RevertWhen_TheCallerIsNotTheOwnerAndTheContractIsNotPaused",
+    ] {
+        assert!(stdout.contains(name), "{name} is not selected: {stdout}");
+    }
+
+    let output =
+        cmd(&binary_path, "run", &tree_path, &["--print", "-b", "rust"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for name in [
+        "test_when_the_caller_is_the_owner_and_the_contract_is_paused",
+        "test_when_the_caller_is_the_owner_and_the_contract_is_not_paused",
+        "test_when_the_caller_is_not_the_owner_and_the_contract_is_paused",
+        "test_when_the_caller_is_not_the_owner_and_the_contract_is_not_paused",
+    ] {
+        let selected = stdout
+            .split_whitespace()
+            .any(|word| word == name || word.ends_with(&format!("::{name}")));
+        assert!(selected, "{name} is not selected: {stdout}");
+    }
+}
+
+#[test]
+fn run_reads_markdown_specs() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let spec = dir.path().join("spec.md");
+    let text = "# Vault\n\nSome prose.\n\n```tree\nSpec\n├── when \
+                paused\n│   └── it should revert.\n└── it should \
+                work.\n```\n";
+    fs::write(&spec, text).unwrap();
+
+    let output = cmd(&binary_path, "run", &spec, &["--print"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("^(test_RevertWhen_Paused|test_ShouldWork)$"));
+}
+
+#[test]
+fn run_fails_without_specs() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let typo = cwd.join("tests").join("scaffold").join("combinatoins.tree");
+
+    let output = cmd(&binary_path, "run", &typo, &["--print"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("no specs to run"), "{stderr}");
+
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("combinations.tree");
+    let output = cmd(
+        &binary_path,
+        "run",
+        &tree_path,
+        &[typo.to_str().unwrap(), "--print", "--strict-globs"],
+    );
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -1,5 +1,9 @@
 #![allow(missing_docs)]
-use std::{env, fs};
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
 
 use common::{cmd, get_binary_path};
 use owo_colors::OwoColorize;
@@ -7,7 +11,6 @@ use pretty_assertions::assert_eq;
 
 mod common;
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn scaffolds_trees() {
     let cwd = env::current_dir().unwrap();
@@ -22,6 +25,11 @@ fn scaffolds_trees() {
         "format_descriptions.tree",
         "revert_when.tree",
         "spurious_comments.tree",
+        "ascii_branches.tree",
+        "tab_indentation.tree",
+        "pragma_directive.tree",
+        "front_matter.tree",
+        "combinations.tree",
     ];
 
     for tree_name in trees {
@@ -38,7 +46,23 @@ fn scaffolds_trees() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffold_warns_about_ignored_comments() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("spurious_comments.tree");
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    let warning = "comment outside of any tree is ignored";
+    assert!(stderr.contains(&format!("spurious_comments.tree:1:1: {warning}")));
+    assert!(stderr.contains(&format!("spurious_comments.tree:12:1: {warning}")));
+    assert_eq!(stderr.matches(warning).count(), 2);
+}
+
 #[test]
 fn scaffolds_trees_with_vm_skip() {
     let cwd = env::current_dir().unwrap();
@@ -73,7 +97,81 @@ fn scaffolds_trees_with_vm_skip() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_trees_in_order_with_jobs() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+    let trees =
+        ["basic.tree", "complex.tree", "hash_pair.tree", "revert_when.tree"];
+    let trees: Vec<_> =
+        trees.iter().map(|tree| tests_path.join(tree)).collect();
+
+    let scaffold = |jobs: &str| {
+        let output = Command::new(&binary_path)
+            .arg("scaffold")
+            .args(&trees)
+            .args(["--jobs", jobs])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let actual = scaffold("3");
+
+    assert_eq!(scaffold("1"), actual);
+    let expected = trees
+        .iter()
+        .map(|tree| fs::read_to_string(tree.with_extension("t.sol")).unwrap())
+        .collect::<Vec<_>>();
+    let mut rest = actual.as_str();
+    for expected in expected {
+        let at = rest.find(expected.trim()).unwrap();
+        rest = &rest[at + expected.trim().len()..];
+    }
+}
+
+#[test]
+fn scaffolds_trees_with_options_from_env() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+    let tree_path = tests_path.join("basic.tree");
+
+    let output = Command::new(&binary_path)
+        .arg("scaffold")
+        .arg(&tree_path)
+        .env("BULLOAK_VM_SKIP", "true")
+        .output()
+        .unwrap();
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected =
+        fs::read_to_string(tests_path.join("basic_vm_skip.t.sol")).unwrap();
+    assert_eq!(expected.trim(), actual.trim());
+
+    // Options passed on the command line take precedence.
+    let output = Command::new(&binary_path)
+        .arg("scaffold")
+        .arg(&tree_path)
+        .args(["-s", "0.8.20"])
+        .env("BULLOAK_SOLIDITY_VERSION", "0.7.6")
+        .env("BULLOAK_LANG", "solidity")
+        .output()
+        .unwrap();
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains("pragma solidity 0.8.20;"));
+
+    let output = Command::new(&binary_path)
+        .arg("scaffold")
+        .arg(&tree_path)
+        .env("BULLOAK_SKIP_MODIFIERS", "maybe")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("BULLOAK_"));
+}
+
 #[test]
 fn scaffolds_trees_with_format_descriptions() {
     let cwd = env::current_dir().unwrap();
@@ -103,7 +201,24 @@ fn scaffolds_trees_with_format_descriptions() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_trees_with_branch_summary() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+
+    let tree_path = tests_path.join("complex.tree");
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["--branch-summary"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected =
+        fs::read_to_string(tests_path.join("complex_branch_summary.t.sol"))
+            .unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
 #[test]
 fn scaffolds_trees_with_skip_modifiers() {
     let cwd = env::current_dir().unwrap();
@@ -125,7 +240,6 @@ fn scaffolds_trees_with_skip_modifiers() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn skips_trees_when_file_exists() {
     let cwd = env::current_dir().unwrap();
@@ -135,7 +249,12 @@ fn skips_trees_when_file_exists() {
 
     for tree_name in trees {
         let tree_path = tests_path.join(tree_name);
-        let output = cmd(&binary_path, "scaffold", &tree_path, &["-w"]);
+        let output = cmd(
+            &binary_path,
+            "scaffold",
+            &tree_path,
+            &["-w", "--color", "always"],
+        );
         let actual = String::from_utf8(output.stderr).unwrap();
 
         let expected = format!("{}", "warn".yellow());
@@ -175,7 +294,6 @@ fn errors_when_condition_appears_multiple_times() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn errors_when_root_contract_identifier_is_missing_multiple_roots() {
     let cwd = env::current_dir().unwrap();
@@ -224,7 +342,38 @@ fn scaffold_invalid_glob_warns_but_no_output() {
     );
 }
 
-#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffold_strict_globs_fail_on_patterns_matching_nothing() {
+    let cwd = env::current_dir().unwrap();
+    let bin = common::get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+    let typo = tests_path.join("*.treee");
+
+    let out = Command::new(&bin)
+        .arg("scaffold")
+        .arg(tests_path.join("basic.tree"))
+        .arg(&typo)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(out.status.success());
+    assert!(stderr.contains(&format!("no files match {}", typo.display())));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("contract "));
+
+    // Nothing is scaffolded when a pattern matches no files.
+    let out = Command::new(&bin)
+        .arg("scaffold")
+        .arg(tests_path.join("basic.tree"))
+        .arg(&typo)
+        .arg("--strict-globs")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!out.status.success());
+    assert!(stderr.contains(&format!("no files match {}", typo.display())));
+    assert!(out.stdout.is_empty());
+}
+
 #[test]
 fn scaffold_dissambiguates_function_name_collisions() {
     let cwd = env::current_dir().unwrap();
@@ -245,3 +394,229 @@ fn scaffold_dissambiguates_function_name_collisions() {
         assert_eq!(expected.trim(), actual.trim());
     }
 }
+
+#[test]
+fn scaffolds_crlf_trees_with_crlf_line_endings() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree = fs::read_to_string(tests_path.join("multiple_roots.tree"))
+        .unwrap()
+        .replace('\n', "\r\n");
+    let tree_path = dir.path().join("multiple_roots.tree");
+    fs::write(&tree_path, tree).unwrap();
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-w"]);
+    assert!(output.status.success());
+
+    let actual = fs::read_to_string(tree_path.with_extension("t.sol")).unwrap();
+    let expected =
+        fs::read_to_string(tests_path.join("multiple_roots.t.sol")).unwrap();
+    assert!(!actual.replace("\r\n", "").contains('\n'));
+    assert_eq!(expected.trim(), actual.replace("\r\n", "\n").trim());
+}
+
+#[test]
+fn scaffold_regenerates_marked_code_only() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let tree_path = dir.path().join("Vault.tree");
+    let sol_path = tree_path.with_extension("t.sol");
+    fs::write(&tree_path, "Vault\n└── it should work.\n").unwrap();
+
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["-w", "--markers"]);
+    assert!(output.status.success());
+    let scaffolded = fs::read_to_string(&sol_path).unwrap();
+    assert!(scaffolded.starts_with("// bulloak:begin\n"));
+    assert!(scaffolded.ends_with("}\n// bulloak:end\n"));
+
+    // Implement the test and add code of our own after the markers.
+    let helper = "\ncontract Helper {}\n";
+    let implemented =
+        scaffolded.replace("// it should work", "assertTrue(vault.works());");
+    assert_ne!(implemented, scaffolded);
+    fs::write(&sol_path, format!("{implemented}{helper}")).unwrap();
+    fs::write(
+        &tree_path,
+        "Vault\n├── it should work.\n└── when paused\n    └── it should \
+         revert.\n",
+    )
+    .unwrap();
+
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["-w", "--markers"]);
+    assert!(output.status.success());
+    let regenerated = fs::read_to_string(&sol_path).unwrap();
+    assert!(regenerated.contains("function test_RevertWhen_Paused()"));
+    assert!(regenerated.contains("assertTrue(vault.works());"));
+    assert!(!regenerated.contains("// it should work"));
+    assert!(regenerated.ends_with(&format!("// bulloak:end\n{helper}")));
+
+    // Scaffolding the same tree again changes nothing.
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["-w", "--markers"]);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&sol_path).unwrap(), regenerated);
+}
+
+#[test]
+fn scaffold_writes_no_files_if_any_write_fails() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+    let dir = tempfile::tempdir().unwrap();
+
+    for tree in ["basic.tree", "complex.tree"] {
+        fs::copy(tests_path.join(tree), dir.path().join(tree)).unwrap();
+    }
+    // A directory in place of the output makes its write fail.
+    fs::create_dir(dir.path().join("complex.t.sol")).unwrap();
+
+    let output = Command::new(&binary_path)
+        .arg("scaffold")
+        .args(["-w", "-f"])
+        .arg(dir.path().join("basic.tree"))
+        .arg(dir.path().join("complex.tree"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("no files were changed"));
+    assert!(!dir.path().join("basic.t.sol").exists());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+}
+
+#[test]
+fn scaffold_focuses_only_tagged_actions() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("focus.tree");
+    fs::write(
+        &tree_path,
+        "Focus\n├── when a\n│   └── it should x.\n└── when b\n    └── it should y. [only]\n",
+    )
+    .unwrap();
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &[]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(actual.contains("function test_WhenB()"));
+    assert!(!actual.contains("function test_WhenA()"));
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["--forbid-only"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("found an `[only]` tag"));
+}
+
+#[test]
+fn scaffolds_aliased_conditions_with_one_modifier() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("alias.tree");
+    fs::write(
+        &tree_path,
+        "// alias: given the contract is paused = when paused\nAlias\n├── when paused\n│   └── when the amount is zero\n│       └── it should revert.\n└── when the caller is the owner\n    └── given the contract is paused\n        └── when the amount is zero\n            └── it should revert.\n",
+    )
+    .unwrap();
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-w"]);
+    assert!(output.status.success());
+    let actual = fs::read_to_string(dir.path().join("alias.t.sol")).unwrap();
+    assert_eq!(actual.matches("modifier whenPaused()").count(), 1);
+    assert!(actual.contains("external whenTheCallerIsTheOwner whenPaused {"));
+    assert!(!actual.contains("GivenTheContractIsPaused"));
+    assert!(!actual.contains("alias"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+}
+
+#[test]
+fn scaffolds_trees_from_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("unsaved.tree");
+    fs::write(&tree_path, "Saved\n└── it should be stale.\n").unwrap();
+
+    let mut child = Command::new(&binary_path)
+        .args(["scaffold", "--stdin-filepath"])
+        .arg(&tree_path)
+        .args(["--header", "from {tree}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all("Unsaved\n└── when a\n    └── it should x.\n".as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(actual.contains(&format!("// from {}", tree_path.display())));
+    assert!(actual.contains("contract Unsaved {"));
+    assert!(actual.contains("function test_WhenA()"));
+    assert!(!dir.path().join("unsaved.t.sol").exists());
+}
+
+#[test]
+fn scaffolds_trees_with_imports_and_remappings() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("vault.tree");
+    fs::write(&tree_path, "Vault\n└── it should work.\n").unwrap();
+
+    let args = [
+        "-S",
+        "--import",
+        "Vault=src/Vault.sol",
+        "--import",
+        "src/Utils.sol",
+        "--remapping",
+        "forge-std/=lib/forge-std/src/",
+    ];
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(actual.contains(
+        "import {Test} from \"lib/forge-std/src/Test.sol\";\nimport {Vault} from \"src/Vault.sol\";\nimport \"src/Utils.sol\";\n"
+    ));
+    assert!(actual.contains("contract Vault is Test {"));
+
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["--remapping", "src"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn scaffolds_tag_snippets() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = dir.path().join("vault.tree");
+    fs::write(
+        &tree_path,
+        "Vault\n└── when forked [fork]\n    └── it should work.\n",
+    )
+    .unwrap();
+
+    let args = ["--snippet", "fork=vm.createSelectFork(\"mainnet\");"];
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(actual.contains("contract Vault is Test {"));
+    assert!(actual.contains(
+        "function testFork_WhenForked() external {\n        vm.createSelectFork(\"mainnet\");\n        // it should work.\n    }"
+    ));
+
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["--snippet", "fork"]);
+    assert!(!output.status.success());
+}
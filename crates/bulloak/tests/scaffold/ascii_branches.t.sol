@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract AsciiBranchesTest {
+    function test_ShouldNeverRevert() external {
+        // It should never revert.
+    }
+
+    function test_WhenFirstArgIsSmallerThanSecondArg() external {
+        // It should match the result of `keccak256(abi.encodePacked(a,b))`.
+    }
+
+    modifier whenFirstArgIsBiggerThanSecondArg() {
+        _;
+    }
+
+    function test_WhenFirstArgIsBiggerThanSecondArg() external whenFirstArgIsBiggerThanSecondArg {
+        // It should match the result of `keccak256(abi.encodePacked(b,a))`.
+    }
+
+    function test_RevertWhen_FirstArgIsZero() external whenFirstArgIsBiggerThanSecondArg {
+        // It should revert.
+    }
+}
+
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract CombinationsTest {
+    function test_RevertWhen_TheCallerIsTheOwnerAndTheContractIsPaused() external {
+        // it should revert.
+        // it should not change the owner.
+    }
+
+    function test_WhenTheCallerIsTheOwnerAndTheContractIsNotPaused() external {
+        // it should update the balance.
+        // it should not change the owner.
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwnerAndTheContractIsPaused() external {
+        // it should revert.
+        // it should not change the owner.
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwnerAndTheContractIsNotPaused() external {
+        // it should revert.
+        // it should update the balance.
+        // it should not change the owner.
+    }
+}
+
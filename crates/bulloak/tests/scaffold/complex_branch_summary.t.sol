@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract CancelTest {
+    // When delegate called
+    function test_RevertWhen_DelegateCalled() external {
+        // it should revert
+    }
+
+    modifier whenNotDelegateCalled() {
+        _;
+    }
+
+    // When not delegate called › Given the id references a null stream
+    function test_RevertGiven_TheIdReferencesANullStream() external whenNotDelegateCalled {
+        // it should revert
+    }
+
+    modifier givenTheIdDoesNotReferenceANullStream() {
+        _;
+    }
+
+    modifier givenTheStreamIsCold() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is cold › Given the streams status is DEPLETED
+    function test_RevertGiven_TheStreamsStatusIsDEPLETED()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsCold
+    {
+        // it should revert
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is cold › Given the streams status is CANCELED
+    function test_RevertGiven_TheStreamsStatusIsCANCELED()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsCold
+    {
+        // it should revert
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is cold › Given the streams status is SETTLED
+    function test_RevertGiven_TheStreamsStatusIsSETTLED()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsCold
+    {
+        // it should revert
+    }
+
+    modifier givenTheStreamIsWarm() {
+        _;
+    }
+
+    modifier whenTheCallerIsUnauthorized() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is unauthorized › When the caller is a malicious third party
+    function test_RevertWhen_TheCallerIsAMaliciousThirdParty()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsUnauthorized
+    {
+        // it should revert
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is unauthorized › When the caller is an approved third party
+    function test_RevertWhen_TheCallerIsAnApprovedThirdParty()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsUnauthorized
+    {
+        // it should revert
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is unauthorized › When the caller is a former recipient
+    function test_RevertWhen_TheCallerIsAFormerRecipient()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsUnauthorized
+    {
+        // it should revert
+    }
+
+    modifier whenTheCallerIsAuthorized() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is not cancelable
+    function test_RevertGiven_TheStreamIsNotCancelable()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+    {
+        // it should revert
+    }
+
+    modifier givenTheStreamIsCancelable() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is PENDING
+    function test_GivenTheStreamsStatusIsPENDING()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+    {
+        // it should cancel the stream
+        // it should mark the stream as depleted
+        // it should make the stream not cancelable
+    }
+
+    modifier givenTheStreamsStatusIsSTREAMING() {
+        _;
+    }
+
+    modifier whenTheCallerIsTheSender() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the sender › Given the recipient is not a contract
+    function test_GivenTheRecipientIsNotAContract()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheSender
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+    }
+
+    modifier givenTheRecipientIsAContract() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the sender › Given the recipient is a contract › Given the recipient does not implement the hook
+    function test_GivenTheRecipientDoesNotImplementTheHook()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheSender
+        givenTheRecipientIsAContract
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should call the recipient hook
+        // it should ignore the revert
+    }
+
+    modifier givenTheRecipientImplementsTheHook() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the sender › Given the recipient is a contract › Given the recipient implements the hook › When the recipient reverts
+    function test_WhenTheRecipientReverts()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheSender
+        givenTheRecipientIsAContract
+        givenTheRecipientImplementsTheHook
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should call the recipient hook
+        // it should ignore the revert
+    }
+
+    modifier whenTheRecipientDoesNotRevert() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the sender › Given the recipient is a contract › Given the recipient implements the hook › When the recipient does not revert › When there is reentrancy 1
+    function test_WhenThereIsReentrancy1()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheSender
+        givenTheRecipientIsAContract
+        givenTheRecipientImplementsTheHook
+        whenTheRecipientDoesNotRevert
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should call the recipient hook
+        // it should ignore the revert
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the sender › Given the recipient is a contract › Given the recipient implements the hook › When the recipient does not revert › When there is no reentrancy 1
+    function test_WhenThereIsNoReentrancy1()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheSender
+        givenTheRecipientIsAContract
+        givenTheRecipientImplementsTheHook
+        whenTheRecipientDoesNotRevert
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should make the stream not cancelable
+        // it should update the refunded amount
+        // it should refund the sender
+        // it should call the recipient hook
+        // it should emit a {CancelLockupStream} event
+        // it should emit a {MetadataUpdate} event
+    }
+
+    modifier whenTheCallerIsTheRecipient() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the recipient › Given the sender is not a contract
+    function test_GivenTheSenderIsNotAContract()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheRecipient
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+    }
+
+    modifier givenTheSenderIsAContract() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the recipient › Given the sender is a contract › Given the sender does not implement the hook
+    function test_GivenTheSenderDoesNotImplementTheHook()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheRecipient
+        givenTheSenderIsAContract
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should call the sender hook
+        // it should ignore the revert
+    }
+
+    modifier givenTheSenderImplementsTheHook() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the recipient › Given the sender is a contract › Given the sender implements the hook › When the sender reverts
+    function test_WhenTheSenderReverts()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheRecipient
+        givenTheSenderIsAContract
+        givenTheSenderImplementsTheHook
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should call the sender hook
+        // it should ignore the revert
+    }
+
+    modifier whenTheSenderDoesNotRevert() {
+        _;
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the recipient › Given the sender is a contract › Given the sender implements the hook › When the sender does not revert › When there is reentrancy 2
+    function test_WhenThereIsReentrancy2()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheRecipient
+        givenTheSenderIsAContract
+        givenTheSenderImplementsTheHook
+        whenTheSenderDoesNotRevert
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should call the sender hook
+        // it should ignore the revert
+    }
+
+    // When not delegate called › Given the id does not reference a null stream › Given the stream is warm › When the caller is authorized › Given the stream is cancelable › Given the streams status is STREAMING › When the caller is the recipient › Given the sender is a contract › Given the sender implements the hook › When the sender does not revert › When there is no reentrancy 2
+    function test_WhenThereIsNoReentrancy2()
+        external
+        whenNotDelegateCalled
+        givenTheIdDoesNotReferenceANullStream
+        givenTheStreamIsWarm
+        whenTheCallerIsAuthorized
+        givenTheStreamIsCancelable
+        givenTheStreamsStatusIsSTREAMING
+        whenTheCallerIsTheRecipient
+        givenTheSenderIsAContract
+        givenTheSenderImplementsTheHook
+        whenTheSenderDoesNotRevert
+    {
+        // it should cancel the stream
+        // it should mark the stream as canceled
+        // it should make the stream not cancelable
+        // it should update the refunded amount
+        // it should refund the sender
+        // it should call the sender hook
+        // it should emit a {MetadataUpdate} event
+        // it should emit a {CancelLockupStream} event
+    }
+}
+
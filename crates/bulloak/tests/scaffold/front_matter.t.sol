@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+/// @author Alice
+/// @custom:ticket https://github.com/alexfertel/bulloak/issues/1
+/// @custom:audit-scope vault
+contract VaultTest {
+    function test_ShouldNeverRevert() external {
+        // It should never revert.
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwner() external {
+        // It should revert.
+    }
+}
+
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.7.6;
+
+contract LegacyTest {
+    function test_ShouldNeverRevert() external {
+        // It should never revert.
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwner() external {
+        // It should revert.
+    }
+}
+
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract TabIndentationTest {
+    function test_ShouldNeverRevert() external {
+        // It should never revert.
+    }
+
+    modifier whenFirstArgIsSmallerThanSecondArg() {
+        _;
+    }
+
+    function test_WhenFirstArgIsSmallerThanSecondArg() external whenFirstArgIsSmallerThanSecondArg {
+        // It should match the result of `keccak256(abi.encodePacked(a,b))`.
+    }
+
+    function test_RevertWhen_FirstArgIsZero() external whenFirstArgIsSmallerThanSecondArg {
+        // It should revert.
+    }
+}
+
@@ -6,7 +6,6 @@ use pretty_assertions::assert_eq;
 
 mod common;
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn scaffolds_rust_trees() {
     let cwd = env::current_dir().unwrap();
@@ -49,7 +48,6 @@ fn scaffolds_rust_trees() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn scaffolds_rust_trees_skip_helpers() {
     let cwd = env::current_dir().unwrap();
@@ -71,7 +69,6 @@ fn scaffolds_rust_trees_skip_helpers() {
     assert!(actual.contains("struct TestContext"));
 }
 
-#[cfg(not(target_os = "windows"))]
 #[test]
 fn scaffolds_rust_trees_format_descriptions() {
     let cwd = env::current_dir().unwrap();
@@ -92,3 +89,183 @@ fn scaffolds_rust_trees_format_descriptions() {
     assert!(actual.contains("// It should match the result of hash(a, b)."));
     assert!(actual.contains("// It should match the result of hash(b, a)."));
 }
+
+#[test]
+fn scaffolds_rust_trees_keep_condition_prefix() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("basic.tree");
+    fs::copy(tests_path.join("basic.tree"), &tree_path).unwrap();
+    let output = cmd(
+        &binary_path,
+        "scaffold",
+        &tree_path,
+        &["--backend", "rust", "--keep-condition-prefix", "-w"],
+    );
+    assert!(output.status.success());
+
+    let actual = fs::read_to_string(dir.path().join("basic_test.rs")).unwrap();
+    assert!(actual.contains("fn when_first_arg_is_smaller_than_second_arg("));
+    assert!(actual.contains("fn test_when_first_arg_is_smaller_than_second_arg("));
+    assert!(!actual.contains("fn first_arg_is_smaller_than_second_arg("));
+
+    // The check must be run with the same flag to match the emitted names.
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["-b", "rust", "--keep-condition-prefix"],
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn scaffolds_rust_trees_split_actions() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("vault.tree");
+    fs::write(
+        &tree_path,
+        "Vault\n└── when paused\n    ├── it should revert.\n    └── it should emit an event.\n",
+    )
+    .unwrap();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["--backend", "rust", "--split-actions", "-w"]);
+    assert!(output.status.success());
+
+    let actual = fs::read_to_string(dir.path().join("vault_test.rs")).unwrap();
+    assert!(actual.contains("fn test_when_paused_should_revert("));
+    assert!(actual.contains("fn test_when_paused_should_emit_an_event("));
+    assert!(!actual.contains("fn test_when_paused("));
+
+    // The choice is symmetric: the check must be run with the same flag.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust", "--split-actions"]);
+    assert!(output.status.success());
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn scaffolds_rust_trees_with_extra_keywords() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+
+    let tree_path = dir.path().join("pago.tree");
+    fs::write(
+        &tree_path,
+        "Pago\n├── debería funcionar siempre.\n└── cuando el monto es cero\n    └── debería revertir.\n",
+    )
+    .unwrap();
+    let keywords = ["--condition-keyword", "cuando", "--action-keyword", "debería"];
+
+    let mut args = vec!["--backend", "rust", "-w"];
+    args.extend(keywords);
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    assert!(output.status.success());
+
+    let actual = fs::read_to_string(dir.path().join("pago_test.rs")).unwrap();
+    assert!(actual.contains("fn el_monto_es_cero("));
+    assert!(actual.contains("fn test_funcionar_siempre("));
+    assert!(actual.contains("fn test_when_el_monto_es_cero("));
+
+    let mut args = vec!["-b", "rust"];
+    args.extend(keywords);
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    assert!(output.status.success());
+
+    // Without the keywords, the tree doesn't parse.
+    let output = cmd(&binary_path, "check", &tree_path, &["-b", "rust"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn scaffolds_rust_trees_with_custom_header() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let tree_path = tests_path.join("basic.tree");
+    let expected = fs::read_to_string(tests_path.join("basic_test.rs")).unwrap();
+
+    let output = cmd(
+        &binary_path,
+        "scaffold",
+        &tree_path,
+        &["--backend", "rust", "--header", "Generated from {tree}.\nDo not edit."],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let header = format!(
+        "// Generated from {}.\n// Do not edit.\n\n",
+        tree_path.display()
+    );
+    assert!(actual.starts_with(&header));
+    assert_eq!(
+        expected.trim().strip_prefix("// Generated by bulloak\n\n").unwrap(),
+        actual.trim().strip_prefix(&header).unwrap()
+    );
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["--backend", "rust", "--no-header"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        expected.trim().strip_prefix("// Generated by bulloak\n\n").unwrap(),
+        actual.trim()
+    );
+}
+
+#[test]
+fn scaffolds_rust_trees_with_backend_from_env() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold_rust");
+    let tree_path = tests_path.join("basic.tree");
+
+    let output = std::process::Command::new(&binary_path)
+        .arg("scaffold")
+        .arg(&tree_path)
+        .env("BULLOAK_LANG", "rust")
+        .output()
+        .unwrap();
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = fs::read_to_string(tests_path.join("basic_test.rs")).unwrap();
+
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn scaffolds_rust_trees_wired_into_the_crate() {
+    let binary_path = get_binary_path();
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"vault\"\n").unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("tests").join("specs")).unwrap();
+    fs::write(root.join("src").join("lib.rs"), "//! Vault.\n\nmod math;\n").unwrap();
+    fs::write(root.join("src").join("math.rs"), "").unwrap();
+    let tree = "Vault\n└── it should work.\n";
+    let unit = root.join("src").join("vault.tree");
+    let integration = root.join("tests").join("specs").join("vault.tree");
+    fs::write(&unit, tree).unwrap();
+    fs::write(&integration, tree).unwrap();
+
+    for tree_path in [&unit, &integration] {
+        let output = cmd(&binary_path, "scaffold", tree_path, &["-b", "rust", "-w", "--wire-modules"]);
+        assert!(output.status.success());
+    }
+
+    assert_eq!(
+        fs::read_to_string(root.join("src").join("lib.rs")).unwrap(),
+        "//! Vault.\n\nmod math;\n#[cfg(test)]\nmod vault_test;\n"
+    );
+    let aggregator = fs::read_to_string(root.join("tests").join("bulloak_generated").join("main.rs")).unwrap();
+    assert!(aggregator.ends_with("\n\n#[path = \"../specs/vault_test.rs\"]\nmod specs_vault_test;\n"));
+
+    // Wiring is idempotent.
+    let output = cmd(&binary_path, "scaffold", &unit, &["-b", "rust", "-w", "-f", "--wire-modules"]);
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(root.join("src").join("lib.rs")).unwrap(),
+        "//! Vault.\n\nmod math;\n#[cfg(test)]\nmod vault_test;\n"
+    );
+}
@@ -100,13 +100,5 @@ mod tests {
         // it should validate successfully
         // it should return config data
     }
-    #[test]
-    fn test_when_user_is_authorized() {
-        let _ctx = user_is_authorized(
-            permissions_are_set(
-                version_is_current(config_is_not_null(TestContext::default())),
-            ),
-        );
-    }
 }
 
@@ -0,0 +1,219 @@
+//! Detects fixes that would overwrite hand-written code.
+//!
+//! Before `bulloak check --fix` edits a Solidity file, its fixable violations
+//! are screened for edits that would clobber code written or changed by hand,
+//! e.g. a generated modifier that was renamed and given a body. If any are
+//! found, the file is left untouched and each conflict is reported as a
+//! [`ViolationKind::ManualResolutionRequired`] violation instead.
+
+use forge_fmt::solang_ext::CodeLocationExt;
+use solang_parser::pt::{
+    ContractPart, FunctionDefinition, FunctionTy, SourceUnitPart,
+};
+
+use super::{
+    context::Context,
+    location::Location,
+    utils::offset_to_line,
+    violation::{Violation, ViolationKind},
+};
+use crate::hir::Hir;
+
+/// Returns the conflicts that fixing `violations` in `ctx` would run into.
+///
+/// An empty result means every fix can be applied safely.
+#[must_use]
+pub fn find_conflicts(
+    ctx: &Context,
+    violations: &[Violation],
+) -> Vec<Violation> {
+    let mut conflicts = Vec::new();
+    let sol = ctx.sol.to_string_lossy().into_owned();
+    let contract_sol = ctx.find_contract();
+    let declared = |name: &str| {
+        contract_sol.as_ref().and_then(|contract| {
+            sol_functions(&contract.parts).find(|fn_sol| {
+                fn_sol.name.as_ref().is_some_and(|id| id.name == name)
+            })
+        })
+    };
+
+    for violation in violations {
+        match &violation.kind {
+            // Without a targeted contract to append to, the fix replaces the
+            // whole file with a fresh scaffold.
+            ViolationKind::ContractMissing(_) if replaces_code(ctx) => {
+                conflicts.push(Violation::new(
+                    ViolationKind::ManualResolutionRequired(
+                        "the file has no test contract, and scaffolding one \
+                         would replace the code already in it"
+                            .to_owned(),
+                    ),
+                    Location::File(sol.clone()),
+                ));
+            }
+            // Fixes look functions up by name, so they could edit either
+            // definition.
+            ViolationKind::FunctionDuplicated(name) => {
+                conflicts.push(Violation::new(
+                    ViolationKind::ManualResolutionRequired(format!(
+                        r#"function "{name}" is defined more than once, so fixing the file could edit the wrong definition"#
+                    )),
+                    violation.location.clone(),
+                ));
+            }
+            ViolationKind::FunctionNameMismatch(fn_hir, old_name) => {
+                if let Some(fn_sol) = declared(&fn_hir.identifier) {
+                    conflicts.push(Violation::new(
+                        ViolationKind::ManualResolutionRequired(format!(
+                            r#"renaming "{old_name}" to "{}" would clash with the existing "{}""#,
+                            fn_hir.identifier, fn_hir.identifier
+                        )),
+                        Location::Code(
+                            sol.clone(),
+                            offset_to_line(&ctx.src, fn_sol.loc.start()),
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // A modifier that isn't in the tree but has a body of its own is most
+    // likely a generated one that was renamed, so adding the modifiers the
+    // tree expects would duplicate it.
+    let missing_modifier = violations.iter().any(|v| {
+        matches!(&v.kind, ViolationKind::MatchingFunctionMissing(fn_hir, _) if fn_hir.is_modifier())
+    });
+    if let (true, Some(contract_sol), Some(contract_hir)) =
+        (missing_modifier, &contract_sol, ctx.hir.find_contract())
+    {
+        let in_tree = |name: &str| {
+            contract_hir.children.iter().any(|child| {
+                matches!(child, Hir::Function(f) if f.is_modifier() && f.identifier == name)
+            })
+        };
+        for fn_sol in sol_functions(&contract_sol.parts) {
+            let Some(name) = fn_sol.name.as_ref().map(|id| id.name.as_str())
+            else {
+                continue;
+            };
+            if fn_sol.ty == FunctionTy::Modifier
+                && !in_tree(name)
+                && has_custom_body(fn_sol, &ctx.src)
+            {
+                conflicts.push(Violation::new(
+                    ViolationKind::ManualResolutionRequired(format!(
+                        r#"modifier "{name}" is not in the tree but has a hand-written body, so it may be a renamed modifier the fix would duplicate"#
+                    )),
+                    Location::Code(
+                        sol.clone(),
+                        offset_to_line(&ctx.src, fn_sol.loc.start()),
+                    ),
+                ));
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Whether scaffolding the missing contract would replace code in the file.
+fn replaces_code(ctx: &Context) -> bool {
+    // A targeted contract is appended to the file instead.
+    if ctx.cfg.contract.is_some()
+        && crate::sol::find_contract(&ctx.pt).is_some()
+    {
+        return false;
+    }
+
+    ctx.pt
+        .0
+        .iter()
+        .any(|part| !matches!(part, SourceUnitPart::PragmaDirective(..)))
+}
+
+/// Whether the body of `fn_sol` does more than the generated `_;`.
+fn has_custom_body(fn_sol: &FunctionDefinition, src: &str) -> bool {
+    fn_sol.body.as_ref().is_some_and(|body| {
+        let loc = body.loc();
+        let text: String =
+            src[loc.start()..loc.end()].split_whitespace().collect();
+        text != "{_;}"
+    })
+}
+
+/// Iterates over the function definitions in `parts`.
+fn sol_functions(
+    parts: &[ContractPart],
+) -> impl Iterator<Item = &FunctionDefinition> {
+    parts.iter().filter_map(|part| match part {
+        ContractPart::FunctionDefinition(fn_sol) => Some(&**fn_sol),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use tempfile::tempdir;
+
+    use super::find_conflicts;
+    use crate::{
+        check::{
+            context::Context,
+            rules::{Checker, StructuralMatcher},
+            violation::ViolationKind,
+        },
+        config::Config,
+    };
+
+    fn conflicts(tree: &str, sol: &str) -> Vec<ViolationKind> {
+        let dir = tempdir().unwrap();
+        let tree_path: PathBuf = dir.path().join("foo.tree");
+        fs::write(&tree_path, tree).unwrap();
+        fs::write(dir.path().join("foo.t.sol"), sol).unwrap();
+
+        let ctx = Context::new(tree_path, &Config::default()).unwrap();
+        let violations = StructuralMatcher::check(&ctx);
+        find_conflicts(&ctx, &violations)
+            .into_iter()
+            .map(|violation| violation.kind)
+            .collect()
+    }
+
+    #[test]
+    fn renamed_modifier_with_body_conflicts() {
+        let tree = "Foo\n└── when x\n    └── when y\n        └── it z.\n";
+        let sol = "contract Foo {\n  modifier whenXIsSet() {\n    x = 1;\n    _;\n  }\n\n  function test_WhenY() external whenXIsSet {}\n}\n";
+        let found = conflicts(tree, sol);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].to_string().contains(r#"modifier "whenXIsSet""#));
+    }
+
+    #[test]
+    fn empty_extra_modifier_does_not_conflict() {
+        let tree = "Foo\n└── when x\n    └── when y\n        └── it z.\n";
+        let sol = "contract Foo {\n  modifier whenOther() {\n    _;\n  }\n\n  function test_WhenY() external {}\n}\n";
+        assert!(conflicts(tree, sol).is_empty());
+    }
+
+    #[test]
+    fn duplicated_function_conflicts() {
+        let tree = "Foo\n└── it y.\n";
+        let sol = "contract Foo {\n  function test_Y() external {}\n\n  function test_Y() external {}\n}\n";
+        let found = conflicts(tree, sol);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].to_string().contains("defined more than once"));
+    }
+
+    #[test]
+    fn missing_contract_over_code_conflicts() {
+        let tree = "Foo\n└── it y.\n";
+        let sol = "pragma solidity 0.8.0;\n\nfunction helper() pure returns (uint256) {\n  return 1;\n}\n";
+        assert_eq!(conflicts(tree, sol).len(), 1);
+        assert!(conflicts(tree, "pragma solidity 0.8.0;\n").is_empty());
+    }
+}
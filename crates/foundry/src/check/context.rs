@@ -7,6 +7,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use bulloak_syntax::{markdown, utils::sanitize};
 use forge_fmt::{
     format, parse,
     solang_ext::{CodeLocationExt, SafeUnwrap},
@@ -16,11 +17,15 @@ use solang_parser::pt::{self, ContractDefinition, ContractPart, SourceUnit};
 
 use super::{location::Location, violation::ViolationKind};
 use crate::{
-    check::{pretty::Pretty, violation::Violation},
+    check::{
+        pretty::Pretty,
+        rules::description_policy::{descriptions, line_comments},
+        violation::Violation,
+    },
     config::Config,
     hir::{self, Hir},
-    scaffold::emitter::Emitter,
-    sol::{self, find_contract, find_matching_fn},
+    scaffold::{emitter::Emitter, insert_header},
+    sol::{self, find_anchored_fn, find_contract, find_matching_fn},
 };
 
 /// The context in which rule-checking happens.
@@ -55,14 +60,30 @@ impl Context {
     /// This structure contains everything necessary to perform checks between
     /// trees and Solidity files.
     pub fn new(tree: PathBuf, cfg: &Config) -> Result<Self, Violation> {
+        let cfg = cfg.for_tree(&tree);
         let tree_path_cow = tree.to_string_lossy();
-        let tree_contents = try_read_to_string(&tree)?;
-        let hir = crate::hir::translate(&tree_contents, cfg).map_err(|e| {
+        // The spec may also be a Markdown document holding trees.
+        let tree_contents = markdown::read_spec(&tree).map_err(|_| {
             Violation::new(
-                ViolationKind::ParsingFailed(e),
-                Location::File(tree_path_cow.into_owned()),
+                ViolationKind::FileUnreadable,
+                Location::File(tree_path_cow.clone().into_owned()),
             )
         })?;
+        let cfg = cfg.for_spec(&tree_contents);
+        let mut hir =
+            crate::hir::translate(&tree_contents, &cfg).map_err(|e| {
+                let e = markdown::explain(&tree, &cfg.parse_options(), e);
+                // Point at the block that failed in a Markdown document.
+                let file = tree_path_cow.into_owned();
+                let location = match e.downcast_ref::<markdown::BlockError>() {
+                    Some(block) => Location::Code(file, block.fence),
+                    None => Location::File(file),
+                };
+                Violation::new(ViolationKind::ParsingFailed(e), location)
+            })?;
+        if !cfg.include_fork {
+            remove_fork_tests(&mut hir);
+        }
 
         let sol = get_path_with_ext(&tree, "t.sol")?;
         let src = try_read_to_string(&sol)?;
@@ -78,7 +99,27 @@ impl Context {
 
         let pt = parsed.pt.clone();
         let comments = parsed.comments;
-        Ok(Context { tree, hir, sol, src, pt, comments, cfg: cfg.clone() })
+        Ok(Context { tree, hir, sol, src, pt, comments, cfg })
+    }
+
+    /// Returns the Solidity contract that is checked against the tree.
+    ///
+    /// This is the contract named in the config, if any. Otherwise, it is the
+    /// contract named after the tree's root, falling back to the first
+    /// contract in the file.
+    #[must_use]
+    pub fn find_contract(&self) -> Option<Box<ContractDefinition>> {
+        if let Some(name) = &self.cfg.contract {
+            return sol::find_contract_by_name(&self.pt, name);
+        }
+
+        self.hir
+            .find_contract()
+            .and_then(|contract_hir| {
+                let name = sanitize(&contract_hir.identifier);
+                sol::find_contract_by_name(&self.pt, &name)
+            })
+            .or_else(|| find_contract(&self.pt))
     }
 
     /// Updates this `Context` with the result of parsing a Solidity file.
@@ -138,6 +179,19 @@ impl Context {
     }
 }
 
+/// Removes the `testFork_` tests from `hir`, so that checks don't expect
+/// them.
+fn remove_fork_tests(hir: &mut Hir) {
+    let Hir::Root(root) = hir else { return };
+    for child in &mut root.children {
+        if let Hir::Contract(contract) = child {
+            contract
+                .children
+                .retain(|child| !matches!(child, Hir::Function(f) if f.fork));
+        }
+    }
+}
+
 fn get_path_with_ext(
     path: impl AsRef<Path>,
     ext: impl AsRef<OsStr>,
@@ -170,7 +224,26 @@ fn try_read_to_string(path: impl AsRef<Path>) -> Result<String, Violation> {
 impl Context {
     pub(crate) fn fix_contract_missing(self) -> anyhow::Result<Context> {
         let pt = sol::Translator::new(&self.cfg).translate(&self.hir);
-        let source = sol::Formatter::new().emit(pt.clone());
+        let emitted = sol::Formatter::new().emit(pt.clone());
+        let source = match (&self.cfg.contract, self.hir.find_contract()) {
+            // A contract was targeted by name but the file only has other
+            // contracts, so keep them and append the missing one.
+            (Some(name), Some(contract_hir))
+                if find_contract(&self.pt).is_some() =>
+            {
+                let start = emitted.find("contract ").unwrap_or_default();
+                let contract = emitted[start..].replacen(
+                    &format!("contract {}", sanitize(&contract_hir.identifier)),
+                    &format!("contract {name}"),
+                    1,
+                );
+                format!("{}\n\n{}", self.src.trim_end(), contract)
+            }
+            _ => match self.cfg.header() {
+                Some(header) => keep_hand_written(&self.src, &emitted, &header),
+                None => emitted,
+            },
+        };
         let filename = self.sol.to_string_lossy();
         let parsed = parse(&source).map_err(|diagnostics| {
             let full = diagnostics
@@ -204,6 +277,28 @@ impl Context {
         Ok(self.update_from_parsed(parsed))
     }
 
+    pub(crate) fn fix_fn_rename(
+        self,
+        new_name: &str,
+        old_name: &str,
+    ) -> anyhow::Result<Context> {
+        let source = self.src.replacen(
+            &format!("function {old_name}("),
+            &format!("function {new_name}("),
+            1,
+        );
+        let filename = self.sol.to_string_lossy();
+        let parsed = parse(&source).map_err(|diagnostics| {
+            let full = diagnostics
+                .into_iter()
+                .map(|d| Pretty::new(&d, &filename, &source).to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow::anyhow!(full)
+        })?;
+        Ok(self.update_from_parsed(parsed))
+    }
+
     pub(crate) fn fix_matching_fn_missing(
         mut self,
         fn_hir: &hir::FunctionDefinition,
@@ -213,7 +308,7 @@ impl Context {
             Some(c) => c,
             None => return Ok(self),
         };
-        let contract_sol = match find_contract(&self.pt) {
+        let contract_sol = match self.find_contract() {
             Some(c) => c,
             None => return Ok(self),
         };
@@ -234,11 +329,105 @@ impl Context {
         })?;
         Ok(self.update_from_parsed(parsed))
     }
+
+    /// Rewrites the description comment `found` of the test `name` to
+    /// `expected`, or adds `expected` to the test if it has no such comment.
+    ///
+    /// A missing comment goes right after the last description that precedes
+    /// it in the spec, or at the top of the test's body.
+    pub(crate) fn fix_description(
+        self,
+        name: &str,
+        found: Option<&str>,
+        expected: &str,
+    ) -> anyhow::Result<Context> {
+        let body = self.find_contract().and_then(|contract_sol| {
+            contract_sol.parts.iter().find_map(|part| match part {
+                ContractPart::FunctionDefinition(fn_sol)
+                    if fn_sol
+                        .name
+                        .as_ref()
+                        .is_some_and(|id| id.name == name) =>
+                {
+                    fn_sol.body.as_ref().map(CodeLocationExt::loc)
+                }
+                _ => None,
+            })
+        });
+        let Some(body) = body else {
+            return Ok(self);
+        };
+        let start = body.start();
+        let comments = line_comments(&self.src[start..body.end()]);
+        let comment = format!("// {expected}");
+        let source = if let Some(found) = found {
+            let Some((range, _)) =
+                comments.iter().find(|(_, text)| text == found)
+            else {
+                return Ok(self);
+            };
+            format!(
+                "{}{comment}{}",
+                &self.src[..start + range.start],
+                &self.src[start + range.end..]
+            )
+        } else {
+            let preceding: Vec<String> = self
+                .hir
+                .find_contract()
+                .and_then(|contract_hir| {
+                    contract_hir.children.iter().find_map(|child| match child {
+                        Hir::Function(fn_hir) if fn_hir.identifier == name => {
+                            Some(descriptions(fn_hir))
+                        }
+                        _ => None,
+                    })
+                })
+                .map(|descriptions| {
+                    descriptions
+                        .into_iter()
+                        .take_while(|description| description != expected)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let offset = comments
+                .iter()
+                .rev()
+                .find(|(_, text)| preceding.contains(text))
+                .map_or(start + 1, |(range, _)| start + range.end);
+            let line_start = self.src[..start].rfind('\n').map_or(0, |i| i + 1);
+            let indentation: String = self.src[line_start..]
+                .chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect();
+            format!(
+                "{}\n{indentation}    {comment}{}",
+                &self.src[..offset],
+                &self.src[offset..]
+            )
+        };
+
+        let filename = self.sol.to_string_lossy();
+        let parsed = parse(&source).map_err(|diagnostics| {
+            let full = diagnostics
+                .into_iter()
+                .map(|d| Pretty::new(&d, &filename, &source).to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow::anyhow!(full)
+        })?;
+        Ok(self.update_from_parsed(parsed))
+    }
 }
 
 /// Calculates the insertion offset for a new function in a contract's source
 /// code.
 ///
+/// The function goes right after the nearest function that precedes it in
+/// the HIR and is present in the source, or at the start of the contract's
+/// body if there is none. Functions that precede it but are missing too, e.g.
+/// when fixing a single violation of several, are skipped.
+///
 /// # Arguments
 /// * `contract_sol` - Solidity parse tree contract definition
 /// * `contract_hir` - HIR contract definition
@@ -249,28 +438,30 @@ impl Context {
 /// Offset position for function insertion
 ///
 /// # Panics
-/// If the contract's opening brace cannot be located when processing the first
-/// function
+/// If the contract's opening brace cannot be located when no preceding
+/// function is present
 fn get_insertion_offset(
     contract_sol: &pt::ContractDefinition,
     contract_hir: &hir::ContractDefinition,
     index: usize,
     src: impl AsRef<str>,
 ) -> usize {
-    if index == 0 {
-        return find_contract_body_start(contract_sol, src.as_ref());
-    }
-
-    match &contract_hir.children[index - 1] {
-        Hir::Function(prev_fn_hir) => {
-            find_matching_fn(contract_sol, prev_fn_hir)
-                .expect("matching function should exist")
-                .1
-                .loc()
-                .end()
-        }
-        _ => unreachable!("previous child should be a function definition"),
-    }
+    let src = src.as_ref();
+    contract_hir.children[..index.min(contract_hir.children.len())]
+        .iter()
+        .rev()
+        .filter_map(|child| match child {
+            Hir::Function(fn_hir) => Some(fn_hir),
+            _ => None,
+        })
+        .find_map(|fn_hir| {
+            find_matching_fn(contract_sol, fn_hir)
+                .or_else(|| find_anchored_fn(contract_sol, fn_hir, src))
+        })
+        .map_or_else(
+            || find_contract_body_start(contract_sol, src),
+            |(_, fn_sol)| fn_sol.loc().end(),
+        )
 }
 
 /// Finds the starting position of a contract's body in the source code.
@@ -424,6 +615,30 @@ pub fn fix_order(
     ctx.update_from_parsed(parsed)
 }
 
+/// Returns `emitted` with `header` below its license, keeping the code above
+/// `header` in `src`, if any.
+///
+/// The header starts the code `bulloak` generates, so anything above it is
+/// hand-written.
+fn keep_hand_written(src: &str, emitted: &str, header: &str) -> String {
+    let generated = insert_header(emitted, header);
+    match (find_line(src, header), find_line(&generated, header)) {
+        (Some(old), Some(new)) => {
+            format!("{}{}", &src[..old], &generated[new..])
+        }
+        _ => generated,
+    }
+}
+
+/// Returns the offset of the first occurrence of `text` that starts a line
+/// of `source`.
+fn find_line(source: &str, text: &str) -> Option<usize> {
+    source
+        .match_indices(text)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || source[..i].ends_with('\n'))
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, io::Write};
@@ -484,6 +699,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fix_matching_fn_missing_skips_missing_predecessors() {
+        let tree = "Foo\n├── It one.\n├── It two.\n└── It three.\n";
+        let sol = "\
+            // SPDX-License-Identifier: UNLICENSED\n\
+            pragma solidity 0.8.0;\n\
+            contract Foo {}\n";
+        let ctx0 = make_ctx(tree, sol);
+        let vs = StructuralMatcher::check(&ctx0);
+        assert_eq!(3, vs.len());
+
+        // Each fix is applied on its own, as for suggestions, so the tests
+        // before the last one are still missing.
+        for (v, name) in vs.into_iter().zip(["One", "Two", "Three"]) {
+            let ctx1 = v.kind.fix(ctx0.clone()).unwrap();
+            assert!(ctx1.src.contains(&format!("function test_{name}()")));
+        }
+    }
+
     #[test]
     fn fix_contract_missing_scaffolds_tests() {
         let tree = "Foo\n└── It one.\n";
@@ -499,6 +733,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fix_contract_missing_keeps_code_above_the_header() {
+        let tree = "Foo\n└── It one.\n";
+        let sol = "\
+            // SPDX-License-Identifier: MIT\n\
+            // Audited by alice.\n\
+            // Generated by bulloak\n\
+            pragma solidity 0.8.0;\n\
+            contract Bar {}\n";
+        let mut ctx = make_ctx(tree, sol);
+        ctx.cfg.header = Some("Generated by bulloak".to_owned());
+        let ctx = ctx.fix_contract_missing().unwrap();
+        assert!(
+            ctx.src.starts_with(
+                "// SPDX-License-Identifier: MIT\n// Audited by alice.\n// \
+                 Generated by bulloak\npragma solidity 0.8.0;"
+            ),
+            "{}",
+            ctx.src
+        );
+        assert!(ctx.src.contains("function test_One()"));
+        assert!(!ctx.src.contains("contract Bar"));
+    }
+
     #[test]
     fn fix_order_reorders_functions() {
         let tree = "\
@@ -622,4 +880,20 @@ mod tests {
         assert!(s.contains("contract \"Foo\" is missing in .sol"));
         assert!(s.contains("consider adding a contract with name \"Foo\""));
     }
+
+    #[test]
+    fn violation_display_with_suggestion() {
+        let tree = "Foo\n└── It one.\n";
+        let sol = "\
+            // SPDX-License-Identifier: UNLICENSED\n\
+            pragma solidity 0.8.0;\n\
+            contract Foo {}\n";
+        let ctx = make_ctx(tree, sol);
+        let violation = StructuralMatcher::check(&ctx).pop().unwrap();
+
+        let s = format!("{violation}");
+        assert!(s.contains(r#"help: consider adding the function "test_One""#));
+        assert!(s.contains("     | function test_One() external {\n"));
+        assert!(s.contains("     |   // It one.\n"));
+    }
 }
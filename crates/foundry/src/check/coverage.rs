@@ -0,0 +1,150 @@
+//! Measures how many leaves of a spec have a test.
+
+use std::path::Path;
+
+use bulloak_syntax::{markdown::read_spec, Ast, Span};
+
+use super::violation::{Violation, ViolationKind};
+use crate::config::Config;
+
+/// Counts the actions in the tree at `tree` and how many of them have a test,
+/// given the `violations` found when checking it.
+///
+/// An action has a test when the function scaffolded for its branch is in
+/// the Solidity file, whether under its own name or under the one its anchor
+/// points to. When the Solidity file couldn't be checked at all, no action
+/// has a test.
+///
+/// Returns `(covered, total)`, which is `(0, 0)` if the tree can't be read.
+#[must_use]
+pub fn coverage(
+    tree: &Path,
+    violations: &[Violation],
+    cfg: &Config,
+) -> (usize, usize) {
+    let cfg = cfg.for_tree(tree);
+    let Some(asts) = read_spec(tree).ok().and_then(|text| {
+        bulloak_syntax::cache::parse_with(&text, &cfg.parse_options()).ok()
+    }) else {
+        return (0, 0);
+    };
+
+    let mut tests = Vec::new();
+    for ast in &asts {
+        if let Ast::Root(root) = ast {
+            collect_tests(&root.children, true, &mut tests);
+        }
+    }
+    let total = tests.iter().map(|(_, actions)| actions).sum();
+
+    let unchecked = violations.iter().any(|violation| {
+        matches!(
+            violation.kind,
+            ViolationKind::ContractMissing(_)
+                | ViolationKind::SolidityFileMissing(_)
+                | ViolationKind::FileUnreadable
+                | ViolationKind::ParsingFailed(_)
+        )
+    });
+    if unchecked {
+        return (0, total);
+    }
+
+    let mut missing = 0;
+    for violation in violations {
+        if let ViolationKind::MatchingFunctionMissing(fn_hir, _) =
+            &violation.kind
+        {
+            let test = tests.iter().position(|(span, _)| *span == fn_hir.span);
+            if let Some(i) = test.filter(|_| fn_hir.is_function()) {
+                missing += tests.swap_remove(i).1;
+            }
+        }
+    }
+
+    (total - missing, total)
+}
+
+/// Collects the span of every branch in `children` that gets a test, along
+/// with the number of actions the test covers.
+///
+/// Actions only get a test of their own at the top level of a tree.
+fn collect_tests(
+    children: &[Ast],
+    top_level: bool,
+    tests: &mut Vec<(Span, usize)>,
+) {
+    for child in children {
+        match child {
+            Ast::Condition(condition) => {
+                let actions = condition
+                    .children
+                    .iter()
+                    .filter(|child| Ast::is_action(child))
+                    .count();
+                if actions > 0 {
+                    tests.push((condition.span, actions));
+                }
+                collect_tests(&condition.children, false, tests);
+            }
+            Ast::Action(action) if top_level => {
+                tests.push((action.span, 1));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::coverage;
+    use crate::{
+        check::{
+            context::Context,
+            rules::{Checker as _, StructuralMatcher},
+        },
+        config::Config,
+    };
+
+    fn measure(tree: &str, sol: Option<&str>) -> (usize, usize) {
+        let dir = tempfile::tempdir().unwrap();
+        let tree_path = dir.path().join("foo.tree");
+        fs::write(&tree_path, tree).unwrap();
+        if let Some(sol) = sol {
+            fs::write(dir.path().join("foo.t.sol"), sol).unwrap();
+        }
+
+        let cfg = Config::default();
+        let violations = match Context::new(PathBuf::from(&tree_path), &cfg) {
+            Ok(ctx) => StructuralMatcher::check(&ctx),
+            Err(violation) => vec![violation],
+        };
+        coverage(&tree_path, &violations, &cfg)
+    }
+
+    #[test]
+    fn counts_actions_with_tests() {
+        let tree = "Foo
+├── It should never revert.
+└── When bar
+    ├── It should baz.
+    └── It should qux.
+";
+        assert_eq!(measure(tree, None), (0, 3));
+
+        let sol = "contract Foo {
+    function test_ShouldNeverRevert() external {}
+}
+";
+        assert_eq!(measure(tree, Some(sol)), (1, 3));
+
+        let sol = "contract Foo {
+    function test_ShouldNeverRevert() external {}
+    function test_WhenBar() external {}
+}
+";
+        assert_eq!(measure(tree, Some(sol)), (3, 3));
+    }
+}
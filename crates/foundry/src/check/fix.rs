@@ -0,0 +1,163 @@
+//! Fixes every fixable violation of a Solidity test file.
+//!
+//! This is what `bulloak check --fix` does to each file, exposed so that
+//! other tools can fix test files the same way.
+
+use super::{
+    context::{fix_order, Context},
+    rules::{self, Checker},
+    violation::{Violation, ViolationKind},
+};
+
+/// A context with its fixable violations fixed, as returned by [`fix_all`].
+#[derive(Debug)]
+pub struct FixedContext {
+    /// The context, whose source holds the fixes.
+    ///
+    /// The source isn't formatted; call [`Context::fmt`] for that.
+    pub ctx: Context,
+    /// How many violations were fixed.
+    pub fixed: usize,
+    /// The violations that couldn't be fixed, along with why.
+    pub failed: Vec<(Violation, anyhow::Error)>,
+}
+
+/// Fixes the fixable violations of the structural match and description
+/// rules found in `ctx`.
+///
+/// Fixes are applied in passes: first those that don't affect the order of
+/// functions, then the order of functions, and last the description
+/// comments, once every test is in place. A fix that fails is recorded in
+/// [`FixedContext::failed`] and the others are still applied.
+///
+/// Check for conflicts with
+/// [`find_conflicts`](super::conflicts::find_conflicts) first to leave
+/// hand-written code alone.
+#[must_use]
+pub fn fix_all(mut ctx: Context) -> FixedContext {
+    let violations = rules::StructuralMatcher::check(&ctx);
+    let fixed = violations.iter().filter(|v| v.is_fixable()).count()
+        + rules::DescriptionPolicy::check(&ctx).len();
+    let mut failed = Vec::new();
+
+    // Process violations that don't affect function order first.
+    let violations = violations.into_iter().filter(|v| !is_order_mismatch(v));
+    for violation in violations {
+        ctx = fix(ctx, violation, &mut failed);
+    }
+
+    // Second pass fixing order violations.
+    let violations: Vec<Violation> = rules::StructuralMatcher::check(&ctx)
+        .into_iter()
+        .filter(is_order_mismatch)
+        .collect();
+    if !violations.is_empty() {
+        if let Some(contract_sol) = ctx.find_contract() {
+            if let Some(contract_hir) = ctx.hir.clone().find_contract() {
+                ctx = fix_order(&violations, &contract_sol, contract_hir, ctx);
+            }
+        }
+    }
+
+    // Last pass fixing description comments, once every test is in place.
+    for violation in rules::DescriptionPolicy::check(&ctx) {
+        ctx = fix(ctx, violation, &mut failed);
+    }
+
+    let fixed = fixed.saturating_sub(failed.len());
+    FixedContext { ctx, fixed, failed }
+}
+
+/// Fixes `violation` in `ctx`, or records in `failed` why it couldn't be
+/// fixed and returns `ctx` as is.
+fn fix(
+    ctx: Context,
+    violation: Violation,
+    failed: &mut Vec<(Violation, anyhow::Error)>,
+) -> Context {
+    match violation.kind.fix(ctx.clone()) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            failed.push((violation, e));
+            ctx
+        }
+    }
+}
+
+fn is_order_mismatch(violation: &Violation) -> bool {
+    matches!(violation.kind, ViolationKind::FunctionOrderMismatch(_, _, _))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::fix_all;
+    use crate::{
+        check::{
+            context::Context,
+            rules::{Checker, StructuralMatcher},
+        },
+        config::Config,
+    };
+
+    const TREE: &str = r"Vault
+├── it should be empty.
+└── when paused
+    └── it should revert.
+";
+
+    fn context(sol: &str) -> (tempfile::TempDir, Context) {
+        let dir = tempdir().unwrap();
+        let tree_path = dir.path().join("Vault.tree");
+        fs::write(&tree_path, TREE).unwrap();
+        fs::write(dir.path().join("Vault.t.sol"), sol).unwrap();
+        let ctx = Context::new(tree_path, &Config::default()).unwrap();
+        (dir, ctx)
+    }
+
+    #[test]
+    fn fixes_missing_and_misordered_functions() {
+        let (_dir, ctx) = context(
+            r"contract Vault {
+    modifier whenPaused() {
+        _;
+    }
+
+    function test_RevertWhen_Paused() external whenPaused {}
+}",
+        );
+
+        let fixed = fix_all(ctx);
+        assert_eq!(fixed.fixed, 1);
+        assert!(fixed.failed.is_empty());
+        assert_eq!(StructuralMatcher::check(&fixed.ctx), vec![]);
+        let src = fixed.ctx.fmt().unwrap();
+        let empty = src.find("test_ShouldBeEmpty").unwrap();
+        assert!(empty < src.find("modifier whenPaused").unwrap());
+    }
+
+    #[test]
+    fn leaves_matching_files_alone() {
+        let sol = r"contract Vault {
+    function test_ShouldBeEmpty() external {
+        // it should be empty.
+    }
+
+    modifier whenPaused() {
+        _;
+    }
+
+    function test_RevertWhen_Paused() external whenPaused {
+        // it should revert.
+    }
+}";
+        let (_dir, ctx) = context(sol);
+
+        let fixed = fix_all(ctx);
+        assert_eq!(fixed.fixed, 0);
+        assert_eq!(fixed.ctx.src, sol);
+    }
+}
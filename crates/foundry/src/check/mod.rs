@@ -3,7 +3,10 @@
 //! This command performs checks on the relationship between a bulloak tree and
 //! a Solidity file.
 
+pub mod conflicts;
 pub mod context;
+pub mod coverage;
+pub mod fix;
 pub mod location;
 pub mod pretty;
 pub mod rules;
@@ -0,0 +1,155 @@
+//! Defines the visibility and `virtual` tag policy as a rule.
+//!
+//! This rule enforces the following:
+//! - Tests of branches tagged `[public]` are `public`, so that contracts
+//!   deriving from the test contract can call them.
+//! - Modifiers of conditions tagged `[virtual]` are `virtual`, so that
+//!   contracts deriving from the test contract can override them.
+//!
+//! Functions of untagged branches may have any visibility. Functions missing
+//! from the Solidity file are reported by the
+//! [`StructuralMatcher`](super::StructuralMatcher) instead.
+
+use solang_parser::pt::{FunctionAttribute, Visibility};
+
+use super::{Checker, Context};
+use crate::{
+    check::{
+        location::Location,
+        utils::offset_to_line,
+        violation::{Violation, ViolationKind},
+    },
+    hir::{self, Hir},
+    sol::{find_anchored_fn, find_matching_fn},
+};
+
+/// An implementation of the visibility and `virtual` tag policy rule.
+///
+/// Read more at the [module-level documentation][self].
+pub struct AttributePolicy;
+
+impl Checker for AttributePolicy {
+    fn check(ctx: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        let (Some(contract_hir), Some(contract_sol)) =
+            (ctx.hir.find_contract(), ctx.find_contract())
+        else {
+            return violations;
+        };
+
+        for fn_hir in &contract_hir.children {
+            let Hir::Function(fn_hir) = fn_hir else {
+                continue;
+            };
+            let public = fn_hir.visibility == hir::Visibility::Public;
+            let attribute = if public {
+                "public"
+            } else if fn_hir.is_virtual {
+                "virtual"
+            } else {
+                continue;
+            };
+            let Some((_, fn_sol)) = find_matching_fn(&contract_sol, fn_hir)
+                .or_else(|| find_anchored_fn(&contract_sol, fn_hir, &ctx.src))
+            else {
+                continue;
+            };
+
+            let found = fn_sol.attributes.iter().any(|attr| match attr {
+                FunctionAttribute::Visibility(Visibility::Public(_)) => public,
+                FunctionAttribute::Virtual(_) => fn_hir.is_virtual,
+                _ => false,
+            });
+            if found {
+                continue;
+            }
+            let name = fn_sol.name.as_ref().map_or_else(
+                || fn_hir.identifier.clone(),
+                |name| name.name.clone(),
+            );
+            violations.push(Violation::new(
+                ViolationKind::AttributeMissing(name, attribute.to_owned()),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ));
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::AttributePolicy;
+    use crate::{
+        check::{context::Context, rules::Checker, violation::ViolationKind},
+        config::Config,
+    };
+
+    fn check(sol: &str) -> Vec<ViolationKind> {
+        let dir = tempdir().unwrap();
+        let tree_path = dir.path().join("Vault.tree");
+        fs::write(&tree_path, TREE).unwrap();
+        fs::write(dir.path().join("Vault.t.sol"), sol).unwrap();
+        let ctx = Context::new(tree_path, &Config::default()).unwrap();
+        AttributePolicy::check(&ctx).into_iter().map(|v| v.kind).collect()
+    }
+
+    const TREE: &str = r"Vault
+├── it should be empty. [public]
+└── when paused [virtual]
+    ├── when the caller is the owner
+    │   └── it should unpause.
+    └── it should revert.
+";
+
+    #[test]
+    fn tagged_functions_must_have_their_attribute() {
+        let sol = r"contract Vault {
+    function test_ShouldBeEmpty() external {}
+
+    modifier whenPaused() {
+        _;
+    }
+
+    function test_RevertWhen_Paused() external whenPaused {}
+
+    function test_WhenTheCallerIsTheOwner() external whenPaused {}
+}";
+        assert_eq!(
+            check(sol),
+            vec![
+                ViolationKind::AttributeMissing(
+                    "test_ShouldBeEmpty".to_owned(),
+                    "public".to_owned()
+                ),
+                ViolationKind::AttributeMissing(
+                    "whenPaused".to_owned(),
+                    "virtual".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_tagged_functions_with_their_attribute() {
+        let sol = r"contract Vault {
+    function test_ShouldBeEmpty() public {}
+
+    modifier whenPaused() virtual {
+        _;
+    }
+
+    function test_RevertWhen_Paused() external whenPaused {}
+
+    function test_WhenTheCallerIsTheOwner() public whenPaused {}
+}";
+        assert_eq!(check(sol), vec![]);
+    }
+}
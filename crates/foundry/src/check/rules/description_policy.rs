@@ -0,0 +1,222 @@
+//! Defines the description comment policy as a rule.
+//!
+//! With `--format-descriptions`, the body of each test holds the description
+//! comments of its actions, e.g. `// It should revert.`, capitalized and
+//! punctuated the way `scaffold --format-descriptions` writes them. Comments
+//! that only differ in casing or punctuation are reported as unformatted, and
+//! missing comments as missing.
+//!
+//! Tests missing from the Solidity file are reported by the
+//! [`StructuralMatcher`](super::StructuralMatcher) instead.
+
+use std::ops::Range;
+
+use forge_fmt::solang_ext::CodeLocationExt;
+
+use super::{Checker, Context};
+use crate::{
+    check::{
+        location::Location,
+        utils::offset_to_line,
+        violation::{Violation, ViolationKind},
+    },
+    hir::{self, Hir},
+    scaffold::comment,
+    sol::{find_anchored_fn, find_matching_fn},
+};
+
+/// An implementation of the description comment policy rule.
+///
+/// Read more at the [module-level documentation][self].
+pub struct DescriptionPolicy;
+
+impl Checker for DescriptionPolicy {
+    fn check(ctx: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        if !ctx.cfg.format_descriptions {
+            return violations;
+        }
+        let (Some(contract_hir), Some(contract_sol)) =
+            (ctx.hir.find_contract(), ctx.find_contract())
+        else {
+            return violations;
+        };
+
+        for fn_hir in &contract_hir.children {
+            let Hir::Function(fn_hir) = fn_hir else {
+                continue;
+            };
+            if fn_hir.is_modifier() {
+                continue;
+            }
+            let Some((_, fn_sol)) = find_matching_fn(&contract_sol, fn_hir)
+                .or_else(|| find_anchored_fn(&contract_sol, fn_hir, &ctx.src))
+            else {
+                continue;
+            };
+            let Some(body) = &fn_sol.body else {
+                continue;
+            };
+
+            let start = body.loc().start();
+            let comments = line_comments(&ctx.src[start..body.loc().end()]);
+            let name = fn_sol.name.as_ref().map_or_else(
+                || fn_hir.identifier.clone(),
+                |name| name.name.clone(),
+            );
+            for expected in descriptions(fn_hir) {
+                if comments.iter().any(|(_, text)| *text == expected) {
+                    continue;
+                }
+                let found = comments
+                    .iter()
+                    .find(|(_, text)| loosely_equal(text, &expected));
+                let offset = found.map_or(fn_sol.loc.start(), |(range, _)| {
+                    start + range.start
+                });
+                violations.push(Violation::new(
+                    ViolationKind::DescriptionMismatch(
+                        name.clone(),
+                        found.map(|(_, text)| text.clone()),
+                        expected,
+                    ),
+                    Location::Code(
+                        ctx.sol.to_string_lossy().into_owned(),
+                        offset_to_line(&ctx.src, offset),
+                    ),
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+/// Returns the description comments scaffolded in the body of `fn_hir`,
+/// formatted.
+pub(crate) fn descriptions(fn_hir: &hir::FunctionDefinition) -> Vec<String> {
+    fn_hir
+        .children
+        .iter()
+        .flatten()
+        .filter_map(|child| match child {
+            Hir::Comment(c) => Some(comment::normalize(&c.lexeme)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the range and the text of each line comment in `src`, where the
+/// text is what follows `// `.
+pub(crate) fn line_comments(src: &str) -> Vec<(Range<usize>, String)> {
+    let mut comments = vec![];
+    let mut offset = 0;
+    for line in src.split_inclusive('\n') {
+        let content = line.trim_end();
+        let trimmed = content.trim_start();
+        if trimmed.starts_with("//") && !trimmed.starts_with("///") {
+            let start = offset + content.len() - trimmed.len();
+            let text = &trimmed[2..];
+            let text = text.strip_prefix(' ').unwrap_or(text);
+            comments.push((start..offset + content.len(), text.to_owned()));
+        }
+        offset += line.len();
+    }
+
+    comments
+}
+
+/// Whether `text` is `description` up to casing and final punctuation.
+fn loosely_equal(text: &str, description: &str) -> bool {
+    let loose =
+        |s: &str| s.trim().trim_end_matches(['.', '!', '?']).to_lowercase();
+    loose(text) == loose(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::DescriptionPolicy;
+    use crate::{
+        check::{context::Context, rules::Checker, violation::ViolationKind},
+        config::Config,
+    };
+
+    fn context(sol: &str, format_descriptions: bool) -> Context {
+        let dir = tempdir().unwrap();
+        let tree_path = dir.path().join("X.tree");
+        fs::write(&tree_path, TREE).unwrap();
+        fs::write(dir.path().join("X.t.sol"), sol).unwrap();
+        let cfg = Config { format_descriptions, ..Config::default() };
+        Context::new(tree_path, &cfg).unwrap()
+    }
+
+    fn check(sol: &str, format_descriptions: bool) -> Vec<ViolationKind> {
+        let ctx = context(sol, format_descriptions);
+        DescriptionPolicy::check(&ctx).into_iter().map(|v| v.kind).collect()
+    }
+
+    const TREE: &str = r"Token
+├── it should be fast.
+└── when transferring
+    ├── it should move the tokens
+    └── it should emit a {Transfer} event
+";
+
+    const SOL: &str = r"contract Token {
+    function test_ShouldBeFast() external {
+        // it should be fast
+    }
+
+    function test_WhenTransferring() external {
+        token.transfer(bob, 1);
+        // It should emit a {Transfer} event.
+    }
+}";
+
+    #[test]
+    fn descriptions_must_be_formatted() {
+        assert_eq!(
+            check(SOL, true),
+            vec![
+                ViolationKind::DescriptionMismatch(
+                    "test_ShouldBeFast".to_owned(),
+                    Some("it should be fast".to_owned()),
+                    "It should be fast.".to_owned()
+                ),
+                ViolationKind::DescriptionMismatch(
+                    "test_WhenTransferring".to_owned(),
+                    None,
+                    "It should move the tokens.".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(check(SOL, false), vec![]);
+    }
+
+    #[test]
+    fn fixes_descriptions() {
+        let mut ctx = context(SOL, true);
+        for violation in DescriptionPolicy::check(&ctx.clone()) {
+            ctx = violation.kind.fix(ctx).unwrap();
+        }
+        assert_eq!(DescriptionPolicy::check(&ctx), vec![]);
+        assert_eq!(
+            ctx.src,
+            r"contract Token {
+    function test_ShouldBeFast() external {
+        // It should be fast.
+    }
+
+    function test_WhenTransferring() external {
+        // It should move the tokens.
+        token.transfer(bob, 1);
+        // It should emit a {Transfer} event.
+    }
+}"
+        );
+    }
+}
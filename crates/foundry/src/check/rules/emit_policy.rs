@@ -0,0 +1,139 @@
+//! Defines the `vm.expectEmit` policy as a rule.
+//!
+//! With `--expect-emit`, tests of actions like `it should emit a {Transfer}
+//! event` call `vm.expectEmit`, so that they at least set up the expectation
+//! the spec asks for.
+//!
+//! Tests missing from the Solidity file are reported by the
+//! [`StructuralMatcher`](super::StructuralMatcher) instead.
+
+use super::{Checker, Context};
+use crate::{
+    check::{
+        location::Location,
+        utils::offset_to_line,
+        violation::{Violation, ViolationKind},
+    },
+    hir::{self, Hir},
+    sol::{body, find_anchored_fn, find_matching_fn, is_expect_emit},
+};
+
+/// An implementation of the `vm.expectEmit` policy rule.
+///
+/// Read more at the [module-level documentation][self].
+pub struct EmitPolicy;
+
+impl Checker for EmitPolicy {
+    fn check(ctx: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        if !ctx.cfg.emit_expect_emit {
+            return violations;
+        }
+        let (Some(contract_hir), Some(contract_sol)) =
+            (ctx.hir.find_contract(), ctx.find_contract())
+        else {
+            return violations;
+        };
+
+        for fn_hir in &contract_hir.children {
+            let Hir::Function(fn_hir) = fn_hir else {
+                continue;
+            };
+            if !expects_emit(fn_hir) {
+                continue;
+            }
+            let Some((_, fn_sol)) = find_matching_fn(&contract_sol, fn_hir)
+                .or_else(|| find_anchored_fn(&contract_sol, fn_hir, &ctx.src))
+            else {
+                continue;
+            };
+            if body(fn_sol).iter().any(is_expect_emit) {
+                continue;
+            }
+
+            let name = fn_sol.name.as_ref().map_or_else(
+                || fn_hir.identifier.clone(),
+                |name| name.name.clone(),
+            );
+            violations.push(Violation::new(
+                ViolationKind::ExpectEmitMissing(name),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ));
+        }
+
+        violations
+    }
+}
+
+/// Whether `fn_hir` is a test that scaffolds a `vm.expectEmit()` call.
+fn expects_emit(fn_hir: &hir::FunctionDefinition) -> bool {
+    fn_hir.children.iter().flatten().any(|child| {
+        matches!(
+            child,
+            Hir::Statement(hir::Statement {
+                ty: hir::StatementType::ExpectEmit
+            })
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::EmitPolicy;
+    use crate::{
+        check::{context::Context, rules::Checker, violation::ViolationKind},
+        config::Config,
+    };
+
+    fn check(sol: &str, emit_expect_emit: bool) -> Vec<ViolationKind> {
+        let dir = tempdir().unwrap();
+        let tree_path = dir.path().join("X.tree");
+        fs::write(&tree_path, TREE).unwrap();
+        fs::write(dir.path().join("X.t.sol"), sol).unwrap();
+        let cfg = Config { emit_expect_emit, ..Config::default() };
+        let ctx = Context::new(tree_path, &cfg).unwrap();
+        EmitPolicy::check(&ctx).into_iter().map(|v| v.kind).collect()
+    }
+
+    const TREE: &str = r"Token
+├── it should be fast.
+└── when transferring
+    └── it should emit a {Transfer} event.
+";
+
+    #[test]
+    fn emitting_tests_must_expect_emit() {
+        let sol = r"contract Token {
+    function test_ShouldBeFast() external {}
+
+    function test_WhenTransferring() external {
+        // it should emit a {Transfer} event.
+    }
+}";
+        assert_eq!(
+            check(sol, true),
+            vec![ViolationKind::ExpectEmitMissing(
+                "test_WhenTransferring".to_owned()
+            )]
+        );
+        assert_eq!(check(sol, false), vec![]);
+
+        let sol = r"contract Token {
+    function test_ShouldBeFast() external {}
+
+    function test_WhenTransferring() external {
+        vm.expectEmit(true, true, false, true);
+        emit Transfer(alice, bob, 1);
+        token.transfer(bob, 1);
+    }
+}";
+        assert_eq!(check(sol, true), vec![]);
+    }
+}
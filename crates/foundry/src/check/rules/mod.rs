@@ -5,7 +5,15 @@
 
 use super::{context::Context, violation::Violation};
 
+pub mod attribute_policy;
+pub mod description_policy;
+pub mod emit_policy;
+pub mod skip_policy;
 pub mod structural_match;
+pub use attribute_policy::AttributePolicy;
+pub use description_policy::DescriptionPolicy;
+pub use emit_policy::EmitPolicy;
+pub use skip_policy::SkipPolicy;
 pub use structural_match::StructuralMatcher;
 
 /// Trait definition for a rule checker object.
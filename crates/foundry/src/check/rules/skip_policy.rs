@@ -0,0 +1,178 @@
+//! Defines the `vm.skip` policy as a rule.
+//!
+//! This rule enforces the following:
+//! - Tests of branches tagged `[skip]` call `vm.skip(true)`.
+//! - With `--vm-skip`, tests whose bodies are empty call `vm.skip(true)`, so
+//!   that they don't pass before they are implemented. A scaffolded
+//!   `vm.expectEmit()` call doesn't make a body non-empty.
+//! - No other test calls `vm.skip(true)`, so that tests aren't left skipped
+//!   once they are implemented.
+//!
+//! Tests missing from the Solidity file are reported by the
+//! [`StructuralMatcher`](super::StructuralMatcher) instead.
+
+use super::{Checker, Context};
+use crate::{
+    check::{
+        location::Location,
+        utils::offset_to_line,
+        violation::{Violation, ViolationKind},
+    },
+    hir::Hir,
+    sol::{
+        body, find_anchored_fn, find_matching_fn, is_expect_emit, is_vm_skip,
+    },
+};
+
+/// An implementation of the `vm.skip` policy rule.
+///
+/// Read more at the [module-level documentation][self].
+pub struct SkipPolicy;
+
+impl Checker for SkipPolicy {
+    fn check(ctx: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        let (Some(contract_hir), Some(contract_sol)) =
+            (ctx.hir.find_contract(), ctx.find_contract())
+        else {
+            return violations;
+        };
+
+        for fn_hir in &contract_hir.children {
+            let Hir::Function(fn_hir) = fn_hir else {
+                continue;
+            };
+            if fn_hir.is_modifier() {
+                continue;
+            }
+            let Some((_, fn_sol)) = find_matching_fn(&contract_sol, fn_hir)
+                .or_else(|| find_anchored_fn(&contract_sol, fn_hir, &ctx.src))
+            else {
+                continue;
+            };
+
+            let statements = body(fn_sol);
+            let skips = statements.iter().any(is_vm_skip);
+            let empty =
+                statements.iter().all(|s| is_vm_skip(s) || is_expect_emit(s));
+            let expected = fn_hir.skipped || (ctx.cfg.emit_vm_skip && empty);
+            let name = fn_sol.name.as_ref().map_or_else(
+                || fn_hir.identifier.clone(),
+                |name| name.name.clone(),
+            );
+            let kind = match (expected, skips) {
+                (true, false) => ViolationKind::VmSkipMissing(name),
+                (false, true) => ViolationKind::VmSkipUnexpected(name),
+                _ => continue,
+            };
+            violations.push(Violation::new(
+                kind,
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ));
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::SkipPolicy;
+    use crate::{
+        check::{context::Context, rules::Checker, violation::ViolationKind},
+        config::Config,
+    };
+
+    fn check(tree: &str, sol: &str, emit_vm_skip: bool) -> Vec<ViolationKind> {
+        let dir = tempdir().unwrap();
+        let tree_path = dir.path().join("X.tree");
+        fs::write(&tree_path, tree).unwrap();
+        fs::write(dir.path().join("X.t.sol"), sol).unwrap();
+        let cfg = Config { emit_vm_skip, ..Config::default() };
+        let ctx = Context::new(tree_path, &cfg).unwrap();
+        SkipPolicy::check(&ctx).into_iter().map(|v| v.kind).collect()
+    }
+
+    const TREE: &str = r"Foo
+├── it should be fast.
+└── when paused [skip]
+    └── it should revert.
+";
+
+    #[test]
+    fn tagged_tests_must_be_skipped() {
+        let sol = r"contract Foo {
+    function test_ShouldBeFast() external {}
+
+    function test_RevertWhen_Paused() external {
+        // it should revert.
+    }
+}";
+        assert_eq!(
+            check(TREE, sol, false),
+            vec![ViolationKind::VmSkipMissing(
+                "test_RevertWhen_Paused".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn untagged_tests_must_not_be_skipped() {
+        let sol = r#"contract Foo {
+    function test_ShouldBeFast() external {
+        vm.skip(true, "flaky");
+    }
+
+    function test_RevertWhen_Paused() external {
+        vm.skip(true);
+    }
+}"#;
+        assert_eq!(
+            check(TREE, sol, false),
+            vec![ViolationKind::VmSkipUnexpected(
+                "test_ShouldBeFast".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn empty_tests_must_be_skipped_with_vm_skip() {
+        let sol = r"contract Foo {
+    function test_ShouldBeFast() external {
+        // it should be fast.
+    }
+
+    function test_RevertWhen_Paused() external {
+        vm.skip(true);
+    }
+}";
+        assert_eq!(
+            check(TREE, sol, true),
+            vec![ViolationKind::VmSkipMissing("test_ShouldBeFast".to_owned())]
+        );
+
+        let sol = r"contract Foo {
+    function test_ShouldBeFast() external {
+        vm.skip(true);
+        assertTrue(true);
+    }
+
+    function test_RevertWhen_Paused() external {
+        vm.skip(true);
+    }
+}";
+        assert_eq!(
+            check(TREE, sol, true),
+            vec![ViolationKind::VmSkipUnexpected(
+                "test_ShouldBeFast".to_owned()
+            )]
+        );
+    }
+}
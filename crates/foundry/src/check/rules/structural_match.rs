@@ -10,6 +10,12 @@
 //! - Their name is exactly the same.
 //! - Their function type is exactly the same. Currently, only regular functions
 //! and modifiers are supported.
+//!
+//! A function generated from an anchored branch is also found by the
+//! `// #anchor` comment above it, in which case only its name is reported.
+//!
+//! Functions defined more than once, e.g. after a manual merge, can't be told
+//! apart by name, so they are reported instead of the structure of the file.
 
 use std::collections::BTreeSet;
 
@@ -24,7 +30,7 @@ use crate::{
         violation::{Violation, ViolationKind},
     },
     hir::{self, Hir},
-    sol::{find_contract, find_matching_fn},
+    sol::{find_anchored_fn, find_matching_fn},
 };
 
 /// An implementation of a structural matching rule.
@@ -46,12 +52,17 @@ impl Checker for StructuralMatcher {
             return violations;
         };
 
-        // Find the first occurrence of a contract.
-        let Some(contract_sol) = find_contract(&ctx.pt) else {
+        // Find the contract targeted by this check.
+        let Some(contract_sol) = ctx.find_contract() else {
             // If we find no contract in the Solidity file, then there must
             // be no contract in the HIR, else we found a violation.
+            let name = ctx
+                .cfg
+                .contract
+                .clone()
+                .unwrap_or_else(|| contract_hir.identifier.clone());
             let violation = Violation::new(
-                ViolationKind::ContractMissing(contract_hir.identifier.clone()),
+                ViolationKind::ContractMissing(name),
                 Location::File(ctx.tree.to_string_lossy().into_owned()),
             );
             violations.push(violation);
@@ -60,22 +71,65 @@ impl Checker for StructuralMatcher {
             return violations;
         };
 
-        // We know a contract exists in both trees.
-        violations.append(&mut check_contract_names(
-            contract_hir,
-            &contract_sol,
-            ctx,
-        ));
-        violations.append(&mut check_fns_structure(
-            contract_hir,
-            &contract_sol,
-            ctx,
-        ));
+        // We know a contract exists in both trees. If the contract was
+        // targeted explicitly, its name is allowed to differ from the tree's.
+        if ctx.cfg.contract.is_none() {
+            violations.append(&mut check_contract_names(
+                contract_hir,
+                &contract_sol,
+                ctx,
+            ));
+        }
+        let mut duplicated = check_fns_duplicated(&contract_sol, ctx);
+        if duplicated.is_empty() {
+            violations.append(&mut check_fns_structure(
+                contract_hir,
+                &contract_sol,
+                ctx,
+            ));
+        } else {
+            violations.append(&mut duplicated);
+        }
 
         violations
     }
 }
 
+/// Checks that no function is defined more than once, reporting every
+/// definition after the first.
+///
+/// Functions overloaded with a different number of parameters are told
+/// apart.
+fn check_fns_duplicated(
+    contract_sol: &pt::ContractDefinition,
+    ctx: &Context,
+) -> Vec<Violation> {
+    let mut seen = Vec::new();
+    let mut violations = Vec::new();
+    for part in &contract_sol.parts {
+        let ContractPart::FunctionDefinition(fn_sol) = part else {
+            continue;
+        };
+        let Some(name) = &fn_sol.name else {
+            continue;
+        };
+        let signature = (&name.name, fn_sol.ty, fn_sol.params.len());
+        if seen.contains(&signature) {
+            violations.push(Violation::new(
+                ViolationKind::FunctionDuplicated(name.name.clone()),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ));
+        } else {
+            seen.push(signature);
+        }
+    }
+
+    violations
+}
+
 /// Checks that contract names match.
 fn check_contract_names(
     contract_hir: &hir::ContractDefinition,
@@ -125,7 +179,21 @@ fn check_fns_structure(
         };
 
         let maybe_matching_fn = find_matching_fn(contract_sol, fn_hir);
-        let Some((sol_idx, _)) = maybe_matching_fn else {
+        let maybe_anchored_fn = || {
+            let (sol_idx, fn_sol) =
+                find_anchored_fn(contract_sol, fn_hir, &ctx.src)?;
+            let name = fn_sol.name.as_ref()?.name.clone();
+            violations.push(Violation::new(
+                ViolationKind::FunctionNameMismatch(fn_hir.clone(), name),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ));
+            Some((sol_idx, fn_sol))
+        };
+        let Some((sol_idx, _)) = maybe_matching_fn.or_else(maybe_anchored_fn)
+        else {
             // We didn't find a matching function, so this is a
             // violation.
 
@@ -327,6 +395,35 @@ contract Foo {
         );
     }
 
+    #[test]
+    fn duplicated_functions_reported_instead_of_structure() {
+        let tree = r"
+Foo
+├── It one.
+└── It two.
+";
+        let sol = r"
+contract Foo {
+  function test_Two() external {}
+  function test_One() external {}
+  function test_Two() external {}
+  function testFuzz_Three(uint256 a) external {}
+  function testFuzz_Three(uint256 a, uint256 b) external {}
+}
+";
+        let ctx = make_ctx(tree, sol);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(vs.len(), 1);
+        assert!(matches!(
+            &vs[0].kind,
+            ViolationKind::FunctionDuplicated(name) if name == "test_Two"
+        ));
+        assert_eq!(
+            vs[0].location.to_string(),
+            format!("{}:5", ctx.sol.display())
+        );
+    }
+
     #[test]
     fn skip_modifiers_flag_ignores_missing_modifiers() {
         let tree = r#"
@@ -3,12 +3,16 @@ use std::{borrow::Cow, fmt};
 
 use bulloak_syntax::FrontendError;
 use forge_fmt::solang_ext::SafeUnwrap;
-use owo_colors::OwoColorize;
+use owo_colors::{OwoColorize, Stream};
 use solang_parser::pt;
 use thiserror::Error;
 
 use super::{context::Context, location::Location};
-use crate::hir;
+use crate::{
+    config::Config,
+    hir::{self, Hir},
+    scaffold::emitter::Emitter,
+};
 
 /// An error that occurred while checking specification rules between
 /// a tree and a Solidity contract.
@@ -74,9 +78,72 @@ pub enum ViolationKind {
     #[error("function \"{}\" is missing in .sol", .0.identifier.clone())]
     MatchingFunctionMissing(hir::FunctionDefinition, usize),
 
+    /// Found the function generated for an anchored branch under another
+    /// name, e.g. after the branch's title changed.
+    ///
+    /// (hir function, sol name)
+    #[error(
+        "function \"{1}\" should be named \"{}\" to match its anchor",
+        .0.identifier
+    )]
+    FunctionNameMismatch(hir::FunctionDefinition, String),
+
+    /// Found a function defined more than once in the contract, e.g. after a
+    /// manual merge.
+    ///
+    /// (sol name)
+    #[error("function \"{0}\" is defined more than once")]
+    FunctionDuplicated(String),
+
+    /// Found a test that should call `vm.skip(true)` but doesn't.
+    ///
+    /// (sol name)
+    #[error("test \"{0}\" should call `vm.skip(true)`")]
+    VmSkipMissing(String),
+
+    /// Found a test that calls `vm.skip(true)` but shouldn't.
+    ///
+    /// (sol name)
+    #[error("test \"{0}\" should not call `vm.skip(true)`")]
+    VmSkipUnexpected(String),
+
+    /// Found a test of an action that emits an event which doesn't call
+    /// `vm.expectEmit`.
+    ///
+    /// (sol name)
+    #[error("test \"{0}\" should call `vm.expectEmit`")]
+    ExpectEmitMissing(String),
+
+    /// Found a test whose description comment is missing or isn't formatted
+    /// under `--format-descriptions`.
+    ///
+    /// (sol name, found comment, expected comment)
+    #[error("{}", describe_mismatch(.0, .1.as_deref(), .2))]
+    DescriptionMismatch(String, Option<String>, String),
+
+    /// Found a function of a branch tagged `[public]` or `[virtual]` that
+    /// lacks the attribute the tag asks for.
+    ///
+    /// (sol name, attribute)
+    #[error("function \"{0}\" should be `{1}`")]
+    AttributeMissing(String, String),
+
+    /// A custom rule loaded from the rules directory reported a violation.
+    ///
+    /// (rule name, message)
+    #[error("{1} (rule \"{0}\")")]
+    CustomRuleViolated(String, String),
+
     /// The parsing of a tree or a Solidity file failed.
     #[error("{}", format_frontend_error(.0))]
     ParsingFailed(#[from] anyhow::Error),
+
+    /// Fixing the file would overwrite code that was written by hand, so it
+    /// was left untouched.
+    ///
+    /// (reason)
+    #[error("manual resolution required: {0}")]
+    ManualResolutionRequired(String),
 }
 
 impl ViolationKind {
@@ -88,9 +155,102 @@ impl ViolationKind {
                 | ViolationKind::ContractNameNotMatches(_, _)
                 | ViolationKind::FunctionOrderMismatch(_, _, _)
                 | ViolationKind::MatchingFunctionMissing(_, _)
+                | ViolationKind::FunctionNameMismatch(_, _)
+                | ViolationKind::DescriptionMismatch(_, _, _)
         )
     }
 
+    /// The stable ID of the rule this violation kind breaks, e.g. `BK105`.
+    ///
+    /// IDs are shared with the Rust backend, and `bulloak explain <ID>`
+    /// describes the rule.
+    #[must_use]
+    pub fn rule(&self) -> &'static str {
+        match self {
+            ViolationKind::ParsingFailed(_) => "BK001",
+            ViolationKind::SolidityFileMissing(_) => "BK101",
+            ViolationKind::FileUnreadable => "BK102",
+            ViolationKind::ContractMissing(_) => "BK103",
+            ViolationKind::ContractNameNotMatches(_, _) => "BK104",
+            ViolationKind::MatchingFunctionMissing(fn_hir, _) => {
+                if fn_hir.is_function() {
+                    "BK105"
+                } else {
+                    "BK106"
+                }
+            }
+            ViolationKind::FunctionOrderMismatch(_, _, _) => "BK107",
+            ViolationKind::FunctionNameMismatch(_, _) => "BK108",
+            ViolationKind::FunctionDuplicated(_) => "BK113",
+            ViolationKind::VmSkipMissing(_) => "BK201",
+            ViolationKind::VmSkipUnexpected(_) => "BK202",
+            ViolationKind::ExpectEmitMissing(_) => "BK203",
+            ViolationKind::DescriptionMismatch(_, _, _) => "BK204",
+            ViolationKind::AttributeMissing(_, _) => "BK205",
+            ViolationKind::CustomRuleViolated(_, _) => "BK401",
+            ViolationKind::ManualResolutionRequired(_) => "BK501",
+        }
+    }
+
+    /// The category of this violation kind in the summary at the end of
+    /// `bulloak check`, as a singular and a plural noun, e.g.
+    /// `("missing test", "missing tests")`.
+    #[must_use]
+    pub fn category(&self) -> (&'static str, &'static str) {
+        match self {
+            ViolationKind::ContractMissing(_) => {
+                ("missing contract", "missing contracts")
+            }
+            ViolationKind::ContractNameNotMatches(_, _) => {
+                ("contract name mismatch", "contract name mismatches")
+            }
+            ViolationKind::SolidityFileMissing(_) => {
+                ("missing file", "missing files")
+            }
+            ViolationKind::FileUnreadable => {
+                ("unreadable file", "unreadable files")
+            }
+            ViolationKind::FunctionOrderMismatch(_, _, _) => {
+                ("order issue", "order issues")
+            }
+            ViolationKind::MatchingFunctionMissing(fn_hir, _) => {
+                if fn_hir.is_function() {
+                    ("missing test", "missing tests")
+                } else {
+                    ("missing modifier", "missing modifiers")
+                }
+            }
+            ViolationKind::FunctionNameMismatch(_, _) => {
+                ("name mismatch", "name mismatches")
+            }
+            ViolationKind::FunctionDuplicated(_) => {
+                ("duplicate function", "duplicate functions")
+            }
+            ViolationKind::VmSkipMissing(_) => {
+                ("missing vm.skip", "missing vm.skips")
+            }
+            ViolationKind::VmSkipUnexpected(_) => {
+                ("unexpected vm.skip", "unexpected vm.skips")
+            }
+            ViolationKind::ExpectEmitMissing(_) => {
+                ("missing vm.expectEmit", "missing vm.expectEmits")
+            }
+            ViolationKind::DescriptionMismatch(_, _, _) => {
+                ("description mismatch", "description mismatches")
+            }
+            ViolationKind::AttributeMissing(_, _) => {
+                ("missing attribute", "missing attributes")
+            }
+            ViolationKind::CustomRuleViolated(_, _) => {
+                ("custom rule violation", "custom rule violations")
+            }
+            ViolationKind::ParsingFailed(_) => ("parse error", "parse errors"),
+            ViolationKind::ManualResolutionRequired(_) => {
+                ("fix conflict", "fix conflicts")
+            }
+        }
+    }
+
     /// Optionally returns a help text to be used when displaying the violation
     /// kind.
     pub fn help(&self) -> Option<Cow<'static, str>> {
@@ -106,15 +266,80 @@ impl ViolationKind {
                 let filename = filename.replace(".t.sol", ".tree");
                 format!("consider running `bulloak scaffold {filename}`").into()
             }
+            ViolationKind::FileUnreadable => {
+                "make sure the file exists and is readable".into()
+            }
             ViolationKind::FunctionOrderMismatch(_, _, _) => {
                 "consider reordering the function in the file".into()
             }
-            _ => return None,
+            ViolationKind::MatchingFunctionMissing(fn_hir, _) => format!(
+                r#"consider adding the function "{}" to the contract"#,
+                fn_hir.identifier
+            )
+            .into(),
+            ViolationKind::FunctionNameMismatch(fn_hir, _) => format!(
+                r#"consider renaming the function to "{}""#,
+                fn_hir.identifier
+            )
+            .into(),
+            ViolationKind::FunctionDuplicated(_) => {
+                "consider removing all but one of the definitions".into()
+            }
+            ViolationKind::VmSkipMissing(_) => {
+                "consider adding `vm.skip(true);` at the start of the test"
+                    .into()
+            }
+            ViolationKind::VmSkipUnexpected(_) => {
+                "consider removing the call, or tagging the branch of the test \
+                 `[skip]`"
+                    .into()
+            }
+            ViolationKind::ExpectEmitMissing(_) => {
+                "consider calling `vm.expectEmit();` before the code that \
+                 emits the event"
+                    .into()
+            }
+            ViolationKind::DescriptionMismatch(_, Some(_), expected) => {
+                format!("consider changing the comment to `// {expected}`")
+                    .into()
+            }
+            ViolationKind::DescriptionMismatch(_, None, expected) => {
+                format!("consider adding `// {expected}` to the test").into()
+            }
+            ViolationKind::AttributeMissing(_, attribute) => format!(
+                "consider making the function `{attribute}`, or removing the \
+                 `[{attribute}]` tag of its branch"
+            )
+            .into(),
+            ViolationKind::ManualResolutionRequired(_) => {
+                "resolve the conflict by hand, then run `bulloak check --fix` \
+                 again"
+                    .into()
+            }
+            ViolationKind::ParsingFailed(_)
+            | ViolationKind::CustomRuleViolated(_, _) => return None,
         };
 
         Some(text)
     }
 
+    /// Optionally returns a code snippet that would resolve the violation,
+    /// e.g. the stub of a missing function.
+    #[must_use]
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            ViolationKind::ContractMissing(name) => {
+                Some(format!("contract {name} {{}}"))
+            }
+            ViolationKind::MatchingFunctionMissing(fn_hir, _) => {
+                let function = Hir::Function(fn_hir.clone());
+                let emitted = Emitter::new(&Config::default()).emit(&function);
+                Some(dedent(&emitted))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns a new context with this violation fixed.
     pub fn fix(&self, ctx: Context) -> anyhow::Result<Context> {
         match self {
@@ -126,6 +351,12 @@ impl ViolationKind {
             ViolationKind::MatchingFunctionMissing(fn_hir, index) => {
                 ctx.fix_matching_fn_missing(fn_hir, *index)
             }
+            ViolationKind::FunctionNameMismatch(fn_hir, old_name) => {
+                ctx.fix_fn_rename(&fn_hir.identifier, old_name)
+            }
+            ViolationKind::DescriptionMismatch(name, found, expected) => {
+                ctx.fix_description(name, found.as_deref(), expected)
+            }
             _ => Ok(ctx),
         }
     }
@@ -133,16 +364,43 @@ impl ViolationKind {
 
 impl fmt::Display for Violation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{}: {}", "warn".yellow(), self.kind)?;
+        writeln!(
+            f,
+            "{}: {}",
+            "warn".if_supports_color(Stream::Stderr, |t| t.yellow()),
+            self.kind
+        )?;
         if let Some(help_text) = self.kind.help() {
-            writeln!(f, "     {} help: {}", "=".blue(), help_text)?;
+            writeln!(
+                f,
+                "     {} help: {}",
+                "=".if_supports_color(Stream::Stderr, |t| t.blue()),
+                help_text
+            )?;
+        }
+        if let Some(snippet) = self.kind.suggestion() {
+            let bar = "|".if_supports_color(Stream::Stderr, |t| t.blue());
+            writeln!(f, "     {bar}")?;
+            for line in snippet.lines() {
+                writeln!(f, "     {bar} {line}")?;
+            }
+            writeln!(f, "     {bar}")?;
         }
         if self.kind.is_fixable() {
             let file = self.location.file().replace(".t.sol", ".tree");
-            write!(f, "     {} fix: run ", "+".blue())?;
+            write!(
+                f,
+                "     {} fix: run ",
+                "+".if_supports_color(Stream::Stderr, |t| t.blue())
+            )?;
             writeln!(f, "`bulloak check --fix {file}`")?;
         }
-        writeln!(f, "   {} {}", "-->".blue(), self.location)?;
+        writeln!(
+            f,
+            "   {} {}",
+            "-->".if_supports_color(Stream::Stderr, |t| t.blue()),
+            self.location
+        )?;
 
         Ok(())
     }
@@ -176,6 +434,23 @@ impl PartialEq for ViolationKind {
             {
                 f1.identifier == f2.identifier && pos1 == pos2
             }
+            (FunctionNameMismatch(f1, n1), FunctionNameMismatch(f2, n2)) => {
+                f1.identifier == f2.identifier && n1 == n2
+            }
+            (VmSkipMissing(a), VmSkipMissing(b)) => a == b,
+            (VmSkipUnexpected(a), VmSkipUnexpected(b)) => a == b,
+            (ExpectEmitMissing(a), ExpectEmitMissing(b)) => a == b,
+            (
+                DescriptionMismatch(a1, a2, a3),
+                DescriptionMismatch(b1, b2, b3),
+            ) => a1 == b1 && a2 == b2 && a3 == b3,
+            (CustomRuleViolated(a1, a2), CustomRuleViolated(b1, b2))
+            | (AttributeMissing(a1, a2), AttributeMissing(b1, b2)) => {
+                a1 == b1 && a2 == b2
+            }
+            (ManualResolutionRequired(a), ManualResolutionRequired(b)) => {
+                a == b
+            }
             (ParsingFailed(e1), ParsingFailed(e2)) =>
             // Compare on the formatted error message.
             {
@@ -188,6 +463,39 @@ impl PartialEq for ViolationKind {
     }
 }
 
+/// Removes the indentation shared by all non-empty lines of `text`.
+fn dedent(text: &str) -> String {
+    let indentation = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    text.trim_end()
+        .lines()
+        .map(|l| l.get(indentation..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Describes a description comment of the test `name` that is missing or isn't
+/// formatted.
+fn describe_mismatch(
+    name: &str,
+    found: Option<&str>,
+    expected: &str,
+) -> String {
+    match found {
+        Some(found) => format!(
+            r#"comment "// {found}" of test "{name}" should read "// {expected}""#
+        ),
+        None => {
+            format!(r#"test "{name}" is missing the comment "// {expected}""#)
+        }
+    }
+}
+
 /// Formats frontend errors into human-readable messages.
 ///
 /// # Arguments
@@ -208,6 +516,15 @@ fn format_frontend_error(error: &anyhow::Error) -> String {
         error.downcast_ref::<crate::hir::combiner::Error>()
     {
         format!("an error occurred while parsing the tree: {}", error.kind())
+    } else if let Some(errors) =
+        error.downcast_ref::<bulloak_syntax::semantics::Errors>()
+    {
+        let kinds: Vec<String> =
+            errors.0.iter().map(|error| error.kind().to_string()).collect();
+        format!(
+            "an error occurred while parsing the tree: {}",
+            kinds.join("; ")
+        )
     } else if error.downcast_ref::<bulloak_syntax::semantics::Error>().is_some()
     {
         "at least one semantic error occurred while parsing the tree".to_owned()
@@ -1,6 +1,15 @@
 //! `bulloak-core`'s configuration.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use bulloak_syntax::{
+    tokenizer::DEFAULT_TAB_WIDTH,
+    utils::{
+        comment_block, front_matter, keeps_descriptions_verbatim,
+        pragma_version, render_template,
+    },
+    Keywords, ParseOptions,
+};
 
 use crate::constants::DEFAULT_SOL_VERSION;
 
@@ -10,17 +19,85 @@ use crate::constants::DEFAULT_SOL_VERSION;
 /// struct only if it makes sense. For example, the `--fix` flag, doesn't make
 /// sense in the context of `bulloak-core`.
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
     /// The set of tree files to work on.
     pub files: Vec<PathBuf>,
     /// Whether to emit modifiers.
     pub skip_modifiers: bool,
     /// Sets a Solidity version for the test contracts.
+    ///
+    /// A `// pragma solidity <version>;` comment at the top of a `.tree` file
+    /// takes precedence, see [`Config::for_spec`].
     pub solidity_version: String,
     /// Whether to add `vm.skip(true)` at the beginning of each test.
     pub emit_vm_skip: bool,
     /// Whether to capitalize and punctuate branch descriptions.
     pub format_descriptions: bool,
+    /// Whether to emit commented-out assertions for actions like `it should
+    /// return X` or `it should emit Y`.
+    pub emit_assertion_stubs: bool,
+    /// Whether to add `vm.expectEmit();` and a commented-out event to tests of
+    /// actions like `it should emit Y`, and check that such tests call
+    /// `vm.expectEmit`.
+    pub emit_expect_emit: bool,
+    /// Whether to add a comment summarizing the branch path above each test,
+    /// e.g. `// Given paused › When caller is owner`.
+    pub emit_branch_summary: bool,
+    /// The name of the contract to check in the Solidity file.
+    ///
+    /// When unset, the contract named after the tree's root is checked,
+    /// falling back to the first contract in the file.
+    pub contract: Option<String>,
+    /// Whether to prefix test names with their zero-padded index in the spec,
+    /// e.g. `test_001_ShouldNeverRevert`.
+    pub ordering_prefix: bool,
+    /// A comment added to the body of every test, e.g.
+    /// `TODO(bulloak): implement {title}`.
+    ///
+    /// `{title}` is replaced with the title of the branch the test covers and
+    /// `{test}` with the name of the test function. `{tree}` is replaced with
+    /// the path of the tree file by [`Config::for_tree`].
+    pub todo_marker: Option<String>,
+    /// A comment to add below the license of generated files, e.g.
+    /// `Generated by bulloak v{version} from {tree}`.
+    ///
+    /// `{version}` is replaced with the version of `bulloak` and `{tree}` with
+    /// the path of the tree file by [`Config::for_tree`].
+    pub header: Option<String>,
+    /// The number of columns a tab stands for in `.tree` files.
+    pub tab_width: usize,
+    /// Extra keywords that start conditions and actions, e.g. to write specs
+    /// in other languages.
+    pub keywords: Keywords,
+    /// Whether `[only]` tags in trees are an error, instead of narrowing the
+    /// trees down to the actions they focus.
+    pub forbid_only: bool,
+    /// Extra imports to add to the generated files, e.g. the project's
+    /// contracts under test.
+    pub imports: Vec<SolidityImport>,
+    /// Prefixes of import paths to replace, as `(prefix, replacement)` pairs,
+    /// like Foundry's remappings.
+    ///
+    /// These apply to every import of the generated files, including the
+    /// `forge-std` one.
+    pub remappings: Vec<(String, String)>,
+    /// Code to add at the start of the tests of branches with a given tag, as
+    /// `(tag, code)` pairs, e.g. `("fork",
+    /// "vm.createSelectFork(\"mainnet\");")`.
+    ///
+    /// A test gets the snippets of the tags of its condition, of the
+    /// conditions above it and of its actions.
+    pub snippets: Vec<(String, String)>,
+    /// The metadata in the front-matter of the spec, e.g. its author or
+    /// ticket, emitted as `///` tags on the test contract.
+    ///
+    /// Filled in from `// key: value` lines at the top of a `.tree` file by
+    /// [`Config::for_spec`].
+    pub metadata: Vec<(String, String)>,
+    /// Whether `check` expects the `testFork_` tests of branches tagged
+    /// `[fork]`, which it ignores by default.
+    pub include_fork: bool,
 }
 
 impl Default for Config {
@@ -31,6 +108,174 @@ impl Default for Config {
             emit_vm_skip: false,
             skip_modifiers: false,
             format_descriptions: false,
+            emit_assertion_stubs: false,
+            emit_expect_emit: false,
+            emit_branch_summary: false,
+            contract: None,
+            ordering_prefix: false,
+            todo_marker: None,
+            header: None,
+            tab_width: DEFAULT_TAB_WIDTH,
+            keywords: Keywords::new(),
+            forbid_only: false,
+            imports: Vec::new(),
+            remappings: Vec::new(),
+            snippets: Vec::new(),
+            metadata: Vec::new(),
+            include_fork: false,
         }
     }
 }
+
+impl Config {
+    /// Returns this configuration specialized for the tree at `tree`.
+    ///
+    /// This fills in the `{tree}` placeholder of the TODO marker and the
+    /// header.
+    #[must_use]
+    pub fn for_tree(&self, tree: &Path) -> Self {
+        let tree = tree.display().to_string();
+        let values = [("tree", tree.as_str())];
+        let todo_marker = self
+            .todo_marker
+            .as_ref()
+            .map(|marker| render_template(marker, &values));
+        let header =
+            self.header.as_ref().map(|header| render_template(header, &values));
+        Self { todo_marker, header, ..self.clone() }
+    }
+
+    /// Returns this configuration specialized for the spec in `text`.
+    ///
+    /// A `// pragma solidity <version>;` comment among the comments at the top
+    /// of the spec overrides the Solidity version, e.g. for repos that mix
+    /// `0.8.x` and legacy contracts. `// key: value` lines at the top of the
+    /// spec are kept as metadata, except for `// format-descriptions: off`,
+    /// which keeps the comments of the spec verbatim.
+    #[must_use]
+    pub fn for_spec(&self, text: &str) -> Self {
+        let solidity_version = pragma_version(text)
+            .map_or_else(|| self.solidity_version.clone(), str::to_owned);
+        let metadata = front_matter(text)
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+        let format_descriptions =
+            self.format_descriptions && !keeps_descriptions_verbatim(text);
+        Self { solidity_version, format_descriptions, metadata, ..self.clone() }
+    }
+
+    /// The header comment block to put at the top of generated files, if
+    /// any.
+    #[must_use]
+    pub fn header(&self) -> Option<String> {
+        let header = self.header.as_ref()?;
+        Some(comment_block(&render_template(
+            header,
+            &[("version", env!("CARGO_PKG_VERSION"))],
+        )))
+    }
+
+    /// The options to parse `.tree` files with.
+    #[must_use]
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            tab_width: self.tab_width,
+            keywords: self.keywords.clone(),
+            forbid_only: self.forbid_only,
+        }
+    }
+}
+
+/// An import of a generated Solidity file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolidityImport {
+    /// The symbols imported by name, e.g. `Test` in
+    /// `import {Test} from "forge-std/Test.sol";`.
+    ///
+    /// The whole file is imported when empty.
+    pub symbols: Vec<String>,
+    /// The path of the imported file, before any remapping.
+    pub path: String,
+}
+
+impl From<&str> for SolidityImport {
+    /// Parses an import written as `Symbol, Other=path/to/File.sol`, or as
+    /// `path/to/File.sol` to import the whole file.
+    fn from(value: &str) -> Self {
+        match value.split_once('=') {
+            Some((symbols, path)) => Self {
+                symbols: symbols
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|symbol| !symbol.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+                path: path.trim().to_owned(),
+            },
+            None => Self { symbols: Vec::new(), path: value.trim().to_owned() },
+        }
+    }
+}
+
+impl SolidityImport {
+    /// Returns this import with its path remapped by the longest matching
+    /// prefix in `remappings`, if any.
+    #[must_use]
+    pub fn remapped(&self, remappings: &[(String, String)]) -> Self {
+        let path = remappings
+            .iter()
+            .filter(|(prefix, _)| self.path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or_else(
+                || self.path.clone(),
+                |(prefix, replacement)| {
+                    format!("{replacement}{}", &self.path[prefix.len()..])
+                },
+            );
+        Self { symbols: self.symbols.clone(), path }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, SolidityImport};
+
+    #[test]
+    fn overrides_the_solidity_version() {
+        let cfg = Config::default();
+        assert_eq!(cfg.for_spec("Foo").solidity_version, "0.8.0");
+        let text = "// pragma solidity 0.7.6;\nFoo";
+        assert_eq!(cfg.for_spec(text).solidity_version, "0.7.6");
+        let text = "// author: alice\n// pragma solidity ^0.7.6;\n\nFoo";
+        assert_eq!(cfg.for_spec(text).solidity_version, "^0.7.6");
+    }
+
+    #[test]
+    fn keeps_the_front_matter() {
+        let cfg = Config::default();
+        let text = "// pragma solidity 0.7.6;\n// author: alice\nFoo";
+        let cfg = cfg.for_spec(text);
+        assert_eq!(cfg.solidity_version, "0.7.6");
+        assert_eq!(
+            cfg.metadata,
+            vec![("author".to_owned(), "alice".to_owned())]
+        );
+    }
+
+    #[test]
+    fn parses_and_remaps_imports() {
+        let import = SolidityImport::from("Test, StdCheats=forge-std/Test.sol");
+        assert_eq!(import.symbols, vec!["Test", "StdCheats"]);
+        assert_eq!(import.path, "forge-std/Test.sol");
+        assert!(SolidityImport::from("src/Vault.sol").symbols.is_empty());
+
+        let remappings = vec![
+            ("forge-std/".to_owned(), "lib/forge-std/src/".to_owned()),
+            ("forge-std/Test".to_owned(), "std/Test".to_owned()),
+        ];
+        assert_eq!(import.remapped(&remappings).path, "std/Test.sol");
+        let vault = SolidityImport::from("src/Vault.sol");
+        assert_eq!(vault.remapped(&remappings), vault);
+    }
+}
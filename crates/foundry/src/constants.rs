@@ -4,6 +4,9 @@
 pub(crate) const INTERNAL_DEFAULT_INDENTATION: usize = 2;
 /// Default solidity version used internally.
 pub const DEFAULT_SOL_VERSION: &str = "0.8.0";
+/// The directive that sets the Solidity version of a single `.tree` file,
+/// e.g. `// pragma solidity ^0.7.6;`.
+pub use bulloak_syntax::utils::PRAGMA_DIRECTIVE;
 /// The separator used between contract name and function name when parsing
 /// `.tree` files with multiple trees.
 pub const CONTRACT_IDENTIFIER_SEPARATOR: &str = "::";
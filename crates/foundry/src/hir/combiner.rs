@@ -209,8 +209,8 @@ fn prefix_test(child: Hir, prefix: &str) -> Hir {
     };
 
     if test_or_modifier.is_function() {
-        test_or_modifier.identifier =
-            prefix_test_with(&test_or_modifier.identifier, prefix);
+        let identifier = prefix_test_with(&test_or_modifier.identifier, prefix);
+        test_or_modifier.rename(identifier);
     }
 
     Hir::Function(test_or_modifier)
@@ -303,6 +303,12 @@ mod tests {
             span,
             modifiers,
             children,
+            summary: None,
+            anchor: None,
+            skipped: false,
+            fork: false,
+            visibility: hir::Visibility::External,
+            is_virtual: false,
         })
     }
 
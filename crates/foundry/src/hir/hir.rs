@@ -47,6 +47,31 @@ impl Hir {
     pub fn is_contract(&self) -> bool {
         matches!(self, Hir::Contract(_))
     }
+
+    /// Whether this hir is or contains a statement that needs forge-std: a
+    /// `vm.skip(true);` or `vm.expectEmit();` call, or a snippet, which is
+    /// setup code that typically uses cheatcodes.
+    #[must_use]
+    pub fn uses_forge_std(&self) -> bool {
+        match self {
+            Hir::Root(root) => root.children.iter().any(Hir::uses_forge_std),
+            Hir::Contract(contract) => {
+                contract.children.iter().any(Hir::uses_forge_std)
+            }
+            Hir::Function(function) => {
+                function.children.iter().flatten().any(Hir::uses_forge_std)
+            }
+            Hir::Statement(statement) => {
+                matches!(
+                    statement.ty,
+                    StatementType::VmSkip
+                        | StatementType::ExpectEmit
+                        | StatementType::Snippet(_)
+                )
+            }
+            Hir::Comment(_) => false,
+        }
+    }
 }
 
 impl Default for Hir {
@@ -102,6 +127,16 @@ impl Default for FunctionTy {
     }
 }
 
+/// The visibility of a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    /// `external`
+    #[default]
+    External,
+    /// `public`, for tests of branches tagged `[public]`.
+    Public,
+}
+
 /// A function definition HIR node.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct FunctionDefinition {
@@ -119,6 +154,24 @@ pub struct FunctionDefinition {
     pub modifiers: Option<Vec<Identifier>>,
     /// The children HIR nodes of this node.
     pub children: Option<Vec<Hir>>,
+    /// A one-line summary of the branch this function covers, emitted as a
+    /// comment above it, e.g. `Given paused › When caller is owner`.
+    pub summary: Option<String>,
+    /// The explicit id of the node that generated this function, emitted as
+    /// a `// #anchor` comment above it.
+    pub anchor: Option<String>,
+    /// Whether the branch this function covers is tagged `[skip]`, in which
+    /// case the test must call `vm.skip(true)`.
+    pub skipped: bool,
+    /// Whether the branch this function covers is tagged `[fork]`, in which
+    /// case the test is named `testFork_`.
+    pub fork: bool,
+    /// The visibility of this function, `public` if the branch it covers is
+    /// tagged `[public]`.
+    pub visibility: Visibility,
+    /// Whether the condition this modifier stands for is tagged `[virtual]`,
+    /// in which case the modifier is `virtual`.
+    pub is_virtual: bool,
 }
 
 impl FunctionDefinition {
@@ -133,6 +186,21 @@ impl FunctionDefinition {
     pub fn is_function(&self) -> bool {
         matches!(self.ty, FunctionTy::Function)
     }
+
+    /// Renames this function.
+    ///
+    /// TODO markers that mention the old name are updated to the new one.
+    pub fn rename(&mut self, identifier: String) {
+        for child in self.children.iter_mut().flatten() {
+            if let Hir::Statement(Statement {
+                ty: StatementType::Todo(marker),
+            }) = child
+            {
+                *marker = marker.replace(&self.identifier, &identifier);
+            }
+        }
+        self.identifier = identifier;
+    }
 }
 
 /// A comment node.
@@ -147,6 +215,20 @@ pub struct Comment {
 pub enum StatementType {
     /// The `vm.skip(true);` statement.
     VmSkip,
+    /// The `vm.expectEmit();` statement.
+    ExpectEmit,
+    /// A commented-out assertion skeleton, e.g. `// assertEq(result, x);`.
+    ///
+    /// Holds the code of the assertion, without the leading `//`.
+    AssertionStub(String),
+    /// A marker comment for a test that still has to be implemented, e.g.
+    /// `// TODO: implement`.
+    ///
+    /// Holds the text of the marker, without the leading `//`.
+    Todo(String),
+    /// A line of code configured for a tag of the branch, e.g.
+    /// `vm.createSelectFork("mainnet");` for `[fork]`.
+    Snippet(String),
 }
 
 /// A statement node.
@@ -24,14 +24,72 @@ use crate::{config::Config, scaffold::modifiers::ModifierDiscoverer};
 ///
 /// Returns a `Result` containing the translated `Hir` or a `TranslationError`.
 pub fn translate(text: &str, cfg: &Config) -> anyhow::Result<Hir> {
-    let asts = bulloak_syntax::parse(text)?;
+    let asts = bulloak_syntax::cache::parse_with(text, &cfg.parse_options())?;
 
-    if asts.len() == 1 {
-        return Ok(translate_one(&asts[0], cfg));
+    let mut hir = if asts.len() == 1 {
+        translate_one(&asts[0], cfg)
+    } else {
+        let hirs = asts.into_iter().map(|ast| translate_one(&ast, cfg));
+        combiner::Combiner::new().combine(text, hirs)?
+    };
+
+    if cfg.ordering_prefix {
+        prefix_with_index(&mut hir);
+    }
+    prefix_fork_tests(&mut hir);
+
+    Ok(hir)
+}
+
+/// Names fork tests `testFork_` instead of `test_`, which is how Foundry
+/// tells them apart, e.g. to only run them with an RPC URL at hand.
+fn prefix_fork_tests(hir: &mut Hir) {
+    let Hir::Root(root) = hir else { return };
+    let functions = root
+        .children
+        .iter_mut()
+        .filter_map(|child| match child {
+            Hir::Contract(contract) => Some(&mut contract.children),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|child| match child {
+            Hir::Function(f) if f.is_function() && f.fork => Some(f),
+            _ => None,
+        });
+
+    for function in functions {
+        if let Some(suffix) = function.identifier.strip_prefix("test_") {
+            let identifier = format!("testFork_{suffix}");
+            function.rename(identifier);
+        }
     }
+}
 
-    let hirs = asts.into_iter().map(|ast| translate_one(&ast, cfg));
-    Ok(combiner::Combiner::new().combine(text, hirs)?)
+/// Prefixes the name of every test function with its zero-padded index, e.g.
+/// `test_001_ShouldNeverRevert`.
+///
+/// Tests are numbered in the order they appear in the spec, so that tooling
+/// which sorts tests alphabetically keeps them in spec order.
+fn prefix_with_index(hir: &mut Hir) {
+    let Hir::Root(root) = hir else { return };
+    let contracts = root.children.iter_mut().filter_map(|child| match child {
+        Hir::Contract(contract) => Some(contract),
+        _ => None,
+    });
+
+    for contract in contracts {
+        let functions =
+            contract.children.iter_mut().filter_map(|child| match child {
+                Hir::Function(f) if f.is_function() => Some(f),
+                _ => None,
+            });
+        for (i, function) in functions.enumerate() {
+            let suffix = function.identifier.trim_start_matches("test_");
+            let identifier = format!("test_{:03}_{suffix}", i + 1);
+            function.rename(identifier);
+        }
+    }
 }
 
 /// Generates the HIR for a single AST.
@@ -3,7 +3,10 @@
 use std::collections::HashSet;
 
 use bulloak_syntax::{
-    utils::{sanitize, upper_first_letter},
+    utils::{
+        branch_hash, branch_summary, expectation, render_template, sanitize,
+        to_pascal_case, upper_first_letter, Expectation,
+    },
     Action, Ast, Condition, Description, Visitor,
 };
 use indexmap::IndexMap;
@@ -13,6 +16,46 @@ use crate::{
     hir::{self, Hir},
 };
 
+/// The tag that marks a branch as skipped, so that its tests call
+/// `vm.skip(true)` whether or not `vm.skip` is emitted everywhere.
+pub(crate) const SKIP_TAG: &str = "skip";
+
+/// Whether `tags` include the [`SKIP_TAG`].
+fn is_skipped(tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag == SKIP_TAG)
+}
+
+/// The tag that marks a branch as a fork test, named `testFork_` as Foundry
+/// does.
+pub(crate) const FORK_TAG: &str = "fork";
+
+/// Whether `tags` include the [`FORK_TAG`].
+fn is_fork(tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag == FORK_TAG)
+}
+
+/// The tag that makes the tests of a branch `public` rather than `external`,
+/// e.g. so that derived test contracts can call them.
+pub(crate) const PUBLIC_TAG: &str = "public";
+
+/// Whether `tags` include the [`PUBLIC_TAG`].
+fn is_public(tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag == PUBLIC_TAG)
+}
+
+/// The visibility of the tests of a branch with `tags`.
+fn visibility(tags: &[String]) -> hir::Visibility {
+    if is_public(tags) {
+        hir::Visibility::Public
+    } else {
+        hir::Visibility::External
+    }
+}
+
+/// The tag that makes the modifier of a condition `virtual`, so that derived
+/// test contracts can override it.
+pub(crate) const VIRTUAL_TAG: &str = "virtual";
+
 /// Used in `make_unique_name` to signify joining ancestors with an underscore
 /// or something else.
 #[derive(Debug, Clone, Copy)]
@@ -61,6 +104,7 @@ impl Translator {
 }
 
 /// The internal implementation of the Translator.
+#[allow(clippy::struct_excessive_bools)]
 struct TranslatorI<'a> {
     /// A stack of modifiers that will be applied to the
     /// currently visited function.
@@ -80,6 +124,28 @@ struct TranslatorI<'a> {
     modifiers: &'a IndexMap<String, String>,
     /// Whether to add `vm.skip(true)` at the beginning of each test.
     with_vm_skip: bool,
+    /// Whether to add commented-out assertions derived from action titles.
+    with_assertion_stubs: bool,
+    /// Whether to add `vm.expectEmit();` to tests of actions that emit an
+    /// event.
+    with_expect_emit: bool,
+    /// The marker comment to add to every test, if any.
+    todo_marker: Option<String>,
+    /// Whether to summarize the branch path of each test in a comment.
+    with_branch_summary: bool,
+    /// The code to add to the tests of branches with a given tag, as `(tag,
+    /// code)` pairs.
+    snippets: &'a [(String, String)],
+    /// The titles of the conditions leading to the currently visited node.
+    branch: Vec<String>,
+    /// The tags of the conditions leading to the currently visited node.
+    tags: Vec<String>,
+    /// How many of the conditions leading to the currently visited node are
+    /// tagged `[skip]`.
+    skip_depth: usize,
+    /// How many of the conditions leading to the currently visited node are
+    /// tagged `[fork]`.
+    fork_depth: usize,
     /// Keep track of the generated functions so far.
     ///
     /// This is used to make sure only unique function are generated.
@@ -90,12 +156,21 @@ struct TranslatorI<'a> {
 
 impl<'a> TranslatorI<'a> {
     /// Creates a new internal translator.
-    fn new(modifiers: &'a IndexMap<String, String>, cfg: &Config) -> Self {
+    fn new(modifiers: &'a IndexMap<String, String>, cfg: &'a Config) -> Self {
         let with_vm_skip = cfg.emit_vm_skip;
         Self {
             modifier_stack: Vec::new(),
             modifiers,
             with_vm_skip,
+            with_assertion_stubs: cfg.emit_assertion_stubs,
+            with_expect_emit: cfg.emit_expect_emit,
+            todo_marker: cfg.todo_marker.clone(),
+            with_branch_summary: cfg.emit_branch_summary,
+            snippets: &cfg.snippets,
+            branch: Vec::new(),
+            tags: Vec::new(),
+            skip_depth: 0,
+            fork_depth: 0,
             used_fns: HashSet::new(),
             seen_modifiers: HashSet::new(),
         }
@@ -113,13 +188,171 @@ impl<'a> TranslatorI<'a> {
         std::mem::take(&mut hirs[0])
     }
 
+    /// Whether the test of `condition` is skipped, i.e. whether the condition,
+    /// one of its ancestors or one of its actions is tagged `[skip]`.
+    fn is_skipped(&self, condition: &Condition) -> bool {
+        self.skip_depth > 0
+            || condition.children.iter().any(
+                |child| matches!(child, Ast::Action(a) if is_skipped(&a.tags)),
+            )
+    }
+
+    /// Whether the test of `condition` is a fork test, i.e. whether the
+    /// condition, one of its ancestors or one of its actions is tagged
+    /// `[fork]`.
+    fn is_fork(&self, condition: &Condition) -> bool {
+        self.fork_depth > 0
+            || condition.children.iter().any(
+                |child| matches!(child, Ast::Action(a) if is_fork(&a.tags)),
+            )
+    }
+
+    /// The visibility of the test of `condition`, `public` if the condition,
+    /// one of its ancestors or one of its actions is tagged `[public]`.
+    fn visibility(&self, condition: &Condition) -> hir::Visibility {
+        let public = condition
+            .children
+            .iter()
+            .any(|child| matches!(child, Ast::Action(a) if is_public(&a.tags)));
+        if public {
+            hir::Visibility::Public
+        } else {
+            visibility(&self.tags)
+        }
+    }
+
+    /// Builds the snippets of the tags of the currently visited branch and of
+    /// `tags`, as one statement per line of code.
+    fn snippets<'t>(
+        &self,
+        tags: impl IntoIterator<Item = &'t String>,
+    ) -> Vec<Hir> {
+        let tags: Vec<&String> = tags.into_iter().collect();
+        self.snippets
+            .iter()
+            .filter(|(tag, _)| self.tags.contains(tag) || tags.contains(&tag))
+            .flat_map(|(_, code)| code.lines().map(str::trim))
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                Hir::Statement(hir::Statement {
+                    ty: hir::StatementType::Snippet(line.to_owned()),
+                })
+            })
+            .collect()
+    }
+
+    /// Builds the TODO marker of the test `test` covering the branch titled
+    /// `title`, if a marker is configured.
+    fn todo(&self, title: &str, test: &str) -> Option<Hir> {
+        let marker = self.todo_marker.as_ref()?;
+        let marker =
+            render_template(marker, &[("title", title), ("test", test)]);
+        Some(Hir::Statement(hir::Statement {
+            ty: hir::StatementType::Todo(marker),
+        }))
+    }
+
+    /// Builds the unique name of the test of `condition`.
+    ///
+    /// The explicit name of its first action that has one, e.g.
+    /// `@name=test_revert_zero_amount`, is used as is.
+    fn test_name(&mut self, condition: &Condition) -> String {
+        let name = condition.children.iter().find_map(|child| match child {
+            Ast::Action(action) => action.name.clone(),
+            _ => None,
+        });
+        if let Some(name) = name {
+            self.used_fns.insert(name.clone());
+            return name;
+        }
+
+        // If the only action is `it should revert`, we slightly change the
+        // function name to reflect this.
+        let is_revert = first_action_reverts(condition);
+
+        let mut words = condition.title.split_whitespace();
+        // It is fine to unwrap because conditions have at least one word in
+        // them.
+        let keyword = upper_first_letter(words.next().unwrap());
+
+        let (prefix, test_name, joiner) = if is_revert {
+            // Map an iterator over the words of a condition to the test
+            // name.
+            //
+            // Example: [when, something, happens] -> WhenSomethingHappens
+            let test_name = words.fold(
+                String::with_capacity(condition.title.len() - keyword.len()),
+                |mut acc, w| {
+                    acc.reserve(w.len() + 1);
+                    acc.push_str(&upper_first_letter(w));
+                    acc
+                },
+            );
+
+            // The structure for a function name when it is a revert is:
+            //
+            // test_Revert[KEYWORD]_Description
+            //
+            // where `KEYWORD` is the starting word of the condition.
+            (format!("test_Revert{keyword}_"), test_name, Joiner::None)
+        } else {
+            // Map an iterator over the words of a condition to the test
+            // name.
+            //
+            // Example: [when, something, happens] -> WhenSomethingHappens
+            let test_name = words.fold(keyword, |mut acc, w| {
+                acc.reserve(w.len() + 1);
+                acc.push_str(&upper_first_letter(w));
+                acc
+            });
+
+            ("test_".to_owned(), test_name, Joiner::Underscore)
+        };
+        self.make_unique_name(&prefix, &test_name, joiner, Some(condition))
+    }
+
+    /// Builds the unique name of the test of a top-level `action`, unless it
+    /// has an explicit name.
+    fn action_test_name(&mut self, action: &Action) -> String {
+        if let Some(name) = &action.name {
+            self.used_fns.insert(name.clone());
+            return name.clone();
+        }
+
+        let words = action.title.split_whitespace();
+        let words = words.skip(1); // Removes "it" from the test name.
+
+        // Map an iterator over the words of an action to the test name.
+        //
+        // Example: [do, stuff] -> DoStuff
+        let test_name = words.fold(
+            String::with_capacity(action.title.len()),
+            |mut acc, w| {
+                acc.reserve(w.len() + 1);
+                acc.push_str(&upper_first_letter(w));
+                acc
+            },
+        );
+
+        // We need to sanitize here and not in a previous compiler phase
+        // because we want to emit the action as-is in a comment.
+        let test_name = sanitize(&test_name);
+        self.make_unique_name("test_", &test_name, Joiner::Underscore, None)
+    }
+
     /// Builds a unique function identifier by optionally prepending nearest
     /// ancestor modifiers (PascalCase) to the suffix until unique.
+    ///
+    /// If the name still clashes, the anchor of `branch` or a hash of its
+    /// titles is appended, so that the name doesn't shift when other branches
+    /// are added to the tree. A numeric suffix is the last resort, for
+    /// branches that are identical.
     fn make_unique_name(
         &mut self,
         prefix: &str,
         base_suffix: &str,
         joiner: Joiner,
+        branch: Option<&Condition>,
     ) -> String {
         // Try the base name first.
         let mut suffix = base_suffix.to_string();
@@ -139,7 +372,23 @@ impl<'a> TranslatorI<'a> {
             }
         }
 
-        // Still a collision? Add a numeric suffix.
+        // Still a collision? Tell the branch apart by its anchor or content.
+        if let Some(branch) = branch {
+            let id = match &branch.anchor {
+                Some(anchor) => {
+                    to_pascal_case(&anchor.replace(['-', '_'], " "))
+                }
+                None => branch_hash(branch),
+            };
+            suffix.push('_');
+            suffix.push_str(&sanitize(&id));
+            full = format!("{prefix}{suffix}");
+            if self.used_fns.insert(full.clone()) {
+                return full;
+            }
+        }
+
+        // The branches are identical, so add a numeric suffix.
         let mut n = 2;
         loop {
             let attempt = format!("{prefix}{suffix}{joiner}{n}");
@@ -172,37 +421,16 @@ impl<'a> Visitor for TranslatorI<'a> {
                 }
                 // Found a top-level action. This corresponds to a function.
                 Ast::Action(action) => {
-                    let words = action.title.split_whitespace();
-                    let words = words.skip(1); // Removes "it" from the test name.
-
-                    // Map an iterator over the words of an action to the test
-                    // name.
-                    //
-                    // Example: [do, stuff] -> DoStuff
-                    let test_name = words.fold(
-                        String::with_capacity(action.title.len()),
-                        |mut acc, w| {
-                            acc.reserve(w.len() + 1);
-                            acc.push_str(&upper_first_letter(w));
-                            acc
-                        },
-                    );
-
-                    // We need to sanitize here and not in a previous compiler
-                    // phase because we want to emit the action as-is in a
-                    // comment.
-                    let test_name = sanitize(&test_name);
-                    let test_name = self.make_unique_name(
-                        "test_",
-                        &test_name,
-                        Joiner::Underscore,
-                    );
-
-                    let mut hirs = self.visit_action(action)?;
+                    let test_name = self.action_test_name(action);
+
+                    let mut hirs = self.snippets(&action.tags);
+                    hirs.append(&mut self.visit_action(action)?);
+                    hirs.extend(self.todo(&action.title, &test_name));
 
                     // Include any optional statement for the first function
                     // node.
-                    if self.with_vm_skip {
+                    let skipped = is_skipped(&action.tags);
+                    if self.with_vm_skip || skipped {
                         hirs.push(Hir::Statement(hir::Statement {
                             ty: hir::StatementType::VmSkip,
                         }));
@@ -214,6 +442,12 @@ impl<'a> Visitor for TranslatorI<'a> {
                         span: action.span,
                         modifiers: None,
                         children: Some(hirs),
+                        summary: None,
+                        anchor: action.anchor.clone(),
+                        skipped,
+                        fork: is_fork(&action.tags),
+                        visibility: visibility(&action.tags),
+                        is_virtual: false,
                     });
                     contract_children.push(hir);
                 }
@@ -238,6 +472,11 @@ impl<'a> Visitor for TranslatorI<'a> {
         condition: &Condition,
     ) -> Result<Self::Output, Self::Error> {
         let mut children = Vec::new();
+        self.branch.push(condition.title.clone());
+        self.skip_depth += usize::from(is_skipped(&condition.tags));
+        self.fork_depth += usize::from(is_fork(&condition.tags));
+        let depth = self.tags.len();
+        self.tags.extend(condition.tags.iter().cloned());
 
         let action_count = condition
             .children
@@ -261,6 +500,15 @@ impl<'a> Visitor for TranslatorI<'a> {
                         span: condition.span,
                         modifiers: None,
                         children: None,
+                        summary: None,
+                        anchor: None,
+                        skipped: false,
+                        fork: false,
+                        visibility: hir::Visibility::External,
+                        is_virtual: condition
+                            .tags
+                            .iter()
+                            .any(|tag| tag == VIRTUAL_TAG),
                     });
                     children.push(hir);
                 }
@@ -270,67 +518,17 @@ impl<'a> Visitor for TranslatorI<'a> {
         // We first visit all actions in order to keep the functions
         // in the same order that they appear in the source .tree text.
         let mut actions = Vec::new();
+        let mut action_tags = Vec::new();
         for action in &condition.children {
             if let Ast::Action(action) = action {
                 actions.append(&mut self.visit_action(action)?);
+                action_tags.extend(&action.tags);
             }
         }
 
         // Add this condition's function definition if it has children actions.
         if !actions.is_empty() {
-            // If the only action is `it should revert`, we slightly change the
-            // function name to reflect this.
-            let is_revert = actions.first().is_some_and(|action| {
-                if let hir::Hir::Comment(comment) = action {
-                    let sanitized_lexeme =
-                        sanitize(&comment.lexeme.trim().to_lowercase());
-                    sanitized_lexeme == "it should revert"
-                } else {
-                    false
-                }
-            });
-
-            let mut words = condition.title.split_whitespace();
-            // It is fine to unwrap because conditions have at least one word in
-            // them.
-            let keyword = upper_first_letter(words.next().unwrap());
-
-            let function_name = if is_revert {
-                // Map an iterator over the words of a condition to the test
-                // name.
-                //
-                // Example: [when, something, happens] -> WhenSomethingHappens
-                let test_name = words.fold(
-                    String::with_capacity(
-                        condition.title.len() - keyword.len(),
-                    ),
-                    |mut acc, w| {
-                        acc.reserve(w.len() + 1);
-                        acc.push_str(&upper_first_letter(w));
-                        acc
-                    },
-                );
-
-                // The structure for a function name when it is a revert is:
-                //
-                // test_Revert[KEYWORD]_Description
-                //
-                // where `KEYWORD` is the starting word of the condition.
-                let prefix = format!("test_Revert{keyword}_");
-                self.make_unique_name(&prefix, &test_name, Joiner::None)
-            } else {
-                // Map an iterator over the words of a condition to the test
-                // name.
-                //
-                // Example: [when, something, happens] -> WhenSomethingHappens
-                let test_name = words.fold(keyword, |mut acc, w| {
-                    acc.reserve(w.len() + 1);
-                    acc.push_str(&upper_first_letter(w));
-                    acc
-                });
-
-                self.make_unique_name("test_", &test_name, Joiner::Underscore)
-            };
+            let function_name = self.test_name(condition);
 
             let modifiers = if self.modifier_stack.is_empty() {
                 None
@@ -340,8 +538,11 @@ impl<'a> Visitor for TranslatorI<'a> {
                 )
             };
 
+            actions.splice(0..0, self.snippets(action_tags));
+            actions.extend(self.todo(&condition.title, &function_name));
+
             // Add a `vm.skip(true);` at the start of the function.
-            if self.with_vm_skip {
+            if self.with_vm_skip || self.is_skipped(condition) {
                 actions.push(Hir::Statement(hir::Statement {
                     ty: hir::StatementType::VmSkip,
                 }));
@@ -353,6 +554,14 @@ impl<'a> Visitor for TranslatorI<'a> {
                 span: condition.span,
                 modifiers,
                 children: Some(actions),
+                summary: self
+                    .with_branch_summary
+                    .then(|| branch_summary(&self.branch)),
+                anchor: condition.anchor.clone(),
+                skipped: self.is_skipped(condition),
+                fork: self.is_fork(condition),
+                visibility: self.visibility(condition),
+                is_virtual: false,
             });
             children.push(hir);
         }
@@ -367,6 +576,10 @@ impl<'a> Visitor for TranslatorI<'a> {
         if condition.children.len() != action_count {
             self.modifier_stack.pop();
         }
+        self.skip_depth -= usize::from(is_skipped(&condition.tags));
+        self.fork_depth -= usize::from(is_fork(&condition.tags));
+        self.tags.truncate(depth);
+        self.branch.pop();
 
         Ok(children)
     }
@@ -382,11 +595,24 @@ impl<'a> Visitor for TranslatorI<'a> {
             }
         }
 
-        Ok(std::iter::once(hir::Hir::Comment(hir::Comment {
-            lexeme: action.title.clone(),
-        }))
-        .chain(descriptions)
-        .collect())
+        let stubs = match expectation(&action.title) {
+            Some(Expectation::Emit(event)) if self.with_expect_emit => {
+                expect_emit(&event)
+            }
+            _ if self.with_assertion_stubs => assertion_stubs(&action.title),
+            _ => vec![],
+        };
+
+        // Anchors are kept in comments so that tests can be traced back to
+        // their action even after its title changes.
+        let lexeme = match &action.anchor {
+            Some(anchor) => format!("{} #{anchor}", action.title),
+            None => action.title.clone(),
+        };
+        Ok(std::iter::once(hir::Hir::Comment(hir::Comment { lexeme }))
+            .chain(descriptions)
+            .chain(stubs)
+            .collect())
     }
 
     fn visit_description(
@@ -399,6 +625,58 @@ impl<'a> Visitor for TranslatorI<'a> {
     }
 }
 
+/// Whether the first action under `condition` is `It should revert`.
+///
+/// This looks at the title rather than the emitted comment, which also holds
+/// the anchor of the action.
+fn first_action_reverts(condition: &Condition) -> bool {
+    condition
+        .children
+        .iter()
+        .find_map(|child| match child {
+            Ast::Action(action) => Some(action),
+            _ => None,
+        })
+        .is_some_and(|action| {
+            sanitize(&action.title.trim().to_lowercase()) == "it should revert"
+        })
+}
+
+/// Builds the commented-out assertions for the action titled `title`.
+fn assertion_stubs(title: &str) -> Vec<Hir> {
+    let stubs = match expectation(title) {
+        Some(Expectation::Return(value)) => {
+            vec![format!("assertEq(result, {value});")]
+        }
+        Some(Expectation::Emit(event)) => {
+            vec!["vm.expectEmit();".to_owned(), format!("emit {event}();")]
+        }
+        None => vec![],
+    };
+
+    stubs
+        .into_iter()
+        .map(|stub| {
+            Hir::Statement(hir::Statement {
+                ty: hir::StatementType::AssertionStub(stub),
+            })
+        })
+        .collect()
+}
+
+/// Builds the `vm.expectEmit();` call for an action that emits `event`,
+/// followed by the event as a commented-out placeholder.
+fn expect_emit(event: &str) -> Vec<Hir> {
+    let placeholder = format!("emit {event}(/* arguments */);");
+    [
+        hir::StatementType::ExpectEmit,
+        hir::StatementType::AssertionStub(placeholder),
+    ]
+    .into_iter()
+    .map(|ty| Hir::Statement(hir::Statement { ty }))
+    .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -442,6 +720,12 @@ mod tests {
             span,
             modifiers,
             children,
+            summary: None,
+            anchor: None,
+            skipped: false,
+            fork: false,
+            visibility: hir::Visibility::External,
+            is_virtual: false,
         })
     }
 
@@ -634,6 +918,20 @@ Foo_Test
         Ok(())
     }
 
+    #[test]
+    fn names_tests_explicitly() -> Result<()> {
+        let file_contents = r"Foo
+├── It should work. @name=test_Works
+└── when the amount is zero
+   ├── it should revert. @name=test_RevertZeroAmount
+   └── it should emit.
+";
+        let hir = translate(file_contents)?;
+        let names = collect_fn_names(&hir);
+        assert_eq!(names, vec!["test_Works", "test_RevertZeroAmount"]);
+        Ok(())
+    }
+
     #[test]
     fn disambiguates_deep_duplicates_using_multiple_ancestors() -> Result<()> {
         let file_contents = r#"Foo
@@ -666,6 +964,51 @@ Foo_Test
         Ok(())
     }
 
+    #[test]
+    fn disambiguates_identical_ancestors_stably() -> Result<()> {
+        let file_contents = r"Foo
+├── when parent
+│  └── when child
+│     └── it does one
+├── when parent
+│  └── when child #last
+│     └── it does two
+└── when parent
+   └── when child
+      └── it does three
+";
+        let names = collect_fn_names(&translate(file_contents)?);
+        let hash = names[2].rsplit('_').next().unwrap().to_owned();
+        assert_eq!(
+            names,
+            vec![
+                "test_WhenChild".to_owned(),
+                "test_WhenChild_WhenParent".to_owned(),
+                format!("test_WhenChild_WhenParent_{hash}"),
+            ]
+        );
+
+        // Adding a branch doesn't shift the name of the last one.
+        let file_contents = r"Foo
+├── when parent
+│  └── when child
+│     └── it does zero
+├── when parent
+│  └── when child
+│     └── it does one
+├── when parent
+│  └── when child #last
+│     └── it does two
+└── when parent
+   └── when child
+      └── it does three
+";
+        let names = collect_fn_names(&translate(file_contents)?);
+        assert_eq!(names[2], "test_WhenChild_WhenParent_Last");
+        assert_eq!(names[3], format!("test_WhenChild_WhenParent_{hash}"));
+        Ok(())
+    }
+
     #[test]
     fn disambiguates_revert_when_variants() -> Result<()> {
         let file_contents = r#"Foo
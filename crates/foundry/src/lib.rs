@@ -16,3 +16,22 @@ pub mod scaffold;
 pub mod sol;
 
 pub use check::violation::{self, Violation, ViolationKind};
+use hir::translator::{FORK_TAG, PUBLIC_TAG, SKIP_TAG, VIRTUAL_TAG};
+
+/// Returns what the Foundry backend supports.
+#[must_use]
+pub const fn capabilities() -> bulloak_syntax::Capabilities {
+    bulloak_syntax::Capabilities {
+        supports_fix: true,
+        supports_order_check: true,
+        supported_attributes: &[
+            bulloak_syntax::FOCUS_TAG,
+            bulloak_syntax::COMBINE_TAG,
+            SKIP_TAG,
+            FORK_TAG,
+            PUBLIC_TAG,
+            VIRTUAL_TAG,
+        ],
+        file_pattern: "{stem}.t.sol",
+    }
+}
@@ -1,7 +1,12 @@
 //! Helpers for normalizing scaffolded comments.
 
+use bulloak_syntax::utils::starts_with_code;
+
 /// Normalize a description by capitalizing its first alphabetic character and
 /// ensuring it ends with a dot, while preserving surrounding whitespace.
+///
+/// Descriptions that start with code, e.g. `` `transfer()` reverts ``, aren't
+/// capitalized.
 pub(crate) fn normalize(lexeme: &str) -> String {
     let (prefix, core, _suffix) = split_whitespace_affixes(lexeme);
 
@@ -9,39 +14,30 @@ pub(crate) fn normalize(lexeme: &str) -> String {
         return lexeme.to_string();
     }
 
-    let mut normalized = core.to_string();
-    capitalize_first_alpha(&mut normalized);
-    ensure_terminal_dot(&mut normalized);
-
-    format!("{prefix}{normalized}")
-}
-
-fn capitalize_first_alpha(s: &mut String) {
-    if let Some((idx, ch)) = s.char_indices().find(|(_, ch)| ch.is_alphabetic())
-    {
-        let uppercase = ch.to_uppercase().to_string();
-        s.replace_range(idx..idx + ch.len_utf8(), &uppercase);
-    }
-}
-
-fn ensure_terminal_dot(s: &mut String) {
-    let trimmed_len = s.trim_end().len();
-    if trimmed_len == 0 {
-        return;
-    }
-
-    let trimmed = &s[..trimmed_len];
-    if let Some((idx, ch)) = trimmed.char_indices().last() {
-        if ch == '.' {
-            return;
+    // Built in a single pass, since every action of a tree goes through here.
+    let mut normalized = String::with_capacity(prefix.len() + core.len() + 1);
+    normalized.push_str(prefix);
+    let mut capitalize = !starts_with_code(core);
+    for ch in core.chars() {
+        if capitalize && ch.is_alphabetic() {
+            normalized.extend(ch.to_uppercase());
+            capitalize = false;
+        } else {
+            normalized.push(ch);
         }
+    }
 
-        if matches!(ch, '!' | '?') {
-            s.replace_range(idx..idx + ch.len_utf8(), ".");
-        } else {
-            s.insert(idx + ch.len_utf8(), '.');
+    // `core` doesn't end with whitespace, so this is its terminal character.
+    match normalized.chars().next_back() {
+        Some('.') => {}
+        Some('!' | '?') => {
+            normalized.pop();
+            normalized.push('.');
         }
+        _ => normalized.push('.'),
     }
+
+    normalized
 }
 
 fn split_whitespace_affixes(s: &str) -> (&str, &str, &str) {
@@ -89,6 +85,16 @@ mod tests {
         assert_eq!(normalize("FOO"), "FOO.");
     }
 
+    #[test]
+    fn leaves_code_uncapitalized() {
+        assert_eq!(normalize("`transfer()` reverts"), "`transfer()` reverts.");
+        assert_eq!(normalize("  balance_of() grows!"), "  balance_of() grows.");
+        assert_eq!(
+            normalize("it calls `transfer()`"),
+            "It calls `transfer()`."
+        );
+    }
+
     #[test]
     fn handles_empty_core() {
         assert_eq!(normalize("   "), "   ");
@@ -109,10 +109,16 @@ impl EmitterI {
     ///    _;
     /// }
     /// ```
-    fn emit_modifier(&self, modifier: &str) -> String {
+    ///
+    /// Modifiers of conditions tagged `[virtual]` are `virtual`.
+    fn emit_modifier(&self, modifier: &hir::FunctionDefinition) -> String {
         let mut emitted = String::new();
         let indentation = self.emitter.indent();
-        emitted.push_str(&format!("{indentation}modifier {modifier}() {{\n"));
+        let name = &modifier.identifier;
+        let attributes = if modifier.is_virtual { " virtual" } else { "" };
+        emitted.push_str(&format!(
+            "{indentation}modifier {name}(){attributes} {{\n"
+        ));
         emitted.push_str(&format!("{}_;\n", indentation.repeat(2)));
         emitted.push_str(&format!("{indentation}}}\n"));
         emitted.push('\n');
@@ -124,10 +130,15 @@ impl EmitterI {
     ///
     /// This includes:
     /// - The function's name.
-    /// - The function's visibility.
+    /// - The function's visibility, `public` if its branch is tagged `[public]`
+    ///   and `external` otherwise.
     /// - Any modifiers that should be applied to the function.
     fn emit_fn_header(&self, function: &hir::FunctionDefinition) -> String {
         let mut emitted = String::new();
+        let visibility = match function.visibility {
+            hir::Visibility::Public => "public",
+            hir::Visibility::External => "external",
+        };
 
         let fn_indentation = self.emitter.indent();
         let fn_body_indentation = fn_indentation.repeat(2);
@@ -141,14 +152,16 @@ impl EmitterI {
                 )
                 .as_str(),
             );
-            emitted
-                .push_str(format!("{fn_body_indentation}external\n").as_str());
+            emitted.push_str(
+                format!("{fn_body_indentation}{visibility}\n").as_str(),
+            );
         } else {
             emitted.push_str(
                 format!("{}function {}()", fn_indentation, function.identifier)
                     .as_str(),
             );
-            emitted.push_str(" external");
+            emitted.push(' ');
+            emitted.push_str(visibility);
         }
 
         // Emit the modifiers that should be applied to this function.
@@ -236,8 +249,18 @@ impl Visitor for EmitterI {
         let mut emitted = String::new();
 
         if matches!(function.ty, hir::FunctionTy::Modifier) {
-            emitted.push_str(&self.emit_modifier(&function.identifier));
+            emitted.push_str(&self.emit_modifier(function));
         } else {
+            if let Some(ref summary) = function.summary {
+                let indentation = self.emitter.indent();
+                emitted
+                    .push_str(format!("{indentation}// {summary}\n").as_str());
+            }
+            if let Some(ref anchor) = function.anchor {
+                let indentation = self.emitter.indent();
+                emitted
+                    .push_str(format!("{indentation}// #{anchor}\n").as_str());
+            }
             let fn_header = self.emit_fn_header(function);
             emitted.push_str(&fn_header);
 
@@ -283,12 +306,24 @@ impl Visitor for EmitterI {
         let indentation = self.emitter.indent().repeat(2);
 
         // Match any supported statement to its string representation
-        match statement.ty {
+        match &statement.ty {
             hir::StatementType::VmSkip => {
                 emitted.push_str(
                     format!("{indentation}vm.skip(true);\n").as_str(),
                 );
             }
+            hir::StatementType::ExpectEmit => {
+                emitted.push_str(
+                    format!("{indentation}vm.expectEmit();\n").as_str(),
+                );
+            }
+            hir::StatementType::AssertionStub(stub)
+            | hir::StatementType::Todo(stub) => {
+                emitted.push_str(format!("{indentation}// {stub}\n").as_str());
+            }
+            hir::StatementType::Snippet(code) => {
+                emitted.push_str(format!("{indentation}{code}\n").as_str());
+            }
         }
 
         Ok(emitted)
@@ -488,6 +523,237 @@ contract FileTest {
         Ok(())
     }
 
+    #[test]
+    fn with_assertion_stubs() -> anyhow::Result<()> {
+        let file_contents = "FileTest\n├── it should emit a {Transfer} event\n└── when something happens\n   └── it should return the sum";
+        let cfg = Config { emit_assertion_stubs: true, ..Config::default() };
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract FileTest {
+  function test_ShouldEmitATransferEvent() external {
+    // it should emit a {Transfer} event
+    // vm.expectEmit();
+    // emit Transfer();
+  }
+
+  function test_WhenSomethingHappens() external {
+    // it should return the sum
+    // assertEq(result, the sum);
+  }
+}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_expect_emit() -> anyhow::Result<()> {
+        let file_contents = "FileTest\n├── it should emit a {Transfer} event\n└── when something happens\n   └── it should return the sum";
+        let cfg = Config {
+            emit_assertion_stubs: true,
+            emit_expect_emit: true,
+            ..Config::default()
+        };
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract FileTest {
+  function test_ShouldEmitATransferEvent() external {
+    // it should emit a {Transfer} event
+    vm.expectEmit();
+    // emit Transfer(/* arguments */);
+  }
+
+  function test_WhenSomethingHappens() external {
+    // it should return the sum
+    // assertEq(result, the sum);
+  }
+}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_todo_marker() -> anyhow::Result<()> {
+        let file_contents =
+            "FileTest\n└── when something happens\n   └── it should work";
+        let cfg = Config {
+            todo_marker: Some("TODO: {test} ({title}) in {tree}".to_owned()),
+            ..Config::default()
+        };
+        let cfg = cfg.for_tree(std::path::Path::new("file.tree"));
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract FileTest {
+  function test_WhenSomethingHappens() external {
+    // it should work
+    // TODO: test_WhenSomethingHappens (when something happens) in file.tree
+  }
+}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_ordering_prefix() -> anyhow::Result<()> {
+        let file_contents = "Foo::bar\n├── it should work\n└── when stuff\n    └── it should revert\n\nFoo::baz\n└── when stuff\n    └── it should revert";
+        let cfg = Config {
+            ordering_prefix: true,
+            todo_marker: Some("TODO: {test}".to_owned()),
+            ..Config::default()
+        };
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+  function test_001_Bar_ShouldWork() external {
+    // it should work
+    // TODO: test_001_Bar_ShouldWork
+  }
+
+  function test_002_Bar_RevertWhen_Stuff() external {
+    // it should revert
+    // TODO: test_002_Bar_RevertWhen_Stuff
+  }
+
+  function test_003_Baz_RevertWhen_Stuff() external {
+    // it should revert
+    // TODO: test_003_Baz_RevertWhen_Stuff
+  }
+}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_fork_tags() -> anyhow::Result<()> {
+        let file_contents = "Foo\n├── it should work\n├── it should read mainnet [fork]\n└── when bridged [fork]\n    └── it should revert";
+        let cfg = Config::default();
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+  function test_ShouldWork() external {
+    // it should work
+  }
+
+  function testFork_ShouldReadMainnet() external {
+    // it should read mainnet
+  }
+
+  function testFork_RevertWhen_Bridged() external {
+    // it should revert
+  }
+}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_visibility_tags() -> anyhow::Result<()> {
+        let file_contents = "Foo\n├── it should work [public]\n└── when paused [virtual]\n    ├── when bridged\n    │   └── it should revert\n    └── it should pause [public]";
+        let cfg = Config::default();
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+  function test_ShouldWork() public {
+    // it should work
+  }
+
+  modifier whenPaused() virtual {
+    _;
+  }
+
+  function test_WhenPaused()
+    public
+    whenPaused
+  {
+    // it should pause
+  }
+
+  function test_RevertWhen_Bridged()
+    external
+    whenPaused
+  {
+    // it should revert
+  }
+}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_branch_summary() -> anyhow::Result<()> {
+        let file_contents = "Foo\n├── it should work\n└── given paused\n    └── when caller is owner\n        └── it should revert";
+        let cfg = Config { emit_branch_summary: true, ..Config::default() };
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+  function test_ShouldWork() external {
+    // it should work
+  }
+
+  modifier givenPaused() {
+    _;
+  }
+
+  // Given paused › When caller is owner
+  function test_RevertWhen_CallerIsOwner()
+    external
+    givenPaused
+  {
+    // it should revert
+  }
+}"
+        );
+
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn with_vm_skip_top_level_statement() {
@@ -2,6 +2,8 @@
 //!
 //! This command scaffolds a Solidity file from a spec `.tree` file.
 
+use std::fmt::Write;
+
 use forge_fmt::fmt;
 
 use crate::{config::Config, hir::translate, sol};
@@ -14,13 +16,142 @@ pub mod modifiers;
 ///
 /// This function takes the content of a `.tree` file and a configuration,
 /// translates it to an intermediate representation, then to Solidity, and
-/// finally formats the resulting Solidity code.
+/// finally formats the resulting Solidity code. A `// pragma solidity
+/// <version>;` line at the top of the tree overrides the configured Solidity
+/// version, and `// key: value` lines become `///` tags on the contract.
 pub fn scaffold(text: &str, cfg: &Config) -> anyhow::Result<String> {
+    let cfg = &cfg.for_spec(text);
     let hir = translate(text, cfg)?;
     let pt = sol::Translator::new(cfg).translate(&hir);
     let source = sol::Formatter::new().emit(pt);
     let formatted =
         fmt(&source).expect("should format the emitted solidity code");
+    let formatted = document_contract(&formatted, &cfg.metadata);
+
+    Ok(match cfg.header() {
+        Some(header) => insert_header(&formatted, &header),
+        None => formatted,
+    })
+}
+
+/// Inserts `header` right below the license identifier of `source`, or at
+/// the top of the file if there's none.
+pub(crate) fn insert_header(source: &str, header: &str) -> String {
+    let at = if source.starts_with("// SPDX-License-Identifier:") {
+        source.find('\n').map_or(source.len(), |i| i + 1)
+    } else {
+        0
+    };
+
+    let mut output = String::with_capacity(source.len() + header.len());
+    output.push_str(&source[..at]);
+    output.push_str(header);
+    output.push_str(&source[at..]);
+    output
+}
+
+/// Adds a `///` tag for each piece of `metadata` above the contract
+/// definition in `source`.
+///
+/// `author` and `title` map to the `@author` and `@title` tags, any other key
+/// to a custom tag, e.g. `@custom:audit-scope` for `audit scope`.
+fn document_contract(source: &str, metadata: &[(String, String)]) -> String {
+    let Some(at) = source.find("\ncontract ").map(|i| i + 1) else {
+        return source.to_owned();
+    };
+
+    let mut output = String::with_capacity(source.len());
+    output.push_str(&source[..at]);
+    for (key, value) in metadata {
+        let tag = match key.to_lowercase().as_str() {
+            tag @ ("author" | "title") => tag.to_owned(),
+            key => {
+                let tag: String = key
+                    .chars()
+                    .map(|c| if c.is_ascii_lowercase() { c } else { '-' })
+                    .collect();
+                format!("custom:{}", tag.trim_matches('-'))
+            }
+        };
+        let _ = writeln!(output, "/// @{tag} {value}");
+    }
+    output.push_str(&source[at..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::scaffold;
+    use crate::config::Config;
+
+    #[test]
+    fn adds_the_header_below_the_license() {
+        let cfg = Config {
+            header: Some("Generated by bulloak from {tree}".to_owned()),
+            ..Config::default()
+        };
+        let cfg = cfg.for_tree(std::path::Path::new("Foo.tree"));
+        let emitted = scaffold("Foo\n└── It should work.", &cfg).unwrap();
+
+        assert_eq!(
+            emitted.lines().take(3).collect::<Vec<_>>(),
+            vec![
+                "// SPDX-License-Identifier: UNLICENSED",
+                "// Generated by bulloak from Foo.tree",
+                "pragma solidity 0.8.0;",
+            ]
+        );
+    }
+
+    #[test]
+    fn documents_the_contract_with_the_front_matter() {
+        let text =
+            "// author: Alice\n// Audit Scope: vault\nFoo\n└── It works.";
+        let emitted = scaffold(text, &Config::default()).unwrap();
+
+        assert_eq!(
+            emitted.lines().skip(3).take(3).collect::<Vec<_>>(),
+            vec![
+                "/// @author Alice",
+                "/// @custom:audit-scope vault",
+                "contract Foo {",
+            ]
+        );
+    }
+
+    #[test]
+    fn adds_the_snippets_of_tags() {
+        let cfg = Config {
+            snippets: vec![
+                (
+                    "fork".to_owned(),
+                    "vm.createSelectFork(\"mainnet\");".to_owned(),
+                ),
+                ("slow".to_owned(), "vm.pauseGasMetering();".to_owned()),
+            ],
+            ..Config::default()
+        };
+        let text = r"Foo
+├── It should work. [slow]
+└── When forked [fork]
+    └── When paused
+        └── It should revert. [slow]";
+        let emitted = scaffold(text, &cfg).unwrap();
 
-    Ok(formatted)
+        assert!(emitted.contains(
+            "function test_ShouldWork() external {
+        vm.pauseGasMetering();
+        // It should work.
+    }"
+        ));
+        assert!(emitted.contains(
+            "function testFork_RevertWhen_Paused() external whenForked {
+        vm.createSelectFork(\"mainnet\");
+        vm.pauseGasMetering();
+        // It should revert.
+    }"
+        ));
+    }
 }
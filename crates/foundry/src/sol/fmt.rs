@@ -1,6 +1,6 @@
 use bulloak_syntax::utils::sanitize;
 use once_cell::sync::Lazy;
-use regex::Regex;
+use regex::{Captures, Regex};
 use solang_parser::pt::{
     Base, ContractDefinition, ContractPart, ErrorDefinition, ErrorParameter,
     EventDefinition, EventParameter, Expression, FunctionAttribute,
@@ -100,11 +100,25 @@ impl Visitor for Formatter {
             result.push(' ');
         }
 
-        let mut parts = vec![];
+        let mut parts: Vec<String> = vec![];
+        let mut attach = false;
         for p in &mut contract.parts {
-            parts.push(self.visit_contract_part(p)?);
+            let part = self.visit_contract_part(p)?;
+            // Comments stick to the part that follows them.
+            match parts.last_mut() {
+                Some(last) if attach => {
+                    last.push('\n');
+                    last.push_str(&part);
+                }
+                _ => parts.push(part),
+            }
+            attach = is_comment(p);
         }
         result.push('{');
+        // A leading comment would otherwise trail the opening brace.
+        if contract.parts.first().is_some_and(is_comment) {
+            result.push('\n');
+        }
         result.push_str(&parts.join("\n\n"));
         result.push('}');
 
@@ -308,17 +322,28 @@ impl Visitor for Formatter {
     }
 }
 
+/// Whether `part` is a `__bulloak_comment__` variable standing for a comment.
+fn is_comment(part: &ContractPart) -> bool {
+    matches!(part, ContractPart::VariableDefinition(variable)
+        if variable.name.as_ref().is_some_and(|name| name.name == "__bulloak_comment__"))
+}
+
 /// Converts special `__bulloak_comment__` variables to regular solidity
-/// comments.
+/// comments, and `__bulloak_snippet__` variables to the code they hold.
 ///
 /// Specifically, it looks for patterns matching `string __bulloak_comment__ =
 /// "<comment>";` and converts them into `// <comment>` format.
 fn cleanup_comments(source: &str) -> String {
     static RE_BULLOAK_COMMENT: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#"string __bulloak_comment__ = "(.*)";"#).unwrap()
+        Regex::new(r#"string __bulloak_(comment|snippet)__ = "(.*)";"#).unwrap()
     });
 
-    RE_BULLOAK_COMMENT.replace_all(source, "// $1").to_string()
+    RE_BULLOAK_COMMENT
+        .replace_all(source, |caps: &Captures<'_>| match &caps[1] {
+            "comment" => format!("// {}", &caps[2]),
+            _ => caps[2].to_owned(),
+        })
+        .to_string()
 }
 
 #[cfg(test)]
@@ -374,4 +399,15 @@ function test_WhenFirstArgIsBiggerThanSecondArg() external {
 }}"#;
         assert_eq!(expected, cleanup_comments(&source));
     }
+
+    #[test]
+    fn cleanups_snippets() {
+        let source = r#"function test_WhenForked() external {
+string __bulloak_snippet__ = "vm.createSelectFork("mainnet");";
+}"#;
+        let expected = r#"function test_WhenForked() external {
+vm.createSelectFork("mainnet");
+}"#;
+        assert_eq!(expected, cleanup_comments(source));
+    }
 }
@@ -2,8 +2,8 @@
 //! (PT) from `solang_parser`.
 
 use solang_parser::pt::{
-    ContractDefinition, ContractPart, FunctionDefinition, FunctionTy,
-    Identifier, SourceUnit, SourceUnitPart,
+    ContractDefinition, ContractPart, Expression, FunctionDefinition,
+    FunctionTy, Identifier, SourceUnit, SourceUnitPart, Statement,
 };
 
 use crate::hir::hir;
@@ -24,6 +24,100 @@ pub fn find_contract(pt: &SourceUnit) -> Option<Box<ContractDefinition>> {
     })
 }
 
+/// Searches for and returns the `ContractDefinition` named `name` in a given
+/// `SourceUnit`.
+#[must_use]
+pub fn find_contract_by_name(
+    pt: &SourceUnit,
+    name: &str,
+) -> Option<Box<ContractDefinition>> {
+    pt.0.iter().find_map(|part| match part {
+        SourceUnitPart::ContractDefinition(contract)
+            if contract.name.as_ref().is_some_and(|id| id.name == name) =>
+        {
+            Some(contract.clone())
+        }
+        _ => None,
+    })
+}
+
+/// Returns the names of the functions in `src`, alongside whether their bodies
+/// do more than call `vm.skip(true)`, i.e. whether the tests were implemented
+/// after scaffolding.
+///
+/// # Errors
+///
+/// Returns an error if `src` is not valid Solidity.
+pub fn test_fns(src: &str) -> anyhow::Result<Vec<(String, bool)>> {
+    let (pt, _) = solang_parser::parse(src, 0).map_err(|diagnostics| {
+        let messages: Vec<String> =
+            diagnostics.into_iter().map(|d| d.message).collect();
+        anyhow::anyhow!(messages.join("\n"))
+    })?;
+
+    let contracts = pt.0.iter().filter_map(|part| match part {
+        SourceUnitPart::ContractDefinition(contract) => Some(contract),
+        _ => None,
+    });
+    let fns =
+        contracts.flat_map(|contract| &contract.parts).filter_map(|part| {
+            match part {
+                ContractPart::FunctionDefinition(fn_sol)
+                    if matches!(fn_sol.ty, FunctionTy::Function) =>
+                {
+                    Some(fn_sol)
+                }
+                _ => None,
+            }
+        });
+    Ok(fns
+        .filter_map(|fn_sol| {
+            let implemented = !body(fn_sol).iter().all(is_vm_skip);
+            fn_sol.name.as_ref().map(|id| (id.name.clone(), implemented))
+        })
+        .collect())
+}
+
+/// Returns the top-level statements of the body of `fn_sol`.
+pub(crate) fn body(fn_sol: &FunctionDefinition) -> &[Statement] {
+    match &fn_sol.body {
+        Some(Statement::Block { statements, .. }) => statements,
+        _ => &[],
+    }
+}
+
+/// Whether `statement` is a `vm.skip(true)` call, with or without a reason.
+pub(crate) fn is_vm_skip(statement: &Statement) -> bool {
+    let Statement::Expression(_, Expression::FunctionCall(_, callee, args)) =
+        statement
+    else {
+        return false;
+    };
+    let Expression::MemberAccess(_, object, member) = callee.as_ref() else {
+        return false;
+    };
+
+    matches!(object.as_ref(), Expression::Variable(id) if id.name == "vm")
+        && member.name == "skip"
+        && matches!(args.first(), Some(Expression::BoolLiteral(_, true)))
+}
+
+/// Whether `statement` is a `vm.expectEmit` call, with any arguments, or a
+/// `vm.expectEmitAnonymous` call.
+pub(crate) fn is_expect_emit(statement: &Statement) -> bool {
+    let Statement::Expression(_, Expression::FunctionCall(_, callee, _)) =
+        statement
+    else {
+        return false;
+    };
+    let Expression::MemberAccess(_, object, member) = callee.as_ref() else {
+        return false;
+    };
+
+    matches!(object.as_ref(), Expression::Variable(id) if id.name == "vm")
+        && member.name.starts_with("expectEmit")
+}
+
 /// Given a HIR function, `find_matching_fn` performs a search over the sol
 /// contract parts trying to find a sol function with a matching name and type.
 pub(crate) fn find_matching_fn<'a>(
@@ -41,6 +135,40 @@ pub(crate) fn find_matching_fn<'a>(
     })
 }
 
+/// Searches the sol contract parts for a function of the same type as
+/// `fn_hir` with `fn_hir`'s anchor in the comments right above it, e.g.
+/// `// #revert-zero`.
+///
+/// This finds the function generated for `fn_hir` even if it was renamed
+/// after the title of its branch changed. `src` is the source the contract
+/// was parsed from.
+pub(crate) fn find_anchored_fn<'a>(
+    contract_sol: &'a ContractDefinition,
+    fn_hir: &hir::FunctionDefinition,
+    src: &str,
+) -> Option<(usize, &'a FunctionDefinition)> {
+    let anchor = format!("// #{}", fn_hir.anchor.as_ref()?);
+    contract_sol.parts.iter().enumerate().find_map(|(idx, part)| {
+        let ContractPart::FunctionDefinition(fn_sol) = part else {
+            return None;
+        };
+        if !fn_types_match(&fn_hir.ty, fn_sol.ty) {
+            return None;
+        }
+
+        let above = src.get(..fn_sol.loc.start())?;
+        let above = &above[..above.rfind('\n')?];
+        let is_anchored = above
+            .lines()
+            .rev()
+            .map(str::trim)
+            .take_while(|line| line.starts_with("//"))
+            .any(|line| line == anchor);
+
+        is_anchored.then_some((idx, &**fn_sol))
+    })
+}
+
 /// Check whether a Solidity function matches its bulloak counterpart.
 ///
 /// Two functions match if they have the same name and their types match.
@@ -73,7 +201,10 @@ mod tests {
 
     use crate::{
         hir,
-        sol::{find_matching_fn, fn_types_match, fns_match},
+        sol::{
+            find_anchored_fn, find_contract, find_matching_fn, fn_types_match,
+            fns_match, test_fns,
+        },
     };
 
     #[test]
@@ -95,6 +226,12 @@ mod tests {
             span: Default::default(),
             modifiers: Default::default(),
             children: Default::default(),
+            summary: None,
+            anchor: None,
+            skipped: false,
+            fork: false,
+            visibility: hir::Visibility::External,
+            is_virtual: false,
         }
     }
 
@@ -179,4 +316,62 @@ mod tests {
         let actual = find_matching_fn(&contract, &needle_hir);
         assert_eq!(None, actual);
     }
+
+    #[test]
+    fn test_find_anchored_fn() {
+        let src = r"contract Foo {
+    // #zero
+    function test_Old() external {}
+
+    // #other
+    // Keep me.
+    function test_Other() external {}
+}";
+        let (pt, _) = solang_parser::parse(src, 0).unwrap();
+        let contract = find_contract(&pt).unwrap();
+        let mut needle = fn_hir("test_New", hir::FunctionTy::Function);
+
+        assert_eq!(None, find_anchored_fn(&contract, &needle, src));
+        needle.anchor = Some("zero".to_owned());
+        let (idx, found) = find_anchored_fn(&contract, &needle, src).unwrap();
+        assert_eq!(
+            (0, "test_Old"),
+            (idx, found.name.as_ref().unwrap().name.as_str())
+        );
+        needle.anchor = Some("other".to_owned());
+        assert_eq!(1, find_anchored_fn(&contract, &needle, src).unwrap().0);
+        needle.ty = hir::FunctionTy::Modifier;
+        assert_eq!(None, find_anchored_fn(&contract, &needle, src));
+    }
+
+    #[test]
+    fn test_test_fns() {
+        let src = r#"contract Foo {
+    modifier whenPaused() {
+        _;
+    }
+
+    function test_Empty() external {
+        // it should revert.
+    }
+
+    function test_Skipped() external whenPaused {
+        vm.skip(true, "later");
+    }
+
+    function test_Done() external {
+        vm.skip(true);
+        assertTrue(true);
+    }
+}"#;
+        assert_eq!(
+            test_fns(src).unwrap(),
+            vec![
+                ("test_Empty".to_owned(), false),
+                ("test_Skipped".to_owned(), false),
+                ("test_Done".to_owned(), true),
+            ]
+        );
+        assert!(test_fns("contract {").is_err());
+    }
 }
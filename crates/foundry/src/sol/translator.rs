@@ -22,15 +22,21 @@ use solang_parser::pt::{
     Base, ContractDefinition, ContractPart, ContractTy, Expression,
     FunctionAttribute, FunctionDefinition, FunctionTy, Identifier,
     IdentifierPath, Import, ImportPath, Loc, SourceUnit, SourceUnitPart,
-    Statement, StringLiteral, Type, VariableDeclaration, Visibility,
+    Statement, StringLiteral, Type, VariableDeclaration, VariableDefinition,
+    Visibility,
 };
 
 use crate::{
-    config::Config,
+    config::{Config, SolidityImport},
     hir::{self, visitor::Visitor, Hir},
     scaffold::comment,
 };
 
+/// The variable that disguises comments, see [`TranslatorI::gen_comment`].
+const COMMENT: &str = "__bulloak_comment__";
+/// The variable that disguises snippets of code, which the formatter unwraps.
+const SNIPPET: &str = "__bulloak_snippet__";
+
 /// The implementation of a translator between a bulloak tree HIR and a
 /// `solang_parser` parse tree -- HIR -> PT.
 ///
@@ -42,6 +48,10 @@ pub(crate) struct Translator {
     sol_version: String,
     /// A flag indicating if there is a forge-std dependency.
     with_forge_std: bool,
+    /// The imports to emit.
+    imports: Vec<SolidityImport>,
+    /// The remappings applied to the paths of the imports.
+    remappings: Vec<(String, String)>,
     /// Whether to emit modifiers.
     skip_modifiers: bool,
     /// Whether to normalize comments.
@@ -53,10 +63,13 @@ impl Translator {
     #[must_use]
     pub(crate) fn new(cfg: &Config) -> Self {
         let with_forge_std = [cfg.emit_vm_skip].into_iter().any(|f| f);
+        let imports = cfg.imports.clone();
 
         Self {
             sol_version: cfg.solidity_version.clone(),
             with_forge_std,
+            imports,
+            remappings: cfg.remappings.clone(),
             skip_modifiers: cfg.skip_modifiers,
             format_descriptions: cfg.format_descriptions,
         }
@@ -66,7 +79,21 @@ impl Translator {
     ///
     /// This function is the entry point of the translator.
     #[must_use]
-    pub(crate) fn translate(self, hir: &Hir) -> SourceUnit {
+    pub(crate) fn translate(mut self, hir: &Hir) -> SourceUnit {
+        // Tests of branches tagged `[skip]` call `vm.skip`, and snippets
+        // typically use cheatcodes, which come with forge-std.
+        self.with_forge_std |= hir.uses_forge_std();
+        // A configured import of `Test` takes the place of forge-std's.
+        let imports_test =
+            self.imports.iter().any(|i| i.symbols.iter().any(|s| s == "Test"));
+        let forge_std = (self.with_forge_std && !imports_test)
+            .then(|| SolidityImport::from("Test=forge-std/Test.sol"));
+        self.imports = forge_std
+            .into_iter()
+            .chain(std::mem::take(&mut self.imports))
+            .map(|import| import.remapped(&self.remappings))
+            .collect();
+
         TranslatorI::new(self).translate(hir)
     }
 }
@@ -95,6 +122,43 @@ impl TranslatorI {
         }
     }
 
+    /// Generates an import directive, e.g. `import {Test} from
+    /// "forge-std/Test.sol";`, or `import "src/Vault.sol";` when no symbols
+    /// are imported by name.
+    fn gen_import(&self, import: &SolidityImport) -> SourceUnitPart {
+        let loc_import_start = self.offset.get();
+        let mut symbols = Vec::with_capacity(import.symbols.len());
+        if import.symbols.is_empty() {
+            self.bump("import \"");
+        } else {
+            self.bump("import { ");
+            for (index, symbol) in import.symbols.iter().enumerate() {
+                if index > 0 {
+                    self.bump(", ");
+                }
+                let loc = self.bump(symbol);
+                symbols.push((Identifier { loc, name: symbol.clone() }, None));
+            }
+            self.bump(" } from \"");
+        }
+        let loc_path = self.bump(&import.path);
+        let path = ImportPath::Filename(StringLiteral {
+            loc: loc_path,
+            unicode: false,
+            string: import.path.clone(),
+        });
+        let loc = Loc::File(0, loc_import_start, loc_path.end());
+        self.bump("\";\n");
+
+        // The import directive `Rename` corresponds to `import {x} from
+        // y.sol`.
+        SourceUnitPart::ImportDirective(if symbols.is_empty() {
+            Import::Plain(path, loc)
+        } else {
+            Import::Rename(path, symbols, loc)
+        })
+    }
+
     /// Computes a `Loc` out of a string and updates `self.offset` accordingly.
     fn bump(&self, content: &str) -> solang_parser::pt::Loc {
         let start = self.offset.get();
@@ -152,11 +216,11 @@ impl TranslatorI {
     /// of `FunctionAttribute` items, which represent various attributes of
     /// a function in the parse tree (PT), such as visibility and modifiers.
     ///
-    /// In the case of a modifier function, an empty vector is returned as
-    /// modifiers generally do not have additional attributes in this
-    /// context. For a regular function, the function generates the
-    /// visibility attribute (defaulted to 'external') and includes any
-    /// modifiers that are part of the function definition.
+    /// In the case of a modifier function, the only attribute generated is
+    /// `virtual`, for modifiers of conditions tagged `[virtual]`. For a
+    /// regular function, the function generates the visibility attribute
+    /// (defaulted to 'external', or 'public' for branches tagged `[public]`)
+    /// and includes any modifiers that are part of the function definition.
     ///
     /// # Arguments
     /// * `function` - A reference to the `FunctionDefinition` node in the HIR.
@@ -172,11 +236,23 @@ impl TranslatorI {
         function: &hir::FunctionDefinition,
     ) -> Vec<FunctionAttribute> {
         match function.ty {
+            hir::FunctionTy::Modifier if function.is_virtual => {
+                let attrs =
+                    vec![FunctionAttribute::Virtual(self.bump("virtual"))];
+                self.bump(" ");
+                attrs
+            }
             hir::FunctionTy::Modifier => vec![],
             hir::FunctionTy::Function => {
-                let mut attrs = vec![FunctionAttribute::Visibility(
-                    Visibility::External(Some(self.bump("external"))),
-                )];
+                let visibility = match function.visibility {
+                    hir::Visibility::Public => {
+                        Visibility::Public(Some(self.bump("public")))
+                    }
+                    hir::Visibility::External => {
+                        Visibility::External(Some(self.bump("external")))
+                    }
+                };
+                let mut attrs = vec![FunctionAttribute::Visibility(visibility)];
                 self.bump(" ");
                 if let Some(ref modifiers) = function.modifiers {
                     attrs.extend(
@@ -317,6 +393,113 @@ impl TranslatorI {
 
         Ok(stmts)
     }
+
+    /// Generates a statement that is emitted as a `// {text}` comment.
+    fn gen_comment(&mut self, text: String) -> Statement {
+        self.gen_disguised(COMMENT, text)
+    }
+
+    /// Generates a `vm.<member>()` call statement, passing `true` as its only
+    /// argument if `with_true` is set, e.g. `vm.skip(true);`.
+    fn gen_vm_call(&mut self, member: &str, with_true: bool) -> Statement {
+        let start_offset = self.offset.get();
+        let loc_vm = self.bump("vm");
+        self.bump(".");
+        let loc_member = self.bump(member);
+        self.bump("(");
+        let args = if with_true {
+            vec![Expression::BoolLiteral(self.bump("true"), true)]
+        } else {
+            vec![]
+        };
+        let loc_end = self.bump(");");
+
+        let vm_interface = Expression::MemberAccess(
+            Loc::File(0, start_offset, loc_member.end()),
+            Box::new(Expression::Variable(solang_parser::pt::Identifier {
+                loc: loc_vm,
+                name: "vm".to_owned(),
+            })),
+            solang_parser::pt::Identifier {
+                loc: loc_member,
+                name: member.to_owned(),
+            },
+        );
+        let vm_call = Expression::FunctionCall(
+            Loc::File(0, loc_member.start(), loc_end.start()),
+            Box::new(vm_interface),
+            args,
+        );
+
+        Statement::Expression(
+            Loc::File(0, start_offset, self.offset.get()),
+            vm_call,
+        )
+    }
+
+    /// Generates a `string {variable_name} = "{text}";` statement, which the
+    /// formatter replaces with `text` in some form.
+    fn gen_disguised(
+        &mut self,
+        variable_name: &str,
+        text: String,
+    ) -> Statement {
+        // After exploring several paths forward, the least convoluted way to
+        // handle comments is to disguise them as `VariableDefinition`
+        // statements.
+        //
+        // The idea is to remove the extra parts with a search and replace when
+        // emitting the parse tree and leave the comment's lexeme as is.
+        let definition_start = self.offset.get();
+        let declaration_start = definition_start;
+        let ty = Expression::Type(self.bump("string"), Type::String);
+        self.bump(" "); // ` ` after type.
+        let variable_loc = self.bump(variable_name);
+        let declaration_loc =
+            Loc::File(0, declaration_start, self.offset.get());
+        let name =
+            Identifier { loc: variable_loc, name: variable_name.to_owned() };
+        let variable = VariableDeclaration {
+            loc: declaration_loc,
+            ty,
+            storage: None,
+            name: Some(name),
+        };
+        self.bump(" = ");
+        let literal = format!(r#""{}""#, text.clone());
+        let comment_loc = self.bump(&literal);
+        let string_literal =
+            Some(Expression::StringLiteral(vec![StringLiteral {
+                loc: comment_loc,
+                unicode: false,
+                string: text,
+            }]));
+        self.bump(";"); // `;` after string literal.
+        Statement::VariableDefinition(
+            Loc::File(0, definition_start, self.offset.get()),
+            variable,
+            string_literal,
+        )
+    }
+
+    /// Generates a contract part that is emitted as a `// {text}` comment,
+    /// e.g. above a function.
+    fn gen_contract_comment(&mut self, text: String) -> ContractPart {
+        let Statement::VariableDefinition(loc, variable, initializer) =
+            self.gen_comment(text)
+        else {
+            unreachable!("comments are disguised as variable definitions")
+        };
+        self.bump("\n");
+
+        ContractPart::VariableDefinition(Box::new(VariableDefinition {
+            loc,
+            ty: variable.ty,
+            attrs: vec![],
+            name: variable.name,
+            initializer,
+        }))
+    }
 }
 
 impl Visitor for TranslatorI {
@@ -378,35 +561,9 @@ impl Visitor for TranslatorI {
         ));
         self.bump(";\n");
 
-        // Add the forge-std's Test import, if needed.
-        if self.translator.with_forge_std {
-            // Getting the relevant offsets for `import {Test} from
-            // "forge-std/Test.sol"`.
-            let loc_import_start = self.offset.get();
-            self.bump("import { ");
-            let loc_identifier = self.bump("Test");
-            self.bump(" } from \"");
-            let loc_path = self.bump("forge-std/Test.sol");
-
-            // The import directive `Rename` corresponds to `import {x} from
-            // y.sol`.
-            source_unit.push(SourceUnitPart::ImportDirective(Import::Rename(
-                ImportPath::Filename(StringLiteral {
-                    loc: loc_path,
-                    unicode: false,
-                    string: "forge-std/Test.sol".to_string(),
-                }),
-                vec![(
-                    Identifier {
-                        loc: loc_identifier,
-                        name: "Test".to_string(),
-                    },
-                    None,
-                )],
-                Loc::File(0, loc_import_start, loc_path.end()),
-            )));
-
-            self.bump("\";\n");
+        // Add the imports, e.g. forge-std's Test, if needed.
+        for import in &self.translator.imports {
+            source_unit.push(self.gen_import(import));
         }
 
         for child in &root.children {
@@ -481,6 +638,12 @@ impl Visitor for TranslatorI {
                 if function.is_modifier() && self.translator.skip_modifiers {
                     continue;
                 }
+                if let Some(ref summary) = function.summary {
+                    parts.push(self.gen_contract_comment(summary.clone()));
+                }
+                if let Some(ref anchor) = function.anchor {
+                    parts.push(self.gen_contract_comment(format!("#{anchor}")));
+                }
                 parts.push(self.visit_function(function)?);
             }
         }
@@ -594,51 +757,13 @@ impl Visitor for TranslatorI {
         &mut self,
         comment: &hir::Comment,
     ) -> Result<Self::CommentOutput, Self::Error> {
-        // After exploring several paths forward, the least convoluted way to
-        // handle comments is to disguise them as `VariableDefinition`
-        // statements.
-        //
-        // The idea is to remove the extra parts with a search and replace when
-        // emitting the parse tree and leave the comment's lexeme as is.
-        let definition_start = self.offset.get();
-        let declaration_start = definition_start;
-        let ty = Expression::Type(self.bump("string"), Type::String);
-        self.bump(" "); // ` ` after type.
-        let variable_name = "__bulloak_comment__";
-        let variable_loc = self.bump(variable_name);
-        let declaration_loc =
-            Loc::File(0, declaration_start, self.offset.get());
-        let name =
-            Identifier { loc: variable_loc, name: variable_name.to_owned() };
-        let variable = VariableDeclaration {
-            loc: declaration_loc,
-            ty,
-            storage: None,
-            name: Some(name),
-        };
-        self.bump(" = ");
         let normalized = if self.translator.format_descriptions {
             comment::normalize(&comment.lexeme)
         } else {
             comment.lexeme.clone()
         };
 
-        let literal = format!(r#""{}""#, normalized.clone());
-        let comment_loc = self.bump(&literal);
-        let string_literal =
-            Some(Expression::StringLiteral(vec![StringLiteral {
-                loc: comment_loc,
-                unicode: false,
-                string: normalized,
-            }]));
-        self.bump(";"); // `;` after string literal.
-        let definition = Statement::VariableDefinition(
-            Loc::File(0, definition_start, self.offset.get()),
-            variable,
-            string_literal,
-        );
-
-        Ok(definition)
+        Ok(self.gen_comment(normalized))
     }
 
     /// Visits a supported statement node and match based on its type.
@@ -646,43 +771,17 @@ impl Visitor for TranslatorI {
         &mut self,
         statement: &hir::Statement,
     ) -> Result<Self::StatementOutput, Self::Error> {
-        let start_offset = self.offset.get();
-
-        match statement.ty {
-            hir::StatementType::VmSkip => {
-                let loc_vm = self.bump("vm");
-                self.bump(".");
-                let loc_skip = self.bump("skip");
-                self.bump("(");
-                let loc_arg = self.bump("true");
-                self.bump(");");
-
-                let vm_interface = Expression::MemberAccess(
-                    Loc::File(0, start_offset, loc_skip.end()),
-                    Box::new(Expression::Variable(
-                        solang_parser::pt::Identifier {
-                            loc: loc_vm,
-                            name: "vm".to_owned(),
-                        },
-                    )),
-                    solang_parser::pt::Identifier {
-                        loc: loc_skip,
-                        name: "skip".to_owned(),
-                    },
-                );
-
-                let vm_skip_arg = vec![Expression::BoolLiteral(loc_arg, true)];
-
-                let vm_skip_call = Expression::FunctionCall(
-                    Loc::File(0, loc_skip.start(), loc_arg.end()),
-                    Box::new(vm_interface),
-                    vm_skip_arg,
-                );
-
-                Ok(Statement::Expression(
-                    Loc::File(0, start_offset, self.offset.get()),
-                    vm_skip_call,
-                ))
+        match &statement.ty {
+            hir::StatementType::VmSkip => Ok(self.gen_vm_call("skip", true)),
+            hir::StatementType::ExpectEmit => {
+                Ok(self.gen_vm_call("expectEmit", false))
+            }
+            hir::StatementType::AssertionStub(stub)
+            | hir::StatementType::Todo(stub) => {
+                Ok(self.gen_comment(stub.clone()))
+            }
+            hir::StatementType::Snippet(code) => {
+                Ok(self.gen_disguised(SNIPPET, code.clone()))
             }
         }
     }
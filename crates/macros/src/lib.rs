@@ -0,0 +1,98 @@
+//! Procedural macros for `bulloak`.
+//!
+//! [`macro@from_tree`] expands a `.tree` spec into Rust test stubs right
+//! inside a module, for projects that prefer generating tests at compile
+//! time over scaffolding test files.
+
+use std::{env, fs, path::PathBuf};
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Item, ItemMod, LitStr};
+
+/// Expands the `.tree` file at the given path into test stubs inside the
+/// annotated module.
+///
+/// The module gets the test context, the helpers and the test module that
+/// `bulloak scaffold -b rust --doc-comments` would write. Every test ends
+/// with `todo!()`, so unimplemented branches fail loudly. Tests of panicking
+/// actions don't get `#[should_panic]`, which `todo!()` would satisfy; add it
+/// when implementing them. Items already in the module are kept.
+///
+/// The path is relative to the root of the package, i.e. the directory of
+/// its `Cargo.toml`. The module is expanded again whenever the tree changes.
+///
+/// ```ignore
+/// #[bulloak::from_tree("specs/hash_pair.tree")]
+/// mod hash_pair {}
+/// ```
+#[proc_macro_attribute]
+pub fn from_tree(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(attr as LitStr);
+    let module = parse_macro_input!(item as ItemMod);
+    expand(&path, module).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+/// Adds the stubs generated from the tree at `path` to `module`.
+fn expand(
+    path: &LitStr,
+    mut module: ItemMod,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let root = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let tree_path = root.join(path.value());
+    let text = fs::read_to_string(&tree_path).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("failed to read {}: {e}", tree_path.display()),
+        )
+    })?;
+
+    let cfg = bulloak_rust::Config {
+        doc_comments: true,
+        header: None,
+        ..bulloak_rust::Config::default()
+    };
+    let emitted = bulloak_rust::scaffold_tree(&text, &cfg.for_tree(&tree_path))
+        .map_err(|e| syn::Error::new(path.span(), e.to_string()))?;
+    let file = syn::parse_file(&emitted)?;
+
+    // Make the module depend on the tree, so that it is expanded again when
+    // the tree changes.
+    let tree_str = tree_path.to_string_lossy();
+    let mut items: Vec<Item> =
+        vec![parse_quote! { const _: &[u8] = include_bytes!(#tree_str); }];
+    for mut item in file.items {
+        match &mut item {
+            Item::Mod(tests) => add_todos(tests),
+            Item::Struct(s) => {
+                s.attrs.push(parse_quote! { #[allow(dead_code)] });
+            }
+            Item::Fn(f) => {
+                f.attrs.push(parse_quote! { #[allow(dead_code, unused_mut)] });
+            }
+            _ => {}
+        }
+        items.push(item);
+    }
+
+    let (_, content) = module.content.get_or_insert_with(Default::default);
+    content.extend(items);
+
+    Ok(quote! { #module })
+}
+
+/// Ends the body of every test in `tests` with `todo!()`.
+///
+/// The panic of `todo!()` would satisfy the `#[should_panic]` of generated
+/// panicking tests, so the attribute is dropped for stubs to fail.
+fn add_todos(tests: &mut ItemMod) {
+    let Some((_, items)) = &mut tests.content else { return };
+    for item in items {
+        if let Item::Fn(test) = item {
+            test.attrs.retain(|attr| !attr.path().is_ident("should_panic"));
+            test.block.stmts.push(parse_quote! { todo!(); });
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#![allow(missing_docs)]
+
+use std::{fs, path::Path, process::Command};
+
+/// Runs `cargo test` on a crate that expands `tests/specs/hash_pair.tree`,
+/// returning whether it passed and its output.
+///
+/// The expanded stubs are meant to fail, so they can't be part of this test
+/// binary.
+fn test_fixture() -> (bool, String) {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("from_tree");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::create_dir_all(dir.join("specs")).unwrap();
+    // Resolve the same versions as the workspace, to build offline.
+    fs::copy(manifest_dir.join("../../Cargo.lock"), dir.join("Cargo.lock"))
+        .unwrap();
+    fs::copy(
+        manifest_dir.join("tests/specs/hash_pair.tree"),
+        dir.join("specs/hash_pair.tree"),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"from-tree-fixture\"\nversion = \
+             \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\nbulloak-macros \
+             = {{ path = {:?} }}\n\n[workspace]\n",
+            manifest_dir.display().to_string()
+        ),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/lib.rs"),
+        r#"#[bulloak_macros::from_tree("specs/hash_pair.tree")]
+mod hash_pair {
+    #[test]
+    fn keeps_existing_items_and_adds_helpers() {
+        let _ctx = first_arg_is_smaller(TestContext::default());
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO"))
+        .args(["test", "--offline", "--quiet"])
+        .current_dir(&dir)
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    (output.status.success(), format!("{stdout}{stderr}"))
+}
+
+#[test]
+fn expands_trees_into_failing_stubs() {
+    let (passed, output) = test_fixture();
+
+    // Every action of the spec panics, yet the stubs fail until implemented.
+    assert!(!passed, "{output}");
+    assert!(output.contains("1 passed; 2 failed"), "{output}");
+    assert!(output.contains("hash_pair::tests::test_should_panic_on_overflow"));
+    assert!(output.contains("hash_pair::tests::test_when_first_arg_is_smaller"));
+    assert!(output.contains("not yet implemented"), "{output}");
+}
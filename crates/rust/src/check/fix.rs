@@ -0,0 +1,536 @@
+//! Automatic fixes for violations found by `bulloak check`.
+
+use std::ops::Range;
+
+use bulloak_syntax::Ast;
+use syn::{spanned::Spanned, Item, ItemMod, Stmt};
+
+use crate::{
+    check::rules::{
+        descriptions::{line_comments, loosely_equal},
+        expected_tests,
+    },
+    config::Config,
+    error::Result,
+    rust::ParsedRustFile,
+};
+
+/// Reorders the test functions in `rust_source` to match the order in which
+/// they are scaffolded from `ast`.
+///
+/// Only the test functions the spec expects are moved. Each one keeps its
+/// attributes, its body and the comments right above it, while every other
+/// item stays where it is. Tests are reordered within the module that
+/// declares them and never moved to another module.
+///
+/// # Errors
+///
+/// Returns an error if `ast` is not a root node or if `rust_source` is not
+/// valid Rust.
+pub fn fix_order(ast: &Ast, rust_source: &str, cfg: &Config) -> Result<String> {
+    let expected: Vec<(Option<String>, String)> = expected_tests(ast, cfg)?
+        .into_iter()
+        .map(|test| (test.module, test.name))
+        .collect();
+    let parsed = ParsedRustFile::parse(rust_source)?
+        .with_test_attributes(&cfg.test_attributes);
+
+    let mut edits = Vec::new();
+    for module in parsed.find_test_modules() {
+        let order = Order {
+            parsed: &parsed,
+            source: rust_source,
+            expected: &expected,
+            grouped: cfg.group_modules,
+        };
+        order.collect_edits(module, None, &mut edits);
+    }
+
+    // Apply the edits back to front so that the remaining ranges stay valid.
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut fixed = rust_source.to_owned();
+    for (range, text) in edits {
+        fixed.replace_range(range, &text);
+    }
+
+    Ok(fixed)
+}
+
+/// Renames the test function `found` in `rust_source` to `expected`.
+///
+/// Only the name in the signature changes, so the attributes, the body and
+/// the comments of the test stay as they are. The source is returned as is
+/// if there is no such test function.
+///
+/// # Errors
+///
+/// Returns an error if `rust_source` is not valid Rust.
+pub fn rename_test(
+    rust_source: &str,
+    found: &str,
+    expected: &str,
+    cfg: &Config,
+) -> Result<String> {
+    let parsed = ParsedRustFile::parse(rust_source)?
+        .with_test_attributes(&cfg.test_attributes);
+    let mut fixed = rust_source.to_owned();
+    if let Some(func) = parsed
+        .find_test_functions()
+        .into_iter()
+        .find(|func| func.sig.ident == found)
+    {
+        fixed.replace_range(func.sig.ident.span().byte_range(), expected);
+    }
+
+    Ok(fixed)
+}
+
+/// Removes the helper functions named `names` from `rust_source`.
+///
+/// Each helper goes along with its attributes and the comments right above
+/// it. Helpers are looked up at the top level of the file and in its test
+/// modules.
+///
+/// # Errors
+///
+/// Returns an error if `rust_source` is not valid Rust.
+pub fn remove_helpers(
+    rust_source: &str,
+    names: &[String],
+    cfg: &Config,
+) -> Result<String> {
+    let parsed = ParsedRustFile::parse(rust_source)?
+        .with_test_attributes(&cfg.test_attributes);
+    let mut ranges: Vec<Range<usize>> = parsed
+        .find_helper_functions()
+        .into_iter()
+        .filter(|func| names.iter().any(|name| func.sig.ident == name))
+        .map(|func| removal_range(rust_source, func.span().byte_range()))
+        .collect();
+
+    // Remove back to front so that the remaining ranges stay valid.
+    ranges.sort_by_key(|range| std::cmp::Reverse(range.start));
+    let mut fixed = rust_source.to_owned();
+    for range in ranges {
+        fixed.replace_range(range, "");
+    }
+
+    Ok(fixed)
+}
+
+/// Rewrites the comments describing the actions of each test function in
+/// `rust_source` the way `--format-descriptions` formats them.
+///
+/// Comments that only differ in casing or punctuation are rewritten in place.
+/// Missing comments go right after the last description that precedes them
+/// in the spec, or else after the helper calls that open the test's body.
+///
+/// # Errors
+///
+/// Returns an error if `ast` is not a root node or if `rust_source` is not
+/// valid Rust.
+pub fn fix_descriptions(
+    ast: &Ast,
+    rust_source: &str,
+    cfg: &Config,
+) -> Result<String> {
+    let parsed = ParsedRustFile::parse(rust_source)?
+        .with_test_attributes(&cfg.test_attributes);
+    let tests = parsed.find_test_functions();
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for test in expected_tests(ast, cfg)? {
+        let Some(func) = tests.iter().find(|func| func.sig.ident == test.name)
+        else {
+            continue;
+        };
+        let body = func.block.span().byte_range();
+        let comments = line_comments(&rust_source[body.clone()]);
+        let line_start = rust_source[..func.sig.span().byte_range().start]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let indentation: String = rust_source[line_start..]
+            .chars()
+            .take_while(|c| c.is_whitespace() && *c != '\n')
+            .collect();
+        let mut cursor = match func.block.stmts.first() {
+            Some(stmt @ Stmt::Local(_)) => stmt.span().byte_range().end,
+            _ => body.start + 1,
+        };
+        for expected in &test.descriptions {
+            let comment = format!("// {expected}");
+            if let Some((range, _)) =
+                comments.iter().find(|(_, text)| text == expected)
+            {
+                cursor = body.start + range.end;
+            } else if let Some((range, _)) =
+                comments.iter().find(|(_, text)| loosely_equal(text, expected))
+            {
+                edits.push((
+                    body.start + range.start..body.start + range.end,
+                    comment,
+                ));
+                cursor = body.start + range.end;
+            } else {
+                let line = format!("\n{indentation}    {comment}");
+                match edits.last_mut() {
+                    // Keep consecutive insertions in spec order.
+                    Some((range, text))
+                        if range.start == cursor && range.end == cursor =>
+                    {
+                        text.push_str(&line);
+                    }
+                    _ => edits.push((cursor..cursor, line)),
+                }
+            }
+        }
+
+        // Put the closing brace of a body like `{}` back on its own line.
+        if !rust_source[body.clone()].contains('\n') {
+            if let Some((_, text)) = edits
+                .last_mut()
+                .filter(|(range, _)| *range == (body.start + 1..body.start + 1))
+            {
+                text.push('\n');
+                text.push_str(&indentation);
+            }
+        }
+    }
+
+    // Apply the edits back to front so that the remaining ranges stay valid.
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut fixed = rust_source.to_owned();
+    for (range, text) in edits {
+        fixed.replace_range(range, &text);
+    }
+
+    Ok(fixed)
+}
+
+/// Widens the byte range of an item to whole lines, including the comments
+/// right above it and, if the item sat between blank lines, one of them.
+fn removal_range(source: &str, item: Range<usize>) -> Range<usize> {
+    let line_start = |at: usize| source[..at].rfind('\n').map_or(0, |i| i + 1);
+    let mut start = line_start(item.start);
+    while start > 0 {
+        let prev = line_start(start - 1);
+        if !source[prev..start].trim_start().starts_with("//") {
+            break;
+        }
+        start = prev;
+    }
+    let mut end = source[item.end..]
+        .find('\n')
+        .map_or(source.len(), |i| item.end + i + 1);
+    if source[..start].ends_with("\n\n") && source[end..].starts_with('\n') {
+        end += 1;
+    }
+
+    start..end
+}
+
+/// What [`fix_order`] reorders tests against.
+struct Order<'a> {
+    /// The Rust file.
+    parsed: &'a ParsedRustFile,
+    /// The source of the Rust file.
+    source: &'a str,
+    /// The module and the name of each expected test, in spec order.
+    expected: &'a [(Option<String>, String)],
+    /// Whether tests are grouped in a module per top-level condition.
+    grouped: bool,
+}
+
+impl Order<'_> {
+    /// Collects the edits that put the expected tests declared in `module`,
+    /// and in any module nested inside it, in spec order.
+    ///
+    /// `group` is the module nested in the test module that `module` is or
+    /// is in, if any.
+    fn collect_edits(
+        &self,
+        module: &ItemMod,
+        group: Option<&str>,
+        edits: &mut Vec<(Range<usize>, String)>,
+    ) {
+        let Some((brace, items)) = &module.content else { return };
+        let (parsed, source) = (self.parsed, self.source);
+        let group = group.filter(|_| self.grouped);
+
+        // A slot covers a test function and the comments above it, but not
+        // the whitespace separating it from the previous item, nor a comment
+        // trailing the previous item on its last line.
+        let mut slots = Vec::new();
+        let mut prev_end = brace.span.open().byte_range().end;
+        for item in items {
+            let range = item.span().byte_range();
+            match item {
+                Item::Fn(func) if parsed.has_test_attr(&func.attrs) => {
+                    let index =
+                        self.expected.iter().position(|(module, name)| {
+                            func.sig.ident == name && module.as_deref() == group
+                        });
+                    if let Some(index) = index {
+                        let gap = &source[prev_end..range.start];
+                        let gap = gap.find('\n').map_or(gap, |i| &gap[i..]);
+                        let start = range.start - gap.trim_start().len();
+                        slots.push((index, start..range.end));
+                    }
+                }
+                Item::Mod(nested) => {
+                    let name = nested.ident.to_string();
+                    self.collect_edits(nested, group.or(Some(&name)), edits);
+                }
+                _ => {}
+            }
+            prev_end = range.end;
+        }
+
+        let mut sorted = slots.clone();
+        sorted.sort_by_key(|(index, _)| *index);
+        for ((_, slot), (_, chunk)) in slots.into_iter().zip(sorted) {
+            if slot != chunk {
+                edits.push((slot, source[chunk].to_owned()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::{fix_descriptions, fix_order, remove_helpers, rename_test};
+    use crate::config::Config;
+
+    const TREE: &str = indoc! {"
+        HashPair
+        ├── It should never panic.
+        ├── When first arg is smaller
+        │   └── It should match the result.
+        └── When first arg is bigger
+            └── It should be sorted.
+    "};
+
+    fn fix(source: &str) -> String {
+        let ast = bulloak_syntax::parse_one(TREE).unwrap();
+        fix_order(&ast, source, &Config::default()).unwrap()
+    }
+
+    #[test]
+    fn reorders_tests_keeping_attributes_and_comments() {
+        let source = indoc! {r#"
+            fn first_arg_is_smaller() {}
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+
+                /// Checks the bigger branch.
+                #[test]
+                #[ignore = "flaky"]
+                fn test_when_first_arg_is_bigger() {
+                    // Keep me.
+                    assert!(true);
+                }
+
+                fn helper() {} // Stays with the helper.
+
+                // Smaller first.
+                #[test]
+                fn test_when_first_arg_is_smaller() {}
+
+                #[test]
+                fn test_should_never_panic() {}
+
+                // Trailing comment.
+            }
+        "#};
+        let expected = indoc! {r#"
+            fn first_arg_is_smaller() {}
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+
+                #[test]
+                fn test_should_never_panic() {}
+
+                fn helper() {} // Stays with the helper.
+
+                // Smaller first.
+                #[test]
+                fn test_when_first_arg_is_smaller() {}
+
+                /// Checks the bigger branch.
+                #[test]
+                #[ignore = "flaky"]
+                fn test_when_first_arg_is_bigger() {
+                    // Keep me.
+                    assert!(true);
+                }
+
+                // Trailing comment.
+            }
+        "#};
+
+        assert_eq!(fix(source), expected);
+    }
+
+    #[test]
+    fn leaves_other_tests_and_ordered_files_alone() {
+        let source = indoc! {"
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn test_should_never_panic() {}
+
+                #[test]
+                fn test_extra() {}
+
+                #[test]
+                fn test_when_first_arg_is_smaller() {}
+
+                mod nested {
+                    #[test]
+                    fn test_when_first_arg_is_bigger() {}
+                }
+            }
+        "};
+
+        assert_eq!(fix(source), source);
+    }
+
+    #[test]
+    fn formats_descriptions() {
+        let source = indoc! {"
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn test_should_never_panic() {}
+
+                #[test]
+                fn test_when_first_arg_is_smaller() {
+                    let _ctx = first_arg_is_smaller(TestContext::default());
+                    assert_eq!(1, 1);
+                }
+
+                #[test]
+                fn test_when_first_arg_is_bigger() {
+                    // it should be sorted
+                }
+            }
+        "};
+
+        let ast = bulloak_syntax::parse_one(TREE).unwrap();
+        let fixed = fix_descriptions(&ast, source, &Config::default()).unwrap();
+        assert_eq!(
+            fixed,
+            indoc! {"
+                #[cfg(test)]
+                mod tests {
+                    #[test]
+                    fn test_should_never_panic() {
+                        // It should never panic.
+                    }
+
+                    #[test]
+                    fn test_when_first_arg_is_smaller() {
+                        let _ctx = first_arg_is_smaller(TestContext::default());
+                        // It should match the result.
+                        assert_eq!(1, 1);
+                    }
+
+                    #[test]
+                    fn test_when_first_arg_is_bigger() {
+                        // It should be sorted.
+                    }
+                }
+            "}
+        );
+    }
+
+    #[test]
+    fn renames_tests() {
+        let source = indoc! {"
+            #[cfg(test)]
+            mod tests {
+                // #smaller
+                #[test]
+                fn test_when_first_is_smaller() {
+                    // fn test_when_first_is_smaller() stays.
+                }
+            }
+        "};
+
+        let fixed = rename_test(
+            source,
+            "test_when_first_is_smaller",
+            "test_when_first_arg_is_smaller",
+            &Config::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            fixed,
+            source.replacen(
+                "fn test_when_first_is_smaller",
+                "fn test_when_first_arg_is_smaller",
+                1
+            )
+        );
+        assert_eq!(
+            rename_test(source, "test_other", "test_x", &Config::default())
+                .unwrap(),
+            source
+        );
+    }
+
+    #[test]
+    fn removes_helpers_with_their_comments() {
+        let source = indoc! {"
+            // Generated by bulloak
+
+            fn kept(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            /// Helper: when gone
+            fn gone(mut ctx: TestContext) -> TestContext {
+                // TODO: Set up condition
+                ctx
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+                // Also gone.
+                fn nested(mut ctx: TestContext) -> TestContext {
+                    ctx
+                }
+                #[test]
+                fn test_when_kept() {}
+            }
+        "};
+        let expected = indoc! {"
+            // Generated by bulloak
+
+            fn kept(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+                #[test]
+                fn test_when_kept() {}
+            }
+        "};
+
+        let names = ["gone".to_owned(), "nested".to_owned()];
+        assert_eq!(
+            remove_helpers(source, &names, &Config::default()).unwrap(),
+            expected
+        );
+    }
+}
@@ -1,12 +1,14 @@
 //! Check module for validating Rust test files against specs.
 
+pub mod fix;
 pub mod rules;
 pub mod violation;
 
-pub use violation::{Violation, ViolationKind};
+pub use fix::{fix_descriptions, fix_order, remove_helpers, rename_test};
+pub use violation::{Severity, Violation, ViolationKind};
 
-use crate::config::Config;
-use anyhow::{Context, Result};
+use crate::{config::Config, scaffold::{banner, test_file_path}};
+use crate::error::{IoError, ParseError, Result};
 use std::path::Path;
 
 /// Check that a Rust test file matches its tree specification.
@@ -16,30 +18,131 @@ use std::path::Path;
 /// Returns an error if checking fails.
 pub fn check(tree_path: &Path, cfg: &Config) -> Result<Vec<Violation>> {
     // Read tree file
-    let tree_source = std::fs::read_to_string(tree_path)
-        .with_context(|| format!("Failed to read tree file: {}", tree_path.display()))?;
+    let tree_source = bulloak_syntax::markdown::read_spec(tree_path)
+        .map_err(|source| IoError { path: tree_path.to_path_buf(), source })?;
+    let cfg = &cfg.for_tree(tree_path).for_spec(&tree_source);
 
     // Parse tree
-    let ast = bulloak_syntax::parse_one(&tree_source)?;
+    let ast = bulloak_syntax::cache::parse_one_with(&tree_source, &cfg.parse_options())
+        .map_err(|e| {
+            ParseError::Tree(bulloak_syntax::markdown::explain(tree_path, &cfg.parse_options(), e))
+        })?;
 
     // Determine Rust file path (replace .tree with _test.rs)
-    let file_stem = tree_path.file_stem()
-        .and_then(|s| s.to_str())
-        .ok_or_else(|| anyhow::anyhow!("Invalid file name"))?;
-    let rust_path = tree_path.with_file_name(format!("{}_test.rs", file_stem));
+    let rust_path = test_file_path(tree_path);
 
     // Check if Rust file exists
     if !rust_path.exists() {
         return Ok(vec![Violation::new(
             ViolationKind::RustFileMissing,
-            rust_path.display().to_string(),
+            rust_path,
         )]);
     }
 
     // Read Rust file
     let rust_source = std::fs::read_to_string(&rust_path)
-        .with_context(|| format!("Failed to read Rust file: {}", rust_path.display()))?;
+        .map_err(|source| IoError { path: rust_path.clone(), source })?;
 
     // Run structural match rule
-    rules::check_structural_match(&ast, &rust_source, &rust_path.display().to_string(), cfg)
+    let mut violations = rules::check_structural_match(&ast, &rust_source, &rust_path, cfg)?;
+
+    // Report helpers that no condition or test needs anymore
+    violations.extend(rules::check_unused_helpers(&ast, &rust_source, &rust_path, cfg));
+
+    // Reject code the spec doesn't derive
+    if cfg.strict {
+        violations.extend(rules::check_strict(&ast, &rust_source, &rust_path, cfg));
+    }
+
+    // Check the comments describing the actions of each test, unless they
+    // are doc comments
+    if cfg.format_descriptions && !cfg.doc_comments {
+        violations.extend(rules::check_descriptions(&ast, &rust_source, &rust_path, cfg));
+    }
+
+    // Check the spec banner is up to date
+    if cfg.tree_banner && !banner::matches(&rust_source, &tree_source) {
+        violations.push(Violation::new(ViolationKind::TreeBannerOutdated, rust_path));
+    }
+
+    Ok(violations)
+}
+
+/// Counts the actions in the tree at `tree_path` and how many of them have a
+/// test, given the `violations` found when checking it.
+///
+/// An action has a test when the test function covering it is in the Rust
+/// file, whether under its own name or under the one its anchor points to.
+/// When the Rust file couldn't be checked at all, no action has a test.
+///
+/// Returns `(covered, total)`, which is `(0, 0)` if the tree can't be read.
+#[must_use]
+pub fn coverage(tree_path: &Path, violations: &[Violation], cfg: &Config) -> (usize, usize) {
+    let Some(tests) = bulloak_syntax::markdown::read_spec(tree_path)
+        .ok()
+        .and_then(|text| bulloak_syntax::cache::parse_one_with(&text, &cfg.parse_options()).ok())
+        .and_then(|ast| rules::expected_tests(&ast, &cfg.for_tree(tree_path)).ok())
+    else {
+        return (0, 0);
+    };
+    let total = tests.iter().map(|test| test.actions.len()).sum();
+
+    let unchecked = violations.iter().any(|v| {
+        matches!(
+            v.kind,
+            ViolationKind::RustFileMissing
+                | ViolationKind::RustFileInvalid(_)
+                | ViolationKind::TestModuleMissing
+        )
+    });
+    if unchecked {
+        return (0, total);
+    }
+
+    let missing: usize = tests
+        .iter()
+        .filter(|test| {
+            violations.iter().any(|v| matches!(&v.kind, ViolationKind::TestFunctionMissing(name) if *name == test.name))
+        })
+        .map(|test| test.actions.len())
+        .sum();
+
+    (total - missing, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, coverage};
+    use crate::{config::Config, error::{Error, ParseError}};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn missing_tree_is_an_io_error() {
+        let err = check(Path::new("does/not/exist.tree"), &Config::default()).unwrap_err();
+        assert!(matches!(err, Error::Io(e) if e.path == Path::new("does/not/exist.tree")));
+    }
+
+    #[test]
+    fn counts_actions_with_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree = dir.path().join("foo.tree");
+        fs::write(&tree, "foo\n├── it should work.\n└── when bar\n    ├── it should baz.\n    └── it should qux.").unwrap();
+        let cfg = Config::default();
+        let measure = || coverage(&tree, &check(&tree, &cfg).unwrap(), &cfg);
+        assert_eq!(measure(), (0, 3));
+
+        let source = "#[cfg(test)]\nmod tests {\n    #[test]\n    fn test_when_bar() {}\n}\n";
+        fs::write(dir.path().join("foo_test.rs"), source).unwrap();
+        assert_eq!(measure(), (2, 3));
+    }
+
+    #[test]
+    fn invalid_tree_is_a_parse_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree = dir.path().join("bad.tree");
+        fs::write(&tree, "Foo\n├── when bar").unwrap();
+
+        let err = check(&tree, &Config::default()).unwrap_err();
+        assert!(matches!(err, Error::Parse(ParseError::Tree(_))));
+    }
 }
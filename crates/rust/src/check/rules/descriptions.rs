@@ -0,0 +1,166 @@
+//! Rule that checks the comments describing the actions of each test under
+//! `--format-descriptions`.
+
+use std::{ops::Range, path::Path};
+
+use bulloak_syntax::Ast;
+use syn::spanned::Spanned;
+
+use crate::{
+    check::{
+        rules::structural_match::expected_tests,
+        violation::{Violation, ViolationKind},
+    },
+    config::Config,
+    rust::ParsedRustFile,
+};
+
+/// Check that every test function holds the comments describing its actions,
+/// e.g. `// It should revert.`, as `scaffold --format-descriptions` writes
+/// them.
+///
+/// Comments that only differ in casing or punctuation are reported along with
+/// what they should read. Missing tests and files that don't parse are left
+/// to the structural match rule.
+#[must_use]
+pub fn check_descriptions(
+    ast: &Ast,
+    rust_source: &str,
+    file_path: &Path,
+    cfg: &Config,
+) -> Vec<Violation> {
+    let Ok(parsed) = ParsedRustFile::parse(rust_source) else {
+        return Vec::new();
+    };
+    let parsed = parsed.with_test_attributes(&cfg.test_attributes);
+    let Ok(expected) = expected_tests(ast, cfg) else { return Vec::new() };
+    let tests = parsed.find_test_functions();
+
+    let mut violations = Vec::new();
+    for test in expected {
+        let Some(func) = tests.iter().find(|func| func.sig.ident == test.name)
+        else {
+            continue;
+        };
+        let body = func.block.span().byte_range();
+        let comments = line_comments(&rust_source[body.clone()]);
+        for expected in test.descriptions {
+            if comments.iter().any(|(_, text)| *text == expected) {
+                continue;
+            }
+            let found = comments
+                .iter()
+                .find(|(_, text)| loosely_equal(text, &expected));
+            let line = found.map_or(
+                func.sig.ident.span().start().line,
+                |(range, _)| {
+                    rust_source[..body.start + range.start]
+                        .matches('\n')
+                        .count()
+                        + 1
+                },
+            );
+            violations.push(Violation::with_line(
+                ViolationKind::DescriptionIncorrect {
+                    function: test.name.clone(),
+                    found: found.map(|(_, text)| text.clone()),
+                    expected,
+                },
+                file_path.to_path_buf(),
+                line,
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Returns the range and the text of each line comment in `source`, where the
+/// text is what follows `// `.
+pub(crate) fn line_comments(source: &str) -> Vec<(Range<usize>, String)> {
+    let mut comments = Vec::new();
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let content = line.trim_end();
+        let trimmed = content.trim_start();
+        if trimmed.starts_with("//") && !trimmed.starts_with("///") {
+            let text = &trimmed[2..];
+            let text = text.strip_prefix(' ').unwrap_or(text);
+            comments.push((
+                offset + content.len() - trimmed.len()..offset + content.len(),
+                text.to_owned(),
+            ));
+        }
+        offset += line.len();
+    }
+
+    comments
+}
+
+/// Whether `text` is `description` up to casing and final punctuation.
+pub(crate) fn loosely_equal(text: &str, description: &str) -> bool {
+    let loose =
+        |s: &str| s.trim().trim_end_matches(['.', '!', '?']).to_lowercase();
+    loose(text) == loose(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use super::check_descriptions;
+    use crate::{check::ViolationKind, config::Config};
+
+    #[test]
+    fn reports_missing_and_unformatted_descriptions() {
+        let tree = "foo\n├── It should work.\n└── when bar\n    ├── it should baz\n    └── it should qux";
+        let ast = bulloak_syntax::parse_one(tree).unwrap();
+        let source = indoc! {r"
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn test_should_work() {
+                    // It should work.
+                }
+
+                #[test]
+                fn test_when_bar() {
+                    // it should qux!
+                }
+            }
+        "};
+
+        let kinds: Vec<_> = check_descriptions(
+            &ast,
+            source,
+            Path::new("foo_test.rs"),
+            &Config::default(),
+        )
+        .into_iter()
+        .map(|v| (v.kind, v.line))
+        .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    ViolationKind::DescriptionIncorrect {
+                        function: "test_when_bar".to_owned(),
+                        found: None,
+                        expected: "It should baz.".to_owned(),
+                    },
+                    Some(9)
+                ),
+                (
+                    ViolationKind::DescriptionIncorrect {
+                        function: "test_when_bar".to_owned(),
+                        found: Some("it should qux!".to_owned()),
+                        expected: "It should qux.".to_owned(),
+                    },
+                    Some(10)
+                ),
+            ]
+        );
+    }
+}
@@ -1,5 +1,11 @@
 //! Validation rules for checking Rust test files.
 
+pub mod descriptions;
+pub mod strict;
 pub mod structural_match;
+pub mod unused_helpers;
 
-pub use structural_match::check_structural_match;
+pub use descriptions::check_descriptions;
+pub use strict::check_strict;
+pub use structural_match::{check_structural_match, expected_tests, TestInfo};
+pub use unused_helpers::check_unused_helpers;
@@ -0,0 +1,219 @@
+//! Rule that rejects code the spec doesn't account for, so that test files
+//! stay purely spec-derived.
+
+use std::{collections::HashSet, path::Path};
+
+use bulloak_syntax::Ast;
+use syn::{Ident, Item, ItemFn, ItemMod};
+
+use crate::{
+    check::{
+        rules::structural_match::{expected_helpers, expected_tests},
+        violation::{Violation, ViolationKind},
+    },
+    config::Config,
+    rust::ParsedRustFile,
+};
+
+/// Check that the Rust file holds nothing but what the spec derives.
+///
+/// This reports non-test functions that no condition scaffolds, modules other
+/// than the test module, including modules nested in it unless they group the
+/// tests of a top-level condition, and tests outside of the test module. Unused
+/// context helpers are left to the unused helpers rule, and files that don't
+/// parse to the structural match rule.
+#[must_use]
+pub fn check_strict(
+    ast: &Ast,
+    rust_source: &str,
+    file_path: &Path,
+    cfg: &Config,
+) -> Vec<Violation> {
+    let Ok(parsed) = ParsedRustFile::parse(rust_source) else {
+        return Vec::new();
+    };
+    let parsed = parsed.with_test_attributes(&cfg.test_attributes);
+    let strict = Strict {
+        parsed: &parsed,
+        expected: if cfg.skip_helpers {
+            HashSet::default()
+        } else {
+            expected_helpers(ast, cfg)
+        },
+        used: parsed.test_body_idents(),
+        file_path,
+    };
+
+    let groups: HashSet<String> = match expected_tests(ast, cfg) {
+        Ok(tests) => tests.into_iter().filter_map(|test| test.module).collect(),
+        Err(_) => HashSet::new(),
+    };
+    let module = parsed.find_test_module();
+    let mut violations = Vec::new();
+    for item in &parsed.syntax.items {
+        match item {
+            Item::Fn(func) => strict.check_fn(func, false, &mut violations),
+            Item::Mod(m)
+                if module.is_some_and(|module| std::ptr::eq(m, module)) =>
+            {
+                strict.check_test_module(m, &groups, &mut violations);
+            }
+            Item::Mod(m) => violations.push(strict.stray_module(m)),
+            _ => {}
+        }
+    }
+
+    violations
+}
+
+/// What [`check_strict`] checks items against.
+struct Strict<'a> {
+    /// The Rust file.
+    parsed: &'a ParsedRustFile,
+    /// The helpers the spec scaffolds.
+    expected: HashSet<String>,
+    /// The identifiers the test bodies use.
+    used: HashSet<String>,
+    /// The path of the Rust file.
+    file_path: &'a Path,
+}
+
+impl Strict<'_> {
+    /// Reports `func` if it is a test outside of the test module, or a
+    /// non-test function that no condition scaffolds.
+    fn check_fn(
+        &self,
+        func: &ItemFn,
+        in_module: bool,
+        violations: &mut Vec<Violation>,
+    ) {
+        let name = func.sig.ident.to_string();
+        if self.parsed.has_test_attr(&func.attrs) {
+            if !in_module {
+                violations.push(self.violation(
+                    ViolationKind::TestOutsideModule(name),
+                    &func.sig.ident,
+                ));
+            }
+            return;
+        }
+
+        // Unused context helpers are reported by the unused helpers rule.
+        let unused = ParsedRustFile::is_context_helper(func)
+            && !self.used.contains(&name);
+        if !self.expected.contains(&name) && !unused {
+            violations.push(self.violation(
+                ViolationKind::FunctionUnexpected(name),
+                &func.sig.ident,
+            ));
+        }
+    }
+
+    /// Checks the items of the test module `module`, where the modules named
+    /// in `groups` are allowed and checked the same way.
+    fn check_test_module(
+        &self,
+        module: &ItemMod,
+        groups: &HashSet<String>,
+        violations: &mut Vec<Violation>,
+    ) {
+        let Some((_, items)) = &module.content else { return };
+        for item in items {
+            match item {
+                Item::Fn(func) => self.check_fn(func, true, violations),
+                Item::Mod(nested)
+                    if groups.contains(&nested.ident.to_string()) =>
+                {
+                    self.check_test_module(nested, &HashSet::new(), violations);
+                }
+                Item::Mod(nested) => violations.push(self.stray_module(nested)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Reports `module`, which isn't the test module.
+    fn stray_module(&self, module: &ItemMod) -> Violation {
+        self.violation(
+            ViolationKind::ModuleUnexpected(module.ident.to_string()),
+            &module.ident,
+        )
+    }
+
+    fn violation(&self, kind: ViolationKind, ident: &Ident) -> Violation {
+        Violation::with_line(
+            kind,
+            self.file_path.to_path_buf(),
+            ident.span().start().line,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use super::check_strict;
+    use crate::{check::ViolationKind, config::Config};
+
+    #[test]
+    fn reports_code_the_spec_does_not_derive() {
+        let ast =
+            bulloak_syntax::parse_one("foo\n└── when bar\n    └── it works")
+                .unwrap();
+        let source = indoc! {"
+            fn bar(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            fn unused(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            fn setup() -> u8 {
+                1
+            }
+
+            mod fixtures {}
+
+            #[test]
+            fn test_stray() {}
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+
+                fn local() {}
+
+                #[test]
+                fn test_when_bar() {
+                    let _ctx = bar(TestContext::default());
+                }
+
+                mod nested {}
+            }
+        "};
+
+        let kinds: Vec<ViolationKind> = check_strict(
+            &ast,
+            source,
+            Path::new("foo_test.rs"),
+            &Config::default(),
+        )
+        .into_iter()
+        .map(|violation| violation.kind)
+        .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ViolationKind::FunctionUnexpected("setup".to_owned()),
+                ViolationKind::ModuleUnexpected("fixtures".to_owned()),
+                ViolationKind::TestOutsideModule("test_stray".to_owned()),
+                ViolationKind::FunctionUnexpected("local".to_owned()),
+                ViolationKind::ModuleUnexpected("nested".to_owned()),
+            ]
+        );
+    }
+}
@@ -4,11 +4,13 @@ use crate::{
     check::violation::{Violation, ViolationKind},
     config::Config,
     rust::ParsedRustFile,
-    utils::to_snake_case,
+    scaffold::comment::format_comment,
+    utils::{condition_name, condition_test_name, group_module_name, helper_name, ordered_test_name, to_snake_case},
 };
-use anyhow::Result;
-use bulloak_syntax::Ast;
-use std::collections::HashSet;
+use crate::error::{Result, SpecError};
+use bulloak_syntax::{utils::branch_hash, Action, Ast, Condition, Span};
+use std::{collections::{HashMap, HashSet}, path::Path};
+use syn::{spanned::Spanned, ItemFn};
 
 /// Expected test structure extracted from AST.
 struct ExpectedTests {
@@ -16,9 +18,23 @@ struct ExpectedTests {
     test_functions: Vec<TestInfo>,
 }
 
-struct TestInfo {
-    name: String,
-    should_panic: bool,
+/// A test function that the scaffolded Rust file is expected to contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestInfo {
+    /// The name of the test function.
+    pub name: String,
+    /// Whether the test function is expected to be `#[should_panic]`.
+    pub should_panic: bool,
+    /// The spans of the actions this test function covers.
+    pub actions: Vec<Span>,
+    /// The anchor of the branch this test function covers, if it has one.
+    pub anchor: Option<String>,
+    /// The module nested in the test module that holds this test function,
+    /// when tests are grouped by top-level condition.
+    pub module: Option<String>,
+    /// The comments describing the actions this test function covers, as
+    /// `--format-descriptions` formats them, e.g. `It should revert.`.
+    pub descriptions: Vec<String>,
 }
 
 /// Check that the Rust file structurally matches the spec.
@@ -29,18 +45,18 @@ struct TestInfo {
 pub fn check_structural_match(
     ast: &Ast,
     rust_source: &str,
-    file_path: &str,
+    file_path: &Path,
     cfg: &Config,
 ) -> Result<Vec<Violation>> {
     let mut violations = Vec::new();
 
     // Parse the Rust file
     let parsed = match ParsedRustFile::parse(rust_source) {
-        Ok(p) => p,
+        Ok(p) => p.with_test_attributes(&cfg.test_attributes),
         Err(e) => {
             violations.push(Violation::new(
                 ViolationKind::RustFileInvalid(e.to_string()),
-                file_path.to_string(),
+                file_path.to_path_buf(),
             ));
             return Ok(violations);
         }
@@ -50,7 +66,7 @@ pub fn check_structural_match(
     if parsed.find_test_module().is_none() {
         violations.push(Violation::new(
             ViolationKind::TestModuleMissing,
-            file_path.to_string(),
+            file_path.to_path_buf(),
         ));
         return Ok(violations);
     }
@@ -70,144 +86,354 @@ pub fn check_structural_match(
             if !found_helpers.contains(expected_helper) {
                 violations.push(Violation::new(
                     ViolationKind::HelperFunctionMissing(expected_helper.clone()),
-                    file_path.to_string(),
+                    file_path.to_path_buf(),
                 ));
             }
         }
     }
 
-    // Check test functions
-    let found_tests = parsed.find_test_functions();
-    let found_test_names: HashSet<String> =
-        found_tests.iter().map(|f| f.sig.ident.to_string()).collect();
+    // Check test functions, along with the module they are grouped in, if
+    // tests are grouped at all
+    let grouped_tests: Vec<(Option<String>, &ItemFn)> = parsed
+        .find_grouped_test_functions()
+        .into_iter()
+        .map(|(module, func)| (module.filter(|_| cfg.group_modules), func))
+        .collect();
+    let found_tests: Vec<&ItemFn> = grouped_tests.iter().map(|(_, func)| *func).collect();
+    let expected_places: HashSet<(Option<&str>, &str)> =
+        expected.test_functions.iter().map(|test| (test.module.as_deref(), test.name.as_str())).collect();
 
     for expected_test in &expected.test_functions {
-        if !found_test_names.contains(&expected_test.name) {
+        let found_fn = grouped_tests
+            .iter()
+            .find(|(module, f)| f.sig.ident == expected_test.name && *module == expected_test.module)
+            .map(|(_, f)| *f);
+        let Some(found_fn) = found_fn else {
+            // A test of that name in a module where no such test belongs
+            let misplaced = grouped_tests
+                .iter()
+                .find(|(module, f)| {
+                    let name = f.sig.ident.to_string();
+                    name == expected_test.name && !expected_places.contains(&(module.as_deref(), name.as_str()))
+                })
+                .map(|(_, f)| *f);
+            let anchored = expected_test
+                .anchor
+                .as_deref()
+                .and_then(|anchor| find_anchored_test(&found_tests, rust_source, anchor));
+            let kind = match (misplaced, anchored) {
+                (Some(_), _) => ViolationKind::TestModuleIncorrect {
+                    function: expected_test.name.clone(),
+                    module: expected_test.module.clone(),
+                },
+                (None, Some(func)) => ViolationKind::TestFunctionNameMismatch {
+                    expected: expected_test.name.clone(),
+                    found: func.sig.ident.to_string(),
+                },
+                (None, None) => ViolationKind::TestFunctionMissing(expected_test.name.clone()),
+            };
+            violations.push(match misplaced {
+                Some(func) => Violation::with_line(kind, file_path.to_path_buf(), func.sig.ident.span().start().line),
+                None => Violation::new(kind, file_path.to_path_buf()),
+            });
+            continue;
+        };
+
+        // Check attributes
+        if expected_test.should_panic && !ParsedRustFile::has_should_panic(found_fn) {
             violations.push(Violation::new(
-                ViolationKind::TestFunctionMissing(expected_test.name.clone()),
-                file_path.to_string(),
+                ViolationKind::TestAttributeIncorrect {
+                    function: expected_test.name.clone(),
+                    expected: "#[should_panic]".to_string(),
+                    found: "none".to_string(),
+                },
+                file_path.to_path_buf(),
             ));
-        } else {
-            // Check attributes
-            let found_fn = found_tests
-                .iter()
-                .find(|f| f.sig.ident.to_string() == expected_test.name)
-                .unwrap();
+        }
+    }
 
-            let has_should_panic = ParsedRustFile::has_should_panic(found_fn);
+    // Check that the tests of each module appear in spec order
+    if let Some(func) = first_misordered_test(&expected.test_functions, &grouped_tests) {
+        violations.push(Violation::with_line(
+            ViolationKind::TestOrderIncorrect,
+            file_path.to_path_buf(),
+            func.sig.ident.span().start().line,
+        ));
+    }
 
-            if expected_test.should_panic && !has_should_panic {
-                violations.push(Violation::new(
-                    ViolationKind::TestAttributeIncorrect {
-                        function: expected_test.name.clone(),
-                        expected: "#[should_panic]".to_string(),
-                        found: "none".to_string(),
-                    },
-                    file_path.to_string(),
-                ));
-            }
+    Ok(violations)
+}
+
+/// Returns the first test function in `grouped_tests` that is out of spec
+/// order among the tests of its module, if any.
+fn first_misordered_test<'a>(expected: &[TestInfo], grouped_tests: &[(Option<String>, &'a ItemFn)]) -> Option<&'a ItemFn> {
+    let positions: HashMap<(Option<&str>, String), usize> = expected
+        .iter()
+        .enumerate()
+        .map(|(i, test)| ((test.module.as_deref(), test.name.clone()), i))
+        .collect();
+    let mut modules: Vec<Option<&str>> = Vec::new();
+    for (module, _) in grouped_tests {
+        if !modules.contains(&module.as_deref()) {
+            modules.push(module.as_deref());
         }
     }
+    modules.into_iter().find_map(|module| {
+        let found_order: Vec<(&ItemFn, usize)> = grouped_tests
+            .iter()
+            .filter(|(m, _)| m.as_deref() == module)
+            .filter_map(|(_, f)| positions.get(&(module, f.sig.ident.to_string())).map(|&i| (*f, i)))
+            .collect();
+        let mut sorted = found_order.clone();
+        sorted.sort_by_key(|(_, i)| *i);
+        found_order
+            .into_iter()
+            .zip(sorted)
+            .find(|((_, found), (_, expected))| found != expected)
+            .map(|((func, _), _)| func)
+    })
+}
 
-    Ok(violations)
+/// Finds the test function in `tests` with a `// #anchor` comment among the
+/// comments right above it.
+fn find_anchored_test<'a>(tests: &[&'a ItemFn], source: &str, anchor: &str) -> Option<&'a ItemFn> {
+    let lines: Vec<&str> = source.lines().collect();
+    let marker = format!("// #{anchor}");
+    tests.iter().copied().find(|func| {
+        // Lines are 1-based, so this skips the line the function starts on.
+        let start = func.span().start().line.saturating_sub(1);
+        lines[..start.min(lines.len())]
+            .iter()
+            .rev()
+            .map(|line| line.trim())
+            .take_while(|line| line.starts_with("//"))
+            .any(|line| line == marker)
+    })
 }
 
-/// Extract expected test structure from AST.
-fn extract_expected_structure(ast: &Ast, cfg: &Config) -> Result<ExpectedTests> {
+/// Returns the test functions expected for `ast`, in the order they are
+/// scaffolded.
+///
+/// # Errors
+///
+/// Returns an error if `ast` is not a root node.
+pub fn expected_tests(ast: &Ast, cfg: &Config) -> Result<Vec<TestInfo>> {
     let ast_root = match ast {
         Ast::Root(r) => r,
-        _ => anyhow::bail!("Expected Root node"),
+        _ => return Err(SpecError::NotARoot.into()),
     };
 
+    let mut collected = Vec::new();
+    collect_tests_recursive(&ast_root.children, &[], None, cfg, &mut collected);
+
+    // Ordering prefixes already make every name unique, so only unprefixed
+    // names go through disambiguation. Grouped tests only need names that are
+    // unique within their module. Explicit names are kept as they are.
+    let mut used: HashMap<Option<String>, HashSet<String>> = HashMap::new();
+    let test_functions = collected
+        .into_iter()
+        .enumerate()
+        .map(|(index, (mut test, ancestors, branch, explicit))| {
+            if let Some(existing) = cfg.renamed(&test.name) {
+                used.entry(test.module.clone()).or_default().insert(existing.to_owned());
+                existing.clone_into(&mut test.name);
+                return test;
+            }
+            test.name = if explicit {
+                used.entry(test.module.clone()).or_default().insert(test.name.clone());
+                test.name
+            } else if cfg.ordering_prefix {
+                ordered_test_name(test.name, index + 1, true)
+            } else {
+                let used = used.entry(test.module.clone()).or_default();
+                unique_test_name(test.name, &ancestors, branch, cfg, used)
+            };
+            test
+        })
+        .collect();
+    Ok(test_functions)
+}
+
+/// Makes `name` unique among `used` and records it.
+///
+/// Clashing names get the names of their ancestor conditions appended, nearest
+/// first, and then a suffix derived from the branch's anchor or contents. This
+/// keeps suffixes stable when unrelated branches are added to the tree. A
+/// numeric counter is only used for branches that are identical.
+fn unique_test_name(
+    name: String,
+    ancestors: &[String],
+    branch: Option<&Condition>,
+    cfg: &Config,
+    used: &mut HashSet<String>,
+) -> String {
+    if used.insert(name.clone()) {
+        return name;
+    }
+
+    let mut candidate = name;
+    for ancestor in ancestors.iter().rev() {
+        let test_name = condition_test_name(ancestor, cfg.keep_condition_prefix);
+        let part = test_name.strip_prefix("test_").unwrap_or(&test_name);
+        candidate = format!("{candidate}_{part}");
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+    }
+
+    if let Some(branch) = branch {
+        let id = branch.anchor.as_ref().map_or_else(
+            || branch_hash(branch),
+            |anchor| anchor.to_lowercase().replace('-', "_"),
+        );
+        candidate = format!("{candidate}_{id}");
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+    }
+
+    let mut counter = 2;
+    loop {
+        let numbered = format!("{candidate}_{counter}");
+        if used.insert(numbered.clone()) {
+            return numbered;
+        }
+        counter += 1;
+    }
+}
+
+/// Extract expected test structure from AST.
+fn extract_expected_structure(ast: &Ast, cfg: &Config) -> Result<ExpectedTests> {
+    let test_functions = expected_tests(ast, cfg)?;
+    let Ast::Root(ast_root) = ast else { unreachable!() };
+
     let mut helpers = HashSet::new();
-    let mut test_functions = Vec::new();
 
     // Collect helpers
     if !cfg.skip_helpers {
-        collect_helpers_recursive(&ast_root.children, &mut helpers);
+        collect_helpers_recursive(&ast_root.children, cfg, &mut helpers);
     }
 
-    // Collect test functions
-    collect_tests_recursive(&ast_root.children, &[], &mut test_functions);
-
     Ok(ExpectedTests {
         helpers,
         test_functions,
     })
 }
 
+/// Returns the names of the helper functions scaffolded for `ast`.
+pub(crate) fn expected_helpers(ast: &Ast, cfg: &Config) -> HashSet<String> {
+    let mut helpers = HashSet::new();
+    if let Ast::Root(root) = ast {
+        collect_helpers_recursive(&root.children, cfg, &mut helpers);
+    }
+
+    helpers
+}
+
 /// Recursively collect helper function names.
 fn collect_helpers_recursive(
     children: &[Ast],
+    cfg: &Config,
     helpers: &mut HashSet<String>,
 ) {
     for child in children {
         if let Ast::Condition(condition) = child {
-            let name = to_snake_case(&condition.title);
-            helpers.insert(name);
-            collect_helpers_recursive(&condition.children, helpers);
+            let name = condition_name(&condition.title, cfg.keep_condition_prefix, &cfg.keywords);
+            helpers.insert(helper_name(&name, &cfg.helper_prefix, &cfg.helper_suffix));
+            collect_helpers_recursive(&condition.children, cfg, helpers);
         }
     }
 }
 
+/// A collected test, along with its ancestor helper names, the condition it
+/// was scaffolded from and whether its name was given explicitly.
+type CollectedTest<'a> = (TestInfo, Vec<String>, Option<&'a Condition>, bool);
+
 /// Recursively collect test function info.
-fn collect_tests_recursive(
-    children: &[Ast],
+///
+/// `module` is the module the tests of `children` are grouped in, if any.
+fn collect_tests_recursive<'a>(
+    children: &'a [Ast],
     parent_helpers: &[String],
-    tests: &mut Vec<TestInfo>,
+    module: Option<&str>,
+    cfg: &Config,
+    tests: &mut Vec<CollectedTest<'a>>,
 ) {
     for child in children {
         match child {
             Ast::Condition(condition) => {
-                let helper_name = to_snake_case(&condition.title);
+                let helper_name = condition_name(&condition.title, cfg.keep_condition_prefix, &cfg.keywords);
                 let mut new_helpers = parent_helpers.to_vec();
                 new_helpers.push(helper_name);
+                let group = (cfg.group_modules && parent_helpers.is_empty()).then(|| group_module_name(&condition.title));
+                let module = group.as_deref().or(module);
 
                 // Collect all direct action children of this condition
-                let actions: Vec<&bulloak_syntax::Action> = condition.children.iter()
+                let actions: Vec<&Action> = condition.children.iter()
                     .filter_map(|c| if let Ast::Action(a) = c { Some(a) } else { None })
                     .collect();
 
-                if !actions.is_empty() {
-                    // Generate a single test for all actions under this condition
-                    let test_name = if new_helpers.is_empty() {
-                        let action_part = to_snake_case(&actions[0].title);
-                        format!("test_{}", action_part)
-                    } else {
-                        let last_helper = &new_helpers[new_helpers.len() - 1];
-                        format!("test_when_{}", last_helper)
-                    };
-
-                    // Check if any action should panic
-                    let should_panic = actions.iter().any(|action| {
-                        action.title.to_lowercase()
-                            .split_whitespace()
-                            .any(|w| matches!(w, "panic" | "panics" | "revert" | "reverts" | "error" | "errors" | "fail" | "fails"))
-                    });
-
-                    tests.push(TestInfo {
-                        name: test_name,
-                        should_panic,
-                    });
+                let last_helper = &new_helpers[new_helpers.len() - 1];
+                let test_name = condition_test_name(last_helper, cfg.keep_condition_prefix);
+                if cfg.split_actions && actions.len() > 1 {
+                    // Generate a test per action, named after the condition and the action
+                    for action in &actions {
+                        let action_part = to_snake_case(&action.title, &cfg.keywords);
+                        tests.push((
+                            TestInfo {
+                                name: action.name.clone().unwrap_or_else(|| format!("{test_name}_{action_part}")),
+                                should_panic: should_panic(action),
+                                actions: vec![action.span],
+                                anchor: action.anchor.clone(),
+                                module: module.map(str::to_owned),
+                                descriptions: vec![description(action)],
+                            },
+                            parent_helpers.to_vec(),
+                            Some(condition),
+                            action.name.is_some(),
+                        ));
+                    }
+                } else if !actions.is_empty() {
+                    // Generate a single test for all actions under this condition,
+                    // named after the first action with an explicit name, if any
+                    let name = actions.iter().find_map(|action| action.name.clone());
+                    tests.push((
+                        TestInfo {
+                            name: name.clone().unwrap_or(test_name),
+                            should_panic: actions.iter().any(|action| should_panic(action)),
+                            actions: actions.iter().map(|a| a.span).collect(),
+                            anchor: condition.anchor.clone(),
+                            module: module.map(str::to_owned),
+                            descriptions: actions.iter().map(|a| description(a)).collect(),
+                        },
+                        parent_helpers.to_vec(),
+                        Some(condition),
+                        name.is_some(),
+                    ));
                 }
 
                 // Process nested conditions
-                collect_tests_recursive(&condition.children, &new_helpers, tests);
+                collect_tests_recursive(&condition.children, &new_helpers, module, cfg, tests);
             }
             Ast::Action(action) => {
                 // Root-level action (no condition)
                 if parent_helpers.is_empty() {
-                    let action_part = to_snake_case(&action.title);
+                    let action_part = to_snake_case(&action.title, &cfg.keywords);
                     let test_name = format!("test_{}", action_part);
 
-                    let should_panic = action.title.to_lowercase()
-                        .split_whitespace()
-                        .any(|w| matches!(w, "panic" | "panics" | "revert" | "reverts" | "error" | "errors" | "fail" | "fails"));
-
-                    tests.push(TestInfo {
-                        name: test_name,
-                        should_panic,
-                    });
+                    tests.push((
+                        TestInfo {
+                            name: action.name.clone().unwrap_or(test_name),
+                            should_panic: should_panic(action),
+                            actions: vec![action.span],
+                            anchor: action.anchor.clone(),
+                            module: None,
+                            descriptions: vec![description(action)],
+                        },
+                        Vec::new(),
+                        None,
+                        action.name.is_some(),
+                    ));
                 }
             }
             _ => {}
@@ -215,3 +441,20 @@ fn collect_tests_recursive(
     }
 }
 
+/// Whether the test of `action` is expected to be `#[should_panic]`, going by
+/// the words of its title.
+fn should_panic(action: &Action) -> bool {
+    action.title.to_lowercase()
+        .split_whitespace()
+        .any(|w| matches!(w, "panic" | "panics" | "revert" | "reverts" | "error" | "errors" | "fail" | "fails"))
+}
+
+/// The comment describing `action`, as `--format-descriptions` formats it,
+/// followed by the anchor of the action, if any.
+fn description(action: &Action) -> String {
+    let title = format_comment(&action.title);
+    match &action.anchor {
+        Some(anchor) => format!("{title} #{anchor}"),
+        None => title,
+    }
+}
@@ -0,0 +1,134 @@
+//! Rule that reports helpers left behind after their condition was removed
+//! from the spec.
+
+use std::{collections::HashSet, path::Path};
+
+use bulloak_syntax::Ast;
+
+use crate::{
+    check::{
+        rules::structural_match::expected_helpers,
+        violation::{Violation, ViolationKind},
+    },
+    config::Config,
+    rust::ParsedRustFile,
+};
+
+/// Check that every scaffolded helper in the Rust file is still in use.
+///
+/// A helper is unused when no condition of the spec scaffolds it anymore and
+/// no test body calls it. Only functions shaped like scaffolded helpers, which
+/// take and return the test context, are considered. Files that don't parse
+/// are left to the structural match rule.
+#[must_use]
+pub fn check_unused_helpers(
+    ast: &Ast,
+    rust_source: &str,
+    file_path: &Path,
+    cfg: &Config,
+) -> Vec<Violation> {
+    let Ok(parsed) = ParsedRustFile::parse(rust_source) else {
+        return Vec::new();
+    };
+    let parsed = parsed.with_test_attributes(&cfg.test_attributes);
+    let expected = if cfg.skip_helpers {
+        HashSet::default()
+    } else {
+        expected_helpers(ast, cfg)
+    };
+    let used = parsed.test_body_idents();
+
+    parsed
+        .find_helper_functions()
+        .into_iter()
+        .filter(|func| ParsedRustFile::is_context_helper(func))
+        .filter(|func| {
+            let name = func.sig.ident.to_string();
+            !expected.contains(&name) && !used.contains(&name)
+        })
+        .map(|func| {
+            Violation::with_line(
+                ViolationKind::HelperFunctionUnused {
+                    name: func.sig.ident.to_string(),
+                    empty: ParsedRustFile::is_empty_helper(func),
+                },
+                file_path.to_path_buf(),
+                func.sig.ident.span().start().line,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use super::check_unused_helpers;
+    use crate::{check::ViolationKind, config::Config};
+
+    #[test]
+    fn reports_helpers_no_test_uses() {
+        let ast =
+            bulloak_syntax::parse_one("foo\n└── when bar\n    └── it works")
+                .unwrap();
+        let source = indoc! {"
+            fn bar(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            fn baz(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            fn qux(mut ctx: TestContext) -> TestContext {
+                ctx.value = 1;
+                ctx
+            }
+
+            fn used(mut ctx: TestContext) -> TestContext {
+                ctx
+            }
+
+            fn unrelated() {}
+
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn test_when_bar() {
+                    let _ctx = used(bar(TestContext::default()));
+                }
+            }
+        "};
+
+        let kinds: Vec<_> = check_unused_helpers(
+            &ast,
+            source,
+            Path::new("foo_test.rs"),
+            &Config::default(),
+        )
+        .into_iter()
+        .map(|violation| (violation.kind, violation.line))
+        .collect();
+        assert_eq!(
+            kinds,
+            [
+                (
+                    ViolationKind::HelperFunctionUnused {
+                        name: "baz".to_owned(),
+                        empty: true
+                    },
+                    Some(5)
+                ),
+                (
+                    ViolationKind::HelperFunctionUnused {
+                        name: "qux".to_owned(),
+                        empty: false
+                    },
+                    Some(9)
+                ),
+            ]
+        );
+    }
+}
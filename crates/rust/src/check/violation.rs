@@ -1,6 +1,8 @@
 //! Violation types for check command.
 
-use std::fmt;
+use std::{fmt, path::PathBuf};
+
+use crate::constants::CONTEXT_STRUCT_NAME;
 
 /// A violation found during checking.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -8,7 +10,7 @@ pub struct Violation {
     /// The kind of violation.
     pub kind: ViolationKind,
     /// The file path where the violation occurred.
-    pub file_path: String,
+    pub file_path: PathBuf,
     /// Optional line number.
     pub line: Option<usize>,
 }
@@ -16,7 +18,7 @@ pub struct Violation {
 impl Violation {
     /// Create a new violation.
     #[must_use]
-    pub fn new(kind: ViolationKind, file_path: String) -> Self {
+    pub fn new(kind: ViolationKind, file_path: PathBuf) -> Self {
         Self {
             kind,
             file_path,
@@ -26,7 +28,7 @@ impl Violation {
 
     /// Create a new violation with a line number.
     #[must_use]
-    pub fn with_line(kind: ViolationKind, file_path: String, line: usize) -> Self {
+    pub fn with_line(kind: ViolationKind, file_path: PathBuf, line: usize) -> Self {
         Self {
             kind,
             file_path,
@@ -35,6 +37,15 @@ impl Violation {
     }
 }
 
+/// How much a violation matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Reported, but only fails `bulloak check` under `--warnings-as-errors`.
+    Warning,
+    /// Fails `bulloak check`.
+    Error,
+}
+
 /// The kind of violation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ViolationKind {
@@ -48,6 +59,14 @@ pub enum ViolationKind {
     TestFunctionMissing(String),
     /// A helper function is missing.
     HelperFunctionMissing(String),
+    /// A helper function is neither scaffolded from the spec nor used by any
+    /// test.
+    HelperFunctionUnused {
+        /// The function name.
+        name: String,
+        /// Whether the helper does nothing but return the context.
+        empty: bool,
+    },
     /// A test function has incorrect attributes.
     TestAttributeIncorrect {
         /// The function name.
@@ -57,8 +76,202 @@ pub enum ViolationKind {
         /// The found attribute.
         found: String,
     },
+    /// A test function found by the anchor of its branch has another name.
+    TestFunctionNameMismatch {
+        /// The name the spec expects.
+        expected: String,
+        /// The name of the anchored function.
+        found: String,
+    },
+    /// A non-test function that no condition of the spec scaffolds, reported
+    /// under `--strict`.
+    FunctionUnexpected(String),
+    /// A module other than the test module, reported under `--strict`.
+    ModuleUnexpected(String),
+    /// A test function outside of the test module, reported under
+    /// `--strict`.
+    TestOutsideModule(String),
+    /// A test function outside of the module of its top-level condition,
+    /// when tests are grouped by top-level condition.
+    TestModuleIncorrect {
+        /// The function name.
+        function: String,
+        /// The module the test belongs in, or `None` for the test module
+        /// itself.
+        module: Option<String>,
+    },
+    /// A comment describing an action of a test function is missing or isn't
+    /// formatted, under `--format-descriptions`.
+    DescriptionIncorrect {
+        /// The function name.
+        function: String,
+        /// The comment found in its place, if any.
+        found: Option<String>,
+        /// The comment the spec expects.
+        expected: String,
+    },
+    /// A custom rule loaded from the rules directory reported a violation.
+    CustomRuleViolated {
+        /// The name of the rule.
+        rule: String,
+        /// What the rule reported.
+        message: String,
+    },
     /// Test function order does not match spec.
     TestOrderIncorrect,
+    /// The banner holding the spec is missing or out of date.
+    TreeBannerOutdated,
+}
+
+impl ViolationKind {
+    /// Whether `bulloak check --fix` can fix this violation kind.
+    #[must_use]
+    pub fn is_fixable(&self) -> bool {
+        // Only helpers that were never filled in are safe to delete.
+        if let Self::HelperFunctionUnused { empty, .. } = self {
+            return *empty;
+        }
+        matches!(
+            self,
+            Self::RustFileMissing
+                | Self::TestFunctionNameMismatch { .. }
+                | Self::TestOrderIncorrect
+                | Self::TreeBannerOutdated
+                | Self::DescriptionIncorrect { .. }
+        )
+    }
+
+    /// The severity of this violation kind.
+    ///
+    /// Dead helpers don't affect what the tests check, so they are only
+    /// warnings.
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::HelperFunctionUnused { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// The stable ID of the rule this violation kind breaks, e.g. `BK105`.
+    ///
+    /// IDs are shared with the Solidity backend, and `bulloak explain <ID>`
+    /// describes the rule.
+    #[must_use]
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Self::RustFileInvalid(_) => "BK001",
+            Self::RustFileMissing => "BK101",
+            Self::TestModuleMissing => "BK103",
+            Self::TestFunctionMissing(_) => "BK105",
+            Self::HelperFunctionMissing(_) => "BK106",
+            Self::TestOrderIncorrect => "BK107",
+            Self::TestFunctionNameMismatch { .. } => "BK108",
+            Self::TestAttributeIncorrect { .. } => "BK109",
+            Self::HelperFunctionUnused { .. } => "BK110",
+            Self::TreeBannerOutdated => "BK111",
+            Self::TestModuleIncorrect { .. } => "BK112",
+            Self::DescriptionIncorrect { .. } => "BK204",
+            Self::FunctionUnexpected(_) => "BK301",
+            Self::ModuleUnexpected(_) => "BK302",
+            Self::TestOutsideModule(_) => "BK303",
+            Self::CustomRuleViolated { .. } => "BK401",
+        }
+    }
+
+    /// The category of this violation kind in the summary at the end of
+    /// `bulloak check`, as a singular and a plural noun, e.g.
+    /// `("missing test", "missing tests")`.
+    #[must_use]
+    pub fn category(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::RustFileMissing => ("missing file", "missing files"),
+            Self::RustFileInvalid(_) => ("parse error", "parse errors"),
+            Self::TestModuleMissing => ("missing test module", "missing test modules"),
+            Self::TestFunctionMissing(_) => ("missing test", "missing tests"),
+            Self::HelperFunctionMissing(_) => ("missing helper", "missing helpers"),
+            Self::HelperFunctionUnused { .. } => ("unused helper", "unused helpers"),
+            Self::TestAttributeIncorrect { .. } => ("attribute mismatch", "attribute mismatches"),
+            Self::TestFunctionNameMismatch { .. } => ("name mismatch", "name mismatches"),
+            Self::FunctionUnexpected(_) => ("unexpected function", "unexpected functions"),
+            Self::ModuleUnexpected(_) => ("unexpected module", "unexpected modules"),
+            Self::TestOutsideModule(_) | Self::TestModuleIncorrect { .. } => ("misplaced test", "misplaced tests"),
+            Self::DescriptionIncorrect { .. } => ("description mismatch", "description mismatches"),
+            Self::CustomRuleViolated { .. } => ("custom rule violation", "custom rule violations"),
+            Self::TestOrderIncorrect => ("order issue", "order issues"),
+            Self::TreeBannerOutdated => ("outdated banner", "outdated banners"),
+        }
+    }
+
+    /// Optionally returns a help text to be displayed under the violation.
+    #[must_use]
+    pub fn help(&self) -> Option<String> {
+        let text = match self {
+            Self::RustFileMissing => "consider running `bulloak scaffold -b rust` on the tree".to_owned(),
+            Self::RustFileInvalid(_) | Self::CustomRuleViolated { .. } => return None,
+            Self::TestModuleMissing => "consider adding a test module to the file".to_owned(),
+            Self::TestFunctionMissing(name) => {
+                format!("consider adding the test function '{name}' to the test module")
+            }
+            Self::HelperFunctionMissing(name) => {
+                format!("consider adding the helper function '{name}' to the file")
+            }
+            Self::HelperFunctionUnused { name, .. } => {
+                format!("consider removing the helper function '{name}'")
+            }
+            Self::TestAttributeIncorrect { expected, .. } => {
+                format!("consider annotating the function with {expected}")
+            }
+            Self::TestFunctionNameMismatch { expected, .. } => {
+                format!("consider renaming the test function to '{expected}'")
+            }
+            Self::FunctionUnexpected(name) => {
+                format!("consider removing the function '{name}' or deriving it from a condition")
+            }
+            Self::ModuleUnexpected(name) => {
+                format!("consider moving the contents of the module '{name}' out of the test file")
+            }
+            Self::TestOutsideModule(name) => {
+                format!("consider moving the test function '{name}' into the test module")
+            }
+            Self::TestModuleIncorrect { function, module: Some(module) } => {
+                format!("consider moving the test function '{function}' into `mod {module}`")
+            }
+            Self::TestModuleIncorrect { function, module: None } => {
+                format!("consider moving the test function '{function}' to the top of the test module")
+            }
+            Self::DescriptionIncorrect { found: Some(_), expected, .. } => {
+                format!("consider changing the comment to `// {expected}`")
+            }
+            Self::DescriptionIncorrect { found: None, expected, .. } => {
+                format!("consider adding `// {expected}` to the test function")
+            }
+            Self::TestOrderIncorrect => {
+                "consider reordering the test functions to match the spec".to_owned()
+            }
+            Self::TreeBannerOutdated => {
+                "consider copying the spec into the banner at the top of the file".to_owned()
+            }
+        };
+
+        Some(text)
+    }
+
+    /// Optionally returns a code snippet that would resolve the violation,
+    /// e.g. the stub of a missing function.
+    #[must_use]
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            Self::TestModuleMissing => {
+                Some("#[cfg(test)]\nmod tests {\n    use super::*;\n}".to_owned())
+            }
+            Self::TestFunctionMissing(name) => Some(format!("#[test]\nfn {name}() {{\n}}")),
+            Self::HelperFunctionMissing(name) => Some(format!(
+                "fn {name}(mut ctx: {CONTEXT_STRUCT_NAME}) -> {CONTEXT_STRUCT_NAME} {{\n    ctx\n}}"
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for ViolationKind {
@@ -69,6 +282,9 @@ impl fmt::Display for ViolationKind {
             Self::TestModuleMissing => write!(f, "Test module (#[cfg(test)] mod tests) is missing"),
             Self::TestFunctionMissing(name) => write!(f, "Test function '{}' is missing", name),
             Self::HelperFunctionMissing(name) => write!(f, "Helper function '{}' is missing", name),
+            Self::HelperFunctionUnused { name, .. } => {
+                write!(f, "Helper function '{name}' is not used by any test")
+            }
             Self::TestAttributeIncorrect {
                 function,
                 expected,
@@ -78,9 +294,37 @@ impl fmt::Display for ViolationKind {
                 "Test function '{}' has incorrect attributes: expected {}, found {}",
                 function, expected, found
             ),
+            Self::TestFunctionNameMismatch { expected, found } => {
+                write!(f, "Test function '{found}' should be named '{expected}' to match its anchor")
+            }
+            Self::FunctionUnexpected(name) => {
+                write!(f, "Function '{name}' is not derived from the spec")
+            }
+            Self::ModuleUnexpected(name) => {
+                write!(f, "Module '{name}' is not the test module")
+            }
+            Self::TestOutsideModule(name) => {
+                write!(f, "Test function '{name}' is outside of the test module")
+            }
+            Self::TestModuleIncorrect { function, module: Some(module) } => {
+                write!(f, "Test function '{function}' is not in the module '{module}' of its condition")
+            }
+            Self::TestModuleIncorrect { function, module: None } => {
+                write!(f, "Test function '{function}' is not at the top of the test module")
+            }
+            Self::DescriptionIncorrect { function, found: Some(found), expected } => {
+                write!(f, "Comment '// {found}' of test function '{function}' should read '// {expected}'")
+            }
+            Self::DescriptionIncorrect { function, found: None, expected } => {
+                write!(f, "Test function '{function}' is missing the comment '// {expected}'")
+            }
+            Self::CustomRuleViolated { rule, message } => write!(f, "{message} (rule '{rule}')"),
             Self::TestOrderIncorrect => {
                 write!(f, "Test function order does not match spec order")
             }
+            Self::TreeBannerOutdated => {
+                write!(f, "Spec banner is missing or does not match the spec")
+            }
         }
     }
 }
@@ -88,9 +332,45 @@ impl fmt::Display for ViolationKind {
 impl fmt::Display for Violation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(line) = self.line {
-            write!(f, "{}:{}: {}", self.file_path, line, self.kind)
+            write!(f, "{}:{}: {}", self.file_path.display(), line, self.kind)?;
         } else {
-            write!(f, "{}: {}", self.file_path, self.kind)
+            write!(f, "{}: {}", self.file_path.display(), self.kind)?;
+        }
+        if let Some(help) = self.kind.help() {
+            write!(f, "\n     = help: {help}")?;
         }
+        if let Some(snippet) = self.kind.suggestion() {
+            write!(f, "\n     |")?;
+            for line in snippet.lines() {
+                write!(f, "\n     | {line}")?;
+            }
+            write!(f, "\n     |")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Violation, ViolationKind};
+
+    #[test]
+    fn displays_help_and_suggestion() {
+        let violation = Violation::new(
+            ViolationKind::TestFunctionMissing("test_should_revert".to_owned()),
+            "foo_test.rs".into(),
+        );
+
+        assert_eq!(
+            violation.to_string(),
+            "foo_test.rs: Test function 'test_should_revert' is missing
+     = help: consider adding the test function 'test_should_revert' to the test module
+     |
+     | #[test]
+     | fn test_should_revert() {
+     | }
+     |"
+        );
     }
 }
@@ -1,14 +1,155 @@
 //! Configuration for the Rust backend.
 
+use std::path::{Path, PathBuf};
+
+use bulloak_syntax::{
+    tokenizer::DEFAULT_TAB_WIDTH,
+    utils::{
+        comment_block, front_matter, keeps_descriptions_verbatim,
+        render_template,
+    },
+    Keywords, ParseOptions,
+};
+
+use crate::constants::DEFAULT_HEADER;
+
 /// Configuration for the Rust backend.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
     /// List of files to process.
-    pub files: Vec<String>,
+    pub files: Vec<PathBuf>,
     /// Whether to skip emitting helper functions.
     pub skip_helpers: bool,
     /// Whether to format/capitalize branch descriptions.
     pub format_descriptions: bool,
+    /// Whether to emit commented-out assertions for actions like `it should
+    /// return X`.
+    pub assertion_stubs: bool,
+    /// Whether to emit `insta::assert_snapshot!` stubs for actions tagged
+    /// `[snapshot]`, importing the macro in the test module.
+    pub snapshot_stubs: bool,
+    /// Whether to keep the "when"/"given" prefix of conditions in helper and
+    /// test names, e.g. `when_amount_is_zero` instead of `amount_is_zero`.
+    pub keep_condition_prefix: bool,
+    /// Added in front of the name of every helper, e.g. `setup_` for
+    /// `setup_amount_is_zero`, so that helpers don't collide with functions
+    /// imported into the test scope.
+    ///
+    /// Test names are left as they are.
+    pub helper_prefix: String,
+    /// Added after the name of every helper, e.g. `_state` for
+    /// `amount_is_zero_state`.
+    pub helper_suffix: String,
+    /// Whether conditions with several actions get a test per action, e.g.
+    /// `test_when_paused_should_revert`, instead of a single test describing
+    /// all of them.
+    pub split_actions: bool,
+    /// Whether to document tests and helpers with `///` comments holding
+    /// their branch path and actions, instead of commenting test bodies.
+    pub doc_comments: bool,
+    /// Whether to add a comment summarizing the branch path above each test,
+    /// e.g. `// Given paused › When caller is owner`.
+    pub branch_summary: bool,
+    /// Whether to include the `.tree` spec as a comment block at the top of
+    /// the test file, and have `check` verify that it is up to date.
+    pub tree_banner: bool,
+    /// Additional attributes that mark a function as a test, e.g.
+    /// `my_harness::test`.
+    ///
+    /// These extend the built-in list, which covers `#[test]`,
+    /// `#[tokio::test]`, `#[rstest]`, `#[test_case]` and friends.
+    pub test_attributes: Vec<String>,
+    /// Whether to prefix test names with their zero-padded index in the spec,
+    /// e.g. `test_001_should_work`.
+    pub ordering_prefix: bool,
+    /// Whether to nest the tests of each top-level condition in a module
+    /// named after it, e.g. `mod when_paused`, instead of telling clashing
+    /// test names apart with the names of their ancestors.
+    pub group_modules: bool,
+    /// A comment added to the body of every test, e.g.
+    /// `TODO(bulloak): implement {title}`.
+    ///
+    /// `{title}` is replaced with the title of the branch the test covers and
+    /// `{test}` with the name of the test function. `{tree}` is replaced with
+    /// the path of the tree file by [`Config::for_tree`].
+    pub todo_marker: Option<String>,
+    /// The comment at the top of generated files, if any.
+    ///
+    /// `{version}` is replaced with the version of `bulloak` and `{tree}` with
+    /// the path of the tree file by [`Config::for_tree`].
+    pub header: Option<String>,
+    /// The number of columns a tab stands for in `.tree` files.
+    pub tab_width: usize,
+    /// Extra keywords that start conditions and actions, e.g. to write specs
+    /// in other languages.
+    pub keywords: Keywords,
+    /// Whether `[only]` tags in trees are an error, instead of narrowing the
+    /// trees down to the actions they focus.
+    pub forbid_only: bool,
+    /// The metadata in the front-matter of the spec, e.g. its author or
+    /// ticket, emitted as doc comments on the test module.
+    ///
+    /// Filled in from `// key: value` lines at the top of a `.tree` file by
+    /// [`Config::for_spec`].
+    pub metadata: Vec<(String, String)>,
+    /// The Cargo feature that gates the generated tests, e.g. `e2e`, if any.
+    pub feature: Option<String>,
+    /// Whether [`Config::feature`] gates the whole file with an inner
+    /// `#![cfg(feature = "...")]` attribute, as integration tests under
+    /// `tests/` need, instead of only the test module.
+    pub gate_file: bool,
+    /// Code to add at the start of the tests of branches with a given tag, as
+    /// `(tag, code)` pairs, e.g. a tokio runtime for `[async]`.
+    ///
+    /// A test gets the snippets of the tags of its condition, of the
+    /// conditions above it and of its actions.
+    pub snippets: Vec<(String, String)>,
+    /// Whether `check` also rejects code the spec doesn't derive: non-test
+    /// functions no condition scaffolds, stray modules and tests outside of
+    /// the test module.
+    pub strict: bool,
+    /// Existing names to keep for tests whose names don't follow `bulloak`'s
+    /// conventions, as `(tree, generated, existing)` triples, e.g. to adopt
+    /// `bulloak` on a legacy suite without renaming its tests.
+    ///
+    /// `tree` matches the trailing components of the path of a tree, e.g.
+    /// `vault.tree`, and [`Config::for_tree`] keeps the renames of that tree
+    /// only.
+    pub renames: Vec<(String, String, String)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            skip_helpers: false,
+            format_descriptions: false,
+            assertion_stubs: false,
+            snapshot_stubs: false,
+            keep_condition_prefix: false,
+            helper_prefix: String::new(),
+            helper_suffix: String::new(),
+            split_actions: false,
+            doc_comments: false,
+            branch_summary: false,
+            tree_banner: false,
+            test_attributes: Vec::new(),
+            ordering_prefix: false,
+            group_modules: false,
+            todo_marker: None,
+            header: Some(DEFAULT_HEADER.to_owned()),
+            tab_width: DEFAULT_TAB_WIDTH,
+            keywords: Keywords::new(),
+            forbid_only: false,
+            metadata: Vec::new(),
+            feature: None,
+            gate_file: false,
+            snippets: Vec::new(),
+            strict: false,
+            renames: Vec::new(),
+        }
+    }
 }
 
 impl Config {
@@ -17,4 +158,74 @@ impl Config {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Returns this configuration specialized for the tree at `tree`.
+    ///
+    /// This fills in the `{tree}` placeholder of the TODO marker and the
+    /// header, and drops the renames of other trees.
+    #[must_use]
+    pub fn for_tree(&self, tree: &Path) -> Self {
+        let renames = self
+            .renames
+            .iter()
+            .filter(|(file, _, _)| tree.ends_with(file))
+            .cloned()
+            .collect();
+        let tree = tree.display().to_string();
+        let values = [("tree", tree.as_str())];
+        let todo_marker = self
+            .todo_marker
+            .as_ref()
+            .map(|marker| render_template(marker, &values));
+        let header =
+            self.header.as_ref().map(|header| render_template(header, &values));
+        Self { todo_marker, header, renames, ..self.clone() }
+    }
+
+    /// Returns this configuration specialized for the spec in `text`.
+    ///
+    /// `// key: value` lines at the top of the spec are kept as metadata,
+    /// except for `// format-descriptions: off`, which keeps the comments of
+    /// the spec verbatim.
+    #[must_use]
+    pub fn for_spec(&self, text: &str) -> Self {
+        let metadata = front_matter(text)
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+        let format_descriptions =
+            self.format_descriptions && !keeps_descriptions_verbatim(text);
+        Self { format_descriptions, metadata, ..self.clone() }
+    }
+
+    /// The header comment block to put at the top of generated files, if
+    /// any.
+    #[must_use]
+    pub fn header(&self) -> Option<String> {
+        let header = self.header.as_ref()?;
+        Some(comment_block(&render_template(
+            header,
+            &[("version", env!("CARGO_PKG_VERSION"))],
+        )))
+    }
+
+    /// The existing name to keep for the test `bulloak` names `generated`,
+    /// if any.
+    #[must_use]
+    pub fn renamed(&self, generated: &str) -> Option<&str> {
+        self.renames
+            .iter()
+            .find(|(_, name, _)| name == generated)
+            .map(|(_, _, existing)| existing.as_str())
+    }
+
+    /// The options to parse `.tree` files with.
+    #[must_use]
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            tab_width: self.tab_width,
+            keywords: self.keywords.clone(),
+            forbid_only: self.forbid_only,
+        }
+    }
 }
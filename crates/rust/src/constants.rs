@@ -12,5 +12,28 @@ pub(crate) const PANIC_KEYWORDS: &[&str] = &[
     "fails",
 ];
 
+/// Prefix of the `#[doc]` attributes that stand for plain `//` comments above
+/// a test, since comments can't be part of a token stream.
+pub(crate) const COMMENT_MARKER: &str = "__bulloak_comment__ ";
+
+/// The default comment at the top of generated files.
+pub const DEFAULT_HEADER: &str = "Generated by bulloak";
+
+/// The tag of actions that assert on rendered output with `insta` snapshots.
+pub(crate) const SNAPSHOT_TAG: &str = "snapshot";
+
 /// Name of the test context struct.
 pub(crate) const CONTEXT_STRUCT_NAME: &str = "TestContext";
+
+/// Attributes that mark a function as a test.
+///
+/// Besides the built-in `#[test]`, these cover common async and parameterized
+/// test harnesses.
+pub(crate) const TEST_ATTRIBUTES: &[&str] = &[
+    "test",
+    "tokio::test",
+    "async_std::test",
+    "rstest",
+    "test_case",
+    "googletest::test",
+];
@@ -0,0 +1,64 @@
+//! Errors returned by the Rust backend.
+
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+/// A specialized `Result` type for the Rust backend.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error returned by the Rust backend.
+///
+/// Each variant wraps a more specific error, so callers can match on the
+/// cause of a failure.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A `.tree` file or a Rust file could not be parsed.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// A file could not be read.
+    #[error(transparent)]
+    Io(#[from] IoError),
+    /// The spec can't be turned into Rust tests.
+    #[error(transparent)]
+    Spec(#[from] SpecError),
+}
+
+/// A `.tree` file or a Rust file could not be parsed.
+#[derive(Debug, Error)]
+pub enum ParseError {
+    /// The `.tree` file is not a valid spec.
+    #[error("{0}")]
+    Tree(anyhow::Error),
+    /// The Rust file is not valid Rust.
+    #[error("Failed to parse Rust file: {0}")]
+    Rust(#[from] syn::Error),
+}
+
+/// A file could not be read.
+#[derive(Debug, Error)]
+#[error("Failed to read {}: {source}", path.display())]
+pub struct IoError {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The underlying I/O error.
+    #[source]
+    pub source: io::Error,
+}
+
+/// The spec can't be turned into Rust tests.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SpecError {
+    /// The AST passed in is not the root of a tree.
+    #[error("Expected Root node")]
+    NotARoot,
+    /// A test function would cover no actions.
+    #[error("Cannot generate test function with no actions")]
+    NoActions,
+    /// The helper prefix or suffix would make helper names invalid
+    /// identifiers.
+    ///
+    /// (prefix, suffix)
+    #[error("Helper prefix \"{0}\" and suffix \"{1}\" don't make valid function names")]
+    HelperAffixInvalid(String, String),
+}
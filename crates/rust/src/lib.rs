@@ -10,10 +10,27 @@
 pub mod check;
 pub mod config;
 pub mod constants;
+pub mod error;
 pub mod rust;
 pub mod scaffold;
 mod utils;
 
-pub use check::{Violation, ViolationKind};
+pub use check::{Severity, Violation, ViolationKind};
 pub use config::Config;
-pub use scaffold::scaffold;
+pub use error::{Error, IoError, ParseError, Result, SpecError};
+pub use scaffold::{scaffold, scaffold_tree};
+
+/// Returns what the Rust backend supports.
+#[must_use]
+pub const fn capabilities() -> bulloak_syntax::Capabilities {
+    bulloak_syntax::Capabilities {
+        supports_fix: true,
+        supports_order_check: true,
+        supported_attributes: &[
+            bulloak_syntax::FOCUS_TAG,
+            bulloak_syntax::COMBINE_TAG,
+            constants::SNAPSHOT_TAG,
+        ],
+        file_pattern: "{stem}_test.rs",
+    }
+}
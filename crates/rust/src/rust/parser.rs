@@ -1,12 +1,18 @@
 //! Rust code parser using syn.
 
-use anyhow::{Context, Result};
-use syn::{File, Item, ItemFn, ItemMod, ItemStruct};
+use std::collections::HashSet;
+
+use crate::error::{ParseError, Result};
+use syn::{visit::Visit, Expr, File, FnArg, Item, ItemFn, ItemMod, ItemStruct, Pat, ReturnType, Stmt, Type};
+
+use crate::constants::{CONTEXT_STRUCT_NAME, TEST_ATTRIBUTES};
 
 /// Parsed Rust test file.
 pub struct ParsedRustFile {
     /// The parsed syntax tree.
     pub syntax: File,
+    /// Extra attributes that mark a function as a test.
+    test_attributes: Vec<String>,
 }
 
 impl ParsedRustFile {
@@ -16,38 +22,74 @@ impl ParsedRustFile {
     ///
     /// Returns an error if parsing fails.
     pub fn parse(source: &str) -> Result<Self> {
-        let syntax = syn::parse_file(source).context("Failed to parse Rust file")?;
-        Ok(Self { syntax })
+        let syntax = syn::parse_file(source).map_err(ParseError::from)?;
+        Ok(Self { syntax, test_attributes: Vec::new() })
+    }
+
+    /// Treat functions annotated with any of `attributes` as tests, in
+    /// addition to the built-in test attributes.
+    #[must_use]
+    pub fn with_test_attributes(mut self, attributes: &[String]) -> Self {
+        self.test_attributes = attributes.to_vec();
+        self
     }
 
     /// Find the test module in the file.
+    ///
+    /// This is the first top-level module annotated with `#[cfg(test)]`.
     #[must_use]
     pub fn find_test_module(&self) -> Option<&ItemMod> {
-        for item in &self.syntax.items {
-            if let Item::Mod(module) = item {
-                // Check if it has #[cfg(test)] attribute
-                if Self::has_cfg_test(&module.attrs) {
-                    return Some(module);
+        self.find_test_modules().into_iter().next()
+    }
+
+    /// Find all top-level modules annotated with `#[cfg(test)]`.
+    #[must_use]
+    pub fn find_test_modules(&self) -> Vec<&ItemMod> {
+        self.syntax
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Mod(module) if Self::has_cfg_test(&module.attrs) => {
+                    Some(module)
                 }
-            }
-        }
-        None
+                _ => None,
+            })
+            .collect()
     }
 
     /// Find all test functions in the file.
+    ///
+    /// Test functions are looked up in every test module, including any
+    /// modules nested inside them.
     #[must_use]
     pub fn find_test_functions(&self) -> Vec<&ItemFn> {
         let mut functions = Vec::new();
+        for module in self.find_test_modules() {
+            self.collect_functions(module, true, &mut functions);
+        }
+
+        functions
+    }
 
-        // Check in test module
-        if let Some(test_module) = self.find_test_module() {
-            if let Some((_, items)) = &test_module.content {
-                for item in items {
-                    if let Item::Fn(func) = item {
-                        if Self::has_test_attr(&func.attrs) {
-                            functions.push(func);
-                        }
+    /// Find all test functions in the file, along with the name of the
+    /// module nested in a test module that declares them, if any.
+    ///
+    /// Tests in modules nested deeper are grouped under the outermost one.
+    #[must_use]
+    pub fn find_grouped_test_functions(&self) -> Vec<(Option<String>, &ItemFn)> {
+        let mut functions = Vec::new();
+        for module in self.find_test_modules() {
+            let Some((_, items)) = &module.content else { continue };
+            for item in items {
+                match item {
+                    Item::Fn(func) if self.has_test_attr(&func.attrs) => functions.push((None, func)),
+                    Item::Mod(nested) => {
+                        let mut nested_functions = Vec::new();
+                        self.collect_functions(nested, true, &mut nested_functions);
+                        let name = nested.ident.to_string();
+                        functions.extend(nested_functions.into_iter().map(|func| (Some(name.clone()), func)));
                     }
+                    _ => {}
                 }
             }
         }
@@ -55,7 +97,10 @@ impl ParsedRustFile {
         functions
     }
 
-    /// Find all helper functions (non-test functions at module level).
+    /// Find all helper functions.
+    ///
+    /// These are the non-test functions at module level, plus any non-test
+    /// functions declared inside the test modules.
     #[must_use]
     pub fn find_helper_functions(&self) -> Vec<&ItemFn> {
         let mut functions = Vec::new();
@@ -63,15 +108,41 @@ impl ParsedRustFile {
         for item in &self.syntax.items {
             if let Item::Fn(func) = item {
                 // Not a test function
-                if !Self::has_test_attr(&func.attrs) {
+                if !self.has_test_attr(&func.attrs) {
                     functions.push(func);
                 }
             }
         }
 
+        for module in self.find_test_modules() {
+            self.collect_functions(module, false, &mut functions);
+        }
+
         functions
     }
 
+    /// Recursively collect the functions in `module` that are (or are not)
+    /// annotated with `#[test]`.
+    fn collect_functions<'a>(
+        &self,
+        module: &'a ItemMod,
+        tests: bool,
+        functions: &mut Vec<&'a ItemFn>,
+    ) {
+        let Some((_, items)) = &module.content else { return };
+        for item in items {
+            match item {
+                Item::Fn(func) if self.has_test_attr(&func.attrs) == tests => {
+                    functions.push(func);
+                }
+                Item::Mod(nested) => {
+                    self.collect_functions(nested, tests, functions);
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Find the context struct.
     #[must_use]
     pub fn find_context_struct(&self) -> Option<&ItemStruct> {
@@ -86,9 +157,20 @@ impl ParsedRustFile {
         None
     }
 
-    /// Check if a function has #[test] attribute.
-    fn has_test_attr(attrs: &[syn::Attribute]) -> bool {
-        attrs.iter().any(|attr| attr.path().is_ident("test"))
+    /// Check if a function has a test attribute, e.g. `#[test]` or
+    /// `#[tokio::test]`.
+    pub(crate) fn has_test_attr(&self, attrs: &[syn::Attribute]) -> bool {
+        attrs.iter().any(|attr| {
+            let path = attr
+                .path()
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            TEST_ATTRIBUTES.contains(&path.as_str())
+                || self.test_attributes.contains(&path)
+        })
     }
 
     /// Check if an item has #[cfg(test)] attribute.
@@ -103,6 +185,43 @@ impl ParsedRustFile {
         })
     }
 
+    /// Find the identifiers used in the bodies of the test functions.
+    #[must_use]
+    pub fn test_body_idents(&self) -> HashSet<String> {
+        let mut idents = Idents::default();
+        for func in self.find_test_functions() {
+            idents.visit_block(&func.block);
+        }
+
+        idents.0
+    }
+
+    /// Check if a function has the shape of a scaffolded helper, i.e. it
+    /// takes the test context and returns it.
+    #[must_use]
+    pub fn is_context_helper(func: &ItemFn) -> bool {
+        let is_context = |ty: &Type| matches!(ty, Type::Path(path) if path.path.is_ident(CONTEXT_STRUCT_NAME));
+        let mut inputs = func.sig.inputs.iter();
+        let takes_context = matches!((inputs.next(), inputs.next()), (Some(FnArg::Typed(arg)), None) if is_context(&arg.ty));
+        let returns_context = matches!(&func.sig.output, ReturnType::Type(_, ty) if is_context(ty));
+
+        takes_context && returns_context
+    }
+
+    /// Check if a helper does nothing but hand back the context, as
+    /// scaffolded helpers do until they are filled in.
+    #[must_use]
+    pub fn is_empty_helper(func: &ItemFn) -> bool {
+        matches!(func.block.stmts.as_slice(), [Stmt::Expr(Expr::Path(path), None)] if path.path.get_ident().is_some())
+    }
+
+    /// Check if a test does nothing but set up its context, as scaffolded
+    /// tests do until they are implemented.
+    #[must_use]
+    pub fn is_empty_test(func: &ItemFn) -> bool {
+        func.block.stmts.iter().all(|stmt| matches!(stmt, Stmt::Local(local) if matches!(&local.pat, Pat::Ident(pat) if pat.ident == "_ctx")))
+    }
+
     /// Check if a function has #[should_panic] attribute.
     #[must_use]
     pub fn has_should_panic(func: &ItemFn) -> bool {
@@ -112,6 +231,16 @@ impl ParsedRustFile {
     }
 }
 
+/// Collects every identifier it visits.
+#[derive(Default)]
+struct Idents(HashSet<String>);
+
+impl Visit<'_> for Idents {
+    fn visit_ident(&mut self, ident: &proc_macro2::Ident) {
+        self.0.insert(ident.to_string());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +304,139 @@ mod tests {
         assert!(ParsedRustFile::has_should_panic(test_fns[0]));
         assert!(!ParsedRustFile::has_should_panic(test_fns[1]));
     }
+
+    #[test]
+    fn test_find_functions_in_nested_and_multiple_test_modules() {
+        let source = r"
+            fn top_level_helper() {}
+
+            #[cfg(test)]
+            mod tests {
+                fn inner_helper() {}
+
+                #[test]
+                fn test_outer() {}
+
+                mod nested {
+                    #[test]
+                    fn test_nested() {}
+                }
+            }
+
+            #[cfg(test)]
+            mod more_tests {
+                #[test]
+                fn test_more() {}
+            }
+        ";
+
+        let parsed = ParsedRustFile::parse(source).unwrap();
+        assert_eq!(parsed.find_test_modules().len(), 2);
+
+        let names = |fns: Vec<&ItemFn>| -> Vec<String> {
+            fns.iter().map(|f| f.sig.ident.to_string()).collect()
+        };
+        assert_eq!(
+            names(parsed.find_test_functions()),
+            vec!["test_outer", "test_nested", "test_more"]
+        );
+        assert_eq!(
+            names(parsed.find_helper_functions()),
+            vec!["top_level_helper", "inner_helper"]
+        );
+    }
+
+    #[test]
+    fn test_alternative_test_attributes() {
+        let source = r"
+            #[cfg(test)]
+            mod tests {
+                #[tokio::test(flavor = multi_thread)]
+                async fn test_async() {}
+
+                #[rstest]
+                fn test_rstest() {}
+
+                #[test_case(1)]
+                fn test_case_one(_: u8) {}
+
+                #[my_harness::test]
+                fn test_custom() {}
+            }
+        ";
+
+        let parsed = ParsedRustFile::parse(source).unwrap();
+        assert_eq!(parsed.find_test_functions().len(), 3);
+        assert_eq!(parsed.find_helper_functions().len(), 1);
+
+        let parsed = ParsedRustFile::parse(source)
+            .unwrap()
+            .with_test_attributes(&["my_harness::test".to_owned()]);
+        assert_eq!(parsed.find_test_functions().len(), 4);
+        assert!(parsed.find_helper_functions().is_empty());
+    }
+
+    #[test]
+    fn test_context_helpers() {
+        let source = r"
+            fn empty(mut ctx: TestContext) -> TestContext {
+                // TODO: Set up condition
+                ctx
+            }
+
+            fn filled(mut ctx: TestContext) -> TestContext {
+                ctx.amount = 0;
+                ctx
+            }
+
+            fn other(x: u8) -> TestContext {
+                TestContext::default()
+            }
+
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn test_empty() {
+                    let _ctx = empty(TestContext::default());
+                }
+            }
+        ";
+
+        let parsed = ParsedRustFile::parse(source).unwrap();
+        let helpers = parsed.find_helper_functions();
+        assert!(ParsedRustFile::is_context_helper(helpers[0]));
+        assert!(ParsedRustFile::is_empty_helper(helpers[0]));
+        assert!(ParsedRustFile::is_context_helper(helpers[1]));
+        assert!(!ParsedRustFile::is_empty_helper(helpers[1]));
+        assert!(!ParsedRustFile::is_context_helper(helpers[2]));
+
+        let idents = parsed.test_body_idents();
+        assert!(idents.contains("empty"));
+        assert!(!idents.contains("filled"));
+        assert!(ParsedRustFile::is_empty_test(parsed.find_test_functions()[0]));
+    }
+
+    #[test]
+    fn test_empty_tests() {
+        let source = r"
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn test_empty() {
+                    // It should work.
+                }
+
+                #[test]
+                fn test_done() {
+                    let _ctx = helper(TestContext::default());
+                    assert_eq!(1, 1);
+                }
+            }
+        ";
+
+        let parsed = ParsedRustFile::parse(source).unwrap();
+        let tests = parsed.find_test_functions();
+        assert!(ParsedRustFile::is_empty_test(tests[0]));
+        assert!(!ParsedRustFile::is_empty_test(tests[1]));
+    }
 }
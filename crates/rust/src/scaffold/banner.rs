@@ -0,0 +1,162 @@
+//! The banner holding the original `.tree` spec at the top of a test file.
+
+use std::ops::Range;
+
+/// The first line of the banner.
+const BANNER_START: &str = "// Spec:";
+
+/// Render `tree`, the source of a spec, as a comment block.
+#[must_use]
+pub fn render(tree: &str) -> String {
+    let mut banner = format!("{BANNER_START}\n");
+    for line in normalize(tree).lines() {
+        banner.push_str("//");
+        if !line.is_empty() {
+            banner.push(' ');
+            banner.push_str(line);
+        }
+        banner.push('\n');
+    }
+
+    banner
+}
+
+/// Find the banner in `source`.
+///
+/// Returns the byte range of the banner and the spec it holds.
+#[must_use]
+pub fn extract(source: &str) -> Option<(Range<usize>, String)> {
+    let start = source
+        .match_indices(BANNER_START)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || source[..i].ends_with('\n'))?;
+
+    let mut end = start;
+    let mut tree = Vec::new();
+    for line in source[start..].split_inclusive('\n').skip(1) {
+        let text = line.trim_end_matches(['\r', '\n']);
+        let Some(text) = text.strip_prefix("//") else { break };
+        tree.push(text.strip_prefix(' ').unwrap_or(text));
+        end += line.len();
+    }
+    let first_line = source[start..].split_inclusive('\n').next()?;
+
+    Some((start..end + first_line.len(), tree.join("\n")))
+}
+
+/// Whether the banner in `source` holds `tree`.
+#[must_use]
+pub fn matches(source: &str, tree: &str) -> bool {
+    extract(source).is_some_and(|(_, banner)| banner == normalize(tree))
+}
+
+/// Put the banner of `tree` in `source`, replacing any outdated one.
+///
+/// A new banner goes right after `header`, the comment block `bulloak` puts
+/// at the top of generated files, so that anything written above it is left
+/// alone. If `source` has no such header, the banner goes at the top of the
+/// file.
+#[must_use]
+pub fn replace(source: &str, tree: &str, header: Option<&str>) -> String {
+    let banner = render(tree);
+    let mut fixed = source.to_owned();
+    if let Some((range, _)) = extract(source) {
+        fixed.replace_range(range, &banner);
+    } else {
+        let mut at = 0;
+        if let Some(end) = header.and_then(|header| find_header(source, header))
+        {
+            at = end;
+            if source[at..].starts_with('\n') {
+                at += 1;
+            }
+        }
+        fixed.insert_str(at, &format!("{banner}\n"));
+    }
+
+    fixed
+}
+
+/// Find the end of `header` in `source`, if it starts a line.
+fn find_header(source: &str, header: &str) -> Option<usize> {
+    source
+        .match_indices(header)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || source[..i].ends_with('\n'))
+        .map(|i| i + header.len())
+}
+
+/// Drop trailing whitespace and line endings so that banners don't depend
+/// on them.
+fn normalize(tree: &str) -> String {
+    tree.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract, matches, render, replace};
+
+    const HEADER: Option<&str> = Some("// Generated by bulloak\n");
+
+    const TREE: &str = "transfer\r\n├── when amount is zero\r\n│   └── it should revert\r\n\r\n";
+
+    #[test]
+    fn renders_and_extracts_the_tree() {
+        let banner = render(TREE);
+        assert_eq!(
+            banner,
+            "// Spec:\n// transfer\n// ├── when amount is zero\n// │   └── it should revert\n"
+        );
+
+        let source =
+            format!("// Generated by bulloak\n\n{banner}\nfn main() {{}}\n");
+        let (range, tree) = extract(&source).unwrap();
+        assert_eq!(&source[range], banner);
+        assert!(matches(&source, TREE));
+        assert_eq!(
+            tree,
+            "transfer\n├── when amount is zero\n│   └── it should revert"
+        );
+    }
+
+    #[test]
+    fn replaces_outdated_banners() {
+        let source = "// Generated by bulloak\n\n// Spec:\n// transfer\n\nfn main() {}\n";
+        assert!(!matches(source, TREE));
+
+        let fixed = replace(source, TREE, HEADER);
+        assert!(matches(&fixed, TREE));
+        assert!(
+            fixed.ends_with("// │   └── it should revert\n\nfn main() {}\n")
+        );
+    }
+
+    #[test]
+    fn inserts_missing_banners() {
+        let source = "// Generated by bulloak\n\nfn main() {}\n";
+        assert_eq!(
+            replace(source, "foo\n└── it works", HEADER),
+            "// Generated by bulloak\n\n// Spec:\n// foo\n// └── it works\n\nfn main() {}\n"
+        );
+        assert_eq!(
+            replace("fn main() {}\n", "foo", HEADER),
+            "// Spec:\n// foo\n\nfn main() {}\n"
+        );
+        assert_eq!(
+            replace(source, "foo", None),
+            format!("// Spec:\n// foo\n\n{source}")
+        );
+
+        let source =
+            "// Copyright Foo.\n// Generated by bulloak\n\nfn main() {}\n";
+        assert_eq!(
+            replace(source, "foo", HEADER),
+            "// Copyright Foo.\n// Generated by bulloak\n\n// Spec:\n// foo\n\nfn main() {}\n"
+        );
+    }
+}
@@ -1,23 +1,30 @@
 //! Comment formatting utilities.
 
+use bulloak_syntax::utils::starts_with_code;
+
 /// Format a comment by capitalizing the first letter and ensuring it ends with a period.
+///
+/// Comments that start with code, e.g. `` `transfer()` reverts ``, aren't capitalized.
 pub(crate) fn format_comment(text: &str) -> String {
     let trimmed = text.trim();
     if trimmed.is_empty() {
         return String::new();
     }
 
-    let mut chars = trimmed.chars();
-    let first = chars.next().unwrap();
-    let rest: String = chars.collect();
-
-    let capitalized = format!("{}{}", first.to_uppercase(), rest);
-
-    if capitalized.ends_with('.') || capitalized.ends_with('!') || capitalized.ends_with('?') {
-        capitalized
+    let mut formatted = String::with_capacity(trimmed.len() + 1);
+    if starts_with_code(trimmed) {
+        formatted.push_str(trimmed);
     } else {
-        format!("{}.", capitalized)
+        let mut chars = trimmed.chars();
+        formatted.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+        formatted.push_str(chars.as_str());
     }
+
+    if !formatted.ends_with(['.', '!', '?']) {
+        formatted.push('.');
+    }
+
+    formatted
 }
 
 #[cfg(test)]
@@ -33,5 +40,7 @@ mod tests {
         );
         assert_eq!(format_comment("should panic!"), "Should panic!");
         assert_eq!(format_comment(""), "");
+        assert_eq!(format_comment("`transfer()` reverts"), "`transfer()` reverts.");
+        assert_eq!(format_comment("total_supply grows"), "total_supply grows.");
     }
 }
@@ -1,13 +1,16 @@
 //! Scaffold module for generating Rust test code.
 
+pub mod banner;
 pub mod comment;
 pub mod generator;
+pub mod wiring;
 
 pub use generator::Generator;
 
 use crate::config::Config;
-use anyhow::Result;
+use crate::error::{ParseError, Result};
 use bulloak_syntax::Ast;
+use std::path::{Path, PathBuf};
 
 /// Scaffold Rust test code from an AST.
 ///
@@ -18,3 +21,58 @@ pub fn scaffold(ast: &Ast, cfg: &Config) -> Result<String> {
     let generator = Generator::new(cfg);
     generator.generate(ast)
 }
+
+/// Scaffold Rust test code from the source of a `.tree` file.
+///
+/// Unlike [`scaffold`], this can include the spec itself as a banner at the
+/// top of the file when [`Config::tree_banner`] is set, and documents the test
+/// module with the metadata at the top of the spec.
+///
+/// # Errors
+///
+/// Returns an error if the tree can't be parsed or scaffolding fails.
+pub fn scaffold_tree(text: &str, cfg: &Config) -> Result<String> {
+    let ast = bulloak_syntax::parse_one_with(text, &cfg.parse_options())
+        .map_err(ParseError::Tree)?;
+    let emitted = scaffold(&ast, &cfg.for_spec(text))?;
+    if cfg.tree_banner {
+        Ok(banner::replace(&emitted, text, cfg.header().as_deref()))
+    } else {
+        Ok(emitted)
+    }
+}
+
+/// Returns the path of the Rust test file for the tree at `tree_path`.
+///
+/// The test file sits next to the tree, with `.tree` replaced by `_test.rs`,
+/// e.g. `foo.tree` maps to `foo_test.rs`.
+#[must_use]
+pub fn test_file_path(tree_path: &Path) -> PathBuf {
+    let mut file_name = tree_path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push("_test.rs");
+    tree_path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_file_path;
+    use std::path::Path;
+
+    #[test]
+    fn test_file_sits_next_to_the_tree() {
+        assert_eq!(
+            test_file_path(Path::new("specs/hash_pair.tree")),
+            Path::new("specs/hash_pair_test.rs")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_path_keeps_non_utf8_names() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let tree = Path::new(OsStr::from_bytes(b"sp\xffec.tree"));
+        let expected = Path::new(OsStr::from_bytes(b"sp\xffec_test.rs"));
+        assert_eq!(test_file_path(tree), expected);
+    }
+}
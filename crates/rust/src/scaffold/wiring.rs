@@ -0,0 +1,361 @@
+//! Wires generated test files into the crate they belong to.
+//!
+//! Cargo only compiles the files of a crate that are declared as modules,
+//! except at the top level of `tests/`, where every file is a test target of
+//! its own. [`Wiring`] declares the generated test files that Cargo would miss:
+//!
+//! - Under `src/`, with `#[cfg(test)] mod foo_test;` in the parent module,
+//!   creating the `mod.rs` of directories that aren't modules yet.
+//! - Under a subdirectory of `tests/`, with a `#[path]` module in the
+//!   `tests/bulloak_generated/main.rs` aggregator, which Cargo compiles as a
+//!   test target.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use syn::{spanned::Spanned, Item};
+
+use crate::error::{IoError, ParseError, Result};
+
+/// The directory under `tests/` of the aggregator of generated test files.
+pub const AGGREGATOR_DIR: &str = "bulloak_generated";
+
+/// The top of a new aggregator.
+const AGGREGATOR_DOC: &str = "//! Test files generated by bulloak in subdirectories of `tests/`, which Cargo
+//! doesn't compile on its own.
+";
+
+/// Returns the `src/` or `tests/` directory of a crate that `file` is in,
+/// along with the name of the directory.
+fn crate_dir(file: &Path) -> Option<(&Path, &str)> {
+    file.ancestors().skip(1).find_map(|dir| {
+        let name = dir.file_name()?.to_str()?;
+        let is_crate_dir = matches!(name, "src" | "tests")
+            && dir.parent()?.join("Cargo.toml").is_file();
+        is_crate_dir.then_some((dir, name))
+    })
+}
+
+/// Returns the directory of the module `file` is declared in, along with the
+/// name of the module.
+fn module_of(file: &Path) -> Option<(&Path, String)> {
+    let dir = file.parent()?;
+    if file.file_name()? == "mod.rs" {
+        Some((dir.parent()?, dir.file_name()?.to_string_lossy().into_owned()))
+    } else {
+        Some((dir, file.file_stem()?.to_string_lossy().into_owned()))
+    }
+}
+
+/// Returns the name of the module that declares `relative`, a path under
+/// `tests/`, in the aggregator.
+fn aggregated_name(relative: &Path) -> String {
+    relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Returns the path of the module of the test file at `file` within its test
+/// target, once [`Wiring`] declares it, e.g. `vault::deposit_test` for
+/// `src/vault/deposit_test.rs`.
+///
+/// The path is empty for a file at the top level of `tests/`, which is a test
+/// target of its own, and `None` for a file outside of the `src/` and `tests/`
+/// directories of a crate.
+#[must_use]
+pub fn module_path(file: &Path) -> Option<String> {
+    let (dir, name) = crate_dir(file)?;
+    let relative = file.strip_prefix(dir).ok()?;
+    if name == "tests" {
+        return Some(if file.parent() == Some(dir) {
+            String::new()
+        } else {
+            aggregated_name(relative)
+        });
+    }
+
+    let mut path: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if path.last().is_some_and(|name| name == "mod") {
+        path.pop();
+    }
+    Some(path.join("::"))
+}
+
+/// The module files to write so that Cargo compiles generated test files.
+#[derive(Debug, Default)]
+pub struct Wiring(BTreeMap<PathBuf, String>);
+
+impl Wiring {
+    /// Declares the test file at `file` so that Cargo compiles it.
+    ///
+    /// Files that are already declared, that sit at the top level of `tests/`,
+    /// or that are outside of the `src/` and `tests/` directories of a crate
+    /// are left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a module file can't be read or parsed.
+    pub fn add(&mut self, file: &Path) -> Result<()> {
+        match crate_dir(file) {
+            Some((src, "src")) => self.declare_in_parent(src, file, true),
+            Some((tests, _)) if file.parent() != Some(tests) => {
+                self.aggregate(tests, file)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the module files to write, along with their new contents.
+    #[must_use]
+    pub fn into_edits(self) -> Vec<(PathBuf, String)> {
+        self.0.into_iter().collect()
+    }
+
+    /// Declares the module at `file` in its parent module under `src`, behind
+    /// `#[cfg(test)]` if `cfg_test` is set.
+    fn declare_in_parent(
+        &mut self,
+        src: &Path,
+        file: &Path,
+        cfg_test: bool,
+    ) -> Result<()> {
+        let Some((dir, name)) = module_of(file) else { return Ok(()) };
+        let parent = if dir == src {
+            let roots = [src.join("lib.rs"), src.join("main.rs")];
+            let Some(root) = roots.into_iter().find(|root| self.exists(root))
+            else {
+                return Ok(());
+            };
+            root
+        } else if self.exists(&dir.join("mod.rs")) {
+            dir.join("mod.rs")
+        } else if self.exists(&dir.with_extension("rs")) {
+            dir.with_extension("rs")
+        } else {
+            let mod_rs = dir.join("mod.rs");
+            self.0.insert(mod_rs.clone(), String::new());
+            self.declare_in_parent(src, &mod_rs, false)?;
+            mod_rs
+        };
+
+        let declaration = if cfg_test {
+            format!("#[cfg(test)]\nmod {name};")
+        } else {
+            format!("mod {name};")
+        };
+        self.declare(&parent, &name, &declaration)
+    }
+
+    /// Declares `file` in the aggregator of the `tests` directory, creating
+    /// the aggregator if needed.
+    fn aggregate(&mut self, tests: &Path, file: &Path) -> Result<()> {
+        let Ok(relative) = file.strip_prefix(tests) else { return Ok(()) };
+        let path: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let name = aggregated_name(relative);
+
+        let aggregator = tests.join(AGGREGATOR_DIR).join("main.rs");
+        if !self.exists(&aggregator) {
+            self.0.insert(aggregator.clone(), AGGREGATOR_DOC.to_owned());
+        }
+        let declaration =
+            format!("#[path = \"../{}\"]\nmod {name};", path.join("/"));
+        self.declare(&aggregator, &name, &declaration)
+    }
+
+    /// Adds `declaration` to the module at `file`, unless the module already
+    /// declares a module called `name`.
+    ///
+    /// The declaration goes after the last module declaration, if any, or else
+    /// at the end of the file.
+    fn declare(
+        &mut self,
+        file: &Path,
+        name: &str,
+        declaration: &str,
+    ) -> Result<()> {
+        let source = self.read(file)?;
+        let syntax = syn::parse_file(&source).map_err(ParseError::Rust)?;
+        if syntax
+            .items
+            .iter()
+            .any(|item| matches!(item, Item::Mod(m) if m.ident == name))
+        {
+            return Ok(());
+        }
+
+        let last = syntax.items.iter().rev().find_map(|item| match item {
+            Item::Mod(m) if m.content.is_none() => Some(m.span().end().line),
+            _ => None,
+        });
+        let contents = match last {
+            Some(line) => {
+                let mut lines: Vec<&str> = source.lines().collect();
+                lines.insert(line, declaration);
+                lines.join("\n") + "\n"
+            }
+            None if source.trim().is_empty() => format!("{declaration}\n"),
+            None => format!("{}\n\n{declaration}\n", source.trim_end()),
+        };
+        self.0.insert(file.to_path_buf(), contents);
+
+        Ok(())
+    }
+
+    /// Whether the file at `path` exists, or is about to.
+    fn exists(&self, path: &Path) -> bool {
+        self.0.contains_key(path) || path.is_file()
+    }
+
+    /// The contents of the file at `path`, as edited so far.
+    fn read(&self, path: &Path) -> Result<String> {
+        if let Some(contents) = self.0.get(path) {
+            return Ok(contents.clone());
+        }
+
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(source) => {
+                Err(IoError { path: path.to_path_buf(), source }.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use pretty_assertions::assert_eq;
+
+    use super::{module_path, Wiring};
+
+    fn wire(files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+        let mut wiring = Wiring::default();
+        for file in files {
+            wiring.add(file).unwrap();
+        }
+        wiring.into_edits()
+    }
+
+    /// Creates a crate in `dir` with the given files.
+    fn krate(dir: &Path, files: &[(&str, &str)]) {
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\n")
+            .unwrap();
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn declares_test_files_under_src() {
+        let dir = tempfile::tempdir().unwrap();
+        krate(
+            dir.path(),
+            &[
+                ("src/lib.rs", "//! Foo.\n\nmod vault;\n\npub fn foo() {}\n"),
+                ("src/vault.rs", ""),
+            ],
+        );
+        let files = [
+            dir.path().join("src/foo_test.rs"),
+            dir.path().join("src/vault/deposit_test.rs"),
+            dir.path().join("src/specs/pool/swap_test.rs"),
+        ];
+
+        let edits = wire(&files);
+        let paths: Vec<_> = edits
+            .iter()
+            .map(|(path, _)| path.strip_prefix(dir.path()).unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("src/lib.rs"),
+                Path::new("src/specs/mod.rs"),
+                Path::new("src/specs/pool/mod.rs"),
+                Path::new("src/vault.rs")
+            ]
+        );
+        assert_eq!(edits[0].1, "//! Foo.\n\nmod vault;\n#[cfg(test)]\nmod foo_test;\nmod specs;\n\npub fn foo() {}\n");
+        assert_eq!(edits[1].1, "mod pool;\n");
+        assert_eq!(edits[2].1, "#[cfg(test)]\nmod swap_test;\n");
+        assert_eq!(edits[3].1, "#[cfg(test)]\nmod deposit_test;\n");
+
+        for (path, contents) in edits {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        assert!(wire(&files).is_empty());
+    }
+
+    #[test]
+    fn aggregates_test_files_under_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        krate(dir.path(), &[]);
+        let files = [
+            dir.path().join("tests/foo_test.rs"),
+            dir.path().join("tests/specs/vault-v2/deposit_test.rs"),
+            dir.path().join("tests/specs/swap_test.rs"),
+            dir.path().join("elsewhere/bar_test.rs"),
+        ];
+
+        let edits = wire(&files);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            edits[0].0,
+            dir.path().join("tests/bulloak_generated/main.rs")
+        );
+        assert_eq!(
+            edits[0].1,
+            "//! Test files generated by bulloak in subdirectories of `tests/`, which Cargo
+//! doesn't compile on its own.
+
+#[path = \"../specs/vault-v2/deposit_test.rs\"]
+mod specs_vault_v2_deposit_test;
+#[path = \"../specs/swap_test.rs\"]
+mod specs_swap_test;
+"
+        );
+    }
+
+    #[test]
+    fn finds_the_module_path_of_test_files() {
+        let dir = tempfile::tempdir().unwrap();
+        krate(dir.path(), &[]);
+        let path = |file: &str| module_path(&dir.path().join(file));
+
+        assert_eq!(
+            path("src/vault/deposit_test.rs").unwrap(),
+            "vault::deposit_test"
+        );
+        assert_eq!(path("src/foo_test.rs").unwrap(), "foo_test");
+        assert_eq!(path("tests/foo_test.rs").unwrap(), "");
+        assert_eq!(
+            path("tests/specs/vault-v2/deposit_test.rs").unwrap(),
+            "specs_vault_v2_deposit_test"
+        );
+        assert_eq!(path("elsewhere/bar_test.rs"), None);
+    }
+}
@@ -1,43 +1,67 @@
 //! Utility functions for the Rust backend.
 
+use bulloak_syntax::Keywords;
+use bulloak_utils::SnakeCaseOptions;
+
 /// Convert string to snake_case.
 ///
-/// Strips common BDD prefixes (when, given, it) and converts to snake_case.
-pub(crate) fn to_snake_case(s: &str) -> String {
-    let s = s.trim();
-    let s = s
-        .strip_prefix("when ")
-        .or_else(|| s.strip_prefix("When "))
-        .or_else(|| s.strip_prefix("WHEN "))
-        .or_else(|| s.strip_prefix("given "))
-        .or_else(|| s.strip_prefix("Given "))
-        .or_else(|| s.strip_prefix("GIVEN "))
-        .or_else(|| s.strip_prefix("it "))
-        .or_else(|| s.strip_prefix("It "))
-        .or_else(|| s.strip_prefix("IT "))
-        .unwrap_or(s);
-
-    let mut result = String::new();
-    let mut prev_is_alphanumeric = false;
-
-    for c in s.chars() {
-        if c.is_alphanumeric() {
-            if c.is_uppercase() && prev_is_alphanumeric && !result.is_empty() {
-                result.push('_');
-            }
-            result.push(c.to_ascii_lowercase());
-            prev_is_alphanumeric = true;
-        } else if c.is_whitespace() || c == '-' {
-            if prev_is_alphanumeric {
-                result.push('_');
-                prev_is_alphanumeric = false;
-            }
-        } else {
-            prev_is_alphanumeric = false;
-        }
+/// Strips the leading BDD keyword (when, given, it, or any of the extra
+/// `keywords`) and converts to snake_case.
+pub(crate) fn to_snake_case(s: &str, keywords: &Keywords) -> String {
+    snake_case(keywords.strip_prefix(s))
+}
+
+/// Convert a condition title to the name of its helper function.
+///
+/// The "when"/"given" prefix is stripped unless `keep_prefix` is set, in which
+/// case `when amount is zero` becomes `when_amount_is_zero`.
+pub(crate) fn condition_name(
+    title: &str,
+    keep_prefix: bool,
+    keywords: &Keywords,
+) -> String {
+    if keep_prefix {
+        snake_case(title.trim())
+    } else {
+        to_snake_case(title, keywords)
     }
+}
+
+/// The name of the helper function of the condition named `condition`, e.g.
+/// `setup_amount_is_zero` for the `setup_` prefix.
+pub(crate) fn helper_name(condition: &str, prefix: &str, suffix: &str) -> String {
+    format!("{prefix}{condition}{suffix}")
+}
+
+/// The name of the test function covering the actions of the condition whose
+/// helper is `helper`.
+pub(crate) fn condition_test_name(helper: &str, keep_prefix: bool) -> String {
+    if keep_prefix {
+        format!("test_{helper}")
+    } else {
+        format!("test_when_{helper}")
+    }
+}
+
+/// The name of the module grouping the tests of the top-level condition
+/// titled `title`, e.g. `when_paused`.
+pub(crate) fn group_module_name(title: &str) -> String {
+    snake_case(title.trim())
+}
+
+/// Prefix a test name with its zero-padded index in the spec when `enabled`,
+/// e.g. `test_001_should_work`.
+pub(crate) fn ordered_test_name(name: String, index: usize, enabled: bool) -> String {
+    if !enabled {
+        return name;
+    }
+    let suffix = name.strip_prefix("test_").unwrap_or(&name);
+    format!("test_{index:03}_{suffix}")
+}
 
-    result.trim_end_matches('_').to_string()
+/// Convert string to `snake_case`, as is.
+fn snake_case(s: &str) -> String {
+    bulloak_utils::to_snake_case(s, SnakeCaseOptions::default())
 }
 
 #[cfg(test)]
@@ -47,16 +71,56 @@ mod tests {
     #[test]
     fn test_to_snake_case() {
         assert_eq!(
-            to_snake_case("when first arg is smaller"),
+            to_snake_case("when first arg is smaller", &Keywords::new()),
             "first_arg_is_smaller"
         );
         assert_eq!(
-            to_snake_case("It should return the sum"),
+            to_snake_case("It should return the sum", &Keywords::new()),
             "should_return_the_sum"
         );
         assert_eq!(
-            to_snake_case("given a valid input"),
+            to_snake_case("given a valid input", &Keywords::new()),
             "a_valid_input"
         );
+        // The names generated before the conversions moved to
+        // `bulloak-utils`.
+        assert_eq!(
+            to_snake_case("when balanceOf is ERC20Token", &Keywords::new()),
+            "balance_of_is_e_r_c20_token"
+        );
+        assert_eq!(to_snake_case("when a, b", &Keywords::new()), "ab");
+    }
+
+    #[test]
+    fn test_condition_name_keeps_prefix() {
+        assert_eq!(condition_name("when amount is zero", false, &Keywords::new()), "amount_is_zero");
+        assert_eq!(condition_name("when amount is zero", true, &Keywords::new()), "when_amount_is_zero");
+        assert_eq!(condition_name("Given paused", true, &Keywords::new()), "given_paused");
+        assert_eq!(condition_test_name("given_paused", true), "test_given_paused");
+        assert_eq!(condition_test_name("paused", false), "test_when_paused");
+    }
+
+    #[test]
+    fn test_to_snake_case_extra_keywords() {
+        let keywords = Keywords {
+            conditions: vec!["cuando".to_owned()],
+            actions: vec!["debería".to_owned()],
+        };
+        assert_eq!(
+            to_snake_case("Cuando el monto es cero", &keywords),
+            "el_monto_es_cero"
+        );
+        assert_eq!(to_snake_case("debería revertir", &keywords), "revertir");
+        assert_eq!(
+            condition_name("cuando el monto es cero", true, &keywords),
+            "cuando_el_monto_es_cero"
+        );
+    }
+
+    #[test]
+    fn test_ordered_test_name() {
+        assert_eq!(ordered_test_name("test_when_x".to_owned(), 7, true), "test_007_when_x");
+        assert_eq!(ordered_test_name("test_when_x".to_owned(), 1234, true), "test_1234_when_x");
+        assert_eq!(ordered_test_name("test_when_x".to_owned(), 7, false), "test_when_x");
     }
 }
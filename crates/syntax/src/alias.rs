@@ -0,0 +1,136 @@
+//! Aliasing of synonymous condition titles.
+//!
+//! Specs read best when a condition is phrased to fit its branch, e.g. `when
+//! paused` at the top of a tree and `given the contract is paused` deeper
+//! down. An `// alias: <alias> = <title>` line at the top of a `.tree` file
+//! declares that both phrasings are the same condition:
+//!
+//! ```tree
+//! // alias: given the contract is paused = when paused
+//! Foo
+//! ├── when paused
+//! │   └── it should revert.
+//! └── when the caller is the owner
+//!     └── given the contract is paused
+//!         └── it should revert.
+//! ```
+//!
+//! Conditions titled like an alias take the title it stands for, so they
+//! share a single helper or modifier, and `check` expects the same names as
+//! `scaffold` generates.
+
+use crate::ast::Ast;
+
+/// The key of alias directives.
+pub(crate) const DIRECTIVE: &str = "alias";
+
+/// Returns the `(alias, title)` pairs declared at the top of `text`.
+///
+/// Malformed directives, i.e. those without an `=`, are ignored.
+pub(crate) fn directives(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map_while(|line| line.strip_prefix("//"))
+        .filter_map(|comment| {
+            let (key, value) = comment.split_once(':')?;
+            if key.trim() != DIRECTIVE {
+                return None;
+            }
+            let (alias, title) = value.split_once('=')?;
+            let (alias, title) = (normalize(alias), normalize(title));
+            (!alias.is_empty() && !title.is_empty()).then_some((alias, title))
+        })
+        .collect()
+}
+
+/// Renames the conditions of `ast` titled like one of `aliases` to the title
+/// the alias stands for.
+pub(crate) fn resolve(ast: &mut Ast, aliases: &[(String, String)]) {
+    if aliases.is_empty() {
+        return;
+    }
+
+    let children = match ast {
+        Ast::Root(root) => &mut root.children,
+        Ast::Condition(condition) => {
+            let title = normalize(&condition.title);
+            if let Some((_, canonical)) =
+                aliases.iter().find(|(alias, _)| *alias == title)
+            {
+                condition.title.clone_from(canonical);
+            }
+            &mut condition.children
+        }
+        Ast::Action(_) | Ast::ActionDescription(_) => return,
+    };
+    for child in children {
+        resolve(child, aliases);
+    }
+}
+
+/// Lowercases `title` and collapses its whitespace, so that aliases match
+/// regardless of casing and spacing.
+fn normalize(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::directives;
+    use crate::ast::Ast;
+
+    /// The titles of the conditions of `ast`, depth first.
+    fn titles(ast: &Ast) -> Vec<String> {
+        let children = match ast {
+            Ast::Root(root) => &root.children,
+            Ast::Condition(condition) => &condition.children,
+            _ => return vec![],
+        };
+        let own = match ast {
+            Ast::Condition(condition) => vec![condition.title.clone()],
+            _ => vec![],
+        };
+        own.into_iter().chain(children.iter().flat_map(titles)).collect()
+    }
+
+    #[test]
+    fn reads_directives() {
+        let text = "// author: alice\n// alias: Given  the contract is \
+                    paused = when paused\n// alias: no title\nFoo\n\
+                    // alias: a = b";
+        assert_eq!(
+            directives(text),
+            vec![(
+                "given the contract is paused".to_owned(),
+                "when paused".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn renames_aliased_conditions() {
+        let text = indoc! {r"
+            // alias: given the contract is paused = when paused
+
+            Foo
+            ├── when paused
+            │   └── it should revert.
+            └── when the caller is the owner
+                └── given the contract is paused
+                    └── it should revert.
+
+            Bar
+            └── Given the contract is paused
+                └── it should revert.
+        "};
+        let asts = crate::parse(text).unwrap();
+        assert_eq!(
+            titles(&asts[0]),
+            vec!["when paused", "when the caller is the owner", "when paused"]
+        );
+        assert_eq!(titles(&asts[1]), vec!["when paused"]);
+    }
+}
@@ -4,7 +4,7 @@ use crate::span::Span;
 
 /// An Abstract Syntax Tree (AST) that describes the semantic
 /// structure of a bulloak tree.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Ast {
     /// The root node of the AST.
     Root(Root),
@@ -42,7 +42,7 @@ impl Ast {
 }
 
 /// The root node of the AST.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Root {
     /// The name that is used for the emitted contract.
     pub contract_name: String,
@@ -54,12 +54,20 @@ pub struct Root {
 }
 
 /// A condition node of the AST.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Condition {
     /// The title of this condition.
     ///
     /// For example: "when stuff happens".
     pub title: String,
+    /// The explicit id of this condition, if any.
+    ///
+    /// For example: "stuff-happens", for a title ending in `#stuff-happens`.
+    pub anchor: Option<String>,
+    /// The tags of this condition.
+    ///
+    /// For example: "combine", for a title ending in `[combine]`.
+    pub tags: Vec<String>,
     /// The span that encompasses this node. It includes
     /// all of its children.
     pub span: Span,
@@ -68,12 +76,25 @@ pub struct Condition {
 }
 
 /// An action node of the AST.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Action {
     /// The title of this action.
     ///
     /// For example: "It should revert."
     pub title: String,
+    /// The explicit id of this action, if any.
+    ///
+    /// For example: "revert-zero", for a title ending in `#revert-zero`.
+    pub anchor: Option<String>,
+    /// The explicit name of the test of this action, if any.
+    ///
+    /// For example: `test_revert_zero`, for a title ending in
+    /// `@name=test_revert_zero`.
+    pub name: Option<String>,
+    /// The tags of this action.
+    ///
+    /// For example: "only", for a title ending in `[only]`.
+    pub tags: Vec<String>,
     /// The span that encompasses this node.
     pub span: Span,
     /// The children AST nodes of this node.
@@ -84,7 +105,7 @@ pub struct Action {
 }
 
 /// A description node of the AST.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Description {
     /// The text of this action.
     ///
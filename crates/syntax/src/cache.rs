@@ -0,0 +1,185 @@
+//! Caches of parsed trees and of the structures derived from them.
+//!
+//! Checking a tree parses it more than once, e.g. to match it against its
+//! tests and then to measure its coverage, or once per backend when a tree is
+//! checked against several outputs. [`parse_with`] keeps the ASTs it returns,
+//! keyed by a hash of the tree's text and the parse options, so that each tree
+//! is parsed once per process. Backends can keep what they derive from a
+//! tree, e.g. the names of the tests they expect, in a [`Cache`] of their own
+//! under the same [`key`].
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use crate::{ast::Ast, ParseOptions};
+
+/// The number of entries a cache holds before it is emptied.
+///
+/// This bounds the memory long-running processes, e.g. proc macros, use.
+const CAPACITY: usize = 256;
+
+/// The ASTs of the texts parsed through [`parse_with`].
+static ASTS: Cache<Vec<Ast>> = Cache::new();
+
+/// The ASTs of the trees parsed through [`parse_one_with`].
+static AST: Cache<Ast> = Cache::new();
+
+/// A thread-safe map from tree keys, see [`key`], to values.
+#[derive(Debug)]
+pub struct Cache<V> {
+    entries: OnceLock<Mutex<HashMap<u64, V>>>,
+}
+
+impl<V: Clone> Cache<V> {
+    /// Creates an empty cache.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { entries: OnceLock::new() }
+    }
+
+    /// Returns the value under `key`, computing it with `f` if it isn't
+    /// cached yet.
+    ///
+    /// Errors are not cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`, if any.
+    pub fn get_or_try_insert_with<E>(
+        &self,
+        key: u64,
+        f: impl FnOnce() -> Result<V, E>,
+    ) -> Result<V, E> {
+        let entries = self.entries.get_or_init(Mutex::default);
+        if let Some(value) = lock(entries).get(&key) {
+            return Ok(value.clone());
+        }
+
+        // The lock isn't held while computing, so that other trees can be
+        // looked up in the meantime.
+        let value = f()?;
+        let mut entries = lock(entries);
+        if entries.len() >= CAPACITY {
+            entries.clear();
+        }
+        entries.insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+impl<V: Clone> Default for Cache<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Locks `entries`, even if a thread panicked while holding the lock, since
+/// the map is never left half-updated.
+fn lock<V>(
+    entries: &Mutex<HashMap<u64, V>>,
+) -> std::sync::MutexGuard<'_, HashMap<u64, V>> {
+    entries.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the key of the tree in `text` when parsed with `options`.
+#[must_use]
+pub fn key(text: &str, options: &ParseOptions) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    options.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Parses a string containing trees into ASTs with the given options, reusing
+/// the ASTs of a previous call with the same text and options.
+///
+/// # Errors
+///
+/// Returns an error if any of the trees fails to tokenize, parse or pass
+/// semantic analysis.
+pub fn parse_with(
+    text: &str,
+    options: &ParseOptions,
+) -> anyhow::Result<Vec<Ast>> {
+    ASTS.get_or_try_insert_with(key(text, options), || {
+        crate::parse_with(text, options)
+    })
+}
+
+/// Parses a string containing a single tree into an AST with the given
+/// options, reusing the AST of a previous call with the same text and
+/// options.
+///
+/// # Errors
+///
+/// Returns an error if the tree fails to tokenize, parse or pass semantic
+/// analysis.
+pub fn parse_one_with(
+    text: &str,
+    options: &ParseOptions,
+) -> anyhow::Result<Ast> {
+    AST.get_or_try_insert_with(key(text, options), || {
+        crate::parse_one_with(text, options)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::{key, Cache};
+    use crate::ParseOptions;
+
+    #[test]
+    fn computes_values_once_per_key() {
+        let cache: Cache<usize> = Cache::new();
+        let calls = Cell::new(0);
+        let compute = || -> Result<usize, ()> {
+            calls.set(calls.get() + 1);
+            Ok(calls.get())
+        };
+
+        assert_eq!(cache.get_or_try_insert_with(1, compute), Ok(1));
+        assert_eq!(cache.get_or_try_insert_with(1, compute), Ok(1));
+        assert_eq!(cache.get_or_try_insert_with(2, compute), Ok(2));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn does_not_cache_errors() {
+        let cache: Cache<usize> = Cache::new();
+
+        assert_eq!(
+            cache.get_or_try_insert_with(1, || Err("nope")),
+            Err("nope")
+        );
+        assert_eq!(cache.get_or_try_insert_with(1, || Ok::<_, ()>(3)), Ok(3));
+    }
+
+    #[test]
+    fn keys_depend_on_options() {
+        let tree = "Foo\n└── it should work.";
+        let options = ParseOptions::default();
+        let forbid_only = ParseOptions { forbid_only: true, ..options.clone() };
+
+        assert_eq!(key(tree, &options), key(tree, &options));
+        assert_ne!(key(tree, &options), key(tree, &forbid_only));
+        assert_ne!(
+            key(tree, &options),
+            key("Bar\n└── it should work.", &options)
+        );
+    }
+
+    #[test]
+    fn parses_trees_once() {
+        let tree = "Foo\n└── it should be cached.";
+        let options = ParseOptions::default();
+
+        let first = super::parse_one_with(tree, &options).unwrap();
+        assert_eq!(super::parse_one_with(tree, &options).unwrap(), first);
+        assert!(super::parse_one_with("Foo\n└── bar", &options).is_err());
+    }
+}
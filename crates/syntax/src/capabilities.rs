@@ -0,0 +1,21 @@
+//! What a backend can do.
+//!
+//! Backends describe themselves with a [`Capabilities`], so that tooling, e.g.
+//! editor extensions, can adapt to each backend instead of hardcoding what it
+//! supports.
+
+/// The features a backend supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether `bulloak check --fix` can fix the backend's violations.
+    pub supports_fix: bool,
+    /// Whether `bulloak check` reports tests that are out of the order of
+    /// the spec.
+    pub supports_order_check: bool,
+    /// The tags, e.g. `[only]`, that change what the backend generates or
+    /// checks.
+    pub supported_attributes: &'static [&'static str],
+    /// The name of the test file next to a spec, with `{stem}` standing for
+    /// the file name of the spec without its extension.
+    pub file_pattern: &'static str,
+}
@@ -0,0 +1,267 @@
+//! Expansion of condition combinations.
+//!
+//! Sibling conditions tagged `[combine]` are axes, and the conditions under
+//! each of them are its alternatives, e.g. who the caller is and whether the
+//! contract is paused. The axes are replaced by one condition for every
+//! combination of alternatives, so that authors don't have to write out the
+//! cross-product by hand:
+//!
+//! ```tree
+//! Foo
+//! ├── given the caller [combine]
+//! │   ├── when the caller is the owner
+//! │   └── when the caller is not the owner
+//! │       └── it should revert.
+//! └── given the pause state [combine]
+//!     ├── when the contract is paused
+//!     │   └── it should revert.
+//!     └── when the contract is not paused
+//!         └── it should emit a {Transfer} event.
+//! ```
+//!
+//! expands to four conditions, from `when the caller is the owner and the
+//! contract is paused` to `when the caller is not the owner and the contract
+//! is not paused`. Each combination gets the children of every alternative it
+//! combines, followed by the actions under the axes themselves, which apply to
+//! every combination.
+
+use crate::{
+    ast::{Ast, Condition},
+    span::Span,
+};
+
+/// The tag that marks a condition as an axis.
+pub const TAG: &str = "combine";
+
+/// Replaces every group of sibling axes in `ast` with their combinations.
+pub(crate) fn expand(ast: &mut Ast) {
+    let children = match ast {
+        Ast::Root(root) => &mut root.children,
+        Ast::Condition(condition) => &mut condition.children,
+        Ast::Action(_) | Ast::ActionDescription(_) => return,
+    };
+
+    if let Some(position) = children.iter().position(is_axis) {
+        let (axes, mut rest): (Vec<Ast>, Vec<Ast>) =
+            std::mem::take(children).into_iter().partition(is_axis);
+        let axes: Vec<Condition> = axes
+            .into_iter()
+            .filter_map(|axis| match axis {
+                Ast::Condition(condition) => Some(condition),
+                _ => None,
+            })
+            .collect();
+        // The combinations take the place of the first axis.
+        rest.splice(position..position, combine(&axes).map(Ast::Condition));
+        *children = rest;
+    }
+
+    for child in children {
+        expand(child);
+    }
+}
+
+/// Whether `ast` is a condition tagged `[combine]` with alternatives to
+/// combine.
+fn is_axis(ast: &Ast) -> bool {
+    match ast {
+        Ast::Condition(condition) => {
+            condition.tags.iter().any(|tag| tag == TAG)
+                && alternatives(condition).next().is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Iterates over the alternatives of `axis`, i.e. its child conditions.
+fn alternatives(axis: &Condition) -> impl Iterator<Item = &Condition> + Clone {
+    axis.children.iter().filter_map(|child| match child {
+        Ast::Condition(condition) => Some(condition),
+        _ => None,
+    })
+}
+
+/// Returns the cross-product of the alternatives of `axes`, in the order
+/// they appear in the tree.
+fn combine(axes: &[Condition]) -> impl Iterator<Item = Condition> + '_ {
+    let shared: Vec<&Ast> = axes
+        .iter()
+        .flat_map(|axis| &axis.children)
+        .filter(|child| child.is_action())
+        .collect();
+
+    let mut combinations: Vec<Vec<&Condition>> = vec![vec![]];
+    for axis in axes {
+        combinations = combinations
+            .iter()
+            .flat_map(|prefix| {
+                alternatives(axis).map(move |alternative| {
+                    let mut combination = prefix.clone();
+                    combination.push(alternative);
+                    combination
+                })
+            })
+            .collect();
+    }
+
+    combinations
+        .into_iter()
+        .map(move |combination| merge(&combination, &shared))
+}
+
+/// Merges `alternatives` into a single condition, followed by the `shared`
+/// actions.
+///
+/// Panics if `alternatives` is empty.
+fn merge(alternatives: &[&Condition], shared: &[&Ast]) -> Condition {
+    let (first, rest) = alternatives.split_first().unwrap();
+    let last = alternatives.last().unwrap();
+
+    let mut title = first.title.clone();
+    for alternative in rest {
+        // Drop the leading `when` or `given`, so the titles read as one.
+        let clause = alternative
+            .title
+            .split_once(' ')
+            .map_or(alternative.title.as_str(), |(_, clause)| clause);
+        title = format!("{title} and {clause}");
+    }
+
+    let anchors: Vec<&str> =
+        alternatives.iter().filter_map(|a| a.anchor.as_deref()).collect();
+    let anchor = (!anchors.is_empty()).then(|| anchors.join("-"));
+
+    let mut tags: Vec<String> = vec![];
+    for tag in alternatives.iter().flat_map(|a| &a.tags) {
+        if tag != TAG && !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    // An action that more than one alternative expects is only kept once.
+    let mut children: Vec<Ast> = vec![];
+    let own = alternatives.iter().flat_map(|a| &a.children);
+    for child in own.chain(shared.iter().copied()) {
+        let duplicate = match child {
+            Ast::Action(action) => children.iter().any(
+                |c| matches!(c, Ast::Action(a) if a.title == action.title),
+            ),
+            _ => false,
+        };
+        if !duplicate {
+            children.push(child.clone());
+        }
+    }
+
+    Condition {
+        title,
+        anchor,
+        tags,
+        span: Span::new(first.span.start, last.span.end),
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::ast::Ast;
+
+    fn titles(children: &[Ast]) -> Vec<String> {
+        children
+            .iter()
+            .map(|child| match child {
+                Ast::Condition(condition) => condition.title.clone(),
+                Ast::Action(action) => action.title.clone(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expands_the_cross_product() {
+        let tree = indoc! {r"
+            Foo
+            ├── it should be callable.
+            ├── given the caller [combine]
+            │   ├── when the caller is the owner #owner
+            │   │   └── it should work.
+            │   └── when the caller is not the owner
+            │       └── it should revert.
+            ├── given the pause state [combine]
+            │   ├── when the contract is paused #paused
+            │   │   └── it should revert.
+            │   ├── when the contract is not paused
+            │   │   └── it should work.
+            │   └── it should log.
+            └── when nothing else happens
+                └── it should not log.
+        "};
+        let Ast::Root(root) = crate::parse_one(tree).unwrap() else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            titles(&root.children),
+            vec![
+                "it should be callable.",
+                "when the caller is the owner and the contract is paused",
+                "when the caller is the owner and the contract is not paused",
+                "when the caller is not the owner and the contract is paused",
+                "when the caller is not the owner and the contract is not \
+                 paused",
+                "when nothing else happens",
+            ]
+        );
+        let Ast::Condition(first) = &root.children[1] else { unreachable!() };
+        assert_eq!(first.anchor.as_deref(), Some("owner-paused"));
+        assert_eq!(
+            titles(&first.children),
+            vec!["it should work.", "it should revert.", "it should log."]
+        );
+        let Ast::Condition(last) = &root.children[4] else { unreachable!() };
+        assert_eq!(last.anchor, None);
+        assert_eq!(
+            titles(&last.children),
+            vec!["it should revert.", "it should work.", "it should log."]
+        );
+    }
+
+    #[test]
+    fn expands_nested_axes() {
+        let tree = indoc! {r"
+            Foo
+            └── when the amount is not zero
+                ├── given a [combine]
+                │   ├── when a is one
+                │   └── when a is two
+                └── given b [combine]
+                    └── when b is one
+                        └── it should work.
+        "};
+        let Ast::Root(root) = crate::parse_one(tree).unwrap() else {
+            unreachable!()
+        };
+        let Ast::Condition(condition) = &root.children[0] else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            titles(&condition.children),
+            vec!["when a is one and b is one", "when a is two and b is one"]
+        );
+    }
+
+    #[test]
+    fn combinations_need_actions() {
+        let tree = indoc! {r"
+            Foo
+            ├── given a [combine]
+            │   └── when a is one
+            └── given b [combine]
+                └── when b is one
+        "};
+        assert!(crate::parse_one(tree).is_err());
+    }
+}
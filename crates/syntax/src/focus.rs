@@ -0,0 +1,95 @@
+//! Narrowing of trees down to their focused actions.
+//!
+//! While working on a spec, actions can be tagged `[only]` to scaffold and
+//! check just the branches that lead to them, like `it.only` in JavaScript
+//! test frameworks. Every other action, and every condition left without
+//! children, is dropped from the tree.
+
+use crate::ast::Ast;
+
+/// The tag that focuses an action.
+pub const TAG: &str = "only";
+
+/// Whether `ast` has any focused action.
+pub(crate) fn is_focused(ast: &Ast) -> bool {
+    match ast {
+        Ast::Root(root) => root.children.iter().any(is_focused),
+        Ast::Condition(condition) => condition.children.iter().any(is_focused),
+        Ast::Action(action) => action.tags.iter().any(|tag| tag == TAG),
+        Ast::ActionDescription(_) => false,
+    }
+}
+
+/// Drops the branches of `ast` that lead to no focused action.
+///
+/// Leaves `ast` untouched if it has no focused action.
+pub(crate) fn narrow(ast: &mut Ast) {
+    if !is_focused(ast) {
+        return;
+    }
+
+    let children = match ast {
+        Ast::Root(root) => &mut root.children,
+        Ast::Condition(condition) => &mut condition.children,
+        Ast::Action(_) | Ast::ActionDescription(_) => return,
+    };
+    children.retain(is_focused);
+    for child in children {
+        narrow(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{ast::Ast, ParseOptions};
+
+    #[test]
+    fn keeps_only_focused_branches() {
+        let tree = indoc! {r"
+            Foo
+            ├── it should be callable.
+            ├── when the amount is zero
+            │   └── it should revert.
+            └── when the amount is not zero
+                ├── when the caller is the owner
+                │   ├── it should transfer. [only]
+                │   └── it should emit.
+                └── when the caller is not the owner
+                    └── it should revert.
+        "};
+        let Ast::Root(root) = crate::parse_one(tree).unwrap() else {
+            unreachable!()
+        };
+
+        assert_eq!(root.children.len(), 1);
+        let Ast::Condition(condition) = &root.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(condition.title, "when the amount is not zero");
+        let [Ast::Condition(owner)] = &condition.children[..] else {
+            unreachable!()
+        };
+        let [Ast::Action(action)] = &owner.children[..] else { unreachable!() };
+        assert_eq!(action.title, "it should transfer.");
+    }
+
+    #[test]
+    fn keeps_unfocused_trees() {
+        let tree = "Foo\n├── it works.\n└── it still works.";
+        let Ast::Root(root) = crate::parse_one(tree).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(root.children.len(), 2);
+    }
+
+    #[test]
+    fn forbids_focus() {
+        let tree = "Foo\n├── it works. [only]\n└── it still works.";
+        let options = ParseOptions { forbid_only: true, ..Default::default() };
+        let error = crate::parse_one_with(tree, &options).unwrap_err();
+
+        assert!(error.to_string().contains("found an `[only]` tag"));
+    }
+}
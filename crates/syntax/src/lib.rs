@@ -3,9 +3,16 @@
 //! This module includes everything necessary to convert from a tree
 //! in string form to an AST. It also includes a semantic analyzer.
 
+mod alias;
 mod ast;
+pub mod cache;
+mod capabilities;
 mod char;
+mod combine;
 mod error;
+mod focus;
+pub mod markdown;
+mod options;
 pub mod parser;
 pub mod semantics;
 mod span;
@@ -16,22 +23,86 @@ pub mod utils;
 mod visitor;
 
 pub use ast::{Action, Ast, Condition, Description, Root};
+pub use capabilities::Capabilities;
+pub use combine::TAG as COMBINE_TAG;
 pub use error::FrontendError;
+pub use focus::TAG as FOCUS_TAG;
+pub use options::{Keywords, ParseOptions};
 pub use span::{Position, Span};
 pub use tokenizer::{Token, TokenKind};
 pub use visitor::Visitor;
 
 /// Parses a string containing trees into ASTs.
 pub fn parse(text: &str) -> anyhow::Result<Vec<ast::Ast>> {
-    splitter::split_trees(text).map(parse_one).collect()
+    parse_with(text, &ParseOptions::default())
 }
 
 /// Parses a string containing a single tree into an AST.
 pub fn parse_one(text: &str) -> anyhow::Result<ast::Ast> {
-    let tokens = tokenizer::Tokenizer::new().tokenize(text)?;
-    let ast = parser::Parser::new().parse(text, &tokens)?;
-    let mut analyzer = semantics::SemanticAnalyzer::new(text);
+    parse_one_with(text, &ParseOptions::default())
+}
+
+/// Parses a string containing trees into ASTs with the given options.
+///
+/// Alias directives at the top of `text` apply to every tree.
+///
+/// # Errors
+///
+/// Returns an error if any of the trees fails to tokenize, parse or pass
+/// semantic analysis.
+pub fn parse_with(
+    text: &str,
+    options: &ParseOptions,
+) -> anyhow::Result<Vec<ast::Ast>> {
+    let aliases = alias::directives(text);
+    splitter::split_trees(text)
+        .map(|tree| parse_tree(tree, options, &aliases))
+        .collect()
+}
+
+/// Parses a string containing a single tree into an AST with the given
+/// options.
+///
+/// # Errors
+///
+/// Returns an error if the tree fails to tokenize, parse or pass semantic
+/// analysis.
+pub fn parse_one_with(
+    text: &str,
+    options: &ParseOptions,
+) -> anyhow::Result<ast::Ast> {
+    parse_tree(text, options, &alias::directives(text))
+}
+
+/// Returns the spans of the comments in `text` that aren't part of any tree,
+/// i.e. blocks of comments set apart by blank lines.
+///
+/// Parsing drops these comments, so they are worth pointing out to authors.
+/// Front matter, pragma and alias directives above the first tree are not
+/// reported.
+#[must_use]
+pub fn ignored_comments(text: &str) -> Vec<Span> {
+    splitter::ignored_comments(text)
+}
+
+/// Parses a single tree into an AST, renaming the conditions titled like one
+/// of `aliases`.
+fn parse_tree(
+    text: &str,
+    options: &ParseOptions,
+    aliases: &[(String, String)],
+) -> anyhow::Result<ast::Ast> {
+    let tokens = tokenizer::Tokenizer::new()
+        .with_tab_width(options.tab_width)
+        .with_keywords(options.keywords.clone())
+        .tokenize(text)?;
+    let mut ast = parser::Parser::new().parse(text, &tokens)?;
+    combine::expand(&mut ast);
+    alias::resolve(&mut ast, aliases);
+    let mut analyzer = semantics::SemanticAnalyzer::new(text)
+        .with_forbid_only(options.forbid_only);
     analyzer.analyze(&ast)?;
+    focus::narrow(&mut ast);
 
     Ok(ast)
 }
@@ -0,0 +1,293 @@
+//! Trees embedded in Markdown documents.
+//!
+//! Design docs can hold their specs in fenced code blocks tagged `tree`:
+//!
+//! ````markdown
+//! # Vault
+//!
+//! ```tree
+//! Vault
+//! └── when paused
+//!     └── it should revert.
+//! ```
+//! ````
+//!
+//! A Markdown file stands for the trees of its `tree` blocks, as if they were
+//! written one after the other in a `.tree` file. This keeps the document the
+//! source of truth, instead of a copy of the spec that can drift from it.
+
+use std::{fmt, fs, io, path::Path};
+
+use crate::{alias, parse_tree, splitter::split_trees, ParseOptions};
+
+/// Whether the file at `path` is a Markdown document, going by its
+/// extension.
+#[must_use]
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown")
+    })
+}
+
+/// Returns the trees in the `tree` code blocks of the Markdown in `text`,
+/// separated by blank lines.
+///
+/// Blocks may be fenced with backticks or tildes, and the fence may be
+/// indented by up to three spaces, which is stripped from the lines of the
+/// block. An unclosed block runs to the end of the document.
+#[must_use]
+pub fn extract(text: &str) -> String {
+    let trees: Vec<_> =
+        blocks(text).into_iter().map(|block| block.lines.join("\n")).collect();
+    trees.join("\n\n")
+}
+
+/// Explains `err`, raised while parsing the spec read from the file at
+/// `path`, in terms of the Markdown document the spec comes from.
+///
+/// The trees of a document are parsed together, so the positions in `err`
+/// don't point into the document. If a tree fails to parse on its own, its
+/// error is returned instead, with the lines of the document and, as a
+/// [`BlockError`] context, the line of the fence of its block. Otherwise, the trees are fine on their own but
+/// don't make up a spec together, and a note listing their blocks is added
+/// to `err`. Either way, the returned error can still be downcast to the
+/// error type of the parser.
+///
+/// Files that aren't Markdown documents get `err` back as is.
+#[must_use]
+pub fn explain(
+    path: &Path,
+    options: &ParseOptions,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    if !is_markdown(path) {
+        return err;
+    }
+    match fs::read_to_string(path) {
+        Ok(text) => explain_text(&text, options, err),
+        Err(_) => err,
+    }
+}
+
+/// Explains `err` in terms of the Markdown document `text`. See [`explain`].
+fn explain_text(
+    text: &str,
+    options: &ParseOptions,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    let blocks = blocks(text);
+    let aliases = alias::directives(&extract(text));
+    for block in &blocks {
+        let body = block.lines.join("\n");
+        for tree in split_trees(&body).filter(|tree| !tree.is_empty()) {
+            // Parse the tree at its line in the document, so that errors
+            // point at the document.
+            let offset = tree.as_ptr() as usize - body.as_ptr() as usize;
+            let line = block.fence + body[..offset].matches('\n').count();
+            let tree = format!("{}{tree}", "\n".repeat(line));
+            if let Err(e) = parse_tree(&tree, options, &aliases) {
+                let message = format!(
+                    "{}\nin the `tree` block at line {}",
+                    e.to_string().trim_end(),
+                    block.fence
+                );
+                return e.context(BlockError { fence: block.fence, message });
+            }
+        }
+    }
+    if blocks.len() < 2 {
+        return err;
+    }
+
+    let fences: Vec<_> =
+        blocks.iter().map(|block| block.fence.to_string()).collect();
+    let message = format!(
+        "{}\nnote: the `tree` blocks at lines {} are read as a single spec, \
+         in which the positions above are counted",
+        err.to_string().trim_end(),
+        fences.join(", ")
+    );
+    err.context(message)
+}
+
+/// The context [`explain`] adds to the error of a tree that fails to parse on
+/// its own.
+#[derive(Debug)]
+pub struct BlockError {
+    /// The line of the opening fence of the tree's block, starting at 1.
+    pub fence: usize,
+    /// The error of the tree, in terms of the document.
+    message: String,
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A `tree` code block of a Markdown document.
+struct Block<'t> {
+    /// The line of the opening fence, starting at 1.
+    fence: usize,
+    /// The lines of the block, without the indentation of the fence.
+    lines: Vec<&'t str>,
+}
+
+/// Returns the `tree` code blocks of the Markdown in `text`.
+fn blocks(text: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut block: Option<(&str, usize, Block<'_>)> = None;
+    for (number, line) in (1..).zip(text.lines()) {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let trimmed = line.trim();
+        match &mut block {
+            None => {
+                let Some(fence) = fence(trimmed).filter(|_| indent <= 3) else {
+                    continue;
+                };
+                let info = trimmed[fence.len()..].trim();
+                let language = info.split_whitespace().next();
+                if language == Some("tree") {
+                    let lines = Vec::new();
+                    block =
+                        Some((fence, indent, Block { fence: number, lines }));
+                }
+            }
+            Some((open, _, _))
+                if fence(trimmed).is_some_and(|close| {
+                    close.starts_with(*open) && close.len() == trimmed.len()
+                }) =>
+            {
+                blocks.extend(block.take().map(|(_, _, block)| block));
+            }
+            Some((_, indent, block)) => {
+                let strip = line.len() - line.trim_start_matches(' ').len();
+                block.lines.push(&line[strip.min(*indent)..]);
+            }
+        }
+    }
+    blocks.extend(block.map(|(_, _, block)| block));
+
+    blocks
+}
+
+/// Reads the spec at `path`: the trees of a `.tree` file, or the trees in
+/// the `tree` code blocks of a Markdown document.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read.
+pub fn read_spec(path: &Path) -> io::Result<String> {
+    let text = fs::read_to_string(path)?;
+    Ok(if is_markdown(path) { extract(&text) } else { text })
+}
+
+/// Returns the fence that starts `line`, i.e. three or more backticks or
+/// tildes, if any.
+fn fence(line: &str) -> Option<&str> {
+    let marker = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = line.len() - line.trim_start_matches(marker).len();
+    (len >= 3).then(|| &line[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use super::{explain_text, extract, is_markdown, BlockError};
+    use crate::ParseOptions;
+
+    #[test]
+    fn extracts_tree_blocks() {
+        let text = indoc! {r"
+            # Vault
+
+            ```tree
+            Vault::deposit
+            └── it should work.
+            ```
+
+            Some prose, and code that isn't a spec:
+
+            ```rust
+            fn main() {}
+            ```
+
+              ~~~~ tree title
+              Vault::withdraw
+              └── when paused
+                  └── it should revert.
+              ~~~~
+        "};
+        assert_eq!(
+            extract(text),
+            "Vault::deposit\n└── it should work.\n\nVault::withdraw\n└── when \
+             paused\n    └── it should revert."
+        );
+        assert_eq!(extract("# No specs here"), "");
+    }
+
+    #[test]
+    fn explains_errors_in_terms_of_the_document() {
+        let text = indoc! {r"
+            # Vault
+
+            ```tree
+            Vault
+            └── it should work.
+            ```
+
+            ```tree
+            Vault2
+            ├── when paused
+            │   └── it should revert.
+            ├── it should work.
+            ```
+        "};
+        let err = explain_text(
+            text,
+            &ParseOptions::default(),
+            anyhow::anyhow!("whole spec"),
+        );
+        assert!(err.downcast_ref::<crate::parser::Error>().is_some());
+        assert_eq!(err.downcast_ref::<BlockError>().unwrap().fence, 8);
+        let err = err.to_string();
+        assert!(err.contains("├── it should work."), "{err}");
+        assert!(err.contains("--- (line 12, column 1) ---"), "{err}");
+        assert!(err.ends_with("in the `tree` block at line 8"), "{err}");
+
+        let text = indoc! {r"
+            ```tree
+            Md
+            └── it should work.
+            ```
+
+            ```tree
+            Md2
+            └── it should also work.
+            ```
+        "};
+        let err = explain_text(
+            text,
+            &ParseOptions::default(),
+            anyhow::anyhow!("separator missing at tree root #1"),
+        )
+        .to_string();
+        assert_eq!(
+            err,
+            "separator missing at tree root #1\nnote: the `tree` blocks at \
+             lines 1, 6 are read as a single spec, in which the positions \
+             above are counted"
+        );
+    }
+
+    #[test]
+    fn tells_markdown_apart() {
+        assert!(is_markdown(Path::new("docs/specs.md")));
+        assert!(is_markdown(Path::new("SPECS.Markdown")));
+        assert!(!is_markdown(Path::new("vault.tree")));
+    }
+}
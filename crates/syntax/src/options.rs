@@ -0,0 +1,131 @@
+//! Options that tweak how trees are parsed.
+
+use crate::tokenizer::{TokenKind, DEFAULT_TAB_WIDTH};
+
+/// Options that tweak how trees are parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// The number of columns a tab stands for when indenting branches.
+    ///
+    /// Tabs advance to the next multiple of this width, so tabs and spaces
+    /// can be mixed as long as they line up visually.
+    pub tab_width: usize,
+    /// Extra keywords that start conditions and actions.
+    pub keywords: Keywords,
+    /// Whether `[only]` tags are an error, e.g. to keep them out of CI.
+    ///
+    /// Otherwise, trees with `[only]` actions are narrowed down to the
+    /// branches that lead to them.
+    pub forbid_only: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            tab_width: DEFAULT_TAB_WIDTH,
+            keywords: Keywords::new(),
+            forbid_only: false,
+        }
+    }
+}
+
+/// Keywords that start conditions and actions, on top of the built-in `when`,
+/// `given` and `it`.
+///
+/// This lets specs be written in other languages, e.g. with `cuando` and
+/// `dado` for conditions and `debería` for actions. Keywords are single words
+/// and are matched case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Keywords {
+    /// Extra words that start a condition.
+    pub conditions: Vec<String>,
+    /// Extra words that start an action.
+    pub actions: Vec<String>,
+}
+
+impl Keywords {
+    /// Create a keyword set that only recognizes the built-in keywords.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { conditions: Vec::new(), actions: Vec::new() }
+    }
+
+    /// Returns the kind of token `word` stands for.
+    ///
+    /// Extra condition keywords are tokenized as `when` and extra action
+    /// keywords as `it`.
+    #[must_use]
+    pub fn kind(&self, word: &str) -> TokenKind {
+        match TokenKind::from(word) {
+            TokenKind::Word => {
+                if contains(&self.conditions, word) {
+                    TokenKind::When
+                } else if contains(&self.actions, word) {
+                    TokenKind::It
+                } else {
+                    TokenKind::Word
+                }
+            }
+            kind => kind,
+        }
+    }
+
+    /// Removes the leading keyword of a condition or action title, if any.
+    ///
+    /// The title is returned unchanged when it is a single word.
+    #[must_use]
+    pub fn strip_prefix<'t>(&self, title: &'t str) -> &'t str {
+        let title = title.trim();
+        match title.split_once(char::is_whitespace) {
+            Some((first, rest)) if self.kind(first) != TokenKind::Word => rest,
+            _ => title,
+        }
+    }
+}
+
+/// Returns true if `keywords` contains `word`, ignoring case.
+fn contains(keywords: &[String], word: &str) -> bool {
+    let word = word.to_lowercase();
+    keywords.iter().any(|k| k.to_lowercase() == word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keywords;
+    use crate::tokenizer::TokenKind;
+
+    fn spanish() -> Keywords {
+        Keywords {
+            conditions: vec!["cuando".to_owned(), "dado".to_owned()],
+            actions: vec!["debería".to_owned()],
+        }
+    }
+
+    #[test]
+    fn recognizes_extra_keywords() {
+        let keywords = spanish();
+        assert_eq!(keywords.kind("Cuando"), TokenKind::When);
+        assert_eq!(keywords.kind("DADO"), TokenKind::When);
+        assert_eq!(keywords.kind("Debería"), TokenKind::It);
+        assert_eq!(keywords.kind("given"), TokenKind::Given);
+        assert_eq!(keywords.kind("it"), TokenKind::It);
+        assert_eq!(keywords.kind("cuanto"), TokenKind::Word);
+        assert_eq!(Keywords::new().kind("cuando"), TokenKind::Word);
+    }
+
+    #[test]
+    fn strips_leading_keyword() {
+        let keywords = spanish();
+        assert_eq!(
+            keywords.strip_prefix("cuando el monto es cero"),
+            "el monto es cero"
+        );
+        assert_eq!(
+            keywords.strip_prefix("When amount is zero"),
+            "amount is zero"
+        );
+        assert_eq!(keywords.strip_prefix("debería revertir"), "revertir");
+        assert_eq!(keywords.strip_prefix("cuando"), "cuando");
+        assert_eq!(keywords.strip_prefix("amount is zero"), "amount is zero");
+    }
+}
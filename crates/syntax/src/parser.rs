@@ -108,6 +108,10 @@ pub enum ErrorKind {
     /// A tee is the last child.
     #[error("a `Tee` must not be the last child")]
     TeeLastChild,
+
+    /// The explicit name of a test is not an identifier.
+    #[error("invalid test name '{0}'")]
+    NameInvalid(Lexeme),
 }
 
 /// A parser for a sequence of .tree tokens into an abstract syntax tree (AST).
@@ -248,25 +252,30 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
         // `Tee` or the last `Corner`.
         let mut children = vec![];
         while let Some(current_token) = self.current() {
-            let child =
-                match current_token.kind {
-                    TokenKind::Corner | TokenKind::Tee => {
-                        self.parse_branch(current_token)?
-                    }
-                    TokenKind::Word => Err(self.error(
-                        current_token.span,
-                        ErrorKind::WordUnexpected(current_token.lexeme.clone()),
-                    ))?,
-                    TokenKind::When => Err(self
-                        .error(current_token.span, ErrorKind::WhenUnexpected))?,
-                    TokenKind::Given => Err(self.error(
-                        current_token.span,
-                        ErrorKind::GivenUnexpected,
-                    ))?,
-                    TokenKind::It => Err(
-                        self.error(current_token.span, ErrorKind::ItUnexpected)
-                    )?,
-                };
+            let child = match current_token.kind {
+                TokenKind::Corner | TokenKind::Tee => {
+                    self.parse_branch(current_token)?
+                }
+                TokenKind::Word => Err(self.error(
+                    current_token.span,
+                    ErrorKind::WordUnexpected(current_token.lexeme.clone()),
+                ))?,
+                TokenKind::When => {
+                    Err(self
+                        .error(current_token.span, ErrorKind::WhenUnexpected))?
+                }
+                TokenKind::Given => {
+                    Err(self
+                        .error(current_token.span, ErrorKind::GivenUnexpected))?
+                }
+                TokenKind::It => {
+                    Err(self.error(current_token.span, ErrorKind::ItUnexpected))?
+                }
+                TokenKind::Anchor | TokenKind::Tag => Err(self.error(
+                    current_token.span,
+                    ErrorKind::TokenUnexpected(current_token.lexeme.clone()),
+                ))?,
+            };
 
             children.push(child);
         }
@@ -348,6 +357,8 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
         if title.len() == start_token.lexeme.len() {
             return Err(self.error(start_token.span, ErrorKind::TitleMissing));
         };
+        let tags = self.parse_tags();
+        let anchor = self.parse_anchor();
 
         let mut children = vec![];
         while self
@@ -379,6 +390,8 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
         let previous = self.previous().unwrap();
         Ok(Ast::Condition(Condition {
             title: sanitize(&title),
+            anchor,
+            tags,
             children,
             span: Span::new(token.span.start, previous.span.end),
         }))
@@ -403,6 +416,9 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
             ErrorKind::EofUnexpected,
         ))?;
         let title = self.parse_string(start_token);
+        let (title, name) = self.parse_name(start_token, title)?;
+        let tags = self.parse_tags();
+        let anchor = self.parse_anchor();
 
         let mut children = vec![];
         while self
@@ -436,6 +452,9 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
         let previous = self.previous().unwrap();
         Ok(Ast::Action(Action {
             title,
+            anchor,
+            name,
+            tags,
             children,
             span: Span::new(token.span.start, previous.span.end),
         }))
@@ -476,7 +495,15 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
             token.span.with_start(token.span.end),
             ErrorKind::EofUnexpected,
         ))?;
-        let text = self.parse_string(start_token);
+        let mut text = self.parse_string(start_token);
+        // Tags and anchors have no meaning in descriptions, so keep them as
+        // text.
+        for tag in self.parse_tags() {
+            text = format!("{text} [{tag}]");
+        }
+        if let Some(anchor) = self.parse_anchor() {
+            text = format!("{text} #{anchor}");
+        }
 
         let previous = self.previous().unwrap();
         Ok(Ast::ActionDescription(Description {
@@ -510,6 +537,61 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
 
         string
     }
+
+    /// Split the explicit test name ending the title of an action, i.e.
+    /// `@name=<identifier>`, off of it, if any.
+    fn parse_name(
+        &self,
+        start_token: &Token,
+        title: String,
+    ) -> Result<(String, Option<String>)> {
+        let name = title.rsplit_once(' ').and_then(|(rest, last)| {
+            Some((rest, last.strip_prefix("@name=")?))
+        });
+        let Some((rest, name)) = name else {
+            return Ok((title, None));
+        };
+        if !is_identifier(name) {
+            return Err(self.error(
+                start_token.span,
+                ErrorKind::NameInvalid(name.to_owned()),
+            ));
+        }
+
+        Ok((rest.to_owned(), Some(name.to_owned())))
+    }
+
+    /// Parse the tags of a condition or an action, if any.
+    ///
+    /// Consumes the tag tokens and returns their ids, without the brackets.
+    fn parse_tags(&self) -> Vec<String> {
+        let mut tags = vec![];
+        while let Some(token) =
+            self.current().filter(|t| t.kind == TokenKind::Tag)
+        {
+            tags.push(token.lexeme[1..token.lexeme.len() - 1].to_owned());
+            self.consume();
+        }
+
+        tags
+    }
+
+    /// Parse the anchor ending a condition or an action, if any.
+    ///
+    /// Consumes the anchor token and returns its id, without the `#`.
+    fn parse_anchor(&self) -> Option<String> {
+        let token = self.current().filter(|t| t.kind == TokenKind::Anchor)?;
+        let id = token.lexeme[1..].to_owned();
+        self.consume();
+        Some(id)
+    }
+}
+
+/// Whether `name` can name a test function in every backend, i.e. is a letter
+/// or an underscore followed by letters, digits and underscores.
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 #[cfg(test)]
@@ -634,9 +716,14 @@ mod tests {
                 children: vec![Ast::Condition(Condition {
                     span: s(p(9, 2, 1), p(74, 3, 23)),
                     title: String::from("when something bad happens"),
+                    anchor: None,
+                    tags: vec![],
                     children: vec![Ast::Action(Action {
                         span: s(p(49, 3, 4), p(74, 3, 23)),
                         title: String::from("it should revert"),
+                        anchor: None,
+                        name: None,
+                        tags: vec![],
                         children: vec![]
                     })],
                 })],
@@ -660,9 +747,14 @@ mod tests {
                 children: vec![Ast::Condition(Condition {
                     span: s(p(9, 2, 1), p(104, 4, 23)),
                     title: String::from("when something bad happens"),
+                    anchor: None,
+                    tags: vec![],
                     children: vec![Ast::Action(Action {
                         span: s(p(49, 3, 4), p(104, 4, 23)),
                         title: String::from("it should revert"),
+                        anchor: None,
+                        name: None,
+                        tags: vec![],
                         children: vec![Ast::ActionDescription(Description {
                             span: s(p(82, 4, 7), p(104, 4, 23)),
                             text: String::from("   because _bad_"),
@@ -691,9 +783,14 @@ mod tests {
                 children: vec![Ast::Condition(Condition {
                     span: s(p(9, 2, 1), p(177, 6, 24)),
                     title: String::from("when something bad happens"),
+                    anchor: None,
+                    tags: vec![],
                     children: vec![Ast::Action(Action {
                         span: s(p(49, 3, 4), p(177, 6, 24)),
                         title: String::from("it should revert"),
+                        anchor: None,
+                        name: None,
+                        tags: vec![],
                         children: vec![
                             Ast::ActionDescription(Description {
                                 span: s(p(82, 4, 7), p(110, 4, 29)),
@@ -791,18 +888,28 @@ mod tests {
                 children: vec![
                     Ast::Condition(Condition {
                         title: String::from("when stuff called"),
+                        anchor: None,
+                        tags: vec![],
                         span: s(p(19, 2, 1), p(77, 3, 23)),
                         children: vec![Ast::Action(Action {
                             title: String::from("it should revert"),
+                            anchor: None,
+                            name: None,
+                            tags: vec![],
                             span: s(p(52, 3, 4), p(77, 3, 23)),
                             children: vec![]
                         })],
                     }),
                     Ast::Condition(Condition {
                         title: String::from("given not stuff called"),
+                        anchor: None,
+                        tags: vec![],
                         span: s(p(79, 4, 1), p(140, 5, 23)),
                         children: vec![Ast::Action(Action {
                             title: String::from("it should revert"),
+                            anchor: None,
+                            name: None,
+                            tags: vec![],
                             span: s(p(115, 5, 4), p(140, 5, 23)),
                             children: vec![]
                         })],
@@ -827,6 +934,9 @@ mod tests {
                 span: s(p(0, 1, 1), p(31, 2, 22)),
                 children: vec![Ast::Action(Action {
                     title: String::from("It reverts when X."),
+                    anchor: None,
+                    name: None,
+                    tags: vec![],
                     span: s(p(4, 2, 1), p(31, 2, 22)),
                     children: vec![]
                 })],
@@ -834,6 +944,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_anchors() {
+        let input = indoc! {r"
+            Foo
+            ├── when something bad happens #bad
+            │   └── it should revert #revert
+            └── It should work. #work
+                └── Like #this
+        "};
+
+        let Ast::Root(root) = parse(input).unwrap() else { unreachable!() };
+        let Ast::Condition(condition) = &root.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(condition.title, "when something bad happens");
+        assert_eq!(condition.anchor.as_deref(), Some("bad"));
+        let Ast::Action(action) = &condition.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(action.title, "it should revert");
+        assert_eq!(action.anchor.as_deref(), Some("revert"));
+        let Ast::Action(action) = &root.children[1] else { unreachable!() };
+        assert_eq!(action.anchor.as_deref(), Some("work"));
+        let Ast::ActionDescription(description) = &action.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(description.text, "    Like #this");
+    }
+
+    #[test]
+    fn parses_names() {
+        let input = indoc! {r"
+            Foo
+            ├── when the amount is zero
+            │   └── it should revert. @name=test_revert_zero_amount [skip]
+            └── It should work.
+        "};
+
+        let Ast::Root(root) = parse(input).unwrap() else { unreachable!() };
+        let Ast::Condition(condition) = &root.children[0] else {
+            unreachable!()
+        };
+        let Ast::Action(action) = &condition.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(action.title, "it should revert.");
+        assert_eq!(action.name.as_deref(), Some("test_revert_zero_amount"));
+        assert_eq!(action.tags, vec!["skip"]);
+        let Ast::Action(action) = &root.children[1] else { unreachable!() };
+        assert_eq!(action.name, None);
+
+        let input = "Foo\n└── It should work. @name=not-a-name";
+        assert_eq!(
+            parse(input).unwrap_err().kind,
+            ErrorKind::NameInvalid("not-a-name".to_owned())
+        );
+    }
+
+    #[test]
+    fn parses_tags() {
+        let input = indoc! {r"
+            Foo
+            ├── when the caller is the owner [combine] #owner
+            │   └── it should work [only] [slow]
+            └── It should keep [brackets] in titles.
+        "};
+
+        let Ast::Root(root) = parse(input).unwrap() else { unreachable!() };
+        let Ast::Condition(condition) = &root.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(condition.title, "when the caller is the owner");
+        assert_eq!(condition.tags, vec!["combine"]);
+        assert_eq!(condition.anchor.as_deref(), Some("owner"));
+        let Ast::Action(action) = &condition.children[0] else {
+            unreachable!()
+        };
+        assert_eq!(action.title, "it should work");
+        assert_eq!(action.tags, vec!["only", "slow"]);
+        let Ast::Action(action) = &root.children[1] else { unreachable!() };
+        assert_eq!(action.title, "It should keep [brackets] in titles.");
+        assert!(action.tags.is_empty());
+    }
+
     #[test]
     fn unsanitized_input() {
         let input = indoc! {r#"
@@ -849,9 +1043,14 @@ mod tests {
                 span: s(p(0, 1, 1), p(77, 3, 23)),
                 children: vec![Ast::Condition(Condition {
                     title: String::from("when st_ff alld"),
+                    anchor: None,
+                    tags: vec![],
                     span: s(p(21, 2, 1), p(77, 3, 23)),
                     children: vec![Ast::Action(Action {
                         title: String::from("it should revert"),
+                        anchor: None,
+                        name: None,
+                        tags: vec![],
                         span: s(p(52, 3, 4), p(77, 3, 23)),
                         children: vec![]
                     })],
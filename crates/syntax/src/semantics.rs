@@ -6,6 +6,7 @@ use thiserror::Error;
 use super::ast::{self, Ast};
 use crate::{
     error::FrontendError,
+    focus,
     span::Span,
     utils::{lower_first_letter, sanitize, to_pascal_case},
     visitor::Visitor,
@@ -78,9 +79,20 @@ pub enum ErrorKind {
     /// Found two conditions or top-level actions with the same title.
     #[error("found an identifier more than once in lines: {}", format_spans(.0))]
     IdentifierDuplicated(Vec<Span>),
+    /// Found two nodes with the same anchor.
+    #[error("found the anchor `#{0}` more than once in lines: {}", format_spans(.1))]
+    AnchorDuplicated(String, Vec<Span>),
     /// Found a condition with no children.
     #[error("found a condition with no children")]
     ConditionEmpty,
+    /// Found a condition tagged `[combine]` with no conditions under it.
+    #[error("found a `[combine]` condition with no conditions to combine")]
+    CombinationEmpty,
+    /// Found an `[only]` tag while they are forbidden.
+    #[error(
+        "found an `[only]` tag, which is not allowed with `--forbid-only`"
+    )]
+    OnlyForbidden,
     /// Found an unexpected node. This is most probably a bug in the
     /// parser implementation.
     #[error("unexpected child node")]
@@ -99,6 +111,10 @@ pub struct SemanticAnalyzer<'t> {
     text: &'t str,
     /// A map from modifier name to it's locations in the input.
     identifiers: HashMap<String, Vec<Span>>,
+    /// A map from anchor to the locations of the nodes it is attached to.
+    anchors: HashMap<String, Vec<Span>>,
+    /// Whether `[only]` tags are an error.
+    forbid_only: bool,
 }
 
 impl<'t> SemanticAnalyzer<'t> {
@@ -109,14 +125,30 @@ impl<'t> SemanticAnalyzer<'t> {
             text,
             errors: Vec::new(),
             identifiers: HashMap::new(),
+            anchors: HashMap::new(),
+            forbid_only: false,
         }
     }
 
+    /// Set whether `[only]` tags are an error.
+    #[must_use]
+    pub fn with_forbid_only(mut self, forbid_only: bool) -> Self {
+        self.forbid_only = forbid_only;
+        self
+    }
+
     /// Create a new error given an AST node and error type.
     fn error(&mut self, span: Span, kind: ErrorKind) {
         self.errors.push(Error { kind, text: self.text.to_owned(), span });
     }
 
+    /// Store where `anchor` appears, so that duplicates can be reported.
+    fn record_anchor(&mut self, anchor: Option<&String>, span: Span) {
+        if let Some(anchor) = anchor {
+            self.anchors.entry(anchor.clone()).or_default().push(span);
+        }
+    }
+
     /// Traverse the given AST and store any errors that occur.
     ///
     /// Note that this implementation is a bit weird in that we
@@ -147,6 +179,21 @@ impl<'t> SemanticAnalyzer<'t> {
             }
         }
 
+        // Check for duplicate anchors, in the order they first appear.
+        let mut anchors: Vec<_> = self
+            .anchors
+            .clone()
+            .into_iter()
+            .filter(|(_, spans)| spans.len() > 1)
+            .collect();
+        anchors.sort_by_key(|(_, spans)| spans[0].start.offset);
+        for (anchor, spans) in anchors {
+            self.error(
+                spans[1].with_end(spans[1].start),
+                ErrorKind::AnchorDuplicated(anchor, spans),
+            );
+        }
+
         if !self.errors.is_empty() {
             return Err(Errors(self.errors.clone()));
         }
@@ -206,8 +253,14 @@ impl Visitor for SemanticAnalyzer<'_> {
     ) -> result::Result<Self::Output, Self::Error> {
         if condition.children.is_empty() {
             self.error(condition.span, ErrorKind::ConditionEmpty);
+        } else if condition.tags.iter().any(|tag| tag == crate::combine::TAG) {
+            // Conditions with alternatives to combine are expanded before the
+            // analysis, so any left are missing them.
+            self.error(condition.span, ErrorKind::CombinationEmpty);
         }
 
+        self.record_anchor(condition.anchor.as_ref(), condition.span);
+
         // IMPORTANT: Allow duplicate condition titles.
         // We do not record modifiers in `identifiers` anymore, so duplicates
         // of the same condition title won't trigger an error.
@@ -231,9 +284,12 @@ impl Visitor for SemanticAnalyzer<'_> {
 
     fn visit_action(
         &mut self,
-        _action: &ast::Action,
+        action: &ast::Action,
     ) -> result::Result<Self::Output, Self::Error> {
-        // We don't implement any semantic rules here for now.
+        self.record_anchor(action.anchor.as_ref(), action.span);
+        if self.forbid_only && action.tags.iter().any(|t| t == focus::TAG) {
+            self.error(action.span, ErrorKind::OnlyForbidden);
+        }
         Ok(())
     }
 
@@ -313,6 +369,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn duplicated_anchor() {
+        let text = "Foo_Test
+├── when something #dup
+│   └── It should work.
+└── It should revert. #dup";
+        let errors = analyze(text).unwrap_err().0;
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string().lines().nth(1).unwrap(),
+            "bulloak error: found the anchor `#dup` more than once in lines: 2, 4"
+        );
+    }
+
     #[test]
     fn condition_empty() {
         assert_eq!(
@@ -328,6 +399,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn combination_empty() {
+        let text = "Foo_Test\n└── given a [combine]\n    └── it works";
+        let errors = crate::parse_one(text).unwrap_err();
+
+        assert!(errors.to_string().contains(
+            "found a `[combine]` condition with no conditions to combine"
+        ));
+    }
+
     #[test]
     fn allow_action_without_conditions() {
         assert!(analyze("Foo_Test\n└── it a something").is_ok());
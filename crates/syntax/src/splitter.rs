@@ -1,21 +1,86 @@
-/// The separator used between trees when parsing `.tree` files with multiple
-/// trees.
-pub(crate) const TREES_SEPARATOR: &str = "\n\n";
+use crate::{
+    alias,
+    span::{Position, Span},
+    utils,
+};
 
-/// Splits the input text into distinct trees, delimited by two consecutive
-/// newlines.
+/// Splits the input text into distinct trees, delimited by one or more blank
+/// lines.
+///
+/// Both `\n` and `\r\n` line endings are supported.
 pub(crate) fn split_trees(text: &str) -> Box<dyn Iterator<Item = &str> + '_> {
     if text.trim().is_empty() {
         return Box::new(std::iter::once(""));
     }
 
-    let trees = text.split(TREES_SEPARATOR).map(str::trim);
+    let trees = split_on_blank_lines(text).into_iter().map(str::trim);
     let non_empty_trees = trees.filter(|s| !s.is_empty());
     let no_isolated_comments = non_empty_trees.filter(not_only_comments);
 
     Box::new(no_isolated_comments)
 }
 
+/// Splits `text` at every empty line, ignoring carriage returns.
+fn split_on_blank_lines(text: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let end = line_start + line.len();
+        if line.trim_end_matches(['\r', '\n']).is_empty() {
+            chunks.push(&text[start..line_start]);
+            start = end;
+        }
+        line_start = end;
+    }
+    chunks.push(&text[start..]);
+
+    chunks
+}
+
+/// Returns the spans of the blocks of `text` that only hold comments, which
+/// [`split_trees`] drops.
+///
+/// Blocks above the first tree that hold front matter or alias directives
+/// are not reported.
+pub(crate) fn ignored_comments(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut leading = true;
+    for chunk in split_on_blank_lines(text) {
+        let block = chunk.trim();
+        if block.is_empty() {
+            continue;
+        }
+        if not_only_comments(&block) {
+            leading = false;
+            continue;
+        }
+        let used = leading
+            && (utils::front_matter_entries(block).next().is_some()
+                || utils::pragma_version(block).is_some()
+                || !alias::directives(block).is_empty());
+        if !used {
+            let start = block.as_ptr() as usize - text.as_ptr() as usize;
+            let end = start + block.len()
+                - block.chars().last().map_or(0, char::len_utf8);
+            spans.push(Span::new(position(text, start), position(text, end)));
+        }
+    }
+
+    spans
+}
+
+/// Returns the position of the byte at `offset` in `text`.
+fn position(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        before.chars().count(),
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
 /// Return whether the given string only contains lines starting with `//`.
 fn not_only_comments(tree: &&str) -> bool {
     !tree.lines().all(|l| l.trim().starts_with("//"))
@@ -23,7 +88,8 @@ fn not_only_comments(tree: &&str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::split_trees;
+    use super::{ignored_comments, split_trees};
+    use crate::span::{Position, Span};
 
     #[test]
     fn splits_trees() {
@@ -57,4 +123,37 @@ mod tests {
             assert_eq!(results, expected, "Failed on input: {}", input);
         }
     }
+
+    #[test]
+    fn splits_trees_with_crlf_line_endings() {
+        let input = "Foo_Test\r\n└── when something bad happens\r\n   └── it should revert\r\n\r\nFoo_Test2\r\n└── it should revert\r\n";
+        let results: Vec<_> = split_trees(input).collect();
+        assert_eq!(results, vec![
+            "Foo_Test\r\n└── when something bad happens\r\n   └── it should revert",
+            "Foo_Test2\r\n└── it should revert",
+        ]);
+    }
+
+    #[test]
+    fn finds_ignored_comments() {
+        let input = "// author: alice\n\n// stray\nFoo\n└── it works // \
+                     kept\n\n// also stray\n// twice\n\nBar\n└── it \
+                     works\n\n// end\n";
+        assert_eq!(
+            ignored_comments(input),
+            vec![
+                Span::new(Position::new(53, 7, 1), Position::new(74, 8, 8)),
+                Span::new(Position::new(95, 13, 1), Position::new(100, 13, 6)),
+            ]
+        );
+
+        let input = "// not front matter\n\nFoo\n└── it works";
+        assert_eq!(
+            ignored_comments(input),
+            vec![Span::new(Position::new(0, 1, 1), Position::new(18, 1, 19))]
+        );
+        assert_eq!(ignored_comments("Foo\n└── it works"), vec![]);
+        let input = "// pragma solidity ^0.7.6;\n\nFoo\n└── it works";
+        assert_eq!(ignored_comments(input), vec![]);
+    }
 }
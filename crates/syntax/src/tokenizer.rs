@@ -7,11 +7,15 @@ use thiserror::Error;
 use crate::{
     char::CharExt,
     error::FrontendError,
+    options::Keywords,
     span::{Position, Span},
 };
 
 type Result<T> = result::Result<T, Error>;
 
+/// The default number of columns a tab stands for.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
 /// An error that occurred while tokenizing a .tree string into a sequence of
 /// tokens.
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
@@ -82,7 +86,9 @@ impl Token {
             TokenKind::Word
             | TokenKind::When
             | TokenKind::Given
-            | TokenKind::It => false,
+            | TokenKind::It
+            | TokenKind::Anchor
+            | TokenKind::Tag => false,
         }
     }
 }
@@ -97,8 +103,12 @@ impl fmt::Debug for Token {
 #[derive(Debug, PartialEq, Eq)]
 pub enum TokenKind {
     /// A token representing the `├` character.
+    ///
+    /// The ASCII branches `|--` and `+--` are also tokenized as a `Tee`.
     Tee,
     /// A token representing the `└` character.
+    ///
+    /// The ASCII branch `` `-- `` is also tokenized as a `Corner`.
     Corner,
     /// A token representing a string.
     ///
@@ -111,6 +121,16 @@ pub enum TokenKind {
     Given,
     /// A token representing an `it` keyword.
     It,
+    /// A token representing the explicit id of a node, e.g. `#revert-zero`.
+    ///
+    /// Anchors are the last word of a line, a `#` followed by a letter and
+    /// any number of letters, digits, `-` and `_`.
+    Anchor,
+    /// A token representing a tag of a node, e.g. `[combine]`.
+    ///
+    /// Tags are an id in square brackets, and can only be followed by other
+    /// tags and an anchor until the end of the line.
+    Tag,
 }
 
 impl From<&str> for TokenKind {
@@ -139,6 +159,11 @@ pub struct Tokenizer {
     /// a character that is not a valid identifier character.
     /// This is to prevent malformed names when emitting identifiers.
     identifier_mode: Cell<bool>,
+    /// The number of columns a tab advances to, i.e. tabs move the column to
+    /// the next multiple of `tab_width`.
+    tab_width: usize,
+    /// Extra keywords recognized on top of `when`, `given` and `it`.
+    keywords: Keywords,
 }
 
 impl Default for Tokenizer {
@@ -154,9 +179,27 @@ impl Tokenizer {
         Self {
             pos: Cell::new(Position::new(0, 1, 1)),
             identifier_mode: Cell::new(false),
+            tab_width: DEFAULT_TAB_WIDTH,
+            keywords: Keywords::new(),
         }
     }
 
+    /// Set the number of columns a tab stands for.
+    ///
+    /// A width of `0` is treated as `1`.
+    #[must_use]
+    pub const fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = if tab_width == 0 { 1 } else { tab_width };
+        self
+    }
+
+    /// Set the extra keywords that start conditions and actions.
+    #[must_use]
+    pub fn with_keywords(mut self, keywords: Keywords) -> Self {
+        self.keywords = keywords;
+        self
+    }
+
     /// Tokenize the input .tree text.
     ///
     /// `tokenize` is the entry point of the Tokenizer.
@@ -289,6 +332,11 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
         if self.char() == '\n' {
             line = line + 1;
             column = 1;
+        } else if self.char() == '\t' {
+            // Advance to the next tab stop so that tabs and spaces can be
+            // mixed in indentation.
+            let tab_width = self.tokenizer().tab_width;
+            column = (column - 1) / tab_width * tab_width + tab_width + 1;
         } else {
             column = column + 1;
         }
@@ -323,6 +371,30 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
                     span: self.span(),
                     lexeme: "└".to_owned(),
                 }),
+                // ASCII branches, e.g. `|--`, `+--` and `` `-- ``. These
+                // characters are only special in a line's indentation, so that
+                // they can still be used in titles.
+                '|' | '+' | '`'
+                    if self.is_indentation() && self.is_ascii_branch() =>
+                {
+                    let kind = if self.char() == '`' {
+                        TokenKind::Corner
+                    } else {
+                        TokenKind::Tee
+                    };
+                    tokens.push(Token {
+                        kind,
+                        span: self.span(),
+                        lexeme: self.char().to_string(),
+                    });
+                    while self.peek() == Some('-') {
+                        self.bump();
+                    }
+                }
+                // A lone `|` is the ASCII counterpart of `│`.
+                '|' if self.is_indentation() => {}
+                '#' if self.is_anchor() => tokens.push(self.scan_anchor()),
+                '[' if self.is_tag() => tokens.push(self.scan_tag()),
                 // Comments start with `//`.
                 '/' if self.peek().is_some_and(|c| c == '/') => {
                     self.exit_mode();
@@ -351,6 +423,88 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
         Ok(tokens)
     }
 
+    /// Returns true if everything between the start of the current line and
+    /// the current position is indentation, i.e. whitespace and vertical
+    /// lines.
+    fn is_indentation(&self) -> bool {
+        let line_start =
+            self.text()[..self.offset()].rfind('\n').map_or(0, |i| i + 1);
+        self.text()[line_start..self.offset()]
+            .chars()
+            .all(|c| matches!(c, ' ' | '\t' | '│' | '|'))
+    }
+
+    /// Returns true if the current character starts an ASCII branch, i.e. it
+    /// is followed by `--`.
+    fn is_ascii_branch(&self) -> bool {
+        self.text()[self.offset() + self.char().len_utf8()..].starts_with("--")
+    }
+
+    /// Returns true if the current character starts an anchor, i.e. a `#`
+    /// followed by an id and nothing else until the end of the line.
+    fn is_anchor(&self) -> bool {
+        let rest = &self.text()[self.offset() + 1..];
+        let line = rest.split('\n').next().unwrap_or_default();
+        let id = line.split_whitespace().next().unwrap_or_default();
+
+        line.starts_with(id) && line[id.len()..].trim().is_empty() && is_id(id)
+    }
+
+    /// Returns true if the current character starts a tag, i.e. a `[` followed
+    /// by an id and a `]`, and only other tags and an anchor until the end of
+    /// the line.
+    fn is_tag(&self) -> bool {
+        let rest = &self.text()[self.offset()..];
+        let line = rest.split('\n').next().unwrap_or_default();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let is_tag = |word: &str| {
+            word.strip_prefix('[')
+                .and_then(|word| word.strip_suffix(']'))
+                .is_some_and(is_id)
+        };
+        let (tags, anchor) = match words.split_last() {
+            Some((last, tags)) if last.starts_with('#') => {
+                (tags, last.strip_prefix('#').is_some_and(is_id))
+            }
+            _ => (&words[..], true),
+        };
+
+        line.starts_with('[')
+            && !tags.is_empty()
+            && anchor
+            && tags.iter().all(|word| is_tag(word))
+    }
+
+    /// Consumes an anchor from the input.
+    ///
+    /// Panics if called when the tokenizer is not at an anchor.
+    fn scan_anchor(&self) -> Token {
+        let span_start = self.pos();
+        let mut lexeme = String::from(self.char());
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.bump();
+            lexeme.push(self.char());
+        }
+
+        let span = self.span().with_start(span_start);
+        Token { kind: TokenKind::Anchor, span, lexeme }
+    }
+
+    /// Consumes a tag from the input.
+    ///
+    /// Panics if called when the tokenizer is not at a tag.
+    fn scan_tag(&self) -> Token {
+        let span_start = self.pos();
+        let mut lexeme = String::from(self.char());
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.bump();
+            lexeme.push(self.char());
+        }
+
+        let span = self.span().with_start(span_start);
+        Token { kind: TokenKind::Tag, span, lexeme }
+    }
+
     /// Discards all characters until the end of the line.
     fn scan_comments(&self) {
         loop {
@@ -381,7 +535,7 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
                 || self.peek().is_some_and(char::is_whitespace)
             {
                 lexeme.push(self.char());
-                let kind = TokenKind::from(lexeme.as_str());
+                let kind = self.tokenizer().keywords.kind(&lexeme);
                 let span = self.span().with_start(span_start);
                 return Ok(Token { kind, span, lexeme });
             }
@@ -392,12 +546,20 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
     }
 }
 
+/// Returns true if `id` is a valid anchor or tag id, i.e. a letter followed
+/// by any number of letters, digits, `-` and `_`.
+fn is_id(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_alphabetic())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
     use crate::{
+        options::Keywords,
         span::Span,
         test_utils::{p, s, TestError},
         tokenizer::{
@@ -453,6 +615,89 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ascii_branches() {
+        let file_contents = String::from(indoc! {"
+            Foo
+            |-- when a
+            |   `-- it returns a | b
+            +-- it `works`
+        "});
+
+        #[rustfmt::skip]
+        assert_eq!(
+            tokenize(&file_contents).unwrap(),
+            vec![
+                t(TokenKind::Word,   "Foo",     s(p(0, 1, 1),   p(2, 1, 3))),
+                t(TokenKind::Tee,    "|",       s(p(4, 2, 1),   p(4, 2, 1))),
+                t(TokenKind::When,   "when",    s(p(8, 2, 5),   p(11, 2, 8))),
+                t(TokenKind::Word,   "a",       s(p(13, 2, 10), p(13, 2, 10))),
+                t(TokenKind::Corner, "`",       s(p(19, 3, 5),  p(19, 3, 5))),
+                t(TokenKind::It,     "it",      s(p(23, 3, 9),  p(24, 3, 10))),
+                t(TokenKind::Word,   "returns", s(p(26, 3, 12), p(32, 3, 18))),
+                t(TokenKind::Word,   "a",       s(p(34, 3, 20), p(34, 3, 20))),
+                t(TokenKind::Word,   "|",       s(p(36, 3, 22), p(36, 3, 22))),
+                t(TokenKind::Word,   "b",       s(p(38, 3, 24), p(38, 3, 24))),
+                t(TokenKind::Tee,    "+",       s(p(40, 4, 1),  p(40, 4, 1))),
+                t(TokenKind::It,     "it",      s(p(44, 4, 5),  p(45, 4, 6))),
+                t(TokenKind::Word,   "`works`", s(p(47, 4, 8),  p(53, 4, 14))),
+            ]
+        );
+    }
+
+    #[test]
+    fn anchors() {
+        let file_contents = String::from(indoc! {"
+            Foo
+            ├── when a #a-1
+            │   └── it returns #1 #b_2
+            └── it is #not an anchor
+        "});
+
+        #[rustfmt::skip]
+        assert_eq!(
+            tokenize(&file_contents).unwrap(),
+            vec![
+                t(TokenKind::Word,   "Foo",     s(p(0, 1, 1),   p(2, 1, 3))),
+                t(TokenKind::Tee,    "├",       s(p(4, 2, 1),   p(4, 2, 1))),
+                t(TokenKind::When,   "when",    s(p(14, 2, 5),  p(17, 2, 8))),
+                t(TokenKind::Word,   "a",       s(p(19, 2, 10), p(19, 2, 10))),
+                t(TokenKind::Anchor, "#a-1",    s(p(21, 2, 12), p(24, 2, 15))),
+                t(TokenKind::Corner, "└",       s(p(32, 3, 5),  p(32, 3, 5))),
+                t(TokenKind::It,     "it",      s(p(42, 3, 9),  p(43, 3, 10))),
+                t(TokenKind::Word,   "returns", s(p(45, 3, 12), p(51, 3, 18))),
+                t(TokenKind::Word,   "#1",      s(p(53, 3, 20), p(54, 3, 21))),
+                t(TokenKind::Anchor, "#b_2",    s(p(56, 3, 23), p(59, 3, 26))),
+                t(TokenKind::Corner, "└",       s(p(61, 4, 1),  p(61, 4, 1))),
+                t(TokenKind::It,     "it",      s(p(71, 4, 5),  p(72, 4, 6))),
+                t(TokenKind::Word,   "is",      s(p(74, 4, 8),  p(75, 4, 9))),
+                t(TokenKind::Word,   "#not",    s(p(77, 4, 11), p(80, 4, 14))),
+                t(TokenKind::Word,   "an",      s(p(82, 4, 16), p(83, 4, 17))),
+                t(TokenKind::Word,   "anchor",  s(p(85, 4, 19), p(90, 4, 24))),
+            ]
+        );
+    }
+
+    #[test]
+    fn tabs_advance_to_the_next_tab_stop() {
+        let file_contents = "Foo\n└── when a\n\t└── it b\n  \t└── it c";
+
+        let columns = |tokenizer: Tokenizer| -> Vec<usize> {
+            let mut tokenizer = tokenizer;
+            let tokens = tokenizer.tokenize(file_contents).unwrap();
+            tokens
+                .iter()
+                .filter(|t| t.kind == TokenKind::Corner)
+                .map(|t| t.span.start.column)
+                .collect()
+        };
+
+        // Mixed tabs and spaces line up with the default width of 4.
+        assert_eq!(columns(Tokenizer::new()), vec![1, 5, 5]);
+        assert_eq!(columns(Tokenizer::new().with_tab_width(2)), vec![1, 3, 5]);
+        assert_eq!(columns(Tokenizer::new().with_tab_width(0)), vec![1, 2, 4]);
+    }
+
     #[test]
     fn comments() {
         let file_contents = String::from(indoc! {"
@@ -773,4 +1018,33 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn extra_keywords() {
+        let keywords = Keywords {
+            conditions: vec!["cuando".to_owned()],
+            actions: vec!["debería".to_owned()],
+        };
+        let text =
+            "Foo\n└── Cuando el monto es cero\n    └── debería revertir.";
+        let tokens =
+            Tokenizer::new().with_keywords(keywords).tokenize(text).unwrap();
+        let kinds: Vec<_> = tokens.into_iter().map(|t| t.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Word,
+                TokenKind::Corner,
+                TokenKind::When,
+                TokenKind::Word,
+                TokenKind::Word,
+                TokenKind::Word,
+                TokenKind::Word,
+                TokenKind::Corner,
+                TokenKind::It,
+                TokenKind::Word,
+            ]
+        );
+    }
 }
@@ -1,171 +1,454 @@
 //! Various-string manipulation utilities.
 
-use unicode_xid::UnicodeXID;
+pub use bulloak_utils::{
+    lower_first_letter, sanitize, to_pascal_case, upper_first_letter,
+};
 
-/// Capitalizes the first letter of a given string.
+use crate::{
+    alias,
+    ast::{Ast, Condition},
+};
+
+/// Repeats a given string a specified number of times.
+///
+/// # Arguments
+///
+/// * `s` - A string slice to be repeated
+/// * `n` - The number of times to repeat the string
 ///
-/// This function takes a string slice and returns a new `String` with the first
-/// letter capitalized. If the string is empty, it returns an empty string.
+/// # Returns
+///
+/// A `String` containing the repeated string
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_syntax::utils::repeat_str;
+/// let result = repeat_str("abc", 3);
+/// assert_eq!(result, "abcabcabc");
+/// ```
+pub fn repeat_str(s: &str, n: usize) -> String {
+    s.repeat(n)
+}
+
+/// Returns the singular or plural form of a word based on the count.
 ///
 /// # Arguments
 ///
-/// * `s` - A string slice that holds the input string
+/// * `count` - The count to determine which form to use
+/// * `singular` - The singular form of the word
+/// * `plural` - The plural form of the word
 ///
 /// # Returns
 ///
-/// A `String` with the first letter capitalized
+/// A string slice containing either the singular or plural form
 ///
 /// # Examples
 ///
 /// ```
-/// # use bulloak_syntax::utils::upper_first_letter;
-/// let result = upper_first_letter("hello");
-/// assert_eq!(result, "Hello");
+/// # use bulloak_syntax::utils::pluralize;
+/// assert_eq!(pluralize(1, "apple", "apples"), "apple");
+/// assert_eq!(pluralize(2, "apple", "apples"), "apples");
 /// ```
-pub fn upper_first_letter(s: &str) -> String {
-    let mut c = s.chars();
-    c.next()
-        .map(char::to_uppercase)
-        .map(|first| first.to_string() + c.as_str())
-        .unwrap_or_default()
+pub fn pluralize<'a>(
+    count: usize,
+    singular: &'a str,
+    plural: &'a str,
+) -> &'a str {
+    if count == 1 {
+        singular
+    } else {
+        plural
+    }
 }
 
-/// Converts the first letter of a given string to lowercase.
+/// Converts the line endings of `text` to match the ones used in `reference`.
 ///
-/// This function takes a string slice and returns a new `String` with the first
-/// letter in lowercase. If the string is empty, it returns an empty string.
+/// Generated code always uses `\n`. If `reference` uses `\r\n`, e.g. because
+/// it was written on Windows, the returned string uses `\r\n` as well.
 ///
 /// # Arguments
 ///
-/// * `s` - A string slice that holds the input string
+/// * `text` - A string slice that holds the generated text
+/// * `reference` - A string slice whose line endings should be preserved
 ///
 /// # Returns
 ///
-/// A `String` with the first letter in lowercase
+/// A `String` with the same line endings as `reference`
 ///
 /// # Examples
 ///
 /// ```
-/// # use bulloak_syntax::utils::lower_first_letter;
-/// let result = lower_first_letter("Hello");
-/// assert_eq!(result, "hello");
+/// # use bulloak_syntax::utils::match_line_endings;
+/// assert_eq!(match_line_endings("a\nb", "x\r\ny"), "a\r\nb");
+/// assert_eq!(match_line_endings("a\r\nb", "x\ny"), "a\nb");
 /// ```
-pub fn lower_first_letter(s: &str) -> String {
-    let mut c = s.chars();
-    c.next()
-        .map(char::to_lowercase)
-        .map(|first| first.to_string() + c.as_str())
-        .unwrap_or_default()
+#[must_use]
+pub fn match_line_endings(text: &str, reference: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+    if reference.contains("\r\n") {
+        text.replace('\n', "\r\n")
+    } else {
+        text
+    }
 }
 
-/// Sanitizes a string to make it a valid identifier.
+/// What an action expects to happen, as far as its title tells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expectation {
+    /// The action returns a value, e.g. `it should return the sum`.
+    Return(String),
+    /// The action emits an event, e.g. `it should emit a {Transfer} event`.
+    Emit(String),
+}
+
+/// Derives the expectation of an action from its title.
 ///
-/// This function replaces hyphens with underscores and removes any characters
-/// that are not valid in an identifier according to the Unicode Standard Annex
-/// #31.
+/// Only titles of the form `it should return <value>` and
+/// `it should emit <event>` are recognized. The leading keyword can be any
+/// word, so titles using custom keywords are supported too.
 ///
 /// # Arguments
 ///
-/// * `identifier` - A string slice that holds the input identifier
+/// * `title` - A string slice that holds the title of an action
 ///
 /// # Returns
 ///
-/// A `String` containing the sanitized identifier
+/// The `Expectation` of the action, or `None` if the title doesn't follow one
+/// of the recognized forms
 ///
 /// # Examples
 ///
 /// ```
-/// # use bulloak_syntax::utils::sanitize;
-/// let result = sanitize("my-variable@123");
-/// assert_eq!(result, "my_variable123");
+/// # use bulloak_syntax::utils::{expectation, Expectation};
+/// assert_eq!(
+///     expectation("It should return the sum."),
+///     Some(Expectation::Return("the sum".to_owned()))
+/// );
+/// assert_eq!(
+///     expectation("it should emit a {Transfer} event"),
+///     Some(Expectation::Emit("Transfer".to_owned()))
+/// );
+/// assert_eq!(expectation("it should revert"), None);
 /// ```
-pub fn sanitize(identifier: &str) -> String {
-    identifier
-        .replace('-', "_")
-        .replace(|c: char| !c.is_xid_continue() && c != ' ', "")
+#[must_use]
+pub fn expectation(title: &str) -> Option<Expectation> {
+    let title = title.trim().trim_end_matches('.');
+    // Skip the keyword, e.g. `it`.
+    let mut words = title.split_whitespace().skip(1);
+    if !words.next()?.eq_ignore_ascii_case("should") {
+        return None;
+    }
+
+    let verb = words.next()?.to_lowercase();
+    match verb.as_str() {
+        "return" | "returns" => {
+            let value = words.collect::<Vec<_>>().join(" ");
+            let value = value.trim_matches('`');
+            (!value.is_empty()).then(|| Expectation::Return(value.to_owned()))
+        }
+        "emit" | "emits" => {
+            let event = words
+                .map(|w| {
+                    sanitize(w.trim_matches(|c| matches!(c, '{' | '}' | '`')))
+                })
+                .find(|w| {
+                    !matches!(w.to_lowercase().as_str(), "a" | "an" | "the")
+                })?;
+            let is_generic =
+                matches!(event.to_lowercase().as_str(), "event" | "events");
+            (!event.is_empty() && !is_generic)
+                .then_some(Expectation::Emit(event))
+        }
+        _ => None,
+    }
 }
 
-/// Converts a sentence to pascal case.
+/// Turns `text` into a block of `//` comments, one per line.
 ///
-/// The conversion is done by capitalizing the first letter of each word
-/// in the title and removing the spaces. For example, the sentence
-/// `when only owner` is converted to the `WhenOnlyOwner` string.
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text of the comment
+///
+/// # Returns
+///
+/// A `String` holding the comment block, ending with a newline
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_syntax::utils::comment_block;
+/// let result = comment_block("Generated by bulloak\n\nDo not edit.");
+/// assert_eq!(result, "// Generated by bulloak\n//\n// Do not edit.\n");
+/// ```
+#[must_use]
+pub fn comment_block(text: &str) -> String {
+    let mut block = String::with_capacity(text.len());
+    for line in text.lines() {
+        block.push_str("//");
+        if !line.trim().is_empty() {
+            block.push(' ');
+            block.push_str(line.trim_end());
+        }
+        block.push('\n');
+    }
+
+    block
+}
+
+/// Fills in the `{key}` placeholders of a user-provided template.
+///
+/// Placeholders without a value are left as they are.
 ///
 /// # Arguments
 ///
-/// * `sentence` - A string slice that holds the input sentence
+/// * `template` - A string slice that holds the template
+/// * `values` - The `(key, value)` pairs to fill in
 ///
 /// # Returns
 ///
-/// A `String` in pascal case
+/// A `String` holding the rendered template
 ///
 /// # Examples
 ///
 /// ```
-/// # use bulloak_syntax::utils::to_pascal_case;
-/// let result = to_pascal_case("when only owner");
-/// assert_eq!(result, "WhenOnlyOwner");
+/// # use bulloak_syntax::utils::render_template;
+/// let result = render_template("{test} in {tree}", &[("tree", "a.tree")]);
+/// assert_eq!(result, "{test} in a.tree");
 /// ```
-pub fn to_pascal_case(sentence: &str) -> String {
-    sentence.split_whitespace().map(upper_first_letter).collect::<String>()
+#[must_use]
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(template.to_owned(), |rendered, (key, value)| {
+        rendered.replace(&format!("{{{key}}}"), value)
+    })
 }
 
-/// Repeats a given string a specified number of times.
+/// Summarizes a branch path in a single line.
+///
+/// The titles of the conditions leading to a test are capitalized and joined
+/// with `›`.
 ///
 /// # Arguments
 ///
-/// * `s` - A string slice to be repeated
-/// * `n` - The number of times to repeat the string
+/// * `titles` - The titles of the conditions, from the outermost one
 ///
 /// # Returns
 ///
-/// A `String` containing the repeated string
+/// A `String` holding the summary of the branch
 ///
 /// # Examples
 ///
 /// ```
-/// # use bulloak_syntax::utils::repeat_str;
-/// let result = repeat_str("abc", 3);
-/// assert_eq!(result, "abcabcabc");
+/// # use bulloak_syntax::utils::branch_summary;
+/// let result = branch_summary(&["given paused", "when caller is owner"]);
+/// assert_eq!(result, "Given paused › When caller is owner");
 /// ```
-pub fn repeat_str(s: &str, n: usize) -> String {
-    s.repeat(n)
+#[must_use]
+pub fn branch_summary<S: AsRef<str>>(titles: &[S]) -> String {
+    titles
+        .iter()
+        .map(|title| upper_first_letter(title.as_ref().trim()))
+        .collect::<Vec<_>>()
+        .join(" › ")
 }
 
-/// Returns the singular or plural form of a word based on the count.
+/// Reads the metadata in the front-matter of a `.tree` file.
+///
+/// The front-matter is the comments above the first tree. Each comment of
+/// the form `// key: value` holds a piece of metadata, e.g. the author of the
+/// spec or the ticket it implements. Other comments, and directives such as
+/// `// alias: ...`, are ignored.
 ///
 /// # Arguments
 ///
-/// * `count` - The count to determine which form to use
-/// * `singular` - The singular form of the word
-/// * `plural` - The plural form of the word
+/// * `text` - A string slice that holds the contents of the `.tree` file
 ///
 /// # Returns
 ///
-/// A string slice containing either the singular or plural form
+/// A `Vec` holding the key and value of each piece of metadata, in order
 ///
 /// # Examples
 ///
 /// ```
-/// # use bulloak_syntax::utils::pluralize;
-/// assert_eq!(pluralize(1, "apple", "apples"), "apple");
-/// assert_eq!(pluralize(2, "apple", "apples"), "apples");
+/// # use bulloak_syntax::utils::front_matter;
+/// let text = "// author: alice\n// audit scope: vault\nFoo\n└── it works";
+/// let result = front_matter(text);
+/// assert_eq!(result, vec![("author", "alice"), ("audit scope", "vault")]);
 /// ```
-pub fn pluralize<'a>(
-    count: usize,
-    singular: &'a str,
-    plural: &'a str,
-) -> &'a str {
-    if count == 1 {
-        singular
-    } else {
-        plural
+#[must_use]
+pub fn front_matter(text: &str) -> Vec<(&str, &str)> {
+    front_matter_entries(text)
+        .filter(|(key, _)| {
+            *key != alias::DIRECTIVE && *key != FORMAT_DESCRIPTIONS_KEY
+        })
+        .collect()
+}
+
+/// The front-matter key that turns `--format-descriptions` off for a single
+/// spec, as in `// format-descriptions: off`.
+pub const FORMAT_DESCRIPTIONS_KEY: &str = "format-descriptions";
+
+/// Whether the front-matter of the `.tree` file in `text` turns
+/// `--format-descriptions` off, keeping the comments of the spec verbatim.
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_syntax::utils::keeps_descriptions_verbatim;
+/// let text = "// format-descriptions: off\nFoo\n└── it works";
+/// assert!(keeps_descriptions_verbatim(text));
+/// assert!(!keeps_descriptions_verbatim("Foo\n└── it works"));
+/// ```
+#[must_use]
+pub fn keeps_descriptions_verbatim(text: &str) -> bool {
+    front_matter_entries(text).any(|(key, value)| {
+        key == FORMAT_DESCRIPTIONS_KEY
+            && matches!(value.to_lowercase().as_str(), "off" | "false" | "no")
+    })
+}
+
+/// The comment that pins the Solidity version of a spec, e.g.
+/// `// pragma solidity ^0.8.0;`.
+pub const PRAGMA_DIRECTIVE: &str = "// pragma solidity";
+
+/// Returns the Solidity version pinned by a `// pragma solidity <version>;`
+/// comment in the comments at the top of `text`, if any.
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_syntax::utils::pragma_version;
+/// let text = "// author: alice\n// pragma solidity ^0.7.6;\nFoo";
+/// assert_eq!(pragma_version(text), Some("^0.7.6"));
+/// assert_eq!(pragma_version("Foo\n// pragma solidity ^0.7.6;"), None);
+/// ```
+#[must_use]
+pub fn pragma_version(text: &str) -> Option<&str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take_while(|line| line.starts_with("//"))
+        .find_map(|line| {
+            let version = line.strip_prefix(PRAGMA_DIRECTIVE)?;
+            if !version.starts_with(char::is_whitespace) {
+                return None;
+            }
+            let version = version.trim().trim_end_matches(';').trim_end();
+            (!version.is_empty()).then_some(version)
+        })
+}
+
+/// The `// key: value` comments of the front-matter of a `.tree` file,
+/// directives included.
+pub(crate) fn front_matter_entries(
+    text: &str,
+) -> impl Iterator<Item = (&str, &str)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map_while(|line| line.strip_prefix("//"))
+        .filter_map(|comment| {
+            let (key, value) = comment.split_once(": ")?;
+            let (key, value) = (key.trim(), value.trim());
+            let is_key = key.starts_with(|c: char| c.is_ascii_alphabetic())
+                && key.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_')
+                });
+            (is_key && !value.is_empty()).then_some((key, value))
+        })
+}
+
+/// Whether the description in `text` starts with code, which formatting
+/// must not capitalize, e.g. `` `transfer()` reverts ``.
+///
+/// The first word is code if it's a code span, a call, a path or a
+/// `snake_case` name.
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_syntax::utils::starts_with_code;
+/// assert!(starts_with_code("`transfer()` reverts"));
+/// assert!(starts_with_code("total_supply grows"));
+/// assert!(!starts_with_code("it should revert"));
+/// ```
+#[must_use]
+pub fn starts_with_code(text: &str) -> bool {
+    text.split_whitespace().next().is_some_and(|word| {
+        word.starts_with('`')
+            || word.contains(['(', '_'])
+            || word.contains("::")
+    })
+}
+
+/// Returns a short hash of the branch starting at `condition`, e.g. to tell
+/// apart tests whose names would otherwise clash.
+///
+/// The hash only depends on the titles in the branch, so it doesn't change
+/// when other branches are added to or removed from the tree, nor across
+/// runs, platforms or Rust versions.
+///
+/// # Arguments
+///
+/// * `condition` - The condition the branch starts at
+///
+/// # Returns
+///
+/// A `String` holding four lowercase hexadecimal digits
+#[must_use]
+pub fn branch_hash(condition: &Condition) -> String {
+    /// Feeds `text` and a line break to the FNV-1a `hash`.
+    fn feed(text: &str, hash: &mut u32) {
+        for byte in text.bytes().chain([b'\n']) {
+            *hash = (*hash ^ u32::from(byte)).wrapping_mul(0x0100_0193);
+        }
     }
+
+    /// Feeds the titles of `children` and their descendants to `hash`.
+    fn feed_children(children: &[Ast], hash: &mut u32) {
+        for child in children {
+            match child {
+                Ast::Condition(c) => {
+                    feed(&c.title, hash);
+                    feed_children(&c.children, hash);
+                }
+                Ast::Action(a) => {
+                    feed(&a.title, hash);
+                    feed_children(&a.children, hash);
+                }
+                Ast::ActionDescription(d) => feed(&d.text, hash),
+                Ast::Root(_) => {}
+            }
+        }
+    }
+
+    let mut hash = 0x811c_9dc5;
+    feed(&condition.title, &mut hash);
+    feed_children(&condition.children, &mut hash);
+    format!("{:04x}", (hash >> 16) ^ (hash & 0xffff))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::to_pascal_case;
+    use super::{
+        branch_hash, expectation, front_matter, keeps_descriptions_verbatim,
+        pragma_version, starts_with_code, to_pascal_case, Expectation,
+    };
+
+    #[test]
+    fn reads_the_pragma_directive() {
+        let version = pragma_version("// pragma solidity ^0.7.6;\nFoo");
+        assert_eq!(version, Some("^0.7.6"));
+        assert_eq!(
+            pragma_version("\n  // pragma solidity >=0.6.0 <0.8.0\r\nFoo"),
+            Some(">=0.6.0 <0.8.0")
+        );
+        let text = "// author: alice\n\n// pragma solidity 0.7.6;\nFoo";
+        assert_eq!(pragma_version(text), Some("0.7.6"));
+        assert_eq!(pragma_version("Foo\n// pragma solidity 0.7.6;"), None);
+        assert_eq!(pragma_version("// pragma solidity;\nFoo"), None);
+        assert_eq!(pragma_version("// pragma solidityfoo\nFoo"), None);
+    }
 
     #[test]
     fn to_modifier() {
@@ -173,4 +456,85 @@ mod tests {
         assert_eq!(to_pascal_case("when"), "When");
         assert_eq!(to_pascal_case(""), "");
     }
+
+    #[test]
+    fn derives_expectations() {
+        assert_eq!(
+            expectation("It should return `true`."),
+            Some(Expectation::Return("true".to_owned()))
+        );
+        assert_eq!(
+            expectation("it should emit {Transfer} and {Approval} events"),
+            Some(Expectation::Emit("Transfer".to_owned()))
+        );
+        assert_eq!(expectation("it should emit an event"), None);
+        assert_eq!(expectation("it should return"), None);
+        assert_eq!(expectation("it should revert"), None);
+        assert_eq!(expectation("it returns the sum"), None);
+    }
+
+    #[test]
+    fn reads_front_matter() {
+        let text = "// pragma solidity 0.8.0;\n\n// author: alice\r\n// \
+                    ticket: https://example.com/T-1\n// not metadata\nFoo\n\
+                    └── it works // owner: bob";
+        assert_eq!(
+            front_matter(text),
+            vec![("author", "alice"), ("ticket", "https://example.com/T-1")]
+        );
+        assert!(front_matter("// author:\n// 2: x\nFoo").is_empty());
+        assert!(front_matter("// https://example.com\nFoo").is_empty());
+        assert!(front_matter("Foo\n// author: alice").is_empty());
+    }
+
+    #[test]
+    fn reads_the_description_opt_out() {
+        let text = "// author: alice\n// format-descriptions: Off\nFoo";
+        assert!(keeps_descriptions_verbatim(text));
+        assert_eq!(front_matter(text), vec![("author", "alice")]);
+        assert!(!keeps_descriptions_verbatim(
+            "// format-descriptions: on\nFoo"
+        ));
+        assert!(!keeps_descriptions_verbatim(
+            "Foo\n// format-descriptions: off"
+        ));
+    }
+
+    #[test]
+    fn tells_code_apart() {
+        assert!(starts_with_code("`paused` is true"));
+        assert!(starts_with_code("  transfer() reverts"));
+        assert!(starts_with_code("Vault::deposit works"));
+        assert!(!starts_with_code("it should call `transfer()`"));
+        assert!(!starts_with_code(""));
+    }
+
+    #[test]
+    fn hashes_branches() {
+        fn hashes(tree: &str) -> Vec<String> {
+            let crate::Ast::Root(root) = crate::parse_one(tree).unwrap() else {
+                unreachable!()
+            };
+            root.children
+                .iter()
+                .filter_map(|child| match child {
+                    crate::Ast::Condition(condition) => {
+                        Some(branch_hash(condition))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+
+        let tree = "Foo\n├── when a\n│   └── it does one\n└── when a\n    \
+                    └── it does two";
+        let inserted = "Foo\n├── when b\n│   └── it does three\n├── when \
+                        a\n│   └── it does one\n└── when a\n    └── it does \
+                        two";
+        let (before, after) = (hashes(tree), hashes(inserted));
+
+        assert_eq!(before[0].len(), 4);
+        assert_ne!(before[0], before[1]);
+        assert_eq!(before, after[1..]);
+    }
 }
@@ -0,0 +1,59 @@
+//! The built-in backends, wrapped for round-trip testing.
+
+use std::fs;
+
+use bulloak_foundry::check::{
+    context::Context,
+    rules::{Checker, StructuralMatcher},
+};
+
+use crate::Backend;
+
+/// The Solidity (Foundry) backend.
+#[derive(Debug, Clone, Default)]
+pub struct Solidity {
+    /// The configuration to scaffold and check with.
+    pub cfg: bulloak_foundry::config::Config,
+}
+
+impl Backend for Solidity {
+    fn scaffold(&self, tree: &str) -> anyhow::Result<String> {
+        bulloak_foundry::scaffold::scaffold(tree, &self.cfg)
+    }
+
+    fn check(&self, tree: &str, code: &str) -> anyhow::Result<Vec<String>> {
+        let dir = tempfile::tempdir()?;
+        let tree_path = dir.path().join("spec.tree");
+        fs::write(&tree_path, tree)?;
+        fs::write(dir.path().join("spec.t.sol"), code)?;
+
+        let violations = match Context::new(tree_path, &self.cfg) {
+            Ok(ctx) => StructuralMatcher::check(&ctx),
+            Err(violation) => vec![violation],
+        };
+        Ok(violations.iter().map(ToString::to_string).collect())
+    }
+}
+
+/// The Rust backend.
+#[derive(Debug, Clone, Default)]
+pub struct Rust {
+    /// The configuration to scaffold and check with.
+    pub cfg: bulloak_rust::Config,
+}
+
+impl Backend for Rust {
+    fn scaffold(&self, tree: &str) -> anyhow::Result<String> {
+        Ok(bulloak_rust::scaffold_tree(tree, &self.cfg)?)
+    }
+
+    fn check(&self, tree: &str, code: &str) -> anyhow::Result<Vec<String>> {
+        let dir = tempfile::tempdir()?;
+        let tree_path = dir.path().join("spec.tree");
+        fs::write(&tree_path, tree)?;
+        fs::write(bulloak_rust::scaffold::test_file_path(&tree_path), code)?;
+
+        let violations = bulloak_rust::check::check(&tree_path, &self.cfg)?;
+        Ok(violations.iter().map(ToString::to_string).collect())
+    }
+}
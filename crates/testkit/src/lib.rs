@@ -0,0 +1,124 @@
+//! Property-based round-trip tests for bulloak backends.
+//!
+//! A backend must pass its own check on the code it scaffolds, whatever the
+//! tree. This crate generates random valid trees with [`strategy::tree`] and
+//! asserts that property for any [`Backend`], including ones that live outside
+//! of this repository:
+//!
+//! ```no_run
+//! use bulloak_testkit::{backends::Solidity, check_round_trips};
+//!
+//! check_round_trips(&Solidity::default(), 64);
+//! ```
+//!
+//! [`round_trip`] checks a single tree, e.g. inside a `proptest!` block of
+//! your own.
+
+pub mod backends;
+pub mod strategy;
+
+use proptest::{
+    prelude::*,
+    test_runner::{Config, TestRunner},
+};
+
+/// A backend that scaffolds tests from trees and checks tests against them.
+pub trait Backend {
+    /// Scaffolds the test file for the spec in `tree`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend can't scaffold the tree.
+    fn scaffold(&self, tree: &str) -> anyhow::Result<String>;
+
+    /// Checks the test file in `code` against the spec in `tree`, returning
+    /// the violations found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend can't run the check at all.
+    fn check(&self, tree: &str, code: &str) -> anyhow::Result<Vec<String>>;
+}
+
+/// Scaffolds the test file for `tree` with `backend` and checks it against
+/// `tree`.
+///
+/// # Errors
+///
+/// Fails the test case if scaffolding or checking fails, or if the check finds
+/// any violation.
+pub fn round_trip<B: Backend + ?Sized>(
+    backend: &B,
+    tree: &str,
+) -> Result<(), TestCaseError> {
+    let code = backend
+        .scaffold(tree)
+        .map_err(|e| TestCaseError::fail(format!("scaffold failed: {e}")))?;
+    let violations = backend
+        .check(tree, &code)
+        .map_err(|e| TestCaseError::fail(format!("check failed: {e}")))?;
+    if !violations.is_empty() {
+        return Err(TestCaseError::fail(format!(
+            "the scaffolded code has violations:\n{}\n\n{code}",
+            violations.join("\n")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Runs [`round_trip`] on `cases` random trees.
+///
+/// # Panics
+///
+/// Panics with the smallest tree that fails to round-trip, if any.
+pub fn check_round_trips<B: Backend + ?Sized>(backend: &B, cases: u32) {
+    // There is no source file to store regressions next to.
+    let mut runner = TestRunner::new(Config {
+        cases,
+        failure_persistence: None,
+        ..Config::default()
+    });
+    let result = runner
+        .run(&strategy::tree(), |tree| round_trip(backend, &tree.to_string()));
+    if let Err(e) = result {
+        panic!("{e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        backends::{Rust, Solidity},
+        check_round_trips, Backend,
+    };
+
+    /// A backend whose check finds a violation in every test.
+    struct Broken;
+
+    impl Backend for Broken {
+        fn scaffold(&self, tree: &str) -> anyhow::Result<String> {
+            Ok(tree.to_owned())
+        }
+
+        fn check(&self, _: &str, _: &str) -> anyhow::Result<Vec<String>> {
+            Ok(vec!["a test is missing".to_owned()])
+        }
+    }
+
+    #[test]
+    fn solidity_round_trips() {
+        check_round_trips(&Solidity::default(), 64);
+    }
+
+    #[test]
+    fn rust_round_trips() {
+        check_round_trips(&Rust::default(), 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "a test is missing")]
+    fn reports_violations() {
+        check_round_trips(&Broken, 8);
+    }
+}
@@ -0,0 +1,117 @@
+//! Proptest strategies that generate valid `.tree` specs.
+
+use std::fmt;
+
+use proptest::{collection::vec, prelude::*, sample::select};
+
+/// The words titles are made of.
+const WORDS: &[&str] = &[
+    "amount", "balance", "caller", "deadline", "enough", "fee", "is", "not",
+    "owner", "passed", "paused", "the", "token", "zero",
+];
+
+/// The names of the generated contracts.
+const CONTRACTS: &[&str] = &["Vault", "Token", "Auction", "Vault::deposit"];
+
+/// A randomly generated tree.
+///
+/// Its [`Display`](fmt::Display) implementation renders it as the text of a
+/// `.tree` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree {
+    /// The name of the contract the tree is for.
+    pub contract: String,
+    /// The top-level nodes of the tree.
+    pub children: Vec<Node>,
+}
+
+/// A node of a randomly generated tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// A condition, e.g. `when the amount is zero`, and its children.
+    ///
+    /// Conditions always have at least one child.
+    Condition(String, Vec<Node>),
+    /// An action, e.g. `it should revert`.
+    Action(String),
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.contract)?;
+        render(f, &self.children, "")
+    }
+}
+
+/// Renders `nodes` as the branches of a tree, indented by `indent`.
+fn render(
+    f: &mut fmt::Formatter<'_>,
+    nodes: &[Node],
+    indent: &str,
+) -> fmt::Result {
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let branch = if last { "└──" } else { "├──" };
+        match node {
+            Node::Condition(title, children) => {
+                writeln!(f, "{indent}{branch} {title}")?;
+                let indent =
+                    format!("{indent}{}", if last { "    " } else { "│   " });
+                render(f, children, &indent)?;
+            }
+            Node::Action(title) => writeln!(f, "{indent}{branch} {title}")?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates a title of one to four words.
+fn words() -> impl Strategy<Value = String> {
+    vec(select(WORDS), 1..=4).prop_map(|words| words.join(" "))
+}
+
+/// Generates an action, e.g. `it should revert the fee`.
+fn action() -> impl Strategy<Value = Node> {
+    words().prop_map(|words| Node::Action(format!("it should {words}")))
+}
+
+/// Generates a node: an action, or a condition over up to three levels of
+/// nested nodes.
+pub fn node() -> impl Strategy<Value = Node> {
+    action().prop_recursive(3, 24, 4, |inner| {
+        (select(&["when", "given"][..]), words(), vec(inner, 1..=4)).prop_map(
+            |(keyword, words, children)| {
+                Node::Condition(format!("{keyword} {words}"), children)
+            },
+        )
+    })
+}
+
+/// Generates a valid tree.
+///
+/// Titles may repeat, since bulloak reuses the modifier or helper of
+/// repeated conditions and disambiguates tests whose names clash, except at
+/// the top level, where actions become functions of their own and must be
+/// unique.
+pub fn tree() -> impl Strategy<Value = Tree> {
+    (select(CONTRACTS), vec(node(), 1..=4))
+        .prop_map(|(contract, children)| Tree {
+            contract: contract.to_owned(),
+            children,
+        })
+        .prop_filter("top-level actions must be unique", |tree| {
+            let actions: Vec<&String> = tree
+                .children
+                .iter()
+                .filter_map(|node| match node {
+                    Node::Action(title) => Some(title),
+                    Node::Condition(..) => None,
+                })
+                .collect();
+            actions
+                .iter()
+                .enumerate()
+                .all(|(index, title)| !actions[..index].contains(title))
+        })
+}
@@ -0,0 +1,338 @@
+//! Name conversions shared by bulloak's backends.
+//!
+//! Every backend turns tree titles into identifiers, e.g. `when only owner`
+//! into the `whenOnlyOwner` Solidity modifier or the `only_owner` Rust helper.
+//! Scaffolding and checking must agree on these names, so they all go through
+//! the functions in this crate.
+
+use unicode_xid::UnicodeXID;
+
+/// Capitalizes the first letter of a given string.
+///
+/// This function takes a string slice and returns a new `String` with the first
+/// letter capitalized. If the string is empty, it returns an empty string.
+///
+/// # Arguments
+///
+/// * `s` - A string slice that holds the input string
+///
+/// # Returns
+///
+/// A `String` with the first letter capitalized
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_utils::upper_first_letter;
+/// let result = upper_first_letter("hello");
+/// assert_eq!(result, "Hello");
+/// ```
+#[must_use]
+pub fn upper_first_letter(s: &str) -> String {
+    map_first_letter(s, char::to_uppercase)
+}
+
+/// Converts the first letter of a given string to lowercase.
+///
+/// This function takes a string slice and returns a new `String` with the first
+/// letter in lowercase. If the string is empty, it returns an empty string.
+///
+/// # Arguments
+///
+/// * `s` - A string slice that holds the input string
+///
+/// # Returns
+///
+/// A `String` with the first letter in lowercase
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_utils::lower_first_letter;
+/// let result = lower_first_letter("Hello");
+/// assert_eq!(result, "hello");
+/// ```
+#[must_use]
+pub fn lower_first_letter(s: &str) -> String {
+    map_first_letter(s, char::to_lowercase)
+}
+
+/// Returns `s` with its first letter replaced by what `map` turns it into.
+fn map_first_letter<I: Iterator<Item = char>>(
+    s: &str,
+    map: impl FnOnce(char) -> I,
+) -> String {
+    let mut result = String::with_capacity(s.len());
+    push_mapped_first_letter(&mut result, s, map);
+    result
+}
+
+/// Pushes `s` to `result`, with its first letter replaced by what `map` turns
+/// it into.
+fn push_mapped_first_letter<I: Iterator<Item = char>>(
+    result: &mut String,
+    s: &str,
+    map: impl FnOnce(char) -> I,
+) {
+    let mut chars = s.chars();
+    if let Some(first) = chars.next() {
+        result.extend(map(first));
+        result.push_str(chars.as_str());
+    }
+}
+
+/// Sanitizes a string to make it a valid identifier.
+///
+/// This function replaces hyphens with underscores and removes any characters
+/// that are not valid in an identifier according to the Unicode Standard Annex
+/// #31.
+///
+/// # Arguments
+///
+/// * `identifier` - A string slice that holds the input identifier
+///
+/// # Returns
+///
+/// A `String` containing the sanitized identifier
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_utils::sanitize;
+/// let result = sanitize("my-variable@123");
+/// assert_eq!(result, "my_variable123");
+/// ```
+#[must_use]
+pub fn sanitize(identifier: &str) -> String {
+    let mut sanitized = String::with_capacity(identifier.len());
+    for c in identifier.chars() {
+        if c == '-' {
+            sanitized.push('_');
+        } else if c.is_xid_continue() || c == ' ' {
+            sanitized.push(c);
+        }
+    }
+
+    sanitized
+}
+
+/// Converts a sentence to pascal case.
+///
+/// The conversion is done by capitalizing the first letter of each word
+/// in the title and removing the spaces. For example, the sentence
+/// `when only owner` is converted to the `WhenOnlyOwner` string.
+///
+/// # Arguments
+///
+/// * `sentence` - A string slice that holds the input sentence
+///
+/// # Returns
+///
+/// A `String` in pascal case
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_utils::to_pascal_case;
+/// let result = to_pascal_case("when only owner");
+/// assert_eq!(result, "WhenOnlyOwner");
+/// ```
+#[must_use]
+pub fn to_pascal_case(sentence: &str) -> String {
+    let mut result = String::with_capacity(sentence.len());
+    for word in sentence.split_whitespace() {
+        push_mapped_first_letter(&mut result, word, char::to_uppercase);
+    }
+
+    result
+}
+
+/// Options that tweak how [`to_snake_case`] splits a string into words.
+///
+/// The defaults give the names the Rust backend has always generated, so
+/// that existing test suites keep matching their specs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SnakeCaseOptions {
+    /// Whether to also split words where the case changes, e.g. `balanceOf`
+    /// becomes `balance_of`.
+    pub split_camel_case: bool,
+    /// Whether to keep runs of uppercase letters together when splitting
+    /// camel case, so `ERC20Token` becomes `erc20_token` rather than
+    /// `e_r_c20_token`.
+    pub group_acronyms: bool,
+    /// Whether whitespace after punctuation separates words too, so `a, b`
+    /// becomes `a_b` rather than `ab`.
+    pub split_after_punctuation: bool,
+}
+
+impl Default for SnakeCaseOptions {
+    fn default() -> Self {
+        Self {
+            split_camel_case: true,
+            group_acronyms: false,
+            split_after_punctuation: false,
+        }
+    }
+}
+
+/// Converts a sentence to snake case.
+///
+/// Words are lowercased and joined with underscores. Whitespace and `-`
+/// separate words, and any other character that is not a letter or a digit,
+/// including `_`, is dropped. For example, the sentence `it's the owner's
+/// balanceOf` is converted to the `its_the_owners_balance_of` string.
+///
+/// Only ASCII letters are lowercased.
+///
+/// # Arguments
+///
+/// * `sentence` - A string slice that holds the input sentence
+/// * `options` - How to split the sentence into words
+///
+/// # Returns
+///
+/// A `String` in snake case
+///
+/// # Examples
+///
+/// ```
+/// # use bulloak_utils::{to_snake_case, SnakeCaseOptions};
+/// let result = to_snake_case("when only owner", SnakeCaseOptions::default());
+/// assert_eq!(result, "when_only_owner");
+/// ```
+#[must_use]
+pub fn to_snake_case(sentence: &str, options: SnakeCaseOptions) -> String {
+    // Names are built for every branch of every tree, so this is a single
+    // pass over `sentence` into a buffer that rarely needs to grow.
+    let mut result = String::with_capacity(sentence.len());
+    let mut separated = false;
+    let mut prev = None;
+    let mut chars = sentence.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() {
+            let camel_boundary = options.split_camel_case
+                && c.is_uppercase()
+                && prev.is_some_and(|prev: char| {
+                    if options.group_acronyms {
+                        is_camel_boundary(prev, chars.peek().copied())
+                    } else {
+                        prev.is_alphanumeric()
+                    }
+                });
+            if !result.is_empty() && (separated || camel_boundary) {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+            separated = false;
+        } else if c.is_whitespace() || c == '-' {
+            // Unless splitting after punctuation, only whitespace right after
+            // a word separates it from the next one.
+            separated |= options.split_after_punctuation
+                || prev.is_some_and(char::is_alphanumeric);
+        }
+        prev = Some(c);
+    }
+
+    result
+}
+
+/// Whether an uppercase letter preceded by `prev` and followed by `next`
+/// starts a new word, e.g. the `O` in `balanceOf` or the `T` in `ERCToken`.
+fn is_camel_boundary(prev: char, next: Option<char>) -> bool {
+    prev.is_lowercase()
+        || prev.is_numeric()
+        || (prev.is_uppercase() && next.is_some_and(char::is_lowercase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        lower_first_letter, sanitize, to_pascal_case, to_snake_case,
+        upper_first_letter, SnakeCaseOptions,
+    };
+
+    fn snake(sentence: &str) -> String {
+        to_snake_case(sentence, SnakeCaseOptions::default())
+    }
+
+    #[test]
+    fn changes_the_first_letter() {
+        assert_eq!(upper_first_letter("éclair"), "Éclair");
+        assert_eq!(lower_first_letter("Hello World"), "hello World");
+        assert_eq!(upper_first_letter(""), "");
+    }
+
+    #[test]
+    fn sanitizes_identifiers() {
+        assert_eq!(sanitize("when st-ff \"all'd"), "when st_ff alld");
+        assert_eq!(sanitize("-été-"), "_été_");
+    }
+
+    #[test]
+    fn converts_to_pascal_case() {
+        assert_eq!(to_pascal_case("when only owner"), "WhenOnlyOwner");
+        assert_eq!(to_pascal_case("  when   x "), "WhenX");
+        assert_eq!(to_pascal_case("when ßig öwner"), "WhenSSigÖwner");
+        assert_eq!(to_pascal_case(""), "");
+    }
+
+    #[test]
+    fn converts_to_snake_case() {
+        assert_eq!(
+            snake("when first arg is smaller"),
+            "when_first_arg_is_smaller"
+        );
+        assert_eq!(
+            snake("It should return the sum."),
+            "it_should_return_the_sum"
+        );
+        assert_eq!(snake("  a - b__c  "), "a_bc");
+        assert_eq!(
+            snake("when divisor is non_zero"),
+            "when_divisor_is_nonzero"
+        );
+        assert_eq!(snake("it's the owner's"), "its_the_owners");
+        assert_eq!(snake("a, b"), "ab");
+        assert_eq!(snake("ÉTAT À jour"), "É_t_a_t_À_jour");
+        assert_eq!(snake(""), "");
+
+        let options = SnakeCaseOptions {
+            split_after_punctuation: true,
+            ..SnakeCaseOptions::default()
+        };
+        assert_eq!(to_snake_case("a, b", options), "a_b");
+        assert_eq!(to_snake_case(" a ", options), "a");
+    }
+
+    #[test]
+    fn splits_camel_case() {
+        assert_eq!(snake("balanceOf"), "balance_of");
+        assert_eq!(snake("ERC20Token"), "e_r_c20_token");
+
+        let options = SnakeCaseOptions {
+            group_acronyms: true,
+            ..SnakeCaseOptions::default()
+        };
+        assert_eq!(to_snake_case("ERC20Token", options), "erc20_token");
+        assert_eq!(
+            to_snake_case("ERCToken is set", options),
+            "erc_token_is_set"
+        );
+        assert_eq!(
+            to_snake_case("when ERC20 is paused", options),
+            "when_erc20_is_paused"
+        );
+        assert_eq!(to_snake_case("balanceOf", options), "balance_of");
+
+        let options = SnakeCaseOptions {
+            split_camel_case: false,
+            ..SnakeCaseOptions::default()
+        };
+        assert_eq!(
+            to_snake_case("balanceOf ERC20Token", options),
+            "balanceof_erc20token"
+        );
+    }
+}